@@ -131,6 +131,10 @@ pub async fn keep_connection(
 ) {
     let mut error_count = 0;
     loop {
+        // The collection has been stopped.
+        if ws_tx.is_closed() {
+            break;
+        }
         let connect_time = Instant::now();
         let streams_str = symbol_list
             .iter()
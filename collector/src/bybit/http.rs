@@ -108,6 +108,10 @@ pub async fn keep_connection(
 ) {
     let mut error_count = 0;
     loop {
+        // The collection has been stopped.
+        if ws_tx.is_closed() {
+            break;
+        }
         let connect_time = Instant::now();
         let topics_ = symbol_list
             .iter()
@@ -0,0 +1,197 @@
+use std::{fs, path::Path, str::FromStr};
+
+use serde::Deserialize;
+
+use crate::{
+    file::{Compression, Output, Rotation},
+    normalize::Feed,
+};
+
+/// The collector configuration, loaded from a TOML file.
+///
+/// ```toml
+/// reload_interval = 10
+///
+/// [output]
+/// path = "/data/collected"
+/// rotation = "daily"
+/// compression = "gzip"
+/// format = "raw"
+///
+/// [[exchange]]
+/// name = "binancespot"
+/// symbols = ["btcusdt", "ethusdt"]
+/// streams = ["$symbol@trade", "$symbol@depth@100ms", "$symbol@depth20@100ms"]
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// The interval in seconds at which the file is checked for changes to the exchanges. `0`
+    /// disables the reload.
+    #[serde(default)]
+    pub reload_interval: u64,
+    pub output: OutputConfig,
+    #[serde(rename = "exchange")]
+    pub exchanges: Vec<ExchangeConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct OutputConfig {
+    /// The files of each exchange are written under `{path}/{exchange}`.
+    pub path: String,
+    #[serde(default)]
+    pub rotation: Rotation,
+    #[serde(default)]
+    pub compression: Compression,
+    #[serde(default)]
+    pub format: Output,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct ExchangeConfig {
+    pub name: String,
+    pub symbols: Vec<String>,
+    /// The streams, topics, subscriptions or channels to subscribe to, depending on the exchange,
+    /// where `$symbol` is replaced by each symbol. The [`default_streams`] are used if omitted.
+    pub streams: Option<Vec<String>>,
+}
+
+impl ExchangeConfig {
+    pub fn streams(&self) -> Vec<String> {
+        self.streams
+            .clone()
+            .unwrap_or_else(|| default_streams(&self.name))
+    }
+}
+
+impl Config {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, anyhow::Error> {
+        fs::read_to_string(path)?.parse()
+    }
+}
+
+impl FromStr for Config {
+    type Err = anyhow::Error;
+
+    /// Parses the configuration, rejecting the unsupported and duplicated exchanges before any
+    /// collection is started.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let config: Config = toml::from_str(s)?;
+        for exchange in &config.exchanges {
+            if Feed::from_exchange(&exchange.name).is_none() {
+                return Err(anyhow::anyhow!("{} is not supported.", exchange.name));
+            }
+            if config
+                .exchanges
+                .iter()
+                .filter(|other| other.name == exchange.name)
+                .count()
+                > 1
+            {
+                return Err(anyhow::anyhow!("{} is duplicated.", exchange.name));
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Returns the streams subscribed to by default for the exchange.
+pub fn default_streams(exchange: &str) -> Vec<String> {
+    let streams: &[&str] = match exchange {
+        "binancefutures" | "binancefuturesum" | "binancefuturescm" => &[
+            "$symbol@trade",
+            "$symbol@bookTicker",
+            "$symbol@depth@0ms",
+            "$symbol@depth20@100ms",
+            // "$symbol@@markPrice@1s"
+        ],
+        "binance" | "binancespot" => &[
+            "$symbol@trade",
+            "$symbol@bookTicker",
+            "$symbol@depth@100ms",   // Full depth, 100ms
+            "$symbol@depth20@100ms", // 20-level snapshot, 100ms
+        ],
+        "bybit" | "bybitspot" => &[
+            "orderbook.50.$symbol", // 50-level depth
+            "publicTrade.$symbol",  // Trades
+        ],
+        "hyperliquid" => &["trades", "l2Book", "bbo"],
+        "okx" | "okxspot" | "okxswap" | "okxfutures" => &[
+            "trades",  // Trades
+            "bbo-tbt", // Best bid/offer, tick-by-tick
+            "books",   // Incremental depth, 400 levels, changed levels every 100ms
+            "books5",  // 5-level depth snapshot every 100ms
+        ],
+        _ => &[],
+    };
+    streams.iter().map(|stream| stream.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_example() {
+        let config =
+            Config::load(concat!(env!("CARGO_MANIFEST_DIR"), "/config.example.toml")).unwrap();
+        assert_eq!(config.reload_interval, 10);
+        assert_eq!(config.output.rotation, Rotation::Daily);
+        assert_eq!(config.output.format, Output::Raw);
+        let names: Vec<_> = config.exchanges.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["binancefuturesum", "binancespot", "bybit"]);
+        // The omitted streams fall back to the default streams.
+        assert_eq!(
+            config.exchanges[0].streams(),
+            default_streams("binancefuturesum")
+        );
+        assert_eq!(
+            config.exchanges[2].streams(),
+            ["orderbook.50.$symbol", "publicTrade.$symbol"]
+        );
+    }
+
+    #[test]
+    fn test_reject_exchanges() {
+        let config = |names: &[&str]| {
+            let mut s = "[output]\npath = \"data\"\n".to_string();
+            for name in names {
+                s += &format!("[[exchange]]\nname = \"{name}\"\nsymbols = [\"btcusdt\"]\n");
+            }
+            s.parse::<Config>()
+        };
+        assert!(config(&["binancespot", "bybit"]).is_ok());
+        assert_eq!(
+            config(&["binancespot", "bybit", "binancespot"])
+                .unwrap_err()
+                .to_string(),
+            "binancespot is duplicated."
+        );
+        assert_eq!(
+            config(&["binancespot", "kraken"]).unwrap_err().to_string(),
+            "kraken is not supported."
+        );
+    }
+
+    #[test]
+    fn test_default_streams() {
+        for name in [
+            "binancefutures",
+            "binancefuturesum",
+            "binancefuturescm",
+            "binance",
+            "binancespot",
+            "bybit",
+            "bybitspot",
+            "hyperliquid",
+            "okx",
+            "okxspot",
+            "okxswap",
+            "okxfutures",
+        ] {
+            assert!(Feed::from_exchange(name).is_some());
+            assert!(!default_streams(name).is_empty(), "{name}");
+        }
+        assert!(default_streams("kraken").is_empty());
+        assert!(default_streams("binancespot").contains(&"$symbol@depth@100ms".to_string()));
+    }
+}
@@ -1,46 +1,157 @@
 use std::{
     collections::{HashMap, hash_map::Entry},
+    fs,
     fs::File,
     io,
-    io::Write,
+    io::{BufWriter, Seek, Write},
+    path::Path,
+    thread,
+    thread::JoinHandle,
 };
 
-use chrono::{DateTime, NaiveDate, Utc};
-use flate2::{Compression, write::GzEncoder};
-use tracing::info;
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use flate2::write::GzEncoder;
+use hftbacktest::{
+    backtest::data::{
+        NpyChunkReader,
+        correct_event_order,
+        correct_local_timestamp,
+        write_npy,
+        write_npz_file,
+    },
+    types::Event,
+};
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::normalize::{Feed, Normalizer};
+
+/// Selects the files written by the collector.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Output {
+    /// The raw messages in the text files.
+    #[default]
+    Raw,
+    /// The normalized events in the npz files, ready for backtesting.
+    Event,
+    /// Both the raw messages and the normalized events.
+    Both,
+}
+
+/// Determines how often a new file is started.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    /// `{path}_{YYYYMMDD}`
+    #[default]
+    Daily,
+    /// `{path}_{YYYYMMDDHH}`
+    Hourly,
+}
+
+impl Rotation {
+    /// Returns the suffix of the file to which the given time belongs.
+    fn period(&self, datetime: DateTime<Utc>) -> String {
+        match self {
+            Rotation::Daily => datetime.format("%Y%m%d").to_string(),
+            Rotation::Hourly => datetime.format("%Y%m%d%H").to_string(),
+        }
+    }
+}
+
+/// Compression of the raw message files. The npz files are always compressed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// `.gz`
+    #[default]
+    Gzip,
+    /// `.txt`
+    None,
+}
+
+enum Sink {
+    Gzip(GzEncoder<File>),
+    Plain(BufWriter<File>),
+}
+
+impl Sink {
+    fn finish(self) -> Result<(), io::Error> {
+        match self {
+            Sink::Gzip(file) => file.finish().map(|_| ()),
+            Sink::Plain(mut file) => file.flush(),
+        }
+    }
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Sink::Gzip(file) => file.write(buf),
+            Sink::Plain(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Sink::Gzip(file) => file.flush(),
+            Sink::Plain(file) => file.flush(),
+        }
+    }
+}
 
 pub struct RotatingFile {
-    date: NaiveDate,
+    period: String,
     path: String,
-    file: Option<GzEncoder<File>>,
+    rotation: Rotation,
+    compression: Compression,
+    file: Option<Sink>,
 }
 
 impl RotatingFile {
-    fn create(datetime: DateTime<Utc>, path: &str) -> Result<GzEncoder<File>, io::Error> {
-        let date = datetime.date_naive().format("%Y%m%d");
+    fn create(&self) -> Result<Sink, io::Error> {
+        let (path, period) = (&self.path, &self.period);
+        let ext = match self.compression {
+            Compression::Gzip => "gz",
+            Compression::None => "txt",
+        };
         let file = File::options()
             .create(true)
             .write(true)
-            .open(format!("{path}_{date}.gz"))?;
-        Ok(GzEncoder::new(file, Compression::default()))
+            .open(format!("{path}_{period}.{ext}"))?;
+        Ok(match self.compression {
+            Compression::Gzip => Sink::Gzip(GzEncoder::new(file, flate2::Compression::default())),
+            Compression::None => Sink::Plain(BufWriter::new(file)),
+        })
     }
 
-    pub fn new(datetime: DateTime<Utc>, path: String) -> Result<Self, io::Error> {
-        Ok(Self {
-            date: datetime.date_naive(),
-            file: Some(Self::create(datetime, &path)?),
+    pub fn new(
+        datetime: DateTime<Utc>,
+        path: String,
+        rotation: Rotation,
+        compression: Compression,
+    ) -> Result<Self, io::Error> {
+        let mut file = Self {
+            period: rotation.period(datetime),
             path,
-        })
+            rotation,
+            compression,
+            file: None,
+        };
+        file.file = Some(file.create()?);
+        Ok(file)
     }
 
     pub fn write(&mut self, datetime: DateTime<Utc>, data: String) -> Result<(), io::Error> {
-        let date = datetime.date_naive();
-        if date != self.date {
+        let period = self.rotation.period(datetime);
+        if period != self.period {
             let file = self.file.take().unwrap();
             let _ = file.finish();
-            self.file = Some(Self::create(datetime, &self.path)?);
-            self.date = date;
-            info!(%date, %self.path, "date is changed");
+            self.period = period;
+            self.file = Some(self.create()?);
+            info!(%self.period, %self.path, "file is rotated");
         }
         let timestamp = datetime.timestamp_nanos_opt().unwrap();
         self.file
@@ -52,20 +163,219 @@ impl RotatingFile {
 
 impl Drop for RotatingFile {
     fn drop(&mut self) {
-        let _ = self.file.take().unwrap().finish();
+        if let Some(file) = self.file.take() {
+            let _ = file.finish();
+        }
     }
 }
 
+/// The number of events buffered in memory before they are appended to the part file.
+const SPILL_ROWS: usize = 64 * 1024;
+
+/// Converts the messages into [`Event`] rows and writes them to a `{path}_{period}.npz` file per
+/// rotation period, with the feed latency and the event order corrected.
+///
+/// The events are appended in chunks to a `{path}_{period}.npz.part` file, a sequence of npy
+/// arrays, as they are received, so that a crash loses only the last chunk. When the period changes,
+/// the part file is converted into the npz file on a separate thread. The part files left by a
+/// crashed run are converted by [`finalize_part_files`] when the collector starts. Each file begins with the
+/// snapshot of the market depth at the end of the previous period, so that a period can be
+/// backtested on its own.
+pub struct RotatingEventFile {
+    period: String,
+    path: String,
+    rotation: Rotation,
+    normalizer: Normalizer,
+    events: Vec<Event>,
+    filename: String,
+    part: Option<BufWriter<File>>,
+    finalizing: Option<JoinHandle<()>>,
+}
+
+impl RotatingEventFile {
+    pub fn new(datetime: DateTime<Utc>, path: String, rotation: Rotation, feed: Feed) -> Self {
+        let period = rotation.period(datetime);
+        Self {
+            filename: npz_filename(&path, &period),
+            period,
+            path,
+            rotation,
+            normalizer: Normalizer::new(feed),
+            events: Vec::new(),
+            part: None,
+            finalizing: None,
+        }
+    }
+
+    /// Appends the buffered events to the part file.
+    fn spill(&mut self) -> Result<(), io::Error> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
+        let part = match &mut self.part {
+            Some(part) => part,
+            None => {
+                let file = File::create_new(format!("{}.part", self.filename))?;
+                self.part.insert(BufWriter::new(file))
+            }
+        };
+        write_npy(part, &self.events)?;
+        part.flush()?;
+        self.events.clear();
+        Ok(())
+    }
+
+    /// Closes the part file of the current period and returns the npz file to be written from it.
+    fn close(&mut self) -> Result<Option<String>, io::Error> {
+        self.spill()?;
+        Ok(self.part.take().map(|_| self.filename.clone()))
+    }
+
+    pub fn write(&mut self, datetime: DateTime<Utc>, data: &str) -> Result<(), io::Error> {
+        let period = self.rotation.period(datetime);
+        let timestamp = datetime.timestamp_nanos_opt().unwrap();
+        if period != self.period {
+            if let Some(filename) = self.close()? {
+                // Waits for the previous period, which should have been finished long ago, so that
+                // only one period is held in memory while finalizing.
+                if let Some(handle) = self.finalizing.take() {
+                    let _ = handle.join();
+                }
+                self.finalizing = Some(thread::spawn(move || {
+                    if let Err(error) = finalize(&filename) {
+                        error!(?error, %filename, "couldn't write the events.");
+                    }
+                }));
+            }
+            self.filename = npz_filename(&self.path, &period);
+            self.period = period;
+            self.normalizer.snapshot(timestamp, &mut self.events);
+            info!(%self.period, %self.path, "file is rotated");
+        }
+        if let Err(error) = self.normalizer.normalize(timestamp, data, &mut self.events) {
+            warn!(?error, %self.path, %data, "couldn't normalize the message.");
+        }
+        if self.events.len() >= SPILL_ROWS {
+            self.spill()?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RotatingEventFile {
+    fn drop(&mut self) {
+        if let Some(handle) = self.finalizing.take() {
+            let _ = handle.join();
+        }
+        if let Err(error) = self.close().and_then(|filename| match filename {
+            Some(filename) => finalize(&filename),
+            None => Ok(()),
+        }) {
+            error!(?error, %self.path, "couldn't write the events.");
+        }
+    }
+}
+
+/// Returns the npz file of the period, which doesn't overwrite the file written, or being written,
+/// by the previous run in the same period.
+fn npz_filename(path: &str, period: &str) -> String {
+    let mut filename = format!("{path}_{period}.npz");
+    let mut n = 0;
+    while Path::new(&filename).exists() || Path::new(&format!("{filename}.part")).exists() {
+        n += 1;
+        filename = format!("{path}_{period}_{n}.npz");
+    }
+    filename
+}
+
+/// Reads the events from the part file of the npz file, corrects the feed latency and the event
+/// order, and writes them to the npz file. The part file is removed once the npz file is written.
+fn finalize(filename: &str) -> Result<(), io::Error> {
+    let part = format!("{filename}.part");
+    let mut file = File::open(&part)?;
+    let len = file.metadata()?.len();
+    let mut events = Vec::new();
+    while file.stream_position()? < len {
+        let mut chunk = match NpyChunkReader::<_, Event>::new(&mut file) {
+            Ok(chunk) => chunk,
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                warn!(%filename, "the last chunk is truncated.");
+                break;
+            }
+            Err(error) => return Err(error),
+        };
+        let rows = chunk.len();
+        match chunk.read_chunk(rows) {
+            Ok(data) => events.extend_from_slice(data.as_slice()),
+            // The last chunk of a crashed run may be partially written.
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                warn!(%filename, "the last chunk is truncated.");
+                break;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+    correct_local_timestamp(&mut events, 0);
+    let data = correct_event_order(&events);
+    drop(events);
+
+    write_npz_file(filename, "data", &data)?;
+    fs::remove_file(&part)?;
+    info!(%filename, rows = data.len(), "events are written");
+    Ok(())
+}
+
+/// Writes the npz files from the part files in the directory, which are left by a previous run that
+/// didn't finish them, such as one that crashed.
+pub fn finalize_part_files(dir: &str) -> Result<(), io::Error> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let Some(filename) = path
+            .to_str()
+            .and_then(|path| path.strip_suffix(".part"))
+            .filter(|filename| filename.ends_with(".npz"))
+        else {
+            continue;
+        };
+        warn!(%filename, "writing the events left by the previous run.");
+        if let Err(error) = finalize(filename) {
+            error!(?error, %filename, "couldn't write the events.");
+        }
+    }
+    Ok(())
+}
+
 pub struct Writer {
     path: String,
+    output: Output,
+    rotation: Rotation,
+    compression: Compression,
+    feed: Feed,
     file: HashMap<String, RotatingFile>,
+    event_file: HashMap<String, RotatingEventFile>,
 }
 
 impl Writer {
-    pub fn new(path: &str) -> Self {
+    pub fn new(path: &str, output: Output, feed: Feed) -> Self {
         Self {
             path: path.to_string(),
+            output,
+            rotation: Default::default(),
+            compression: Default::default(),
+            feed,
             file: Default::default(),
+            event_file: Default::default(),
+        }
+    }
+
+    pub fn rotation(self, rotation: Rotation) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn compression(self, compression: Compression) -> Self {
+        Self {
+            compression,
+            ..self
         }
     }
 
@@ -75,18 +385,131 @@ impl Writer {
         symbol: String,
         data: String,
     ) -> Result<(), anyhow::Error> {
-        match self.file.entry(symbol.to_lowercase()) {
+        let symbol = symbol.to_lowercase();
+        let path = self.path.as_str();
+        if self.output != Output::Raw {
+            let (rotation, feed) = (self.rotation, self.feed);
+            self.event_file
+                .entry(symbol.clone())
+                .or_insert_with(|| {
+                    RotatingEventFile::new(recv_time, format!("{path}/{symbol}"), rotation, feed)
+                })
+                .write(recv_time, &data)?;
+        }
+        if self.output == Output::Event {
+            return Ok(());
+        }
+        match self.file.entry(symbol) {
             Entry::Occupied(mut entry) => {
                 entry.get_mut().write(recv_time, data)?;
             }
             Entry::Vacant(entry) => {
                 let symbol = entry.key().clone();
-                let path = self.path.as_str();
                 entry
-                    .insert(RotatingFile::new(recv_time, format!("{path}/{symbol}"))?)
+                    .insert(RotatingFile::new(
+                        recv_time,
+                        format!("{path}/{symbol}"),
+                        self.rotation,
+                        self.compression,
+                    )?)
                     .write(recv_time, data)?;
             }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use hftbacktest::{
+        backtest::data::read_npz_file,
+        types::{BUY_EVENT, DEPTH_CLEAR_EVENT, DEPTH_EVENT},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_rotating_event_file() {
+        let dir = std::env::temp_dir().join(format!("collector_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("btcusdt").to_string_lossy().to_string();
+
+        let day1 = Utc.with_ymd_and_hms(2025, 1, 1, 23, 59, 59).unwrap();
+        let day2 = Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 1).unwrap();
+        let update = |ts: i64, px: &str| {
+            format!(
+                r#"{{"stream":"btcusdt@depth","data":{{"e":"depthUpdate","E":{ts},"T":{ts},"s":"BTCUSDT","U":1,"u":1,"pu":0,"b":[["{px}","1"]],"a":[]}}}}"#
+            )
+        };
+
+        let mut file = RotatingEventFile::new(day1, path.clone(), Rotation::Daily, Feed::Binance);
+        file.write(day1, &update(day1.timestamp_millis() - 1, "100"))
+            .unwrap();
+        // The buffered events are appended to the part file as a chunk.
+        file.spill().unwrap();
+        file.write(day1, &update(day1.timestamp_millis() - 1, "101"))
+            .unwrap();
+        assert!(Path::new(&format!("{path}_20250101.npz.part")).exists());
+
+        file.write(day2, &update(day2.timestamp_millis() - 1, "102"))
+            .unwrap();
+        drop(file);
+
+        let day1 = read_npz_file::<Event>(&format!("{path}_20250101.npz"), "data").unwrap();
+        assert_eq!(
+            day1.as_slice().iter().map(|ev| ev.px).collect::<Vec<_>>(),
+            vec![100.0, 101.0]
+        );
+        // The next period begins with the snapshot of the market depth.
+        let day2 = read_npz_file::<Event>(&format!("{path}_20250102.npz"), "data").unwrap();
+        assert!(
+            day2.as_slice()
+                .iter()
+                .any(|ev| ev.ev & 0xff == DEPTH_CLEAR_EVENT)
+        );
+        assert!(!Path::new(&format!("{path}_20250101.npz.part")).exists());
+        assert!(!Path::new(&format!("{path}_20250102.npz.part")).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_finalize_part_files() {
+        let dir = std::env::temp_dir().join(format!("collector_part_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let filename = dir
+            .join("btcusdt_20250101.npz")
+            .to_string_lossy()
+            .to_string();
+
+        // A crashed run left two chunks, the last of which is truncated.
+        let event = |exch_ts: i64, px: f64| Event {
+            ev: DEPTH_EVENT | BUY_EVENT,
+            exch_ts,
+            local_ts: exch_ts + 1,
+            px,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        };
+        let mut part = Vec::new();
+        write_npy(&mut part, &[event(1, 100.0), event(2, 101.0)]).unwrap();
+        let len = part.len();
+        write_npy(&mut part, &[event(3, 102.0)]).unwrap();
+        part.truncate(part.len() - 8);
+        assert!(part.len() > len);
+        fs::write(format!("{filename}.part"), part).unwrap();
+
+        finalize_part_files(&dir.to_string_lossy()).unwrap();
+        let data = read_npz_file::<Event>(&filename, "data").unwrap();
+        assert_eq!(
+            data.as_slice().iter().map(|ev| ev.px).collect::<Vec<_>>(),
+            vec![100.0, 101.0]
+        );
+        assert!(!Path::new(&format!("{filename}.part")).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,17 +1,33 @@
+use std::{collections::HashMap, fs, time::Duration};
+
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use tokio::{self, select, signal, sync::mpsc::unbounded_channel};
-use tracing::{error, info};
+use tokio::{
+    self,
+    select,
+    signal,
+    sync::mpsc::{UnboundedSender, unbounded_channel},
+    task::JoinHandle,
+    time::interval,
+};
+use tracing::{error, info, warn};
 
-use crate::file::Writer;
+use crate::{
+    config::{Config, ExchangeConfig, OutputConfig},
+    file::{Output, Writer, finalize_part_files},
+    normalize::Feed,
+};
 
 mod binance;
 mod binancefuturescm;
 mod binancefuturesum;
 mod bybit;
+mod config;
 mod error;
 mod file;
 mod hyperliquid;
+mod normalize;
 mod okx;
 mod throttler;
 
@@ -19,203 +35,274 @@ mod throttler;
 #[command(version, about, long_about = None)]
 struct Args {
     /// Path for the files where collected data will be written.
-    path: String,
+    #[arg(required_unless_present = "config")]
+    path: Option<String>,
 
     /// Name of the exchange
-    exchange: String,
+    #[arg(required_unless_present = "config")]
+    exchange: Option<String>,
 
     /// Symbols for which data will be collected.
     symbols: Vec<String>,
-}
 
-#[tokio::main(flavor = "multi_thread")]
-async fn main() -> Result<(), anyhow::Error> {
-    let args = Args::parse();
+    /// Files to write: the raw messages, the normalized events, or both.
+    #[arg(long, value_enum, default_value_t = Output::Raw)]
+    output: Output,
 
-    tracing_subscriber::fmt::init();
+    /// TOML configuration file, which can specify multiple exchanges, instead of the arguments
+    /// above. The files of each exchange are written under its own directory.
+    #[arg(long, conflicts_with_all = ["path", "exchange", "symbols", "output"])]
+    config: Option<String>,
+}
 
-    let (writer_tx, mut writer_rx) = unbounded_channel();
-
-    let handle = match args.exchange.as_str() {
-        "binancefutures" | "binancefuturesum" => {
-            let streams = [
-                "$symbol@trade",
-                "$symbol@bookTicker",
-                "$symbol@depth@0ms",
-                "$symbol@depth20@100ms",
-                // "$symbol@@markPrice@1s"
-            ]
-            .iter()
-            .map(|stream| stream.to_string())
-            .collect();
+type WriterTx = UnboundedSender<(DateTime<Utc>, String, String)>;
 
-            tokio::spawn(binancefuturesum::run_collection(
-                streams,
-                args.symbols,
-                writer_tx,
-            ))
-        }
-        "binancefuturescm" => {
-            let streams = [
-                "$symbol@trade",
-                "$symbol@bookTicker",
-                "$symbol@depth@0ms",
-                "$symbol@depth20@100ms",
-                // "$symbol@@markPrice@1s"
-            ]
-            .iter()
-            .map(|stream| stream.to_string())
-            .collect();
+/// Transforms the symbols to the OKX format, e.g., BTCUSDT -> BTC-USDT{suffix}.
+fn okx_symbols(symbols: &[String], suffix: &str) -> Vec<String> {
+    symbols
+        .iter()
+        .map(|s| {
+            let upper = s.to_uppercase();
+            if upper.ends_with("USDT") {
+                let base = &upper[..upper.len() - 4];
+                format!("{base}-USDT{suffix}")
+            } else if upper.ends_with("USDC") {
+                let base = &upper[..upper.len() - 4];
+                format!("{base}-USDC{suffix}")
+            } else {
+                upper
+            }
+        })
+        .collect()
+}
 
-            tokio::spawn(binancefuturescm::run_collection(
-                streams,
-                args.symbols,
-                writer_tx,
-            ))
-        }
+fn spawn_collection(
+    exchange: &ExchangeConfig,
+    symbols: Vec<String>,
+    writer_tx: WriterTx,
+) -> Result<JoinHandle<Result<(), anyhow::Error>>, anyhow::Error> {
+    let streams = exchange.streams();
+    let handle = match exchange.name.as_str() {
+        "binancefutures" | "binancefuturesum" => tokio::spawn(binancefuturesum::run_collection(
+            streams, symbols, writer_tx,
+        )),
+        "binancefuturescm" => tokio::spawn(binancefuturescm::run_collection(
+            streams, symbols, writer_tx,
+        )),
         "binance" | "binancespot" => {
-            let streams = [
-                "$symbol@trade", 
-                "$symbol@bookTicker", 
-                "$symbol@depth@100ms",      // Full depth, 100ms
-                "$symbol@depth20@100ms",    // 20-level snapshot, 100ms (Verified working!)
-            ]
-                .iter()
-                .map(|stream| stream.to_string())
-                .collect();
-
-            tokio::spawn(binance::run_collection(streams, args.symbols, writer_tx))
+            tokio::spawn(binance::run_collection(streams, symbols, writer_tx))
         }
-        "bybit" => {
-            let topics = [
-                "orderbook.50.$symbol",    // 50档深度快照（包含足够的市场深度信息）
-                "publicTrade.$symbol",     // 逐笔成交
-            ]
-            .iter()
-            .map(|topic| topic.to_string())
-            .collect();
+        "bybit" => tokio::spawn(bybit::run_collection(
+            streams, symbols, writer_tx, "linear", // Perpetual
+        )),
+        "bybitspot" => tokio::spawn(bybit::run_collection(streams, symbols, writer_tx, "spot")),
+        "hyperliquid" => tokio::spawn(hyperliquid::run_collection(streams, symbols, writer_tx)),
+        "okx" | "okxspot" => tokio::spawn(okx::run_collection(
+            okx_symbols(&symbols, ""),
+            streams,
+            writer_tx,
+        )),
+        // OKX perpetual swap or futures
+        "okxswap" | "okxfutures" => tokio::spawn(okx::run_collection(
+            okx_symbols(&symbols, "-SWAP"),
+            streams,
+            writer_tx,
+        )),
+        exchange => {
+            return Err(anyhow!("{exchange} is not supported."));
+        }
+    };
+    Ok(handle)
+}
+
+type CollectionTask = (Vec<String>, JoinHandle<Result<(), anyhow::Error>>);
 
-            tokio::spawn(bybit::run_collection(
-                topics,
-                args.symbols,
-                writer_tx,
-                "linear",  // 永续合约
-            ))
+/// The collection of an exchange, which can be updated with the changed configuration. The symbols
+/// are collected by one or more tasks, each with its own connections, so that adding or removing
+/// symbols doesn't reconnect the others. The messages are tagged with the exchange index while
+/// being forwarded to the writers.
+struct Collection {
+    config: ExchangeConfig,
+    writer_tx: WriterTx,
+    tasks: Vec<CollectionTask>,
+}
+
+impl Collection {
+    fn spawn(
+        index: usize,
+        config: ExchangeConfig,
+        tagged_tx: UnboundedSender<(usize, DateTime<Utc>, String, String)>,
+    ) -> Result<Self, anyhow::Error> {
+        let (writer_tx, mut writer_rx) = unbounded_channel();
+        let handle = spawn_collection(&config, config.symbols.clone(), writer_tx.clone())?;
+        tokio::spawn(async move {
+            while let Some((recv_time, symbol, data)) = writer_rx.recv().await {
+                if tagged_tx.send((index, recv_time, symbol, data)).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            tasks: vec![(config.symbols.clone(), handle)],
+            config,
+            writer_tx,
+        })
+    }
+
+    /// Updates the collection to the changed configuration. The added symbols are collected by a
+    /// new task, and the tasks collecting the removed symbols are restarted with the remaining
+    /// ones. Every task is restarted if the streams are changed. The connections of the aborted
+    /// tasks are closed once their messages can no longer be delivered.
+    ///
+    /// A restarted task starts without the previous depth update IDs, so the depth snapshots of
+    /// its symbols are fetched again and the gap while reconnecting is filled.
+    fn update(&mut self, config: ExchangeConfig) -> Result<(), anyhow::Error> {
+        let restart_all = config.streams() != self.config.streams();
+        let mut tasks = Vec::new();
+        for (symbols, handle) in self.tasks.drain(..) {
+            if !restart_all && symbols.iter().all(|symbol| config.symbols.contains(symbol)) {
+                tasks.push((symbols, handle));
+                continue;
+            }
+            handle.abort();
+            let symbols: Vec<_> = symbols
+                .into_iter()
+                .filter(|symbol| config.symbols.contains(symbol))
+                .collect();
+            if !symbols.is_empty() {
+                info!(
+                    exchange = config.name,
+                    ?symbols,
+                    "restarting the collection."
+                );
+                let handle = spawn_collection(&config, symbols.clone(), self.writer_tx.clone())?;
+                tasks.push((symbols, handle));
+            }
         }
-        "bybitspot" => {
-            let topics = [
-                "orderbook.50.$symbol",    // 50档深度快照
-                "publicTrade.$symbol",     // 逐笔成交
-            ]
+        let added: Vec<_> = config
+            .symbols
             .iter()
-            .map(|topic| topic.to_string())
+            .filter(|symbol| !self.config.symbols.contains(symbol))
+            .cloned()
             .collect();
-
-            tokio::spawn(bybit::run_collection(
-                topics,
-                args.symbols,
-                writer_tx,
-                "spot",  // 现货
-            ))
+        if !added.is_empty() {
+            info!(exchange = config.name, symbols = ?added, "starting the collection.");
+            let handle = spawn_collection(&config, added.clone(), self.writer_tx.clone())?;
+            tasks.push((added, handle));
         }
-        "hyperliquid" => {
-            let subscriptions = ["trades", "l2Book", "bbo"]
-                .iter()
-                .map(|sub| sub.to_string())
-                .collect();
+        self.tasks = tasks;
+        self.config = config;
+        Ok(())
+    }
+}
 
-            tokio::spawn(hyperliquid::run_collection(
-                subscriptions,
-                args.symbols,
-                writer_tx,
-            ))
+/// Updates the collections whose symbols or streams are changed in the configuration file.
+fn reload(config_path: &str, collections: &mut [Collection]) -> Result<(), anyhow::Error> {
+    let config = Config::load(config_path)?;
+    let mut exchanges: HashMap<_, _> = config
+        .exchanges
+        .into_iter()
+        .map(|exchange| (exchange.name.clone(), exchange))
+        .collect();
+    for collection in collections.iter_mut() {
+        match exchanges.remove(&collection.config.name) {
+            Some(exchange) if exchange != collection.config => {
+                collection.update(exchange)?;
+            }
+            Some(_) => {}
+            None => {
+                warn!(
+                    exchange = collection.config.name,
+                    "removing an exchange requires a restart."
+                );
+            }
         }
-        "okx" | "okxspot" => {
-            // OKX WebSocket public channels
-            let channels = [
-                "trades",              // 交易数据
-                "bbo-tbt",             // 最优买卖价（tick-by-tick）
-                "books",               // 增量深度（400档，每100ms推送变化的档位）
-                "books5",              // 5档深度快照（每100ms推送完整的5档数据）
-            ]
-                .iter()
-                .map(|ch| ch.to_string())
-                .collect();
+    }
+    for name in exchanges.keys() {
+        warn!(exchange = name, "adding an exchange requires a restart.");
+    }
+    Ok(())
+}
 
-            // Transform symbols to OKX format (e.g., BTCUSDT -> BTC-USDT)
-            let okx_symbols: Vec<String> = args.symbols
-                .iter()
-                .map(|s| {
-                    // Assume format like BTCUSDT, convert to BTC-USDT
-                    let upper = s.to_uppercase();
-                    if upper.ends_with("USDT") {
-                        let base = &upper[..upper.len() - 4];
-                        format!("{}-USDT", base)
-                    } else if upper.ends_with("USDC") {
-                        let base = &upper[..upper.len() - 4];
-                        format!("{}-USDC", base)
-                    } else {
-                        upper
-                    }
-                })
-                .collect();
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<(), anyhow::Error> {
+    let args = Args::parse();
 
-            tokio::spawn(okx::run_collection(
-                okx_symbols,
-                channels,
-                writer_tx,
-            ))
-        }
-        "okxswap" | "okxfutures" => {
-            // OKX perpetual swap or futures
-            let channels = [
-                "trades",              // 交易数据
-                "bbo-tbt",             // 最优买卖价（tick-by-tick）
-                "books",               // 增量深度（400档，每100ms推送变化的档位）
-                "books5",              // 5档深度快照（每100ms推送完整的5档数据）
-            ]
-                .iter()
-                .map(|ch| ch.to_string())
-                .collect();
+    tracing_subscriber::fmt::init();
 
-            // Transform symbols to OKX SWAP format (e.g., BTCUSDT -> BTC-USDT-SWAP)
-            let okx_symbols: Vec<String> = args.symbols
-                .iter()
-                .map(|s| {
-                    let upper = s.to_uppercase();
-                    if upper.ends_with("USDT") {
-                        let base = &upper[..upper.len() - 4];
-                        format!("{}-USDT-SWAP", base)
-                    } else if upper.ends_with("USDC") {
-                        let base = &upper[..upper.len() - 4];
-                        format!("{}-USDC-SWAP", base)
-                    } else {
-                        upper
-                    }
-                })
-                .collect();
+    let config = match &args.config {
+        Some(config_path) => Config::load(config_path)?,
+        None => Config {
+            reload_interval: 0,
+            output: OutputConfig {
+                path: args.path.unwrap(),
+                rotation: Default::default(),
+                compression: Default::default(),
+                format: args.output,
+            },
+            exchanges: vec![ExchangeConfig {
+                name: args.exchange.unwrap(),
+                symbols: args.symbols,
+                streams: None,
+            }],
+        },
+    };
 
-            tokio::spawn(okx::run_collection(
-                okx_symbols,
-                channels,
-                writer_tx,
-            ))
-        }
-        exchange => {
-            return Err(anyhow!("{exchange} is not supported."));
+    let (tagged_tx, mut tagged_rx) = unbounded_channel();
+    let mut writers = Vec::new();
+    let mut collections = Vec::new();
+    for (index, exchange) in config.exchanges.iter().enumerate() {
+        let feed = Feed::from_exchange(&exchange.name)
+            .ok_or_else(|| anyhow!("{} is not supported.", exchange.name))?;
+        let path = if args.config.is_some() {
+            let path = format!("{}/{}", config.output.path, exchange.name);
+            fs::create_dir_all(&path)?;
+            path
+        } else {
+            config.output.path.clone()
+        };
+        if config.output.format != Output::Raw {
+            finalize_part_files(&path)?;
         }
-    };
+        writers.push(
+            Writer::new(&path, config.output.format, feed)
+                .rotation(config.output.rotation)
+                .compression(config.output.compression),
+        );
+        collections.push(Collection::spawn(
+            index,
+            exchange.clone(),
+            tagged_tx.clone(),
+        )?);
+    }
 
-    let mut writer = Writer::new(&args.path);
+    let config_path = args.config.filter(|_| config.reload_interval > 0);
+    let mut modified = match &config_path {
+        Some(config_path) => Some(fs::metadata(config_path)?.modified()?),
+        None => None,
+    };
+    let mut checker = interval(Duration::from_secs(config.reload_interval.max(1)));
     loop {
         select! {
             _ = signal::ctrl_c() => {
                 info!("ctrl-c received");
                 break;
             }
-            r = writer_rx.recv() => match r {
-                Some((recv_time, symbol, data)) => {
-                    if let Err(error) = writer.write(recv_time, symbol, data) {
+            _ = checker.tick(), if config_path.is_some() => {
+                let config_path = config_path.as_deref().unwrap();
+                let Ok(current) = fs::metadata(config_path).and_then(|m| m.modified()) else {
+                    continue;
+                };
+                if modified != Some(current) {
+                    modified = Some(current);
+                    if let Err(error) = reload(config_path, &mut collections) {
+                        error!(?error, "couldn't reload the configuration.");
+                    }
+                }
+            }
+            r = tagged_rx.recv() => match r {
+                Some((index, recv_time, symbol, data)) => {
+                    if let Err(error) = writers[index].write(recv_time, symbol, data) {
                         error!(?error, "write error");
                         break;
                     }
@@ -226,6 +313,5 @@ async fn main() -> Result<(), anyhow::Error> {
             }
         }
     }
-    // let _ = handle.await;
     Ok(())
 }
@@ -0,0 +1,591 @@
+use std::collections::BTreeMap;
+
+use hftbacktest::types::{
+    BUY_EVENT,
+    DEPTH_CLEAR_EVENT,
+    DEPTH_EVENT,
+    DEPTH_SNAPSHOT_EVENT,
+    Event,
+    SELL_EVENT,
+    TRADE_EVENT,
+};
+use serde_json::Value;
+
+use crate::error::ConnectorError;
+
+/// The message format of the collected data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Feed {
+    /// Binance streams and REST snapshots, including OKX, whose messages are transformed into the
+    /// Binance format.
+    Binance,
+    Bybit,
+    Hyperliquid,
+}
+
+impl Feed {
+    pub fn from_exchange(exchange: &str) -> Option<Self> {
+        match exchange {
+            "binancefutures" | "binancefuturesum" | "binancefuturescm" | "binance"
+            | "binancespot" | "okx" | "okxspot" | "okxswap" | "okxfutures" => Some(Feed::Binance),
+            "bybit" | "bybitspot" => Some(Feed::Bybit),
+            "hyperliquid" => Some(Feed::Hyperliquid),
+            _ => None,
+        }
+    }
+}
+
+fn event(ev: u64, exch_ts: i64, local_ts: i64, px: f64, qty: f64) -> Event {
+    Event {
+        ev,
+        exch_ts,
+        local_ts,
+        px,
+        qty,
+        order_id: 0,
+        ival: 0,
+        fval: 0.0,
+    }
+}
+
+fn str_f64(value: &Value) -> Result<f64, ConnectorError> {
+    value
+        .as_str()
+        .ok_or(ConnectorError::FormatError)?
+        .parse()
+        .map_err(|_| ConnectorError::FormatError)
+}
+
+fn ms_to_ns(value: &Value) -> Result<i64, ConnectorError> {
+    Ok(value.as_i64().ok_or(ConnectorError::FormatError)? * 1_000_000)
+}
+
+/// Parses the `[["price", "qty"], ...]` levels.
+fn levels(value: &Value) -> Result<Vec<(f64, f64)>, ConnectorError> {
+    value
+        .as_array()
+        .ok_or(ConnectorError::FormatError)?
+        .iter()
+        .map(|level| {
+            let level = level.as_array().ok_or(ConnectorError::FormatError)?;
+            match level.as_slice() {
+                [px, qty, ..] => Ok((str_f64(px)?, str_f64(qty)?)),
+                _ => Err(ConnectorError::FormatError),
+            }
+        })
+        .collect()
+}
+
+/// A price level key. The bit representation of a positive `f64` has the same order as its value.
+fn key(px: f64) -> u64 {
+    px.to_bits()
+}
+
+/// Tracks the market depth of a symbol from the normalized events, so that the book can be
+/// written as a snapshot when the file rotates.
+#[derive(Default)]
+struct Book {
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+}
+
+impl Book {
+    fn side(&mut self, side: u64) -> &mut BTreeMap<u64, f64> {
+        if side == BUY_EVENT {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        }
+    }
+
+    fn update(&mut self, side: u64, px: f64, qty: f64) {
+        if qty > 0.0 {
+            self.side(side).insert(key(px), qty);
+        } else {
+            self.side(side).remove(&key(px));
+        }
+    }
+
+    /// Clears the depth from the best price up to the given price, or entirely if it's `NaN`.
+    fn clear(&mut self, side: u64, clear_upto: f64) {
+        if clear_upto.is_nan() {
+            self.side(side).clear();
+        } else if side == BUY_EVENT {
+            self.bids.retain(|&px, _| px < key(clear_upto));
+        } else {
+            self.asks.retain(|&px, _| px > key(clear_upto));
+        }
+    }
+}
+
+/// Converts the messages of a symbol into [`Event`] rows, keeping track of its market depth.
+///
+/// The produced events have neither `EXCH_EVENT` nor `LOCAL_EVENT` set, so the event order needs
+/// to be corrected before the events are written.
+pub struct Normalizer {
+    feed: Feed,
+    book: Book,
+}
+
+impl Normalizer {
+    pub fn new(feed: Feed) -> Self {
+        Self {
+            feed,
+            book: Default::default(),
+        }
+    }
+
+    /// Converts the message received at `local_ts` and appends the events to `out`. Messages that
+    /// aren't converted, such as the partial book depth and the book ticker, are ignored.
+    pub fn normalize(
+        &mut self,
+        local_ts: i64,
+        data: &str,
+        out: &mut Vec<Event>,
+    ) -> Result<(), ConnectorError> {
+        let j: Value = serde_json::from_str(data)?;
+        match self.feed {
+            Feed::Binance => self.normalize_binance(local_ts, &j, out),
+            Feed::Bybit => self.normalize_bybit(local_ts, &j, out),
+            Feed::Hyperliquid => self.normalize_hyperliquid(local_ts, &j, out),
+        }
+    }
+
+    /// Appends the snapshot of the current market depth at `timestamp`, which clears the entire
+    /// market depth before refreshing it.
+    pub fn snapshot(&self, timestamp: i64, out: &mut Vec<Event>) {
+        for (side, depth) in [(BUY_EVENT, &self.book.bids), (SELL_EVENT, &self.book.asks)] {
+            if depth.is_empty() {
+                continue;
+            }
+            out.push(event(
+                DEPTH_CLEAR_EVENT | side,
+                timestamp,
+                timestamp,
+                f64::NAN,
+                0.0,
+            ));
+            let levels: Box<dyn Iterator<Item = (&u64, &f64)>> = if side == BUY_EVENT {
+                Box::new(depth.iter().rev())
+            } else {
+                Box::new(depth.iter())
+            };
+            for (&px, &qty) in levels {
+                out.push(event(
+                    DEPTH_SNAPSHOT_EVENT | side,
+                    timestamp,
+                    timestamp,
+                    f64::from_bits(px),
+                    qty,
+                ));
+            }
+        }
+    }
+
+    fn depth(&mut self, side: u64, exch_ts: i64, local_ts: i64, px: f64, qty: f64) -> Event {
+        self.book.update(side, px, qty);
+        event(DEPTH_EVENT | side, exch_ts, local_ts, px, qty)
+    }
+
+    /// Appends the exchange snapshot, which clears the market depth entirely if `full` is set, or
+    /// otherwise within its range, before refreshing it.
+    fn exch_snapshot(
+        &mut self,
+        exch_ts: i64,
+        local_ts: i64,
+        bids: &[(f64, f64)],
+        asks: &[(f64, f64)],
+        full: bool,
+        out: &mut Vec<Event>,
+    ) {
+        for (side, levels) in [(BUY_EVENT, bids), (SELL_EVENT, asks)] {
+            let clear_upto = match levels.last() {
+                _ if full => f64::NAN,
+                Some(&(px, _)) => px,
+                None => continue,
+            };
+            self.book.clear(side, clear_upto);
+            out.push(event(
+                DEPTH_CLEAR_EVENT | side,
+                exch_ts,
+                local_ts,
+                clear_upto,
+                0.0,
+            ));
+            for &(px, qty) in levels {
+                self.book.update(side, px, qty);
+                out.push(event(
+                    DEPTH_SNAPSHOT_EVENT | side,
+                    exch_ts,
+                    local_ts,
+                    px,
+                    qty,
+                ));
+            }
+        }
+    }
+
+    fn normalize_binance(
+        &mut self,
+        local_ts: i64,
+        j: &Value,
+        out: &mut Vec<Event>,
+    ) -> Result<(), ConnectorError> {
+        let Some(j_data) = j.get("data") else {
+            // The REST depth snapshot. The spot snapshot doesn't have the transaction time.
+            if let (Some(bids), Some(asks)) = (j.get("bids"), j.get("asks")) {
+                let exch_ts = match j.get("T") {
+                    Some(t) => ms_to_ns(t)?,
+                    None => local_ts,
+                };
+                self.exch_snapshot(
+                    exch_ts,
+                    local_ts,
+                    &levels(bids)?,
+                    &levels(asks)?,
+                    false,
+                    out,
+                );
+            }
+            return Ok(());
+        };
+        let stream = j.get("stream").and_then(|s| s.as_str()).unwrap_or("");
+        match j_data.get("e").and_then(|e| e.as_str()) {
+            Some("trade") => {
+                if j_data.get("X").is_some_and(|x| x != "MARKET") {
+                    return Ok(());
+                }
+                // The trade initiator's side. The side is unknown for OKX.
+                let side = match j_data.get("m").and_then(|m| m.as_bool()) {
+                    Some(true) => SELL_EVENT,
+                    Some(false) => BUY_EVENT,
+                    None => 0,
+                };
+                out.push(event(
+                    TRADE_EVENT | side,
+                    ms_to_ns(j_data.get("T").ok_or(ConnectorError::FormatError)?)?,
+                    local_ts,
+                    str_f64(j_data.get("p").ok_or(ConnectorError::FormatError)?)?,
+                    str_f64(j_data.get("q").ok_or(ConnectorError::FormatError)?)?,
+                ));
+            }
+            // The partial book depth streams have the same event type as the diff depth stream.
+            Some("depthUpdate")
+                if !["@depth5", "@depth10", "@depth20"]
+                    .iter()
+                    .any(|partial| stream.contains(partial)) =>
+            {
+                // The spot diff depth stream doesn't have the transaction time.
+                let exch_ts = ms_to_ns(
+                    j_data
+                        .get("T")
+                        .or(j_data.get("E"))
+                        .ok_or(ConnectorError::FormatError)?,
+                )?;
+                for (side, key) in [(BUY_EVENT, "b"), (SELL_EVENT, "a")] {
+                    for (px, qty) in levels(j_data.get(key).ok_or(ConnectorError::FormatError)?)? {
+                        out.push(self.depth(side, exch_ts, local_ts, px, qty));
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn normalize_bybit(
+        &mut self,
+        local_ts: i64,
+        j: &Value,
+        out: &mut Vec<Event>,
+    ) -> Result<(), ConnectorError> {
+        let topic = j
+            .get("topic")
+            .and_then(|t| t.as_str())
+            .ok_or(ConnectorError::FormatError)?;
+        let j_data = j.get("data").ok_or(ConnectorError::FormatError)?;
+        if topic.starts_with("orderbook.") {
+            let exch_ts = ms_to_ns(j.get("ts").ok_or(ConnectorError::FormatError)?)?;
+            let bids = levels(j_data.get("b").ok_or(ConnectorError::FormatError)?)?;
+            let asks = levels(j_data.get("a").ok_or(ConnectorError::FormatError)?)?;
+            if j.get("type").and_then(|t| t.as_str()) == Some("snapshot") {
+                // The snapshot replaces the entire market depth.
+                self.exch_snapshot(exch_ts, local_ts, &bids, &asks, true, out);
+            } else {
+                for (side, levels) in [(BUY_EVENT, bids), (SELL_EVENT, asks)] {
+                    for (px, qty) in levels {
+                        out.push(self.depth(side, exch_ts, local_ts, px, qty));
+                    }
+                }
+            }
+        } else if topic.starts_with("publicTrade.") {
+            for trade in j_data.as_array().ok_or(ConnectorError::FormatError)? {
+                let side = match trade.get("S").and_then(|s| s.as_str()) {
+                    Some("Buy") => BUY_EVENT,
+                    Some("Sell") => SELL_EVENT,
+                    _ => 0,
+                };
+                out.push(event(
+                    TRADE_EVENT | side,
+                    ms_to_ns(trade.get("T").ok_or(ConnectorError::FormatError)?)?,
+                    local_ts,
+                    str_f64(trade.get("p").ok_or(ConnectorError::FormatError)?)?,
+                    str_f64(trade.get("v").ok_or(ConnectorError::FormatError)?)?,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn normalize_hyperliquid(
+        &mut self,
+        local_ts: i64,
+        j: &Value,
+        out: &mut Vec<Event>,
+    ) -> Result<(), ConnectorError> {
+        let channel = j
+            .get("channel")
+            .and_then(|c| c.as_str())
+            .ok_or(ConnectorError::FormatError)?;
+        let Some(j_data) = j.get("data") else {
+            return Ok(());
+        };
+        match channel {
+            "trades" => {
+                for trade in j_data.as_array().ok_or(ConnectorError::FormatError)? {
+                    let side = match trade.get("side").and_then(|s| s.as_str()) {
+                        Some("A") => SELL_EVENT,
+                        _ => BUY_EVENT,
+                    };
+                    out.push(event(
+                        TRADE_EVENT | side,
+                        ms_to_ns(trade.get("time").ok_or(ConnectorError::FormatError)?)?,
+                        local_ts,
+                        str_f64(trade.get("px").ok_or(ConnectorError::FormatError)?)?,
+                        str_f64(trade.get("sz").ok_or(ConnectorError::FormatError)?)?,
+                    ));
+                }
+            }
+            "l2Book" => {
+                // Each message is a snapshot of the top levels, which is converted into the depth
+                // changes from the previous one. The levels that moved out of the book are
+                // deleted.
+                let exch_ts = ms_to_ns(j_data.get("time").ok_or(ConnectorError::FormatError)?)?;
+                let book = j_data
+                    .get("levels")
+                    .and_then(|l| l.as_array())
+                    .ok_or(ConnectorError::FormatError)?;
+                for (side, levels) in [BUY_EVENT, SELL_EVENT].into_iter().zip(book) {
+                    let mut depth = BTreeMap::new();
+                    for level in levels.as_array().ok_or(ConnectorError::FormatError)? {
+                        let px = str_f64(level.get("px").ok_or(ConnectorError::FormatError)?)?;
+                        let qty = str_f64(level.get("sz").ok_or(ConnectorError::FormatError)?)?;
+                        depth.insert(key(px), qty);
+                    }
+                    let prev = std::mem::take(self.book.side(side));
+                    for &px in prev.keys().filter(|px| !depth.contains_key(px)) {
+                        out.push(event(
+                            DEPTH_EVENT | side,
+                            exch_ts,
+                            local_ts,
+                            f64::from_bits(px),
+                            0.0,
+                        ));
+                    }
+                    for (&px, &qty) in depth.iter().filter(|&(px, qty)| prev.get(px) != Some(qty)) {
+                        out.push(event(
+                            DEPTH_EVENT | side,
+                            exch_ts,
+                            local_ts,
+                            f64::from_bits(px),
+                            qty,
+                        ));
+                    }
+                    *self.book.side(side) = depth;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(events: &[Event]) -> Vec<(u64, i64, f64, f64)> {
+        events
+            .iter()
+            .map(|ev| (ev.ev, ev.exch_ts, ev.px, ev.qty))
+            .collect()
+    }
+
+    fn book(normalizer: &Normalizer) -> Vec<(u64, f64, f64)> {
+        let mut out = Vec::new();
+        normalizer.snapshot(0, &mut out);
+        out.iter()
+            .filter(|ev| ev.ev & DEPTH_SNAPSHOT_EVENT == DEPTH_SNAPSHOT_EVENT)
+            .map(|ev| (ev.ev, ev.px, ev.qty))
+            .collect()
+    }
+
+    #[test]
+    fn test_binance_partial_stream() {
+        let mut normalizer = Normalizer::new(Feed::Binance);
+        let mut out = Vec::new();
+        let update = r#"{"e":"depthUpdate","E":1700000000100,"T":1700000000090,"s":"BTCUSDT","U":10,"u":12,"pu":9,"b":[["35000.10","1.500"]],"a":[["35000.20","0.000"]]}"#;
+
+        let partial = format!(r#"{{"stream":"btcusdt@depth20@100ms","data":{update}}}"#);
+        normalizer.normalize(1, &partial, &mut out).unwrap();
+        assert!(out.is_empty());
+
+        let diff = format!(r#"{{"stream":"btcusdt@depth@0ms","data":{update}}}"#);
+        normalizer.normalize(1, &diff, &mut out).unwrap();
+        assert_eq!(
+            rows(&out),
+            vec![
+                (DEPTH_EVENT | BUY_EVENT, 1700000000090000000, 35000.1, 1.5),
+                (DEPTH_EVENT | SELL_EVENT, 1700000000090000000, 35000.2, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binance_trade() {
+        let mut normalizer = Normalizer::new(Feed::Binance);
+        let mut out = Vec::new();
+        let trade = r#"{"stream":"btcusdt@trade","data":{"e":"trade","E":1700000000100,"T":1700000000095,"s":"BTCUSDT","t":1,"p":"35000.10","q":"0.010","X":"MARKET","m":true}}"#;
+        normalizer.normalize(1, trade, &mut out).unwrap();
+        let insurance = trade.replace("MARKET", "INSURANCE_FUND");
+        normalizer.normalize(1, &insurance, &mut out).unwrap();
+        assert_eq!(
+            rows(&out),
+            vec![(TRADE_EVENT | SELL_EVENT, 1700000000095000000, 35000.1, 0.01)]
+        );
+    }
+
+    #[test]
+    fn test_bybit_snapshot_and_delta() {
+        let mut normalizer = Normalizer::new(Feed::Bybit);
+        let mut out = Vec::new();
+        let snapshot = r#"{"topic":"orderbook.50.BTCUSDT","type":"snapshot","ts":1700000000000,"data":{"s":"BTCUSDT","b":[["35000.0","1.0"],["34999.5","2.0"]],"a":[["35000.5","3.0"]],"u":1,"seq":100},"cts":1699999999990}"#;
+        normalizer.normalize(1, snapshot, &mut out).unwrap();
+        let ts = 1700000000000000000;
+        assert_eq!(
+            rows(&out)
+                .iter()
+                .map(|&(ev, exch_ts, px, qty)| (ev, exch_ts, px.is_nan(), qty))
+                .collect::<Vec<_>>(),
+            vec![
+                (DEPTH_CLEAR_EVENT | BUY_EVENT, ts, true, 0.0),
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, ts, false, 1.0),
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, ts, false, 2.0),
+                (DEPTH_CLEAR_EVENT | SELL_EVENT, ts, true, 0.0),
+                (DEPTH_SNAPSHOT_EVENT | SELL_EVENT, ts, false, 3.0),
+            ]
+        );
+
+        out.clear();
+        let delta = r#"{"topic":"orderbook.50.BTCUSDT","type":"delta","ts":1700000000020,"data":{"s":"BTCUSDT","b":[["34999.5","0"]],"a":[["35001.0","4.0"]],"u":2,"seq":101},"cts":1700000000010}"#;
+        normalizer.normalize(2, delta, &mut out).unwrap();
+        let ts = 1700000000020000000;
+        assert_eq!(
+            rows(&out),
+            vec![
+                (DEPTH_EVENT | BUY_EVENT, ts, 34999.5, 0.0),
+                (DEPTH_EVENT | SELL_EVENT, ts, 35001.0, 4.0),
+            ]
+        );
+        assert_eq!(
+            book(&normalizer),
+            vec![
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, 35000.0, 1.0),
+                (DEPTH_SNAPSHOT_EVENT | SELL_EVENT, 35000.5, 3.0),
+                (DEPTH_SNAPSHOT_EVENT | SELL_EVENT, 35001.0, 4.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hyperliquid_l2book_diff() {
+        let mut normalizer = Normalizer::new(Feed::Hyperliquid);
+        let mut out = Vec::new();
+        let first = r#"{"channel":"l2Book","data":{"coin":"BTC","time":1700000000000,"levels":[[{"px":"35000","sz":"1.0","n":1},{"px":"34999","sz":"2.0","n":2}],[{"px":"35001","sz":"3.0","n":1}]]}}"#;
+        normalizer.normalize(1, first, &mut out).unwrap();
+        assert_eq!(out.len(), 3);
+
+        out.clear();
+        let second = r#"{"channel":"l2Book","data":{"coin":"BTC","time":1700000000500,"levels":[[{"px":"35000","sz":"1.5","n":1},{"px":"34999","sz":"2.0","n":2}],[{"px":"35002","sz":"3.0","n":1}]]}}"#;
+        normalizer.normalize(2, second, &mut out).unwrap();
+        let ts = 1700000000500000000;
+        // Only the changed levels are written, and the levels that moved out are deleted.
+        assert_eq!(
+            rows(&out),
+            vec![
+                (DEPTH_EVENT | BUY_EVENT, ts, 35000.0, 1.5),
+                (DEPTH_EVENT | SELL_EVENT, ts, 35001.0, 0.0),
+                (DEPTH_EVENT | SELL_EVENT, ts, 35002.0, 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_partial_snapshot_clears_its_range() {
+        let mut normalizer = Normalizer::new(Feed::Binance);
+        let mut out = Vec::new();
+        let update = r#"{"stream":"btcusdt@depth","data":{"e":"depthUpdate","E":1700000000000,"T":1700000000000,"s":"BTCUSDT","U":1,"u":1,"pu":0,"b":[["100.0","1"],["99.0","1"],["98.0","1"]],"a":[["101.0","1"],["103.0","1"]]}}"#;
+        normalizer.normalize(1, update, &mut out).unwrap();
+
+        // The REST snapshot covers the levels down to 99.5 and up to 102, so 101 is deleted while
+        // the levels beyond them are kept.
+        out.clear();
+        let snapshot = r#"{"lastUpdateId":2,"E":1700000000100,"T":1700000000100,"bids":[["100.0","2"],["99.5","1"]],"asks":[["102.0","1"]]}"#;
+        normalizer.normalize(2, snapshot, &mut out).unwrap();
+        assert_eq!(
+            rows(&out)[0],
+            (
+                DEPTH_CLEAR_EVENT | BUY_EVENT,
+                1700000000100000000,
+                99.5,
+                0.0
+            )
+        );
+        assert_eq!(
+            book(&normalizer),
+            vec![
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, 100.0, 2.0),
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, 99.5, 1.0),
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, 99.0, 1.0),
+                (DEPTH_SNAPSHOT_EVENT | BUY_EVENT, 98.0, 1.0),
+                (DEPTH_SNAPSHOT_EVENT | SELL_EVENT, 102.0, 1.0),
+                (DEPTH_SNAPSHOT_EVENT | SELL_EVENT, 103.0, 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rotation_snapshot() {
+        let mut normalizer = Normalizer::new(Feed::Bybit);
+        let mut out = Vec::new();
+        normalizer.snapshot(5, &mut out);
+        assert!(out.is_empty());
+
+        let snapshot = r#"{"topic":"orderbook.1.BTCUSDT","type":"snapshot","ts":1700000000000,"data":{"s":"BTCUSDT","b":[["10.0","1.0"],["9.0","2.0"]],"a":[["11.0","3.0"],["12.0","4.0"]],"u":1,"seq":1},"cts":1700000000000}"#;
+        normalizer.normalize(1, snapshot, &mut out).unwrap();
+        out.clear();
+
+        normalizer.snapshot(5, &mut out);
+        assert!(out.iter().all(|ev| ev.exch_ts == 5 && ev.local_ts == 5));
+        assert_eq!(out[0].ev, DEPTH_CLEAR_EVENT | BUY_EVENT);
+        assert_eq!(out[3].ev, DEPTH_CLEAR_EVENT | SELL_EVENT);
+        // The bids are written from the best price.
+        assert_eq!(
+            out.iter()
+                .filter(|ev| ev.ev & DEPTH_SNAPSHOT_EVENT == DEPTH_SNAPSHOT_EVENT)
+                .map(|ev| ev.px)
+                .collect::<Vec<_>>(),
+            vec![10.0, 9.0, 11.0, 12.0]
+        );
+    }
+}
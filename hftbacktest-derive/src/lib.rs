@@ -222,18 +222,16 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
 
                         let l3 = qm_ident_str == "L3FIFOQueueModel";
                         let (local_ident, exch_ident) = if l3 {
-                            // todo: L3PartialFillExchange is unsupported. This is verified within
-                            //  the build functions in the py-hftbacktest module.
                             (
                                 Ident::new("L3Local", Span::call_site()),
-                                Ident::new("L3NoPartialFillExchange", Span::call_site()),
+                                Ident::new(&format!("L3{}", em_ident), Span::call_site()),
                             )
                         } else {
                             (Ident::new("Local", Span::call_site()), em_ident.clone())
                         };
 
                         let depth_construct = match marketdepth.to_string().as_str() {
-                            "HashMapMarketDepth" => {
+                            "HashMapMarketDepth" | "FusedHashMapMarketDepth" => {
                                 quote! {
                                     #marketdepth::new(#asset.tick_size, #asset.lot_size);
                                 }
@@ -262,16 +260,22 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                             let reader = if #asset.latency_offset == 0 {
                                 Reader::builder()
                                     .parallel_load(#asset.parallel_load)
+                                    .mmap(#asset.mmap)
+                                    .chunk_size(#asset.chunk_size)
+                                    .start(#asset.start)
+                                    .end(#asset.end)
                                     .data(#asset.data.clone())
-                                    .build()
-                                    .unwrap()
+                                    .build()?
                             } else {
                                 Reader::builder()
                                     .parallel_load(#asset.parallel_load)
+                                    .mmap(#asset.mmap)
+                                    .chunk_size(#asset.chunk_size)
+                                    .start(#asset.start)
+                                    .end(#asset.end)
                                     .data(#asset.data.clone())
                                     .preprocessor(FeedLatencyAdjustment::new(#asset.latency_offset))
-                                    .build()
-                                    .unwrap()
+                                    .build()?
                             };
 
                             let asset_type = #at_ident::new(#(#at_args.clone()),*);
@@ -283,7 +287,11 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                             let mut market_depth = #depth_construct;
                             match #asset.initial_snapshot.as_ref() {
                                 Some(DataSource::File(file)) => {
-                                    let data = read_npz_file(&file, "data").unwrap();
+                                    let data = read_npz_file(&file, "data")?;
+                                    market_depth.apply_snapshot(&data);
+                                }
+                                Some(DataSource::Columnar(file)) => {
+                                    let data = read_columnar_file(&file)?;
                                     market_depth.apply_snapshot(&data);
                                 }
                                 Some(DataSource::Data(data)) => {
@@ -302,7 +310,11 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
                             let mut market_depth = #depth_construct;
                             match #asset.initial_snapshot.as_ref() {
                                 Some(DataSource::File(file)) => {
-                                    let data = read_npz_file(&file, "data").unwrap();
+                                    let data = read_npz_file(&file, "data")?;
+                                    market_depth.apply_snapshot(&data);
+                                }
+                                Some(DataSource::Columnar(file)) => {
+                                    let data = read_columnar_file(&file)?;
                                     market_depth.apply_snapshot(&data);
                                 }
                                 Some(DataSource::Data(data)) => {
@@ -342,6 +354,14 @@ pub fn build_asset(input: TokenStream) -> TokenStream {
             &#asset.fee_model,
         ) {
             #(#arms)*
+            // The combinations not listed are raised as a `ValueError` on the Python side instead
+            // of panicking.
+            #[allow(unreachable_patterns)]
+            _ => {
+                return Err(PyErr::new::<PyValueError, _>(
+                    "The combination of the asset type and the models is not supported.",
+                ));
+            }
         }
     };
 
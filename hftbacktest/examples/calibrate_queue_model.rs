@@ -0,0 +1,53 @@
+use clap::Parser;
+use hftbacktest::{
+    backtest::{
+        data::read_npz_file,
+        models::{L3QueueCalibrator, Probability},
+    },
+    types::Event,
+};
+
+/// Fits the queue position probability from Level 3 Market-By-Order data, which can be used with
+/// `ProbQueueModel` for the Level 2 feed of the same venue.
+#[derive(Parser, Debug)]
+#[command(about = None, long_about = None)]
+struct Args {
+    #[arg(long)]
+    output_path: String,
+    #[arg(long, num_args = 1..)]
+    data_files: Vec<String>,
+    #[arg(long)]
+    tick_size: f64,
+    #[arg(long)]
+    lot_size: f64,
+    #[arg(long, default_value_t = 20)]
+    num_bins: usize,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut calibrator = L3QueueCalibrator::new(args.tick_size, args.lot_size);
+    for file in &args.data_files {
+        let data = read_npz_file::<Event>(file, "data").unwrap();
+        for row in 0..data.len() {
+            calibrator.process(&data[row]).unwrap();
+        }
+    }
+    println!("{} cancellations are collected.", calibrator.num_samples());
+
+    let Some(table) = calibrator.fit_table(args.num_bins) else {
+        return;
+    };
+    let power = calibrator.fit_power().unwrap();
+    for (i, value) in table.table().iter().enumerate() {
+        let front = i as f64;
+        let back = (table.table().len() - 1 - i) as f64;
+        println!(
+            "front ratio {:.3}: table {value:.4}, power {:.4}",
+            front / (front + back),
+            power.prob(front, back)
+        );
+    }
+    table.save(&args.output_path).unwrap();
+}
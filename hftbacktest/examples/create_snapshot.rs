@@ -0,0 +1,114 @@
+use std::error::Error;
+
+use clap::{Parser, Subcommand};
+use hftbacktest::{
+    backtest::data::{DataSource, create_snapshot, read_npz_file, write_npz_file},
+    prelude::{ApplySnapshot, HashMapMarketDepth},
+};
+
+/// Creates market depth snapshots that can be used as the initial snapshot of a backtest.
+#[derive(Parser, Debug)]
+#[command(about = None, long_about = None)]
+struct Args {
+    #[arg(long)]
+    tick_size: f64,
+    #[arg(long)]
+    lot_size: f64,
+    /// The snapshot from which the replay starts.
+    #[arg(long)]
+    initial_snapshot: Option<String>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Replays the files up to the local timestamp and writes the snapshot at that time.
+    At {
+        /// The data files to replay, in order.
+        #[arg(long, num_args = 1.., required = true)]
+        files: Vec<String>,
+        #[arg(long)]
+        timestamp: i64,
+        #[arg(long)]
+        output: String,
+    },
+    /// Writes the End-Of-Day snapshot of each date in the range, replaying each day from the
+    /// previous day's snapshot.
+    Eod {
+        /// The data file path, in which `{date}` is replaced with the date in YYYYMMDD format.
+        #[arg(long)]
+        pattern: String,
+        /// The output file path, in which `{date}` is replaced with the date in YYYYMMDD format.
+        #[arg(long)]
+        output_pattern: String,
+        /// The first date in YYYYMMDD format.
+        #[arg(long)]
+        start_date: u32,
+        /// The last date in YYYYMMDD format, inclusive.
+        #[arg(long)]
+        end_date: u32,
+    },
+}
+
+fn is_leap_year(year: u32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+fn next_date(date: u32) -> u32 {
+    let (year, month, day) = (date / 10000, date / 100 % 100, date % 100);
+    let days_in_month = match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if day < days_in_month {
+        date + 1
+    } else if month < 12 {
+        year * 10000 + (month + 1) * 100 + 1
+    } else {
+        (year + 1) * 10000 + 101
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let mut depth = HashMapMarketDepth::new(args.tick_size, args.lot_size);
+    if let Some(initial_snapshot) = &args.initial_snapshot {
+        depth.apply_snapshot(&read_npz_file(initial_snapshot, "data")?);
+    }
+
+    match args.command {
+        Command::At {
+            files,
+            timestamp,
+            output,
+        } => {
+            let data: Vec<_> = files.into_iter().map(DataSource::File).collect();
+            let snapshot = create_snapshot(&mut depth, &data, timestamp)?;
+            write_npz_file(&output, "data", &snapshot)?;
+            println!("{output}: {} levels", snapshot.len());
+        }
+        Command::Eod {
+            pattern,
+            output_pattern,
+            start_date,
+            end_date,
+        } => {
+            // The market depth carries over to the next day, so each day starts from the previous
+            // day's End-Of-Day snapshot.
+            let mut date = start_date;
+            while date <= end_date {
+                let file = pattern.replace("{date}", &date.to_string());
+                let output = output_pattern.replace("{date}", &date.to_string());
+                let snapshot = create_snapshot(&mut depth, &[DataSource::File(file)], i64::MAX)?;
+                write_npz_file(&output, "data", &snapshot)?;
+                println!("{output}: {} levels", snapshot.len());
+                date = next_date(date);
+            }
+        }
+    }
+    Ok(())
+}
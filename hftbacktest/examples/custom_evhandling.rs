@@ -42,7 +42,7 @@ impl<AT, LM, MD, FM> LocalProcessor<MD> for LocalEvHandler<AT, LM, MD, FM>
 where
     AT: AssetType,
     LM: LatencyModel,
-    MD: MarketDepth + L2MarketDepth,
+    MD: MarketDepth + L2MarketDepth + 'static,
     FM: FeeModel,
 {
     fn submit_order(
@@ -123,7 +123,7 @@ impl<AT, LM, MD, FM> Processor for LocalEvHandler<AT, LM, MD, FM>
 where
     AT: AssetType,
     LM: LatencyModel,
-    MD: MarketDepth + L2MarketDepth,
+    MD: MarketDepth + L2MarketDepth + 'static,
     FM: FeeModel,
 {
     fn event_seen_timestamp(&self, event: &Event) -> Option<i64> {
@@ -0,0 +1,120 @@
+use std::{hint::black_box, time::Instant};
+
+use clap::Parser;
+use hftbacktest::depth::{
+    BTreeMarketDepth,
+    HashMapMarketDepth,
+    L2MarketDepth,
+    MarketDepth,
+    ROIVectorMarketDepth,
+    RingBufferMarketDepth,
+};
+
+/// Benchmarks the market depth implementations with a synthetic depth feed, in which the
+/// mid-price follows a random walk and the levels around it are updated and deleted.
+#[derive(Parser, Debug)]
+#[command(about = None, long_about = None)]
+struct Args {
+    #[arg(long, default_value_t = 10_000_000)]
+    num_updates: usize,
+    /// The maximum distance of the updated levels from the mid-price in ticks.
+    #[arg(long, default_value_t = 500)]
+    max_offset: u64,
+    /// The maximum step of the mid-price random walk in ticks.
+    #[arg(long, default_value_t = 2)]
+    max_step: u64,
+}
+
+struct Update {
+    bid: bool,
+    px: f64,
+    qty: f64,
+}
+
+fn generate(args: &Args) -> (Vec<Update>, f64, f64) {
+    let mut seed = 42u64;
+    let mut next = || {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        seed >> 33
+    };
+    let mut mid = 1_000_000i64;
+    let mut min_mid = mid;
+    let mut max_mid = mid;
+    let mut updates = Vec::with_capacity(args.num_updates);
+    for _ in 0..args.num_updates {
+        mid += (next() % (2 * args.max_step + 1)) as i64 - args.max_step as i64;
+        min_mid = min_mid.min(mid);
+        max_mid = max_mid.max(mid);
+        // Updates concentrate near the mid-price.
+        let offset = (next() % (args.max_offset + 1)) * (next() % (args.max_offset + 1))
+            / (args.max_offset + 1);
+        let qty = if next() % 3 == 0 {
+            0.0
+        } else {
+            (next() % 100 + 1) as f64
+        };
+        let bid = next() % 2 == 0;
+        let px_tick = if bid {
+            mid - 1 - offset as i64
+        } else {
+            mid + 1 + offset as i64
+        };
+        updates.push(Update {
+            bid,
+            px: px_tick as f64,
+            qty,
+        });
+    }
+    let margin = args.max_offset as f64 + 1.0;
+    (updates, min_mid as f64 - margin, max_mid as f64 + margin)
+}
+
+fn run<MD>(name: &str, mut depth: MD, updates: &[Update])
+where
+    MD: MarketDepth + L2MarketDepth,
+{
+    let start = Instant::now();
+    let mut checksum = 0i64;
+    for (i, update) in updates.iter().enumerate() {
+        if update.bid {
+            depth.update_bid_depth(update.px, update.qty, i as i64);
+        } else {
+            depth.update_ask_depth(update.px, update.qty, i as i64);
+        }
+        checksum = checksum.wrapping_add(depth.best_bid_tick() ^ depth.best_ask_tick());
+    }
+    let elapsed = start.elapsed();
+    black_box(checksum);
+    println!(
+        "{name:<24}{:>10.1} ns/update",
+        elapsed.as_nanos() as f64 / updates.len() as f64
+    );
+}
+
+fn main() {
+    let args = Args::parse();
+    let (updates, roi_lb, roi_ub) = generate(&args);
+
+    run(
+        "HashMapMarketDepth",
+        HashMapMarketDepth::new(1.0, 1.0),
+        &updates,
+    );
+    run(
+        "BTreeMarketDepth",
+        BTreeMarketDepth::new(1.0, 1.0),
+        &updates,
+    );
+    run(
+        "ROIVectorMarketDepth",
+        ROIVectorMarketDepth::new(1.0, 1.0, roi_lb, roi_ub),
+        &updates,
+    );
+    run(
+        "RingBufferMarketDepth",
+        RingBufferMarketDepth::new(1.0, 1.0),
+        &updates,
+    );
+}
@@ -0,0 +1,141 @@
+use std::{error::Error, fs::File, io::BufReader};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use hftbacktest::backtest::{
+    data::{
+        OrderLatencyMode,
+        RollingStat,
+        generate_order_latency,
+        order_latency_from_log,
+        read_npz_file,
+        write_npz_file,
+    },
+    models::OrderLatencyRow,
+};
+
+/// Builds order latency data for `IntpOrderLatency`.
+#[derive(Parser, Debug)]
+#[command(about = None, long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Mode {
+    Multiplier,
+    Percentile,
+    Rolling,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generates artificial order latency from the feed latency of the feed data file.
+    Feed {
+        #[arg(long)]
+        feed_file: String,
+        #[arg(long)]
+        output: String,
+        #[arg(long, value_enum, default_value = "multiplier")]
+        mode: Mode,
+        #[arg(long, default_value_t = 1.0)]
+        mul_entry: f64,
+        #[arg(long, default_value_t = 0.0)]
+        offset_entry: f64,
+        #[arg(long, default_value_t = 1.0)]
+        mul_resp: f64,
+        #[arg(long, default_value_t = 0.0)]
+        offset_resp: f64,
+        #[arg(long, default_value_t = 1_000_000_000)]
+        resampling_ns: i64,
+        /// The rolling window in nanoseconds, for the rolling mode.
+        #[arg(long, default_value_t = 60_000_000_000)]
+        window: i64,
+        /// Uses the rolling max instead of the rolling mean, for the rolling mode.
+        #[arg(long)]
+        rolling_max: bool,
+        /// The order latency file whose latency distribution is followed, for the percentile
+        /// mode, such as the output of the `log` command.
+        #[arg(long)]
+        latency_file: Option<String>,
+    },
+    /// Builds order latency from the logs of a live bot, such as the `logging_order_latency`
+    /// example.
+    Log {
+        #[arg(long, num_args = 1.., required = true)]
+        log_files: Vec<String>,
+        #[arg(long)]
+        output: String,
+    },
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    match args.command {
+        Command::Feed {
+            feed_file,
+            output,
+            mode,
+            mul_entry,
+            offset_entry,
+            mul_resp,
+            offset_resp,
+            resampling_ns,
+            window,
+            rolling_max,
+            latency_file,
+        } => {
+            let mode = match mode {
+                Mode::Multiplier => OrderLatencyMode::Multiplier {
+                    mul_entry,
+                    offset_entry,
+                    mul_resp,
+                    offset_resp,
+                },
+                Mode::Percentile => {
+                    let latency_file = latency_file
+                        .ok_or("`--latency-file` is required for the percentile mode")?;
+                    let latency = read_npz_file::<OrderLatencyRow>(&latency_file, "data")?;
+                    // Rejected orders don't represent the latency.
+                    let rows: Vec<_> = latency
+                        .as_slice()
+                        .iter()
+                        .filter(|row| row.exch_ts > 0)
+                        .collect();
+                    OrderLatencyMode::PercentileMapped {
+                        entry: rows.iter().map(|row| row.exch_ts - row.req_ts).collect(),
+                        resp: rows.iter().map(|row| row.resp_ts - row.exch_ts).collect(),
+                    }
+                }
+                Mode::Rolling => OrderLatencyMode::RollingWindow {
+                    window,
+                    stat: if rolling_max {
+                        RollingStat::Max
+                    } else {
+                        RollingStat::Mean
+                    },
+                    mul_entry,
+                    offset_entry,
+                    mul_resp,
+                    offset_resp,
+                },
+            };
+            let data = read_npz_file(&feed_file, "data")?;
+            let latency = generate_order_latency(data.as_slice(), &mode, resampling_ns)?;
+            write_npz_file(&output, "data", &latency)?;
+            println!("{} rows are written to {output}", latency.len());
+        }
+        Command::Log { log_files, output } => {
+            let mut latency = Vec::new();
+            for log_file in log_files {
+                latency.extend(order_latency_from_log(BufReader::new(File::open(
+                    log_file,
+                )?))?);
+            }
+            latency.sort_by_key(|row| row.req_ts);
+            write_npz_file(&output, "data", &latency)?;
+            println!("{} rows are written to {output}", latency.len());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use hftbacktest::{
+    backtest::{
+        Backtest,
+        ExchangeKind,
+        L2AssetBuilder,
+        assettype::LinearAsset,
+        data::{DataSource, read_npz_file},
+        models::{
+            CommonFees,
+            ConstantLatency,
+            PowerProbQueueFunc3,
+            ProbQueueModel,
+            TradingValueFeeModel,
+        },
+    },
+    bar::{BarBot, BarKind},
+    prelude::{ApplySnapshot, Bot, HashMapMarketDepth, MarketDepth},
+};
+
+fn prepare_backtest() -> Backtest<HashMapMarketDepth> {
+    let data = (20240501..20240532)
+        .map(|date| DataSource::File(format!("1000SHIBUSDT_{date}.npz")))
+        .collect();
+
+    Backtest::builder()
+        .add_asset(
+            L2AssetBuilder::new()
+                .data(data)
+                .latency_model(ConstantLatency::new(10_000_000, 10_000_000))
+                .asset_type(LinearAsset::new(1.0))
+                .fee_model(TradingValueFeeModel::new(CommonFees::new(-0.00005, 0.0007)))
+                .exchange(ExchangeKind::NoPartialFillExchange)
+                .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                .depth(|| {
+                    let mut depth = HashMapMarketDepth::new(0.000001, 1.0);
+                    depth.apply_snapshot(
+                        &read_npz_file("1000SHIBUSDT_20240501_SOD.npz", "data").unwrap(),
+                    );
+                    depth
+                })
+                // The bars are aggregated from the last trades.
+                .last_trades_capacity(10_000)
+                .build()
+                .unwrap(),
+        )
+        .build()
+        .unwrap()
+}
+
+fn main() {
+    tracing_subscriber::fmt::init();
+
+    // Runs on the volume clock, so a bar closes every 10M traded.
+    let mut hbt = BarBot::new(prepare_backtest(), 0, BarKind::Volume(10_000_000.0));
+    while let Some(bar) = hbt.next_bar().unwrap() {
+        let depth = hbt.bot().depth(0);
+        let imbalance = (bar.buy_volume - bar.sell_volume) / bar.volume;
+        println!(
+            "{} close={:.6} vwap={:.6} trades={} imbalance={:.3} mid={:.6}",
+            bar.close_ts,
+            bar.close,
+            bar.vwap(),
+            bar.trade_count,
+            imbalance,
+            (depth.best_bid() + depth.best_ask()) / 2.0
+        );
+    }
+    hbt.bot_mut().close().unwrap();
+}
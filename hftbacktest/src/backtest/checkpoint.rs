@@ -0,0 +1,251 @@
+use std::{fs, path::Path};
+
+use bincode::{Decode, Encode, config};
+
+use crate::{
+    backtest::{BacktestError, data::Data},
+    depth::{ApplySnapshot, L3MarketDepth, MarketDepth},
+    types::{DEPTH_CLEAR_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, OrderId, Side},
+};
+
+const CHECKPOINT_VERSION: u32 = 2;
+
+/// Captures the complete state of a [`Backtest`](crate::backtest::Backtest) at a timestamp,
+/// including the market depth, the orders and their queue positions, the orders in transit, the
+/// trading state, and the position of the data readers.
+///
+/// A checkpoint doesn't contain the configuration of the backtest, such as the data, the models,
+/// and the asset types. To resume from a checkpoint, the backtest must be built with the same
+/// configuration as the one from which the checkpoint was taken; see
+/// [`BacktestBuilder::resume_from`](crate::backtest::BacktestBuilder::resume_from). Since a
+/// checkpoint can be applied any number of times, many strategy variants can be forked from a
+/// single warmed-up point.
+#[derive(Clone, Debug, Encode, Decode)]
+pub struct Checkpoint {
+    version: u32,
+    pub(crate) timestamp: i64,
+    pub(crate) evs: Vec<i64>,
+    pub(crate) assets: Vec<AssetCheckpoint>,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+pub(crate) struct AssetCheckpoint {
+    pub local: ProcessorCheckpoint,
+    pub exch: ProcessorCheckpoint,
+}
+
+#[derive(Clone, Debug, Encode, Decode)]
+pub(crate) struct ProcessorCheckpoint {
+    /// The number of data retrieved by the reader.
+    pub cursor: usize,
+    /// The row of the data being processed.
+    pub row: Option<usize>,
+    /// The market depth snapshot, if the processor exposes its market depth; see
+    /// [`Processor::market_depth`](crate::backtest::proc::Processor::market_depth).
+    pub depth: Option<Vec<Event>>,
+    /// The state encoded by the processor.
+    pub state: Vec<u8>,
+}
+
+/// Returns the snapshot of the market depth, whose events carry the timestamps of the market
+/// depth. An empty market depth is stored as a clear event, so that its timestamp is retained.
+pub(crate) fn save_depth<MD>(depth: &MD) -> Vec<Event>
+where
+    MD: MarketDepth + ApplySnapshot,
+{
+    let mut events = depth.snapshot();
+    if events.is_empty() {
+        events.push(Event {
+            ev: EXCH_EVENT | LOCAL_EVENT | DEPTH_CLEAR_EVENT,
+            exch_ts: depth.timestamp(),
+            local_ts: depth.timestamp(),
+            px: 0.0,
+            qty: 0.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        });
+    }
+    events
+}
+
+/// Restores the market depth from the snapshot returned by [`save_depth`].
+pub(crate) fn load_depth<MD>(depth: &mut MD, events: &[Event])
+where
+    MD: ApplySnapshot,
+{
+    if events.is_empty() {
+        depth.apply_snapshot(&Data::empty());
+    } else {
+        depth.apply_snapshot(&Data::from_data(events));
+    }
+}
+
+/// The orders in the Level-3 market depth and the timestamp of the market depth.
+#[derive(Clone, Debug, Encode, Decode)]
+pub(crate) struct L3DepthCheckpoint {
+    timestamp: i64,
+    // (order_id, side, price_tick, qty, timestamp) of the orders in the order book.
+    orders: Vec<(OrderId, Side, i64, f64, i64)>,
+}
+
+/// Returns the orders in the Level-3 market depth, in the order of their timestamps, and the
+/// timestamp of the market depth.
+pub(crate) fn save_l3_depth<MD>(depth: &MD) -> L3DepthCheckpoint
+where
+    MD: L3MarketDepth,
+{
+    let mut orders: Vec<_> = depth
+        .orders()
+        .values()
+        .map(|order| {
+            (
+                order.order_id,
+                order.side,
+                order.price_tick,
+                order.qty,
+                order.timestamp,
+            )
+        })
+        .collect();
+    orders.sort_by_key(|&(order_id, _, _, _, timestamp)| (timestamp, order_id));
+    L3DepthCheckpoint {
+        timestamp: depth.timestamp(),
+        orders,
+    }
+}
+
+/// Restores the Level-3 market depth from the orders returned by [`save_l3_depth`]. Since adding
+/// an order updates the timestamp of the market depth, the saved timestamp is set afterward.
+pub(crate) fn load_l3_depth<MD>(depth: &mut MD, saved: L3DepthCheckpoint) -> Result<(), MD::Error>
+where
+    MD: L3MarketDepth,
+{
+    depth.clear_orders(Side::None);
+    let tick_size = depth.tick_size();
+    for (order_id, side, price_tick, qty, timestamp) in saved.orders {
+        let px = price_tick as f64 * tick_size;
+        if side == Side::Buy {
+            depth.add_buy_order(order_id, px, qty, timestamp)?;
+        } else {
+            depth.add_sell_order(order_id, px, qty, timestamp)?;
+        }
+    }
+    depth.set_timestamp(saved.timestamp);
+    Ok(())
+}
+
+impl Checkpoint {
+    pub(crate) fn new(timestamp: i64, evs: Vec<i64>, assets: Vec<AssetCheckpoint>) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            timestamp,
+            evs,
+            assets,
+        }
+    }
+
+    /// Returns the backtest timestamp at which the checkpoint was taken.
+    pub fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    /// Returns the number of assets in the checkpoint.
+    pub fn num_assets(&self) -> usize {
+        self.assets.len()
+    }
+
+    /// Encodes the checkpoint into bytes.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BacktestError> {
+        encode(self)
+    }
+
+    /// Decodes a checkpoint from bytes encoded by [`to_bytes`](Checkpoint::to_bytes).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BacktestError> {
+        let checkpoint: Self = decode(bytes)?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(BacktestError::InvalidCheckpoint(format!(
+                "unsupported version {}",
+                checkpoint.version
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Saves the checkpoint to the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), BacktestError> {
+        fs::write(path, self.to_bytes()?)?;
+        Ok(())
+    }
+
+    /// Loads a checkpoint from the file saved by [`save`](Checkpoint::save).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, BacktestError> {
+        Self::from_bytes(&fs::read(path)?)
+    }
+}
+
+/// Encodes the value to be stored in a checkpoint. This can be used to implement
+/// [`Processor::save_state`](crate::backtest::proc::Processor::save_state) for a custom processor.
+pub fn encode<T: Encode>(value: &T) -> Result<Vec<u8>, BacktestError> {
+    bincode::encode_to_vec(value, config::standard())
+        .map_err(|err| BacktestError::InvalidCheckpoint(err.to_string()))
+}
+
+/// Decodes the value stored in a checkpoint by [`encode`].
+pub fn decode<T: Decode<()>>(bytes: &[u8]) -> Result<T, BacktestError> {
+    let (value, len) = bincode::decode_from_slice(bytes, config::standard())
+        .map_err(|err| BacktestError::InvalidCheckpoint(err.to_string()))?;
+    if len != bytes.len() {
+        return Err(BacktestError::InvalidCheckpoint(
+            "trailing bytes after the state".to_string(),
+        ));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{load_depth, load_l3_depth, save_depth, save_l3_depth};
+    use crate::depth::{
+        BTreeMarketDepth,
+        HashMapMarketDepth,
+        L2MarketDepth,
+        L3MarketDepth,
+        MarketDepth,
+    };
+
+    #[test]
+    fn restores_depth_timestamp() {
+        let mut depth = HashMapMarketDepth::new(0.1, 1.0);
+        depth.update_bid_depth(100.0, 5.0, 10);
+        depth.update_ask_depth(100.1, 3.0, 20);
+
+        let mut restored = HashMapMarketDepth::new(0.1, 1.0);
+        load_depth(&mut restored, &save_depth(&depth));
+        assert_eq!(restored.best_bid_tick(), 1000);
+        assert_eq!(restored.best_ask_tick(), 1001);
+        assert_eq!(restored.timestamp(), 20);
+
+        depth.update_bid_depth(100.0, 0.0, 30);
+        depth.update_ask_depth(100.1, 0.0, 40);
+        load_depth(&mut restored, &save_depth(&depth));
+        assert_eq!(restored.best_bid_qty(), 0.0);
+        assert_eq!(restored.timestamp(), 40);
+    }
+
+    #[test]
+    fn restores_l3_depth_timestamp() {
+        let mut depth = BTreeMarketDepth::new(0.1, 1.0);
+        depth.add_buy_order(1, 100.0, 5.0, 10).unwrap();
+        depth.add_sell_order(2, 100.2, 3.0, 30).unwrap();
+        depth.add_buy_order(3, 99.9, 1.0, 20).unwrap();
+        depth.delete_order(2, 50).unwrap();
+
+        let mut restored = BTreeMarketDepth::new(0.1, 1.0);
+        load_l3_depth(&mut restored, save_l3_depth(&depth)).unwrap();
+        assert_eq!(restored.orders().len(), 2);
+        assert_eq!(restored.best_bid_tick(), 1000);
+        assert_eq!(restored.orders()[&3].timestamp, 20);
+        assert_eq!(restored.timestamp(), 50);
+    }
+}
@@ -0,0 +1,911 @@
+//! A native chunked columnar file format for [`Event`] data, which decompresses considerably
+//! faster than `npz` and is more compact.
+//!
+//! The rows are split into chunks, and each column of a chunk is encoded with a codec suited to it
+//! before being compressed: the event flags with a dictionary, the timestamps with delta-of-delta,
+//! the price and the quantity quantized by the tick size and the lot size, and the order ID with
+//! delta encoding. Each codec falls back to the raw values if it can't represent the column
+//! losslessly. The chunk index at the end of the file allows random access by timestamp.
+//!
+//! The file layout is as follows, in little-endian:
+//!
+//! ```text
+//! magic (8) | tick_size f64 | lot_size f64 | chunk* | chunk index* | index_offset u64 |
+//! num_chunks u64 | magic (8)
+//! ```
+
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    fs::File,
+    io::{BufReader, BufWriter, Error, ErrorKind, Read, Seek, SeekFrom, Write},
+    mem::size_of,
+    path::Path,
+    sync::Mutex,
+};
+
+use crate::{
+    backtest::data::{Data, DataPtr, NpyChunkReader, NpyDTyped, write_npz_file},
+    types::Event,
+};
+
+const MAGIC: &[u8; 8] = b"HBTCOL\x00\x01";
+
+const DEFAULT_CHUNK_SIZE: usize = 65_536;
+
+const INDEX_ENTRY_LEN: usize = 48;
+
+// Column encodings.
+const RAW: u8 = 0;
+const DICTIONARY: u8 = 1;
+const DELTA_OF_DELTA: u8 = 2;
+const DELTA: u8 = 3;
+const VARINT: u8 = 4;
+const QUANTIZED: u8 = 5;
+const QUANTIZED_DELTA: u8 = 6;
+
+// Column compressions.
+const UNCOMPRESSED: u8 = 0;
+const ZSTD: u8 = 1;
+const LZ4: u8 = 2;
+
+/// Compression applied to each encoded column.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compression {
+    /// The encoded columns are stored as is.
+    None,
+    /// The encoded columns are compressed with Zstandard at the given level.
+    Zstd(i32),
+    /// The encoded columns are compressed with LZ4, which decompresses faster than Zstandard at
+    /// the cost of a larger file.
+    Lz4,
+}
+
+impl Default for Compression {
+    fn default() -> Self {
+        Compression::Zstd(3)
+    }
+}
+
+/// An entry of the chunk index.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ChunkIndex {
+    /// The file offset of the chunk.
+    pub offset: u64,
+    /// The number of rows in the chunk.
+    pub len: usize,
+    pub min_exch_ts: i64,
+    pub max_exch_ts: i64,
+    pub min_local_ts: i64,
+    pub max_local_ts: i64,
+}
+
+fn corrupted() -> Error {
+    Error::new(ErrorKind::InvalidData, "corrupted columnar file")
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn get_varint(buf: &[u8], pos: &mut usize) -> std::io::Result<u64> {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *buf.get(*pos).ok_or_else(corrupted)?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return Ok(value);
+        }
+        shift += 7;
+        if shift > 63 {
+            return Err(corrupted());
+        }
+    }
+}
+
+fn get_u32(buf: &[u8], pos: &mut usize) -> std::io::Result<u32> {
+    let bytes = buf.get(*pos..*pos + 4).ok_or_else(corrupted)?;
+    *pos += 4;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Converts between the values and the integer multiples of the unit, such as the tick size.
+#[derive(Clone, Copy, Debug)]
+struct Quantizer {
+    unit: f64,
+    // Dividing by the reciprocal, if it is an integer, yields the correctly rounded value, such as
+    // `1001 / 10 == 100.1`, while `1001 * 0.1 != 100.1`.
+    recip: Option<f64>,
+}
+
+impl Quantizer {
+    fn new(unit: f64) -> Option<Self> {
+        if !(unit > 0.0 && unit.is_finite()) {
+            return None;
+        }
+        let recip = (1.0 / unit).round();
+        Some(Self {
+            unit,
+            recip: (recip >= 1.0 && (recip * unit - 1.0).abs() < 1e-9).then_some(recip),
+        })
+    }
+
+    fn value(&self, n: i64) -> f64 {
+        match self.recip {
+            Some(recip) => n as f64 / recip,
+            None => n as f64 * self.unit,
+        }
+    }
+
+    /// Returns the multiple only if it converts back to exactly the same value.
+    fn quantize(&self, value: f64) -> Option<i64> {
+        let n = (value / self.unit).round();
+        if !n.is_finite() || n.abs() > (1u64 << 53) as f64 {
+            return None;
+        }
+        let n = n as i64;
+        (self.value(n).to_bits() == value.to_bits()).then_some(n)
+    }
+}
+
+fn encode_raw(values: &[u64], out: &mut Vec<u8>) -> u8 {
+    for value in values {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    RAW
+}
+
+fn encode_dictionary(values: &[u64], out: &mut Vec<u8>) -> u8 {
+    let mut dict: HashMap<u64, u8> = HashMap::new();
+    let mut entries = Vec::new();
+    for &value in values {
+        if let Entry::Vacant(entry) = dict.entry(value) {
+            if entries.len() == 256 {
+                out.clear();
+                return encode_raw(values, out);
+            }
+            entry.insert(entries.len() as u8);
+            entries.push(value);
+        }
+    }
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    for entry in entries {
+        out.extend_from_slice(&entry.to_le_bytes());
+    }
+    out.extend(values.iter().map(|value| dict[value]));
+    DICTIONARY
+}
+
+fn encode_delta_of_delta(values: &[u64], out: &mut Vec<u8>) -> u8 {
+    let (mut prev, mut prev_delta) = (0i64, 0i64);
+    for &value in values {
+        let delta = (value as i64).wrapping_sub(prev);
+        put_varint(out, zigzag(delta.wrapping_sub(prev_delta)));
+        prev = value as i64;
+        prev_delta = delta;
+    }
+    DELTA_OF_DELTA
+}
+
+fn encode_delta(values: &[u64], out: &mut Vec<u8>) -> u8 {
+    let mut prev = 0i64;
+    for &value in values {
+        put_varint(out, zigzag((value as i64).wrapping_sub(prev)));
+        prev = value as i64;
+    }
+    DELTA
+}
+
+fn encode_varint(values: &[u64], out: &mut Vec<u8>) -> u8 {
+    for &value in values {
+        put_varint(out, zigzag(value as i64));
+    }
+    VARINT
+}
+
+fn encode_quantized(
+    values: &[u64],
+    quantizer: Option<Quantizer>,
+    delta: bool,
+    out: &mut Vec<u8>,
+) -> u8 {
+    let Some(quantizer) = quantizer else {
+        return encode_raw(values, out);
+    };
+    let mut prev = 0;
+    for &value in values {
+        let Some(n) = quantizer.quantize(f64::from_bits(value)) else {
+            out.clear();
+            return encode_raw(values, out);
+        };
+        if delta {
+            put_varint(out, zigzag(n - prev));
+            prev = n;
+        } else {
+            put_varint(out, zigzag(n));
+        }
+    }
+    if delta { QUANTIZED_DELTA } else { QUANTIZED }
+}
+
+fn decode_column(
+    encoding: u8,
+    buf: &[u8],
+    len: usize,
+    quantizer: Option<Quantizer>,
+) -> std::io::Result<Vec<u64>> {
+    let mut values = Vec::with_capacity(len);
+    let mut pos = 0;
+    match encoding {
+        RAW => {
+            if buf.len() != len * 8 {
+                return Err(corrupted());
+            }
+            values.extend(
+                buf.chunks_exact(8)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap())),
+            );
+        }
+        DICTIONARY => {
+            let num_entries =
+                u16::from_le_bytes(buf.get(0..2).ok_or_else(corrupted)?.try_into().unwrap())
+                    as usize;
+            let entries: Vec<_> = buf
+                .get(2..2 + num_entries * 8)
+                .ok_or_else(corrupted)?
+                .chunks_exact(8)
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                .collect();
+            let indices = buf.get(2 + num_entries * 8..).ok_or_else(corrupted)?;
+            if indices.len() != len {
+                return Err(corrupted());
+            }
+            for &i in indices {
+                values.push(*entries.get(i as usize).ok_or_else(corrupted)?);
+            }
+        }
+        DELTA_OF_DELTA => {
+            let (mut prev, mut prev_delta) = (0i64, 0i64);
+            for _ in 0..len {
+                let delta = prev_delta.wrapping_add(unzigzag(get_varint(buf, &mut pos)?));
+                prev = prev.wrapping_add(delta);
+                prev_delta = delta;
+                values.push(prev as u64);
+            }
+        }
+        DELTA => {
+            let mut prev = 0i64;
+            for _ in 0..len {
+                prev = prev.wrapping_add(unzigzag(get_varint(buf, &mut pos)?));
+                values.push(prev as u64);
+            }
+        }
+        VARINT => {
+            for _ in 0..len {
+                values.push(unzigzag(get_varint(buf, &mut pos)?) as u64);
+            }
+        }
+        QUANTIZED | QUANTIZED_DELTA => {
+            let quantizer = quantizer.ok_or_else(corrupted)?;
+            let mut prev = 0i64;
+            for _ in 0..len {
+                let mut n = unzigzag(get_varint(buf, &mut pos)?);
+                if encoding == QUANTIZED_DELTA {
+                    n = prev.wrapping_add(n);
+                    prev = n;
+                }
+                values.push(quantizer.value(n).to_bits());
+            }
+        }
+        _ => return Err(corrupted()),
+    }
+    Ok(values)
+}
+
+fn encode_chunk(
+    events: &[Event],
+    price_quantizer: Option<Quantizer>,
+    qty_quantizer: Option<Quantizer>,
+    compression: Compression,
+) -> std::io::Result<Vec<u8>> {
+    let column = |f: fn(&Event) -> u64| events.iter().map(f).collect::<Vec<_>>();
+    let mut chunk = Vec::new();
+    chunk.extend_from_slice(&(events.len() as u32).to_le_bytes());
+    let mut encoded = Vec::new();
+    for i in 0..8 {
+        encoded.clear();
+        let encoding = match i {
+            0 => encode_dictionary(&column(|ev| ev.ev), &mut encoded),
+            1 => encode_delta_of_delta(&column(|ev| ev.exch_ts as u64), &mut encoded),
+            2 => encode_delta_of_delta(&column(|ev| ev.local_ts as u64), &mut encoded),
+            3 => encode_quantized(
+                &column(|ev| ev.px.to_bits()),
+                price_quantizer,
+                true,
+                &mut encoded,
+            ),
+            4 => encode_quantized(
+                &column(|ev| ev.qty.to_bits()),
+                qty_quantizer,
+                false,
+                &mut encoded,
+            ),
+            5 => encode_delta(&column(|ev| ev.order_id), &mut encoded),
+            6 => encode_varint(&column(|ev| ev.ival as u64), &mut encoded),
+            _ => encode_raw(&column(|ev| ev.fval.to_bits()), &mut encoded),
+        };
+        chunk.push(encoding);
+        match compression {
+            Compression::None => {
+                chunk.push(UNCOMPRESSED);
+                chunk.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+                chunk.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+                chunk.extend_from_slice(&encoded);
+            }
+            Compression::Zstd(level) => {
+                let compressed = zstd::bulk::compress(&encoded, level)?;
+                chunk.push(ZSTD);
+                chunk.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+                chunk.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                chunk.extend_from_slice(&compressed);
+            }
+            Compression::Lz4 => {
+                let compressed = lz4_flex::block::compress(&encoded);
+                chunk.push(LZ4);
+                chunk.extend_from_slice(&(encoded.len() as u32).to_le_bytes());
+                chunk.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+                chunk.extend_from_slice(&compressed);
+            }
+        }
+    }
+    Ok(chunk)
+}
+
+fn decode_chunk(
+    buf: &[u8],
+    price_quantizer: Option<Quantizer>,
+    qty_quantizer: Option<Quantizer>,
+    out: &mut [Event],
+) -> std::io::Result<()> {
+    let mut pos = 0;
+    let len = get_u32(buf, &mut pos)? as usize;
+    if len != out.len() {
+        return Err(corrupted());
+    }
+    for i in 0..8 {
+        let header = buf.get(pos..pos + 2).ok_or_else(corrupted)?;
+        let (encoding, compression) = (header[0], header[1]);
+        pos += 2;
+        let raw_len = get_u32(buf, &mut pos)? as usize;
+        let stored_len = get_u32(buf, &mut pos)? as usize;
+        let stored = buf.get(pos..pos + stored_len).ok_or_else(corrupted)?;
+        pos += stored_len;
+        let decompressed;
+        let encoded = match compression {
+            UNCOMPRESSED => stored,
+            ZSTD => {
+                decompressed = zstd::bulk::decompress(stored, raw_len)?;
+                &decompressed[..]
+            }
+            LZ4 => {
+                decompressed =
+                    lz4_flex::block::decompress(stored, raw_len).map_err(|_| corrupted())?;
+                &decompressed[..]
+            }
+            _ => return Err(corrupted()),
+        };
+        let quantizer = match i {
+            3 => price_quantizer,
+            4 => qty_quantizer,
+            _ => None,
+        };
+        let values = decode_column(encoding, encoded, len, quantizer)?;
+        for (ev, value) in out.iter_mut().zip(values) {
+            match i {
+                0 => ev.ev = value,
+                1 => ev.exch_ts = value as i64,
+                2 => ev.local_ts = value as i64,
+                3 => ev.px = f64::from_bits(value),
+                4 => ev.qty = f64::from_bits(value),
+                5 => ev.order_id = value,
+                6 => ev.ival = value as i64,
+                _ => ev.fval = f64::from_bits(value),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes [`Event`] data in the columnar format. The rows are buffered until a chunk is full, and
+/// the chunk index is written by [`finish`](ColumnarWriter::finish).
+pub struct ColumnarWriter<W: Write> {
+    writer: W,
+    tick_size: f64,
+    lot_size: f64,
+    chunk_size: usize,
+    compression: Compression,
+    buf: Vec<Event>,
+    index: Vec<ChunkIndex>,
+    offset: u64,
+}
+
+impl<W: Write> ColumnarWriter<W> {
+    /// Constructs a `ColumnarWriter`. The price and the quantity are quantized by `tick_size` and
+    /// `lot_size` respectively; a non-positive value disables the quantization.
+    pub fn new(writer: W, tick_size: f64, lot_size: f64) -> Self {
+        Self {
+            writer,
+            tick_size,
+            lot_size,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            compression: Default::default(),
+            buf: Vec::new(),
+            index: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Sets the number of rows in each chunk, which is the unit of random access.
+    /// The default value is `65536`.
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        Self {
+            chunk_size: chunk_size.max(1),
+            ..self
+        }
+    }
+
+    /// Sets the compression. The default value is [`Compression::Zstd`] at level `3`.
+    pub fn compression(self, compression: Compression) -> Self {
+        Self {
+            compression,
+            ..self
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        if self.offset == 0 {
+            self.writer.write_all(MAGIC)?;
+            self.writer.write_all(&self.tick_size.to_le_bytes())?;
+            self.writer.write_all(&self.lot_size.to_le_bytes())?;
+            self.offset = 24;
+        }
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn flush_chunk(&mut self) -> std::io::Result<()> {
+        let Some(first) = self.buf.first() else {
+            return Ok(());
+        };
+        let mut entry = ChunkIndex {
+            offset: 0,
+            len: self.buf.len(),
+            min_exch_ts: first.exch_ts,
+            max_exch_ts: first.exch_ts,
+            min_local_ts: first.local_ts,
+            max_local_ts: first.local_ts,
+        };
+        for ev in &self.buf {
+            entry.min_exch_ts = entry.min_exch_ts.min(ev.exch_ts);
+            entry.max_exch_ts = entry.max_exch_ts.max(ev.exch_ts);
+            entry.min_local_ts = entry.min_local_ts.min(ev.local_ts);
+            entry.max_local_ts = entry.max_local_ts.max(ev.local_ts);
+        }
+        let chunk = encode_chunk(
+            &self.buf,
+            Quantizer::new(self.tick_size),
+            Quantizer::new(self.lot_size),
+            self.compression,
+        )?;
+        // Writes the header first, if not yet written, to determine the chunk offset.
+        self.write_bytes(&[])?;
+        entry.offset = self.offset;
+        self.write_bytes(&chunk)?;
+        self.index.push(entry);
+        self.buf.clear();
+        Ok(())
+    }
+
+    /// Writes the events.
+    pub fn write(&mut self, events: &[Event]) -> std::io::Result<()> {
+        for ev in events {
+            self.buf.push(ev.clone());
+            if self.buf.len() == self.chunk_size {
+                self.flush_chunk()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the remaining rows and the chunk index, and returns the underlying writer.
+    pub fn finish(mut self) -> std::io::Result<W> {
+        self.flush_chunk()?;
+        let mut footer = Vec::with_capacity(self.index.len() * INDEX_ENTRY_LEN + 24);
+        for entry in &self.index {
+            footer.extend_from_slice(&entry.offset.to_le_bytes());
+            footer.extend_from_slice(&(entry.len as u64).to_le_bytes());
+            footer.extend_from_slice(&entry.min_exch_ts.to_le_bytes());
+            footer.extend_from_slice(&entry.max_exch_ts.to_le_bytes());
+            footer.extend_from_slice(&entry.min_local_ts.to_le_bytes());
+            footer.extend_from_slice(&entry.max_local_ts.to_le_bytes());
+        }
+        self.write_bytes(&[])?;
+        footer.extend_from_slice(&self.offset.to_le_bytes());
+        footer.extend_from_slice(&(self.index.len() as u64).to_le_bytes());
+        footer.extend_from_slice(MAGIC);
+        self.write_bytes(&footer)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads a columnar [`Event`] file chunk by chunk.
+pub struct ColumnarReader {
+    file: BufReader<File>,
+    tick_size: f64,
+    lot_size: f64,
+    index: Vec<ChunkIndex>,
+    index_offset: u64,
+    // The running maximum of `max_local_ts` over the chunks, which is non-decreasing, so that
+    // `find_chunk` can binary search it.
+    max_local_ts: Vec<i64>,
+}
+
+impl ColumnarReader {
+    /// Opens the columnar file and reads its chunk index.
+    pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let mut header = [0u8; 24];
+        file.read_exact(&mut header)?;
+        if &header[0..8] != MAGIC {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "not a columnar event file",
+            ));
+        }
+        let tick_size = f64::from_le_bytes(header[8..16].try_into().unwrap());
+        let lot_size = f64::from_le_bytes(header[16..24].try_into().unwrap());
+
+        let mut footer = [0u8; 24];
+        file.seek(SeekFrom::End(-24))?;
+        file.read_exact(&mut footer)?;
+        if &footer[16..24] != MAGIC {
+            return Err(corrupted());
+        }
+        let index_offset = u64::from_le_bytes(footer[0..8].try_into().unwrap());
+        let num_chunks = u64::from_le_bytes(footer[8..16].try_into().unwrap()) as usize;
+        let mut buf = vec![0u8; num_chunks * INDEX_ENTRY_LEN];
+        file.seek(SeekFrom::Start(index_offset))?;
+        file.read_exact(&mut buf)?;
+        let index = buf
+            .chunks_exact(INDEX_ENTRY_LEN)
+            .map(|entry| {
+                let field = |i: usize| entry[i * 8..(i + 1) * 8].try_into().unwrap();
+                ChunkIndex {
+                    offset: u64::from_le_bytes(field(0)),
+                    len: u64::from_le_bytes(field(1)) as usize,
+                    min_exch_ts: i64::from_le_bytes(field(2)),
+                    max_exch_ts: i64::from_le_bytes(field(3)),
+                    min_local_ts: i64::from_le_bytes(field(4)),
+                    max_local_ts: i64::from_le_bytes(field(5)),
+                }
+            })
+            .collect::<Vec<ChunkIndex>>();
+        let max_local_ts = index
+            .iter()
+            .scan(i64::MIN, |max, entry| {
+                *max = (*max).max(entry.max_local_ts);
+                Some(*max)
+            })
+            .collect();
+        Ok(Self {
+            file,
+            tick_size,
+            lot_size,
+            index,
+            index_offset,
+            max_local_ts,
+        })
+    }
+
+    /// Returns the total number of rows.
+    pub fn len(&self) -> usize {
+        self.index.iter().map(|entry| entry.len).sum()
+    }
+
+    /// Returns `true` if the file has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns the chunk index.
+    pub fn index(&self) -> &[ChunkIndex] {
+        &self.index
+    }
+
+    /// Returns the number of the first chunk containing an event whose local timestamp is equal to
+    /// or greater than the given timestamp, or `None` if there is no such chunk. Reading from this
+    /// chunk doesn't miss any event received by the local at or after the timestamp.
+    pub fn find_chunk(&self, local_ts: i64) -> Option<usize> {
+        let chunk_num = self.max_local_ts.partition_point(|&max| max < local_ts);
+        (chunk_num < self.index.len()).then_some(chunk_num)
+    }
+
+    fn read_chunk_into(&mut self, chunk_num: usize, out: &mut [Event]) -> std::io::Result<()> {
+        let entry = self.index[chunk_num];
+        // The last chunk is followed by the chunk index.
+        let end = self
+            .index
+            .get(chunk_num + 1)
+            .map(|next| next.offset)
+            .unwrap_or(self.index_offset);
+        let len = end.checked_sub(entry.offset).ok_or_else(corrupted)?;
+        let mut buf = vec![0u8; len as usize];
+        self.file.seek(SeekFrom::Start(entry.offset))?;
+        self.file.read_exact(&mut buf)?;
+        decode_chunk(
+            &buf,
+            Quantizer::new(self.tick_size),
+            Quantizer::new(self.lot_size),
+            out,
+        )
+    }
+
+    pub(super) fn read_chunk_ptr(&mut self, chunk_num: usize) -> std::io::Result<DataPtr> {
+        let len = self
+            .index
+            .get(chunk_num)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "chunk is out of range"))?
+            .len;
+        let mut ptr = DataPtr::new(len * size_of::<Event>());
+        let out =
+            unsafe { std::slice::from_raw_parts_mut(ptr[..].as_mut_ptr() as *mut Event, len) };
+        self.read_chunk_into(chunk_num, out)?;
+        Ok(ptr)
+    }
+
+    /// Reads the chunk.
+    pub fn read_chunk(&mut self, chunk_num: usize) -> std::io::Result<Data<Event>> {
+        let ptr = self.read_chunk_ptr(chunk_num)?;
+        Ok(unsafe { Data::from_data_ptr(ptr, 0) })
+    }
+
+    /// Reads all chunks into a single [`Data`].
+    pub fn read_all(&mut self) -> std::io::Result<Data<Event>> {
+        let len = self.len();
+        if len == 0 {
+            return Ok(Data::empty());
+        }
+        let mut ptr = DataPtr::new(len * size_of::<Event>());
+        let out =
+            unsafe { std::slice::from_raw_parts_mut(ptr[..].as_mut_ptr() as *mut Event, len) };
+        let mut start = 0;
+        for chunk_num in 0..self.index.len() {
+            let end = start + self.index[chunk_num].len;
+            self.read_chunk_into(chunk_num, &mut out[start..end])?;
+            start = end;
+        }
+        Ok(unsafe { Data::from_data_ptr(ptr, 0) })
+    }
+}
+
+/// Reads the chunk of the open columnar file as `Data<D>`, where `D` must have the same layout as
+/// [`Event`].
+pub(super) fn read_columnar_chunk<D: NpyDTyped + Clone>(
+    reader: &Mutex<ColumnarReader>,
+    chunk_num: usize,
+) -> std::io::Result<Data<D>> {
+    if D::descr() != Event::descr() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "columnar files only contain `Event` data",
+        ));
+    }
+    let ptr = reader
+        .lock()
+        .map_err(|_| Error::other("columnar file reader is poisoned"))?
+        .read_chunk_ptr(chunk_num)?;
+    Ok(unsafe { Data::from_data_ptr(ptr, 0) })
+}
+
+/// Reads a columnar [`Event`] file entirely.
+pub fn read_columnar_file<P: AsRef<Path>>(path: P) -> std::io::Result<Data<Event>> {
+    ColumnarReader::open(path)?.read_all()
+}
+
+/// Writes the [`Event`] data into a columnar file with the default chunk size and compression. See
+/// [`ColumnarWriter`] for the details.
+pub fn write_columnar_file<P: AsRef<Path>>(
+    path: P,
+    data: &[Event],
+    tick_size: f64,
+    lot_size: f64,
+) -> std::io::Result<()> {
+    let mut writer = ColumnarWriter::new(BufWriter::new(File::create(path)?), tick_size, lot_size);
+    writer.write(data)?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Converts the `data` array of an `npz` file into a columnar file, streaming it chunk by chunk.
+pub fn convert_npz_to_columnar<P: AsRef<Path>, Q: AsRef<Path>>(
+    npz_path: P,
+    columnar_path: Q,
+    tick_size: f64,
+    lot_size: f64,
+) -> std::io::Result<()> {
+    let mut archive = zip::ZipArchive::new(File::open(npz_path)?)?;
+    let mut reader = NpyChunkReader::<_, Event>::new(archive.by_name("data.npy")?)?;
+    let mut writer = ColumnarWriter::new(
+        BufWriter::new(File::create(columnar_path)?),
+        tick_size,
+        lot_size,
+    );
+    loop {
+        let data = reader.read_chunk(DEFAULT_CHUNK_SIZE)?;
+        if data.is_empty() {
+            break;
+        }
+        writer.write(data.as_slice())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Converts a columnar file into an `npz` file with the `data` array.
+pub fn convert_columnar_to_npz<P: AsRef<Path>, Q: AsRef<Path>>(
+    columnar_path: P,
+    npz_path: Q,
+) -> std::io::Result<()> {
+    let data = read_columnar_file(columnar_path)?;
+    write_npz_file(npz_path, "data", data.as_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{
+        backtest::data::{
+            ColumnarReader,
+            ColumnarWriter,
+            Compression,
+            DataSource,
+            Reader,
+            convert_columnar_to_npz,
+            convert_npz_to_columnar,
+            read_npz_file,
+            write_npz_file,
+        },
+        types::{BUY_EVENT, DEPTH_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT, TRADE_EVENT},
+    };
+
+    fn events(len: usize) -> Vec<Event> {
+        (0..len)
+            .map(|i| Event {
+                ev: if i % 3 == 0 {
+                    TRADE_EVENT | BUY_EVENT | EXCH_EVENT | LOCAL_EVENT
+                } else {
+                    DEPTH_EVENT | SELL_EVENT | EXCH_EVENT | LOCAL_EVENT
+                },
+                exch_ts: 1_000_000 + i as i64 * 1_000 + (i % 7) as i64,
+                local_ts: 1_500_000 + i as i64 * 1_000,
+                px: 100.0 + (i % 11) as f64 * 0.1,
+                qty: (i % 5) as f64 * 0.001,
+                order_id: i as u64 * 3,
+                ival: -(i as i64),
+                fval: i as f64 * 0.5,
+            })
+            .collect()
+    }
+
+    fn assert_same(a: &[Event], b: &[Event]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert_eq!(a.ev, b.ev);
+            assert_eq!(a.exch_ts, b.exch_ts);
+            assert_eq!(a.local_ts, b.local_ts);
+            assert_eq!(a.px.to_bits(), b.px.to_bits());
+            assert_eq!(a.qty.to_bits(), b.qty.to_bits());
+            assert_eq!(a.order_id, b.order_id);
+            assert_eq!(a.ival, b.ival);
+            assert_eq!(a.fval.to_bits(), b.fval.to_bits());
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut evs = events(1000);
+        // Values off the tick grid and NaN fall back to the raw encoding in their chunk.
+        evs[950].px = 100.123456;
+        evs[990].qty = f64::NAN;
+
+        let compressions = [Compression::None, Compression::Zstd(3), Compression::Lz4];
+        for (i, compression) in compressions.into_iter().enumerate() {
+            let path = std::env::temp_dir().join(format!("hftbacktest_test_columnar_{i}.hbtc"));
+            let mut writer = ColumnarWriter::new(
+                std::io::BufWriter::new(std::fs::File::create(&path).unwrap()),
+                0.1,
+                0.001,
+            )
+            .chunk_size(300)
+            .compression(compression);
+            writer.write(&evs[..500]).unwrap();
+            writer.write(&evs[500..]).unwrap();
+            writer.finish().unwrap();
+
+            let mut reader = ColumnarReader::open(&path).unwrap();
+            assert_eq!(reader.len(), 1000);
+            assert_eq!(
+                reader.index().iter().map(|c| c.len).collect::<Vec<_>>(),
+                vec![300, 300, 300, 100]
+            );
+            assert_eq!(reader.find_chunk(1_500_000 + 650_000), Some(2));
+            assert_eq!(reader.find_chunk(i64::MAX), None);
+            assert_same(reader.read_chunk(3).unwrap().as_slice(), &evs[900..]);
+            assert_same(reader.read_all().unwrap().as_slice(), &evs);
+
+            std::fs::remove_file(path).unwrap();
+        }
+
+        // The quantized encoding is more compact than the raw data.
+        let mut writer = ColumnarWriter::new(Cursor::new(Vec::new()), 0.1, 0.001);
+        writer.write(&evs[..900]).unwrap();
+        let buf = writer.finish().unwrap().into_inner();
+        assert!(buf.len() * 10 < 900 * size_of::<Event>());
+    }
+
+    #[test]
+    fn test_npz_conversion_and_reader() {
+        let evs = events(100);
+        let npz = std::env::temp_dir().join("hftbacktest_test_columnar_src.npz");
+        let hbtc = std::env::temp_dir().join("hftbacktest_test_columnar_conv.hbtc");
+        let npz2 = std::env::temp_dir().join("hftbacktest_test_columnar_dst.npz");
+        write_npz_file(&npz, "data", &evs).unwrap();
+        convert_npz_to_columnar(&npz, &hbtc, 0.1, 0.001).unwrap();
+        convert_columnar_to_npz(&hbtc, &npz2).unwrap();
+        let data = read_npz_file::<Event>(npz2.to_str().unwrap(), "data").unwrap();
+        assert_same(data.as_slice(), &evs);
+
+        // Writes smaller chunks to read them through the reader.
+        let mut writer = ColumnarWriter::new(
+            std::io::BufWriter::new(std::fs::File::create(&hbtc).unwrap()),
+            0.1,
+            0.001,
+        )
+        .chunk_size(40);
+        writer.write(&evs).unwrap();
+        writer.finish().unwrap();
+        let mut reader = Reader::<Event>::builder()
+            .data(vec![DataSource::Columnar(
+                hbtc.to_str().unwrap().to_string(),
+            )])
+            .build()
+            .unwrap();
+        let mut read = Vec::new();
+        while let Ok(data) = reader.next_data() {
+            assert!(data.len() <= 40);
+            read.extend_from_slice(data.as_slice());
+            reader.release(data);
+        }
+        assert_same(&read, &evs);
+
+        for path in [npz, hbtc, npz2] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Converts [Databento](https://databento.com) DBN data files.
+//!
+//! Databento's historical data includes a Start-of-Day (SOD) snapshot for CME data. In the
+//! snapshot, the exchange timestamp represents the original time when the order was submitted, and
+//! the data is sorted in chronological order. This ensures that orders are built with the correct
+//! price-time priority. However, since these timestamps are in the past (before the clear message),
+//! the exchange timestamp is artificially set to the local timestamp to indicate the snapshot. This
+//! adjustment maintains the chronological order of exchange timestamps during multi-day
+//! backtesting.
+
+use std::{
+    io::{BufRead, Error, ErrorKind, Read},
+    path::Path,
+};
+
+use crate::{
+    backtest::data::convert::{event, finalize, open},
+    types::{
+        ADD_ORDER_EVENT,
+        BUY_EVENT,
+        CANCEL_ORDER_EVENT,
+        DEPTH_BBO_EVENT,
+        DEPTH_CLEAR_EVENT,
+        DEPTH_EVENT,
+        Event,
+        FILL_EVENT,
+        MODIFY_ORDER_EVENT,
+        SELL_EVENT,
+        TRADE_EVENT,
+    },
+};
+
+const RTYPE_MBP_1: u8 = 0x01;
+const RTYPE_MBP_10: u8 = 0x0A;
+const RTYPE_MBO: u8 = 0xA0;
+
+const MBP_1_LEN: usize = 80;
+const MBP_10_LEN: usize = 368;
+const MBO_LEN: usize = 56;
+
+// The offset of the first book level in MBP records, and the size of each level.
+const LEVELS_OFFSET: usize = 48;
+const LEVEL_LEN: usize = 32;
+
+const UNDEF_PRICE: i64 = i64::MAX;
+
+fn invalid_data(msg: String) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap())
+}
+
+// DBN prices are fixed-point numbers in units of 1e-9.
+fn price_at(buf: &[u8], offset: usize) -> f64 {
+    match i64::from_le_bytes(buf[offset..offset + 8].try_into().unwrap()) {
+        UNDEF_PRICE => f64::NAN,
+        price => price as f64 * 1e-9,
+    }
+}
+
+fn side_event(side: u8) -> std::io::Result<u64> {
+    match side {
+        b'B' => Ok(BUY_EVENT),
+        b'A' => Ok(SELL_EVENT),
+        b'N' => Ok(0),
+        _ => Err(invalid_data(format!("invalid side '{}'", side as char))),
+    }
+}
+
+/// Reads the records of a DBN file one by one, skipping the metadata.
+struct DbnReader {
+    reader: Box<dyn BufRead>,
+    buf: Vec<u8>,
+}
+
+impl DbnReader {
+    fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut reader = open(path)?;
+        let mut prelude = [0u8; 8];
+        reader.read_exact(&mut prelude)?;
+        if &prelude[..3] != b"DBN" {
+            return Err(invalid_data("not a DBN file".to_string()));
+        }
+        let metadata_len = u32_at(&prelude, 4) as u64;
+        std::io::copy(&mut (&mut reader).take(metadata_len), &mut std::io::sink())?;
+        Ok(Self {
+            reader,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Returns the next record including its header, or `None` at the end of the file.
+    fn next_record(&mut self) -> std::io::Result<Option<&[u8]>> {
+        let mut len = [0u8; 1];
+        if self.reader.read(&mut len)? == 0 {
+            return Ok(None);
+        }
+        // The record length is in units of 4 bytes.
+        let len = len[0] as usize * 4;
+        if len < 16 {
+            return Err(invalid_data(format!("invalid record length {len}")));
+        }
+        self.buf.resize(len, 0);
+        self.buf[0] = (len / 4) as u8;
+        self.reader.read_exact(&mut self.buf[1..])?;
+        Ok(Some(&self.buf))
+    }
+}
+
+/// Converts a record into events. Returns `false` if the record is not supported.
+fn convert_record(record: &[u8], out: &mut Vec<Event>) -> std::io::Result<bool> {
+    let rtype = record[1];
+    let min_len = match rtype {
+        RTYPE_MBO => MBO_LEN,
+        RTYPE_MBP_1 => MBP_1_LEN,
+        RTYPE_MBP_10 => MBP_10_LEN,
+        _ => return Ok(false),
+    };
+    if record.len() < min_len {
+        return Err(invalid_data(format!(
+            "invalid record length {} for rtype {rtype:#04x}",
+            record.len()
+        )));
+    }
+    let exch_ts = u64_at(record, 8) as i64;
+    match rtype {
+        RTYPE_MBO => {
+            let ev = match record[38] {
+                b'A' => ADD_ORDER_EVENT,
+                b'C' => CANCEL_ORDER_EVENT,
+                b'M' => MODIFY_ORDER_EVENT,
+                b'R' => DEPTH_CLEAR_EVENT,
+                b'T' => TRADE_EVENT,
+                b'F' => FILL_EVENT,
+                action => {
+                    return Err(invalid_data(format!("invalid action '{}'", action as char)));
+                }
+            };
+            let mut ev = event(
+                ev | side_event(record[39])?,
+                exch_ts,
+                u64_at(record, 40) as i64,
+                price_at(record, 24),
+                u32_at(record, 32) as f64,
+            );
+            ev.order_id = u64_at(record, 16);
+            ev.ival = record[36] as i64;
+            out.push(ev);
+        }
+        _ => {
+            let local_ts = u64_at(record, 32) as i64;
+            let side = side_event(record[29])?;
+            let px = price_at(record, 16);
+            match record[28] {
+                b'A' | b'C' | b'M' if side != 0 => {
+                    // The level offset of the bid or ask price and size.
+                    let (px_offset, sz_offset) = if side == BUY_EVENT { (0, 16) } else { (8, 20) };
+                    if rtype == RTYPE_MBP_1 {
+                        let level_px = price_at(record, LEVELS_OFFSET + px_offset);
+                        if !level_px.is_nan() {
+                            out.push(event(
+                                DEPTH_BBO_EVENT | side,
+                                exch_ts,
+                                local_ts,
+                                level_px,
+                                u32_at(record, LEVELS_OFFSET + sz_offset) as f64,
+                            ));
+                        }
+                    } else {
+                        // The updated price level is gone if it's not found in the book.
+                        let qty = (0..10)
+                            .map(|i| LEVELS_OFFSET + i * LEVEL_LEN)
+                            .find(|&offset| price_at(record, offset + px_offset) == px)
+                            .map(|offset| u32_at(record, offset + sz_offset) as f64)
+                            .unwrap_or(0.0);
+                        out.push(event(DEPTH_EVENT | side, exch_ts, local_ts, px, qty));
+                    }
+                }
+                b'T' => {
+                    out.push(event(
+                        TRADE_EVENT | side,
+                        exch_ts,
+                        local_ts,
+                        px,
+                        u32_at(record, 24) as f64,
+                    ));
+                }
+                b'R' => {
+                    out.push(event(DEPTH_CLEAR_EVENT, exch_ts, local_ts, px, 0.0));
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(true)
+}
+
+/// Converts a Databento DBN file of the MBO (L3 Market-By-Order), MBP-1 or MBP-10 schema.
+/// Records of other schemas, such as symbol mappings and system messages, are skipped.
+///
+/// If the file contains multiple instruments, `instrument_id` should be provided; otherwise, the
+/// output will contain mixed instruments.
+pub fn convert<P: AsRef<Path>>(
+    input_file: P,
+    instrument_id: Option<u32>,
+    base_latency: i64,
+) -> std::io::Result<Vec<Event>> {
+    let mut reader = DbnReader::open(input_file)?;
+    let mut events = Vec::new();
+    let mut snapshot_ts = None;
+    while let Some(record) = reader.next_record()? {
+        if instrument_id.is_some_and(|id| id != u32_at(record, 4)) {
+            continue;
+        }
+        let num_events = events.len();
+        if !convert_record(record, &mut events)? {
+            continue;
+        }
+        // Adjusts the timestamps for the snapshot.
+        for ev in &mut events[num_events..] {
+            if ev.ev == DEPTH_CLEAR_EVENT {
+                snapshot_ts = Some(ev.local_ts);
+            }
+            if snapshot_ts != Some(ev.local_ts) {
+                snapshot_ts = None;
+            }
+            if let Some(ts) = snapshot_ts {
+                ev.exch_ts = ts;
+            }
+        }
+    }
+    finalize(events, base_latency)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write};
+
+    use crate::{
+        backtest::data::convert::databento::{MBO_LEN, RTYPE_MBO, convert},
+        types::{
+            ADD_ORDER_EVENT,
+            BUY_EVENT,
+            CANCEL_ORDER_EVENT,
+            DEPTH_CLEAR_EVENT,
+            EXCH_EVENT,
+            LOCAL_EVENT,
+            SELL_EVENT,
+        },
+    };
+
+    fn mbo(
+        instrument_id: u32,
+        (ts_event, ts_recv): (u64, u64),
+        action: u8,
+        side: u8,
+        order_id: u64,
+        price: i64,
+        size: u32,
+    ) -> Vec<u8> {
+        let mut buf = vec![0u8; MBO_LEN];
+        buf[0] = (MBO_LEN / 4) as u8;
+        buf[1] = RTYPE_MBO;
+        buf[4..8].copy_from_slice(&instrument_id.to_le_bytes());
+        buf[8..16].copy_from_slice(&ts_event.to_le_bytes());
+        buf[16..24].copy_from_slice(&order_id.to_le_bytes());
+        buf[24..32].copy_from_slice(&price.to_le_bytes());
+        buf[32..36].copy_from_slice(&size.to_le_bytes());
+        buf[38] = action;
+        buf[39] = side;
+        buf[40..48].copy_from_slice(&ts_recv.to_le_bytes());
+        buf
+    }
+
+    #[test]
+    fn test_convert_mbo() {
+        let mut content = b"DBN\x02".to_vec();
+        let metadata = [0u8; 12];
+        content.extend_from_slice(&(metadata.len() as u32).to_le_bytes());
+        content.extend_from_slice(&metadata);
+        // The SOD snapshot carries the original order submission times.
+        content.extend(mbo(1, (100, 100), b'R', b'N', 0, i64::MAX, 0));
+        content.extend(mbo(1, (10, 100), b'A', b'B', 1, 99_000_000_000, 5));
+        content.extend(mbo(1, (20, 100), b'A', b'A', 2, 101_000_000_000, 3));
+        // Another instrument.
+        content.extend(mbo(2, (150, 160), b'A', b'B', 3, 10_000_000_000, 1));
+        content.extend(mbo(1, (200, 210), b'C', b'B', 1, 99_000_000_000, 5));
+
+        let path = std::env::temp_dir().join("hftbacktest_test_databento.mbo.dbn");
+        File::create(&path).unwrap().write_all(&content).unwrap();
+
+        let data = convert(&path, Some(1), 0).unwrap();
+        let flags = EXCH_EVENT | LOCAL_EVENT;
+        let evs: Vec<_> = (0..data.len())
+            .map(|i| (data[i].ev, data[i].exch_ts, data[i].order_id))
+            .collect();
+        assert_eq!(
+            evs,
+            vec![
+                (DEPTH_CLEAR_EVENT | flags, 100, 0),
+                (ADD_ORDER_EVENT | BUY_EVENT | flags, 100, 1),
+                (ADD_ORDER_EVENT | SELL_EVENT | flags, 100, 2),
+                (CANCEL_ORDER_EVENT | BUY_EVENT | flags, 200, 1),
+            ]
+        );
+        assert_eq!(data[1].px, 99.0);
+        assert_eq!(data[2].qty, 3.0);
+        assert!(data[0].px.is_nan());
+
+        let data = convert(&path, None, 0).unwrap();
+        assert_eq!(data.len(), 5);
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+//! Converts the market data from data vendors into [`Event`] data compatible with HftBacktest.
+//!
+//! The input files are read in a streaming manner and can be compressed with gzip (`.gz`) or
+//! Zstandard (`.zst`). The converted data has the feed latency and the event order corrected, so it
+//! can be saved using [`write_npz_file`](crate::backtest::data::write_npz_file) as is.
+
+pub mod databento;
+pub mod tardis;
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Error, ErrorKind},
+    path::Path,
+};
+
+use crate::{
+    backtest::data::{correct_event_order, correct_local_timestamp, validate_event_order},
+    types::Event,
+};
+
+/// Determines how the snapshots in the data are processed.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum SnapshotMode {
+    /// All snapshots are processed.
+    #[default]
+    Process,
+    /// The Start-Of-Day snapshot, at the beginning of each file, is ignored.
+    IgnoreSod,
+    /// All snapshots are ignored. The order book will converge to a complete order book over
+    /// time.
+    Ignore,
+}
+
+/// Opens the file, decompressing it according to its extension.
+fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Box<dyn BufRead>> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz") => Ok(Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(
+            file,
+        )))),
+        Some("zst") => Ok(Box::new(BufReader::new(zstd::stream::read::Decoder::new(
+            file,
+        )?))),
+        _ => Ok(Box::new(BufReader::new(file))),
+    }
+}
+
+/// Corrects the feed latency and the event order of the converted events, and validates them.
+fn finalize(mut events: Vec<Event>, base_latency: i64) -> std::io::Result<Vec<Event>> {
+    correct_local_timestamp(&mut events, base_latency);
+    let sorted = correct_event_order(&events);
+    drop(events);
+    validate_event_order(&sorted).map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+    Ok(sorted)
+}
+
+fn event(ev: u64, exch_ts: i64, local_ts: i64, px: f64, qty: f64) -> Event {
+    Event {
+        ev,
+        exch_ts,
+        local_ts,
+        px,
+        qty,
+        order_id: 0,
+        ival: 0,
+        fval: 0.0,
+    }
+}
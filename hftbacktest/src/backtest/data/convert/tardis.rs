@@ -0,0 +1,579 @@
+//! Converts [Tardis.dev](https://tardis.dev) CSV data files.
+//!
+//! For Tardis's Binance Futures feed data, they use the 'E' event timestamp, representing the
+//! sending time, rather than the 'T' transaction time, indicating when the matching occurs. So the
+//! latency is slightly less than it actually is.
+
+use std::{
+    io::{BufRead, Error, ErrorKind},
+    path::Path,
+    str::FromStr,
+};
+
+use crate::{
+    backtest::data::convert::{SnapshotMode, event, finalize, open},
+    depth::FusedHashMapMarketDepth,
+    types::{
+        BUY_EVENT,
+        DEPTH_BBO_EVENT,
+        DEPTH_CLEAR_EVENT,
+        DEPTH_EVENT,
+        DEPTH_SNAPSHOT_EVENT,
+        Event,
+        SELL_EVENT,
+        Side,
+        TRADE_EVENT,
+    },
+};
+
+const TRADES_COLUMNS: [&str; 8] = [
+    "exchange",
+    "symbol",
+    "timestamp",
+    "local_timestamp",
+    "id",
+    "side",
+    "price",
+    "amount",
+];
+
+const INCREMENTAL_BOOK_COLUMNS: [&str; 8] = [
+    "exchange",
+    "symbol",
+    "timestamp",
+    "local_timestamp",
+    "is_snapshot",
+    "side",
+    "price",
+    "amount",
+];
+
+const BOOK_TICKER_COLUMNS: [&str; 8] = [
+    "exchange",
+    "symbol",
+    "timestamp",
+    "local_timestamp",
+    "ask_amount",
+    "ask_price",
+    "bid_price",
+    "bid_amount",
+];
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum FileKind {
+    Trades,
+    IncrementalBook,
+    BookTicker,
+    BookSnapshot,
+}
+
+/// Reads a Tardis CSV file row by row, reusing the line buffer.
+struct CsvReader {
+    path: String,
+    reader: Box<dyn BufRead>,
+    line: String,
+    line_num: usize,
+    // The byte ranges of the fields in the current line.
+    fields: Vec<(usize, usize)>,
+    kind: FileKind,
+    // The column indices of (price, amount) of each level in a book snapshot file.
+    bid_columns: Vec<(usize, usize)>,
+    ask_columns: Vec<(usize, usize)>,
+}
+
+impl CsvReader {
+    fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut reader = Self {
+            path: path.as_ref().display().to_string(),
+            reader: open(path)?,
+            line: String::new(),
+            line_num: 0,
+            fields: Vec::new(),
+            kind: FileKind::Trades,
+            bid_columns: Vec::new(),
+            ask_columns: Vec::new(),
+        };
+        if !reader.read_row()? {
+            return Err(reader.error("no header"));
+        }
+        let header: Vec<_> = (0..reader.fields.len()).map(|i| reader.str(i)).collect();
+        reader.kind = if header == TRADES_COLUMNS {
+            FileKind::Trades
+        } else if header == INCREMENTAL_BOOK_COLUMNS {
+            FileKind::IncrementalBook
+        } else if header == BOOK_TICKER_COLUMNS {
+            FileKind::BookTicker
+        } else if header.starts_with(&TRADES_COLUMNS[..4])
+            && header.contains(&"asks[0].price")
+            && header.contains(&"bids[0].price")
+        {
+            let columns = |side: &str| {
+                let mut columns = Vec::new();
+                while let (Some(price), Some(amount)) = (
+                    header
+                        .iter()
+                        .position(|&c| c == format!("{side}[{}].price", columns.len())),
+                    header
+                        .iter()
+                        .position(|&c| c == format!("{side}[{}].amount", columns.len())),
+                ) {
+                    columns.push((price, amount));
+                }
+                columns
+            };
+            let (bid_columns, ask_columns) = (columns("bids"), columns("asks"));
+            reader.bid_columns = bid_columns;
+            reader.ask_columns = ask_columns;
+            FileKind::BookSnapshot
+        } else {
+            return Err(reader.error("unsupported file type"));
+        };
+        Ok(reader)
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{}:{}: {msg}", self.path, self.line_num),
+        )
+    }
+
+    /// Reads the next row. Returns `false` at the end of the file.
+    fn read_row(&mut self) -> std::io::Result<bool> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(false);
+        }
+        self.line_num += 1;
+        let line = self.line.trim_end_matches(['\r', '\n']);
+        self.fields.clear();
+        let mut start = 0;
+        for (i, c) in line.char_indices() {
+            if c == ',' {
+                self.fields.push((start, i));
+                start = i + 1;
+            }
+        }
+        self.fields.push((start, line.len()));
+        Ok(true)
+    }
+
+    fn str(&self, i: usize) -> &str {
+        self.fields
+            .get(i)
+            .map(|&(start, end)| &self.line[start..end])
+            .unwrap_or("")
+    }
+
+    fn parse<T: FromStr>(&self, i: usize) -> std::io::Result<T> {
+        self.str(i)
+            .parse()
+            .map_err(|_| self.error(&format!("invalid value '{}'", self.str(i))))
+    }
+
+    // Tardis timestamps are in microseconds.
+    fn timestamps(&self) -> std::io::Result<(i64, i64)> {
+        Ok((self.parse::<i64>(2)? * 1000, self.parse::<i64>(3)? * 1000))
+    }
+
+    fn trade(&self) -> std::io::Result<Event> {
+        let (exch_ts, local_ts) = self.timestamps()?;
+        let ev = match self.str(5) {
+            "buy" => TRADE_EVENT | BUY_EVENT,
+            "sell" => TRADE_EVENT | SELL_EVENT,
+            _ => TRADE_EVENT,
+        };
+        Ok(event(ev, exch_ts, local_ts, self.parse(6)?, self.parse(7)?))
+    }
+
+    /// Returns the depth event and whether it is a part of a snapshot.
+    fn depth(&self) -> std::io::Result<(Event, bool)> {
+        let (exch_ts, local_ts) = self.timestamps()?;
+        let side = match self.str(5) {
+            "bid" | "buy" => BUY_EVENT,
+            "ask" | "sell" => SELL_EVENT,
+            _ => return Err(self.error("invalid side")),
+        };
+        let is_snapshot = self.str(4) == "true";
+        let ev = event(
+            DEPTH_EVENT | side,
+            exch_ts,
+            local_ts,
+            self.parse(6)?,
+            self.parse(7)?,
+        );
+        Ok((ev, is_snapshot))
+    }
+
+    fn book_ticker(&self, out: &mut Vec<Event>) -> std::io::Result<()> {
+        let (exch_ts, local_ts) = self.timestamps()?;
+        out.push(event(
+            DEPTH_BBO_EVENT | SELL_EVENT,
+            exch_ts,
+            local_ts,
+            self.parse(5)?,
+            self.parse(4)?,
+        ));
+        out.push(event(
+            DEPTH_BBO_EVENT | BUY_EVENT,
+            exch_ts,
+            local_ts,
+            self.parse(6)?,
+            self.parse(7)?,
+        ));
+        Ok(())
+    }
+
+    fn book_snapshot(&self, depth: &mut DepthConverter) -> std::io::Result<()> {
+        let (exch_ts, local_ts) = self.timestamps()?;
+        for (columns, side) in [
+            (&self.bid_columns, BUY_EVENT),
+            (&self.ask_columns, SELL_EVENT),
+        ] {
+            for &(price, amount) in columns {
+                // The levels beyond the depth of the book are empty.
+                if self.str(price).is_empty() {
+                    break;
+                }
+                depth.add_snapshot(event(
+                    DEPTH_SNAPSHOT_EVENT | side,
+                    exch_ts,
+                    local_ts,
+                    self.parse(price)?,
+                    self.parse(amount)?,
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts the incremental book, buffering a snapshot until it ends so that it can be preceded
+/// by the clear events.
+struct DepthConverter {
+    snapshot_mode: SnapshotMode,
+    is_sod_snapshot: bool,
+    bid_snapshot: Vec<Event>,
+    ask_snapshot: Vec<Event>,
+}
+
+impl DepthConverter {
+    fn new(snapshot_mode: SnapshotMode) -> Self {
+        Self {
+            snapshot_mode,
+            is_sod_snapshot: true,
+            bid_snapshot: Vec::new(),
+            ask_snapshot: Vec::new(),
+        }
+    }
+
+    fn ignores_snapshot(&self) -> bool {
+        self.snapshot_mode == SnapshotMode::Ignore
+            || (self.snapshot_mode == SnapshotMode::IgnoreSod && self.is_sod_snapshot)
+    }
+
+    fn add_snapshot(&mut self, mut ev: Event) {
+        ev.ev = (ev.ev & !0xff) | DEPTH_SNAPSHOT_EVENT;
+        if ev.is(BUY_EVENT) {
+            self.bid_snapshot.push(ev);
+        } else {
+            self.ask_snapshot.push(ev);
+        }
+    }
+
+    fn process(&mut self, ev: Event, is_snapshot: bool, out: &mut Vec<Event>) {
+        if is_snapshot {
+            if !self.ignores_snapshot() {
+                self.add_snapshot(ev);
+            }
+        } else {
+            self.is_sod_snapshot = false;
+            self.flush(out);
+            out.push(ev);
+        }
+    }
+
+    /// Ends the buffered snapshot. The market depth is cleared within the snapshot range before
+    /// being refreshed by the snapshot.
+    fn flush(&mut self, out: &mut Vec<Event>) {
+        for (snapshot, side) in [
+            (&mut self.bid_snapshot, BUY_EVENT),
+            (&mut self.ask_snapshot, SELL_EVENT),
+        ] {
+            if let (Some(first), Some(last)) = (snapshot.first(), snapshot.last()) {
+                out.push(event(
+                    DEPTH_CLEAR_EVENT | side,
+                    first.exch_ts,
+                    first.local_ts,
+                    last.px,
+                    0.0,
+                ));
+                out.append(snapshot);
+            }
+        }
+    }
+}
+
+/// Applies the depth event to the fused market depth and outputs the resulting fused events.
+fn fuse(depth: &mut FusedHashMapMarketDepth, mut ev: Event, out: &mut Vec<Event>) {
+    if ev.is(DEPTH_EVENT) || ev.is(DEPTH_SNAPSHOT_EVENT) {
+        if ev.is(BUY_EVENT) {
+            out.append(&mut depth.update_bid_depth(ev));
+        } else {
+            out.append(&mut depth.update_ask_depth(ev));
+        }
+    } else if ev.is(DEPTH_CLEAR_EVENT) {
+        let side = if ev.is(BUY_EVENT) {
+            Side::Buy
+        } else if ev.is(SELL_EVENT) {
+            Side::Sell
+        } else {
+            Side::None
+        };
+        depth.clear_depth(side, ev.px, ev.exch_ts);
+        out.push(ev);
+    } else if ev.is(DEPTH_BBO_EVENT) {
+        ev.ev = (ev.ev & !DEPTH_BBO_EVENT) | DEPTH_EVENT;
+        if ev.is(BUY_EVENT) {
+            out.append(&mut depth.update_best_bid(ev));
+        } else {
+            out.append(&mut depth.update_best_ask(ev));
+        }
+    }
+}
+
+/// Converts Tardis trades, incremental book L2 and book snapshot files. Trade files should be input
+/// before depth files. If a depth update generated by a trade is handled first, the queue position
+/// is reduced twice—once by the depth message and again by the subsequent trade message.
+///
+/// The file type is determined by the header. To combine book ticker files with the depth data,
+/// use [`convert_fuse`].
+pub fn convert<P: AsRef<Path>>(
+    input_files: &[P],
+    base_latency: i64,
+    snapshot_mode: SnapshotMode,
+) -> std::io::Result<Vec<Event>> {
+    let mut events = Vec::new();
+    for file in input_files {
+        let mut reader = CsvReader::open(file)?;
+        match reader.kind {
+            FileKind::Trades => {
+                while reader.read_row()? {
+                    events.push(reader.trade()?);
+                }
+            }
+            FileKind::IncrementalBook => {
+                let mut depth = DepthConverter::new(snapshot_mode);
+                while reader.read_row()? {
+                    let (ev, is_snapshot) = reader.depth()?;
+                    depth.process(ev, is_snapshot, &mut events);
+                }
+                depth.flush(&mut events);
+            }
+            FileKind::BookSnapshot => {
+                // Every row is a snapshot of the order book.
+                let mut depth = DepthConverter::new(snapshot_mode);
+                while reader.read_row()? {
+                    if !depth.ignores_snapshot() {
+                        reader.book_snapshot(&mut depth)?;
+                        depth.flush(&mut events);
+                    }
+                    depth.is_sod_snapshot = false;
+                }
+            }
+            FileKind::BookTicker => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "use `convert_fuse` to combine the book ticker data with the depth data",
+                ));
+            }
+        }
+    }
+    finalize(events, base_latency)
+}
+
+/// Converts Tardis trades, incremental book L2 and book ticker files, fusing the book ticker data
+/// with the depth data through [`FusedHashMapMarketDepth`] so that the most up-to-date market depth
+/// is used.
+pub fn convert_fuse<P: AsRef<Path>>(
+    trades_file: P,
+    depth_file: P,
+    book_ticker_file: P,
+    tick_size: f64,
+    lot_size: f64,
+    base_latency: i64,
+    snapshot_mode: SnapshotMode,
+) -> std::io::Result<Vec<Event>> {
+    let open_kind = |file: P, kind: FileKind| {
+        let reader = CsvReader::open(file)?;
+        if reader.kind != kind {
+            return Err(reader.error(&format!("expected {kind:?}")));
+        }
+        Ok(reader)
+    };
+    let mut trades = open_kind(trades_file, FileKind::Trades)?;
+    let mut depth_reader = open_kind(depth_file, FileKind::IncrementalBook)?;
+    let mut ticker_reader = open_kind(book_ticker_file, FileKind::BookTicker)?;
+
+    let mut events = Vec::new();
+    while trades.read_row()? {
+        events.push(trades.trade()?);
+    }
+
+    let mut fused = FusedHashMapMarketDepth::new(tick_size, lot_size);
+    let mut depth = DepthConverter::new(snapshot_mode);
+    let mut buf = Vec::new();
+    let mut has_depth = depth_reader.read_row()?;
+    let mut has_ticker = ticker_reader.read_row()?;
+    while has_depth || has_ticker {
+        // Merges the two feeds in the order the local receives them.
+        let ticker_first = has_ticker
+            && (!has_depth || ticker_reader.parse::<i64>(3)? < depth_reader.parse::<i64>(3)?);
+        if ticker_first {
+            ticker_reader.book_ticker(&mut buf)?;
+            has_ticker = ticker_reader.read_row()?;
+        } else {
+            let (ev, is_snapshot) = depth_reader.depth()?;
+            depth.process(ev, is_snapshot, &mut buf);
+            has_depth = depth_reader.read_row()?;
+        }
+        for ev in buf.drain(..) {
+            fuse(&mut fused, ev, &mut events);
+        }
+    }
+    depth.flush(&mut buf);
+    for ev in buf.drain(..) {
+        fuse(&mut fused, ev, &mut events);
+    }
+    finalize(events, base_latency)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, io::Write, path::PathBuf};
+
+    use flate2::{Compression, write::GzEncoder};
+
+    use crate::{
+        backtest::data::convert::{
+            SnapshotMode,
+            tardis::{convert, convert_fuse},
+        },
+        types::{
+            BUY_EVENT,
+            DEPTH_CLEAR_EVENT,
+            DEPTH_EVENT,
+            DEPTH_SNAPSHOT_EVENT,
+            EXCH_EVENT,
+            LOCAL_EVENT,
+            SELL_EVENT,
+            TRADE_EVENT,
+        },
+    };
+
+    fn write_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let file = File::create(&path).unwrap();
+        if name.ends_with(".gz") {
+            let mut encoder = GzEncoder::new(file, Compression::default());
+            encoder.write_all(content.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+        } else {
+            let mut file = file;
+            file.write_all(content.as_bytes()).unwrap();
+        }
+        path
+    }
+
+    const TRADES: &str = "\
+exchange,symbol,timestamp,local_timestamp,id,side,price,amount
+binance-futures,BTCUSDT,1000,1010,1,buy,100.5,0.1
+binance-futures,BTCUSDT,3000,3010,2,sell,100.0,0.2
+";
+
+    const INCREMENTAL_BOOK: &str = "\
+exchange,symbol,timestamp,local_timestamp,is_snapshot,side,price,amount
+binance-futures,BTCUSDT,500,505,true,bid,100.0,1.0
+binance-futures,BTCUSDT,500,505,true,bid,99.5,2.0
+binance-futures,BTCUSDT,500,505,true,ask,100.5,1.0
+binance-futures,BTCUSDT,2000,2020,false,bid,100.0,0.5
+binance-futures,BTCUSDT,2500,2510,false,ask,100.5,0.0
+";
+
+    #[test]
+    fn test_convert() {
+        let trades = write_file("hftbacktest_test_tardis_trades.csv.gz", TRADES);
+        let depth = write_file(
+            "hftbacktest_test_tardis_incremental_book.csv",
+            INCREMENTAL_BOOK,
+        );
+
+        let data = convert(&[&trades, &depth], 0, SnapshotMode::Process).unwrap();
+        let evs: Vec<_> = (0..data.len()).map(|i| data[i].ev).collect();
+        let flags = EXCH_EVENT | LOCAL_EVENT;
+        assert_eq!(
+            evs,
+            vec![
+                DEPTH_CLEAR_EVENT | BUY_EVENT | flags,
+                DEPTH_SNAPSHOT_EVENT | BUY_EVENT | flags,
+                DEPTH_SNAPSHOT_EVENT | BUY_EVENT | flags,
+                DEPTH_CLEAR_EVENT | SELL_EVENT | flags,
+                DEPTH_SNAPSHOT_EVENT | SELL_EVENT | flags,
+                TRADE_EVENT | BUY_EVENT | flags,
+                DEPTH_EVENT | BUY_EVENT | flags,
+                DEPTH_EVENT | SELL_EVENT | flags,
+                TRADE_EVENT | SELL_EVENT | flags,
+            ]
+        );
+        // The bid side is cleared up to the deepest snapshot level.
+        assert_eq!(data[0].px, 99.5);
+        assert_eq!(data[5].exch_ts, 1_000_000);
+        assert_eq!(data[5].local_ts, 1_010_000);
+
+        let data = convert(&[&trades, &depth], 0, SnapshotMode::IgnoreSod).unwrap();
+        assert_eq!(data.len(), 4);
+
+        std::fs::remove_file(trades).unwrap();
+        std::fs::remove_file(depth).unwrap();
+    }
+
+    #[test]
+    fn test_convert_fuse() {
+        let trades = write_file("hftbacktest_test_tardis_fuse_trades.csv", TRADES);
+        let depth = write_file(
+            "hftbacktest_test_tardis_fuse_incremental_book.csv",
+            INCREMENTAL_BOOK,
+        );
+        let book_ticker = write_file(
+            "hftbacktest_test_tardis_fuse_book_ticker.csv",
+            "\
+exchange,symbol,timestamp,local_timestamp,ask_amount,ask_price,bid_price,bid_amount
+binance-futures,BTCUSDT,1500,1505,3.0,101.0,100.0,0.7
+",
+        );
+
+        let data = convert_fuse(
+            &trades,
+            &depth,
+            &book_ticker,
+            0.5,
+            0.1,
+            0,
+            SnapshotMode::Process,
+        )
+        .unwrap();
+        // The book ticker updates the best bid quantity and the best ask.
+        let bid_updates: Vec<_> = (0..data.len())
+            .filter(|&i| data[i].is(DEPTH_EVENT | BUY_EVENT) && data[i].px == 100.0)
+            .map(|i| data[i].qty)
+            .collect();
+        assert_eq!(bid_updates, vec![0.7, 0.5]);
+        assert!(
+            (0..data.len()).any(|i| data[i].is(DEPTH_EVENT | SELL_EVENT) && data[i].px == 101.0)
+        );
+
+        std::fs::remove_file(trades).unwrap();
+        std::fs::remove_file(depth).unwrap();
+        std::fs::remove_file(book_ticker).unwrap();
+    }
+}
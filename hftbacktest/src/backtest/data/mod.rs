@@ -1,5 +1,11 @@
+mod columnar;
+pub mod convert;
 mod npy;
+mod order_latency;
 mod reader;
+mod snapshot;
+mod synthetic;
+mod validation;
 
 use std::{
     marker::PhantomData,
@@ -8,10 +14,57 @@ use std::{
     ptr::null_mut,
     rc::Rc,
     slice::SliceIndex,
+    sync::Arc,
 };
 
-pub use npy::{Field, NpyDTyped, NpyHeader, read_npy_file, read_npz_file, write_npy};
-pub use reader::{Cache, DataPreprocess, DataSource, FeedLatencyAdjustment, Reader, ReaderBuilder};
+pub use columnar::{
+    ChunkIndex,
+    ColumnarReader,
+    ColumnarWriter,
+    Compression,
+    convert_columnar_to_npz,
+    convert_npz_to_columnar,
+    read_columnar_file,
+    write_columnar_file,
+};
+pub use npy::{
+    Field,
+    NpyChunkReader,
+    NpyDTyped,
+    NpyHeader,
+    mmap_npy_file,
+    read_npy_file,
+    read_npz_file,
+    write_npy,
+    write_npz_file,
+};
+pub use order_latency::{
+    OrderLatencyMode,
+    RollingStat,
+    generate_order_latency,
+    order_latency_from_log,
+};
+pub use reader::{
+    Cache,
+    DataPreprocess,
+    DataSource,
+    EventIndex,
+    FeedLatencyAdjustment,
+    Reader,
+    ReaderBuilder,
+    TimeWindow,
+};
+pub use snapshot::{create_snapshot, replay_depth};
+pub use synthetic::{FeedLatency, Hawkes, MarketGenerator, Scenario};
+pub use validation::{
+    EventOrderCorrection,
+    EventOrderValidation,
+    LocalTimestampCorrection,
+    ValidationError,
+    correct_event_order,
+    correct_local_timestamp,
+    validate_event_order,
+};
 
 use crate::utils::{AlignedArray, CACHE_LINE_SIZE};
 
@@ -98,10 +151,36 @@ where
         unsafe { &*(self.ptr.at(i) as *const D) }
     }
 
+    /// Returns the array as a slice.
+    pub fn as_slice(&self) -> &[D] {
+        if self.is_empty() {
+            return &[];
+        }
+        unsafe { std::slice::from_raw_parts(self.ptr.at(self.offset) as *const D, self.len()) }
+    }
+
+    /// Returns the array as a mutable slice. If the buffer belongs to a [`SharedData`], it is copied
+    /// first, so that the other readers of the buffer are unaffected.
+    pub fn as_mut_slice(&mut self) -> &mut [D] {
+        if self.is_empty() {
+            return &mut [];
+        }
+        self.unshare();
+        let len = self.len();
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.at(self.offset) as *mut D, len) }
+    }
+
     /// Returns `true` if the two `Data` point to the same data.
     pub fn data_eq(&self, other: &Self) -> bool {
         Rc::ptr_eq(&self.ptr, &other.ptr)
     }
+
+    /// Replaces the buffer of a [`SharedData`] with a copy of it before it is modified.
+    fn unshare(&mut self) {
+        if self.ptr._shared.is_some() {
+            *self = Self::from_data(self.as_slice());
+        }
+    }
 }
 
 impl<D> Index<usize> for Data<D>
@@ -126,6 +205,7 @@ where
     D: POD + Clone,
 {
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.unshare();
         let size = size_of::<D>();
         let i = self.offset + index * size;
         if i + size > self.ptr.len() {
@@ -139,6 +219,9 @@ where
 pub struct DataPtr {
     ptr: *mut [u8],
     managed: bool,
+    mapped: bool,
+    // Keeps the shared buffer alive while this references it.
+    _shared: Option<Arc<SharedDataPtr>>,
 }
 
 impl DataPtr {
@@ -147,9 +230,47 @@ impl DataPtr {
         Self {
             ptr: arr.into_raw(),
             managed: true,
+            mapped: false,
+            _shared: None,
         }
     }
 
+    /// Constructs a `DataPtr` by memory-mapping the entire file. The mapping is private, so
+    /// modifications, such as those made by a [`DataPreprocess`], are copied on write and never
+    /// reach the file, while the unmodified pages are shared with other processes through the page
+    /// cache.
+    #[cfg(unix)]
+    pub fn map_file(file: &std::fs::File) -> std::io::Result<Self> {
+        use std::os::fd::AsRawFd;
+
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "cannot map an empty file",
+            ));
+        }
+        let ptr = unsafe {
+            libc::mmap(
+                null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Self {
+            ptr: std::ptr::slice_from_raw_parts_mut(ptr as *mut u8, len),
+            managed: false,
+            mapped: true,
+            _shared: None,
+        })
+    }
+
     /// Constructs a `DataPtr` from a fat pointer.
     ///
     /// Unlike other methods that construct an instance from a raw pointer, the raw pointer is not
@@ -161,6 +282,8 @@ impl DataPtr {
         Self {
             ptr,
             managed: false,
+            mapped: false,
+            _shared: None,
         }
     }
 
@@ -187,6 +310,8 @@ impl Default for DataPtr {
         Self {
             ptr: null_mut::<[u8; 0]>() as *mut [u8],
             managed: false,
+            mapped: false,
+            _shared: None,
         }
     }
 }
@@ -220,5 +345,93 @@ impl Drop for DataPtr {
         if self.managed {
             let _ = unsafe { AlignedArray::<u8, CACHE_LINE_SIZE>::from_raw(self.ptr) };
         }
+        #[cfg(unix)]
+        if self.mapped {
+            unsafe {
+                libc::munmap(self.ptr as *mut libc::c_void, self.ptr.len());
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SharedDataPtr(DataPtr);
+
+// The buffer is only read once it is shared, as `Data` copies it before modifying it.
+unsafe impl Send for SharedDataPtr {}
+unsafe impl Sync for SharedDataPtr {}
+
+/// Provides a buffer of an array of structs that can be shared read-only across threads, so that
+/// backtests running in parallel can read the same data without loading it repeatedly.
+///
+/// A [`DataPreprocess`] applied to the [`Data`] referencing the shared buffer, such as the latency
+/// offset of the asset builder, modifies a copy of the buffer instead. Applying it before the data
+/// is shared avoids copying the buffer for each backtest.
+#[derive(Clone, Debug)]
+pub struct SharedData<D>
+where
+    D: POD + Clone,
+{
+    ptr: Arc<SharedDataPtr>,
+    offset: usize,
+    _d_marker: PhantomData<D>,
+}
+
+impl<D> SharedData<D>
+where
+    D: NpyDTyped + POD + Clone,
+{
+    /// Constructs `SharedData` by taking over the buffer of the given [`Data`]. If the buffer is
+    /// also referenced by another [`Data`], the given [`Data`] is returned as the error.
+    pub fn new(data: Data<D>) -> Result<Self, Data<D>> {
+        match Rc::try_unwrap(data.ptr) {
+            Ok(ptr) => Ok(Self {
+                ptr: Arc::new(SharedDataPtr(ptr)),
+                offset: data.offset,
+                _d_marker: PhantomData,
+            }),
+            Err(ptr) => Err(Data {
+                ptr,
+                offset: data.offset,
+                _d_marker: PhantomData,
+            }),
+        }
+    }
+
+    /// Loads the data from the specified `npy` file, or from the `data` array of the specified
+    /// `npz` file.
+    pub fn load(filepath: &str) -> std::io::Result<Self> {
+        let data = if filepath.ends_with(".npz") {
+            read_npz_file::<D>(filepath, "data")?
+        } else {
+            read_npy_file::<D>(filepath)?
+        };
+        Self::new(data).map_err(|_| std::io::Error::other("data is referenced by another `Data`"))
+    }
+
+    /// Returns the length of the array.
+    pub fn len(&self) -> usize {
+        let size = size_of::<D>();
+        (self.ptr.0.len() - self.offset) / size
+    }
+
+    /// Returns `true` if the `SharedData` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a [`Data`] that references this shared buffer. The buffer is kept alive while the
+    /// returned [`Data`] exists, and is copied if the returned [`Data`] is modified.
+    pub fn data(&self) -> Data<D> {
+        Data {
+            ptr: Rc::new(DataPtr {
+                ptr: self.ptr.0.ptr,
+                managed: false,
+                mapped: false,
+                _shared: Some(self.ptr.clone()),
+            }),
+            offset: self.offset,
+            _d_marker: PhantomData,
+        }
     }
 }
@@ -3,6 +3,9 @@ use std::io::Cursor;
 use std::{
     fs::File,
     io::{Error, ErrorKind, Read, Write},
+    marker::PhantomData,
+    mem::size_of,
+    path::Path,
 };
 
 use crate::{
@@ -232,33 +235,28 @@ mod s3_support {
     }
 }
 
-pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
-    reader: &mut R,
-    size: usize,
-) -> std::io::Result<Data<D>> {
-    let mut buf = DataPtr::new(size);
-
-    let mut read_size = 0;
-    while read_size < size {
-        read_size += reader.read(&mut buf[read_size..])?;
-    }
-
-    if buf[0..6].to_vec() != b"\x93NUMPY" {
+/// Parses and validates the `numpy` header at the beginning of the buffer. Returns the header and
+/// the offset at which the array data starts.
+fn check_header<D: NpyDTyped>(buf: &[u8]) -> std::io::Result<(NpyHeader, usize)> {
+    if buf.len() < 10 || buf[0..6] != *b"\x93NUMPY" {
         return Err(Error::new(
             ErrorKind::InvalidData,
             "must start with \\x93NUMPY",
         ));
     }
-    if buf[6..8].to_vec() != b"\x01\x00" {
+    if buf[6..8] != *b"\x01\x00" {
         return Err(Error::new(
             ErrorKind::InvalidData,
             "support only version 1.0",
         ));
     }
     let header_len = u16::from_le_bytes(buf[8..10].try_into().unwrap()) as usize;
+    if buf.len() < 10 + header_len {
+        return Err(Error::new(ErrorKind::InvalidData, "header is truncated"));
+    }
     let header = String::from_utf8(buf[10..(10 + header_len)].to_vec())
         .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
-    let header = NpyHeader::from_header(&header).unwrap();
+    let header = NpyHeader::from_header(&header)?;
 
     if header.fortran_order {
         return Err(Error::new(
@@ -289,10 +287,119 @@ pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
         ));
     }
 
-    let data = unsafe { Data::from_data_ptr(buf, 10 + header_len) };
+    Ok((header, 10 + header_len))
+}
+
+pub fn read_npy<R: Read, D: NpyDTyped + Clone>(
+    reader: &mut R,
+    size: usize,
+) -> std::io::Result<Data<D>> {
+    let mut buf = DataPtr::new(size);
+
+    let mut read_size = 0;
+    while read_size < size {
+        read_size += reader.read(&mut buf[read_size..])?;
+    }
+
+    let (_, offset) = check_header::<D>(&buf[..])?;
+    let data = unsafe { Data::from_data_ptr(buf, offset) };
     Ok(data)
 }
 
+/// Memory-maps a structured array `numpy` file instead of reading it, so that the data is paged in
+/// from the file as it is accessed and the pages are shared with other processes reading the same
+/// file. Modifications to the returned [`Data`] are private and are not written to the file.
+///
+/// On platforms where memory mapping is not supported, this reads the file by
+/// [`read_npy_file`].
+pub fn mmap_npy_file<D: NpyDTyped + Clone>(filepath: &str) -> std::io::Result<Data<D>> {
+    #[cfg(unix)]
+    {
+        let file = File::open(filepath)?;
+        let ptr = DataPtr::map_file(&file)?;
+        let (_, offset) = check_header::<D>(&ptr[..])?;
+        Ok(unsafe { Data::from_data_ptr(ptr, offset) })
+    }
+
+    #[cfg(not(unix))]
+    {
+        read_npy_file(filepath)
+    }
+}
+
+/// Reads a structured array `numpy` stream in chunks, so that only a chunk of the array is held in
+/// memory at a time. This is useful for the arrays in `npz` files, which cannot be memory-mapped.
+pub struct NpyChunkReader<R, D> {
+    reader: R,
+    len: usize,
+    position: usize,
+    _d_marker: PhantomData<D>,
+}
+
+impl<R, D> NpyChunkReader<R, D>
+where
+    R: Read,
+    D: NpyDTyped + Clone,
+{
+    /// Constructs a `NpyChunkReader` by reading the header from the stream.
+    pub fn new(mut reader: R) -> std::io::Result<Self> {
+        let mut buf = vec![0; 10];
+        reader.read_exact(&mut buf)?;
+        if buf[0..6] == *b"\x93NUMPY" {
+            let header_len = u16::from_le_bytes(buf[8..10].try_into().unwrap()) as usize;
+            buf.resize(10 + header_len, 0);
+            reader.read_exact(&mut buf[10..])?;
+        }
+        let (header, _) = check_header::<D>(&buf)?;
+        Ok(Self {
+            reader,
+            len: header.shape[0],
+            position: 0,
+            _d_marker: PhantomData,
+        })
+    }
+
+    /// Returns the number of rows in the array.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the array is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the index of the next row to be read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Reads up to `max_rows` rows as a [`Data`]. Returns an empty [`Data`] at the end of the
+    /// array.
+    pub fn read_chunk(&mut self, max_rows: usize) -> std::io::Result<Data<D>> {
+        let rows = max_rows.min(self.len - self.position);
+        if rows == 0 {
+            return Ok(Data::empty());
+        }
+        let mut buf = DataPtr::new(rows * size_of::<D>());
+        self.reader.read_exact(&mut buf[..])?;
+        self.position += rows;
+        Ok(unsafe { Data::from_data_ptr(buf, 0) })
+    }
+
+    /// Skips the next `rows` rows without holding them in memory.
+    pub fn skip(&mut self, rows: usize) -> std::io::Result<()> {
+        let rows = rows.min(self.len - self.position);
+        let size = (rows * size_of::<D>()) as u64;
+        let skipped = std::io::copy(&mut (&mut self.reader).take(size), &mut std::io::sink())?;
+        if skipped != size {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "array is truncated"));
+        }
+        self.position += rows;
+        Ok(())
+    }
+}
+
 /// Reads a structured array `numpy` file. Currently, it doesn't check if the data structure is the
 /// same as what the file contains. Users should be cautious about this.
 ///
@@ -357,6 +464,8 @@ pub fn read_npz_file<D: NpyDTyped + Clone>(filepath: &str, name: &str) -> std::i
     }
 }
 
+const WRITE_CHUNK_ROWS: usize = 64 * 1024;
+
 pub fn write_npy<W: Write, T: NpyDTyped>(write: &mut W, data: &[T]) -> std::io::Result<()> {
     let descr = T::descr();
     let header = NpyHeader {
@@ -370,7 +479,30 @@ pub fn write_npy<W: Write, T: NpyDTyped>(write: &mut W, data: &[T]) -> std::io::
     let len = header_str.len() as u16;
     write.write_all(&len.to_le_bytes())?;
     write.write_all(header_str.as_bytes())?;
-    write.write_all(vec_as_bytes(data))?;
+    // Writes the rows in chunks so that a large array is streamed into the writer, such as a
+    // compressor, piece by piece.
+    for chunk in data.chunks(WRITE_CHUNK_ROWS) {
+        write.write_all(vec_as_bytes(chunk))?;
+    }
+    Ok(())
+}
+
+/// Writes a structured array into a `numpy` zip archived file under the given name, so that it can
+/// be read by [`read_npz_file`].
+pub fn write_npz_file<P, T>(filepath: P, name: &str, data: &[T]) -> std::io::Result<()>
+where
+    P: AsRef<Path>,
+    T: NpyDTyped,
+{
+    use zip::{ZipWriter, write::SimpleFileOptions};
+
+    let mut zip = ZipWriter::new(File::create(filepath)?);
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::DEFLATE)
+        .compression_level(Some(9));
+    zip.start_file(format!("{name}.npy"), options)?;
+    write_npy(&mut zip, data)?;
+    zip.finish()?;
     Ok(())
 }
 
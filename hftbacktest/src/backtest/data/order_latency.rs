@@ -0,0 +1,379 @@
+//! Builds order latency data for [`IntpOrderLatency`](crate::backtest::models::IntpOrderLatency),
+//! either artificially from the feed latency or from the order latencies observed by a live bot.
+
+use std::{
+    collections::VecDeque,
+    io::{BufRead, Error, ErrorKind},
+};
+
+use crate::{
+    backtest::models::OrderLatencyRow,
+    types::{EXCH_EVENT, Event, LOCAL_EVENT},
+};
+
+/// A statistic of the feed latency over a rolling window.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RollingStat {
+    Mean,
+    Max,
+}
+
+/// Specifies how the order latency is derived from the feed latency.
+#[derive(Clone, Debug)]
+pub enum OrderLatencyMode {
+    /// The order entry latency is `mul_entry * feed_latency + offset_entry` and the order response
+    /// latency is `mul_resp * feed_latency + offset_resp`.
+    Multiplier {
+        mul_entry: f64,
+        offset_entry: f64,
+        mul_resp: f64,
+        offset_resp: f64,
+    },
+    /// The feed latency is ranked among the resampled feed latencies, and the order latencies are
+    /// the same percentile of the given samples of the order entry and response latencies, such as
+    /// those collected by [`order_latency_from_log`]. This follows the distribution of the actual
+    /// order latency while keeping the order latency high when the feed is congested.
+    PercentileMapped { entry: Vec<i64>, resp: Vec<i64> },
+    /// Same as [`Multiplier`](Self::Multiplier), but applied to the statistic of the feed latency
+    /// over the trailing window instead, so that a latency spike persists over the window.
+    RollingWindow {
+        window: i64,
+        stat: RollingStat,
+        mul_entry: f64,
+        offset_entry: f64,
+        mul_resp: f64,
+        offset_resp: f64,
+    },
+}
+
+impl Default for OrderLatencyMode {
+    fn default() -> Self {
+        OrderLatencyMode::Multiplier {
+            mul_entry: 1.0,
+            offset_entry: 0.0,
+            mul_resp: 1.0,
+            offset_resp: 0.0,
+        }
+    }
+}
+
+/// Tracks the statistic of the feed latency over the trailing window.
+struct Rolling {
+    window: i64,
+    stat: RollingStat,
+    // For the mean, all latencies in the window. For the max, the latencies in decreasing order.
+    latencies: VecDeque<(i64, i64)>,
+    sum: i128,
+}
+
+impl Rolling {
+    fn push(&mut self, timestamp: i64, latency: i64) {
+        match self.stat {
+            RollingStat::Mean => {
+                self.sum += latency as i128;
+            }
+            RollingStat::Max => {
+                while self.latencies.back().is_some_and(|&(_, l)| l <= latency) {
+                    self.latencies.pop_back();
+                }
+            }
+        }
+        self.latencies.push_back((timestamp, latency));
+        while let Some(&(ts, l)) = self.latencies.front() {
+            if ts > timestamp - self.window {
+                break;
+            }
+            self.latencies.pop_front();
+            if self.stat == RollingStat::Mean {
+                self.sum -= l as i128;
+            }
+        }
+    }
+
+    fn value(&self) -> f64 {
+        match self.stat {
+            RollingStat::Mean => self.sum as f64 / self.latencies.len() as f64,
+            RollingStat::Max => self.latencies.front().map(|&(_, l)| l).unwrap_or(0) as f64,
+        }
+    }
+}
+
+fn quantile(sorted: &[i64], p: f64) -> i64 {
+    sorted[((sorted.len() - 1) as f64 * p).round() as usize]
+}
+
+/// Generates artificial order latency data from the feed latency, the difference between the local
+/// timestamp and the exchange timestamp of the events received by both. The feed latency is
+/// resampled to the last event in each interval of `resampling_ns` by the local timestamp, and an
+/// order is assumed to be submitted at the local timestamp of that event.
+///
+/// With the default [`OrderLatencyMode::Multiplier`], this is equivalent to `generate_order_latency`
+/// in the Python package.
+pub fn generate_order_latency(
+    data: &[Event],
+    mode: &OrderLatencyMode,
+    resampling_ns: i64,
+) -> std::io::Result<Vec<OrderLatencyRow>> {
+    if resampling_ns <= 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "`resampling_ns` must be positive",
+        ));
+    }
+    let mut rolling = match *mode {
+        OrderLatencyMode::RollingWindow { window, .. } if window <= 0 => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "`window` must be positive",
+            ));
+        }
+        OrderLatencyMode::RollingWindow { window, stat, .. } => Some(Rolling {
+            window,
+            stat,
+            latencies: VecDeque::new(),
+            sum: 0,
+        }),
+        _ => None,
+    };
+
+    // The last local timestamp and feed latency, or its rolling statistic, in each interval.
+    let mut samples: Vec<(i64, f64)> = Vec::new();
+    let mut last_bucket = None;
+    for ev in data {
+        if ev.ev & (EXCH_EVENT | LOCAL_EVENT) != EXCH_EVENT | LOCAL_EVENT {
+            continue;
+        }
+        let feed_latency = ev.local_ts - ev.exch_ts;
+        let value = match &mut rolling {
+            Some(rolling) => {
+                rolling.push(ev.local_ts, feed_latency);
+                rolling.value()
+            }
+            None => feed_latency as f64,
+        };
+        let bucket = ev.local_ts.div_euclid(resampling_ns);
+        if last_bucket == Some(bucket) {
+            *samples.last_mut().unwrap() = (ev.local_ts, value);
+        } else {
+            samples.push((ev.local_ts, value));
+            last_bucket = Some(bucket);
+        }
+    }
+
+    let latencies: Vec<(f64, f64)> = match mode {
+        OrderLatencyMode::Multiplier {
+            mul_entry,
+            offset_entry,
+            mul_resp,
+            offset_resp,
+        }
+        | OrderLatencyMode::RollingWindow {
+            mul_entry,
+            offset_entry,
+            mul_resp,
+            offset_resp,
+            ..
+        } => samples
+            .iter()
+            .map(|&(_, feed_latency)| {
+                (
+                    mul_entry * feed_latency + offset_entry,
+                    mul_resp * feed_latency + offset_resp,
+                )
+            })
+            .collect(),
+        OrderLatencyMode::PercentileMapped { entry, resp } => {
+            if entry.is_empty() || resp.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "the latency samples are empty",
+                ));
+            }
+            let mut entry = entry.clone();
+            let mut resp = resp.clone();
+            entry.sort_unstable();
+            resp.sort_unstable();
+
+            let mut ranks: Vec<usize> = (0..samples.len()).collect();
+            ranks.sort_by(|&a, &b| samples[a].1.total_cmp(&samples[b].1));
+            let mut latencies = vec![(0.0, 0.0); samples.len()];
+            for (rank, &i) in ranks.iter().enumerate() {
+                let p = if samples.len() > 1 {
+                    rank as f64 / (samples.len() - 1) as f64
+                } else {
+                    0.5
+                };
+                latencies[i] = (quantile(&entry, p) as f64, quantile(&resp, p) as f64);
+            }
+            latencies
+        }
+    };
+
+    Ok(samples
+        .iter()
+        .zip(latencies)
+        .map(|(&(req_ts, _), (entry_latency, resp_latency))| {
+            let exch_ts = (req_ts as f64 + entry_latency) as i64;
+            OrderLatencyRow {
+                req_ts,
+                exch_ts,
+                resp_ts: (exch_ts as f64 + resp_latency) as i64,
+                _padding: 0,
+            }
+        })
+        .collect())
+}
+
+fn parse_field(line: &str, name: &str) -> Option<i64> {
+    let start = line.find(name)? + name.len();
+    // Supports both `name=value` of the default `tracing` format and `"name":value` of JSON.
+    let value = line[start..]
+        .trim_start_matches('"')
+        .strip_prefix(['=', ':'])?
+        .trim_start();
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || (i == 0 && c == '-')))
+        .map(|(i, _)| i)
+        .unwrap_or(value.len());
+    value[..end].parse().ok()
+}
+
+/// Builds order latency data from the log of a live bot, in which each order response is logged
+/// with the `req_timestamp`, `exch_timestamp` and `resp_timestamp` fields, as in the
+/// `logging_order_latency` example. Both the default and the JSON formats of `tracing` are
+/// supported, and the lines without these fields are skipped.
+///
+/// The rows are sorted by the request timestamp. The rows with a response timestamp earlier than
+/// the request timestamp are dropped.
+pub fn order_latency_from_log<R: BufRead>(reader: R) -> std::io::Result<Vec<OrderLatencyRow>> {
+    let mut rows = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let (Some(req_ts), Some(exch_ts), Some(resp_ts)) = (
+            parse_field(&line, "req_timestamp"),
+            parse_field(&line, "exch_timestamp"),
+            parse_field(&line, "resp_timestamp"),
+        ) else {
+            continue;
+        };
+        if resp_ts < req_ts {
+            continue;
+        }
+        rows.push(OrderLatencyRow {
+            req_ts,
+            exch_ts,
+            resp_ts,
+            _padding: 0,
+        });
+    }
+    rows.sort_by_key(|row| row.req_ts);
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::{
+            OrderLatencyMode,
+            RollingStat,
+            generate_order_latency,
+            order_latency_from_log,
+        },
+        types::{BUY_EVENT, DEPTH_EVENT, EXCH_EVENT, Event, LOCAL_EVENT},
+    };
+
+    fn event(ev: u64, exch_ts: i64, local_ts: i64) -> Event {
+        Event {
+            ev: ev | DEPTH_EVENT | BUY_EVENT,
+            exch_ts,
+            local_ts,
+            px: 0.0,
+            qty: 0.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    fn latencies(
+        data: &[Event],
+        mode: &OrderLatencyMode,
+        resampling_ns: i64,
+    ) -> Vec<(i64, i64, i64)> {
+        generate_order_latency(data, mode, resampling_ns)
+            .unwrap()
+            .iter()
+            .map(|row| {
+                (
+                    row.req_ts,
+                    row.exch_ts - row.req_ts,
+                    row.resp_ts - row.exch_ts,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_order_latency() {
+        let both = EXCH_EVENT | LOCAL_EVENT;
+        let data = vec![
+            event(both, 0, 10),
+            event(both, 70, 100),
+            // Ignored since it's not received by the local.
+            event(EXCH_EVENT, 100, 500),
+            event(both, 130, 150),
+            event(both, 300, 350),
+        ];
+
+        let mode = OrderLatencyMode::Multiplier {
+            mul_entry: 2.0,
+            offset_entry: 5.0,
+            mul_resp: 1.0,
+            offset_resp: 0.0,
+        };
+        assert_eq!(
+            latencies(&data, &mode, 100),
+            vec![(10, 25, 10), (150, 45, 20), (350, 105, 50)]
+        );
+
+        let mode = OrderLatencyMode::PercentileMapped {
+            entry: vec![300, 100, 200],
+            resp: vec![10, 30, 20],
+        };
+        assert_eq!(
+            latencies(&data, &mode, 100),
+            vec![(10, 100, 10), (150, 200, 20), (350, 300, 30)]
+        );
+
+        let mode = OrderLatencyMode::RollingWindow {
+            window: 200,
+            stat: RollingStat::Max,
+            mul_entry: 1.0,
+            offset_entry: 0.0,
+            mul_resp: 1.0,
+            offset_resp: 0.0,
+        };
+        assert_eq!(
+            latencies(&data, &mode, 100),
+            vec![(10, 10, 10), (150, 30, 30), (350, 50, 50)]
+        );
+    }
+
+    #[test]
+    fn test_order_latency_from_log() {
+        let log = "\
+2024-09-01T00:00:00.000000Z  INFO logging_order_latency: Order response is received. \
+req_timestamp=2000 exch_timestamp=2500 resp_timestamp=3000 req=New
+2024-09-01T00:00:00.000000Z  INFO gridtrading: position=0
+{\"fields\":{\"req_timestamp\":1000,\"exch_timestamp\":0,\"resp_timestamp\":1800}}
+req_timestamp=4000 exch_timestamp=4500 resp_timestamp=3000
+";
+        let rows: Vec<_> = order_latency_from_log(log.as_bytes())
+            .unwrap()
+            .iter()
+            .map(|row| (row.req_ts, row.exch_ts, row.resp_ts))
+            .collect();
+        assert_eq!(rows, vec![(1000, 0, 1800), (2000, 2500, 3000)]);
+    }
+}
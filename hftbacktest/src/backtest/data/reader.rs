@@ -1,10 +1,12 @@
 use std::{
     cell::RefCell,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    fs::File,
     io::{Error as IoError, ErrorKind},
     rc::Rc,
     sync::{
         Arc,
+        Mutex,
         mpsc::{Receiver, Sender, channel},
     },
     thread,
@@ -18,10 +20,23 @@ use crate::{
         data::{
             Data,
             POD,
-            npy::{NpyDTyped, read_npy_file, read_npz_file},
+            columnar::{ColumnarReader, read_columnar_chunk},
+            npy::{NpyChunkReader, NpyDTyped, mmap_npy_file, read_npy_file, read_npz_file},
         },
     },
-    types::Event,
+    types::{
+        ADD_ORDER_EVENT,
+        CANCEL_ORDER_EVENT,
+        DEPTH_BBO_EVENT,
+        DEPTH_CLEAR_EVENT,
+        DEPTH_EVENT,
+        DEPTH_SNAPSHOT_EVENT,
+        EXCH_EVENT,
+        Event,
+        FILL_EVENT,
+        LOCAL_EVENT,
+        MODIFY_ORDER_EVENT,
+    },
 };
 
 /// Data source for the [`Reader`].
@@ -33,8 +48,14 @@ where
     /// Data needs to be loaded from the specified file. This should be a `numpy` file.
     ///
     /// It will be loaded when needed and released
-    /// when no [Processor](`crate::backtest::proc::Processor`) is reading the data.
+    /// when no [Processor](`crate::backtest::proc::Processor`) is reading the data. See
+    /// [`ReaderBuilder::mmap`] and [`ReaderBuilder::chunk_size`] to avoid holding the entire file in
+    /// memory.
     File(String),
+    /// Data needs to be loaded from the specified columnar [`Event`] file, written by
+    /// [`ColumnarWriter`](crate::backtest::data::ColumnarWriter). It is loaded chunk by chunk, and a
+    /// [`DataPreprocess`] is applied to each chunk separately.
+    Columnar(String),
     /// Data is loaded and set by the user.
     Data(Data<D>),
 }
@@ -184,38 +205,48 @@ where
     D: NpyDTyped + Clone,
 {
     key: String,
-    result: Result<DataSend<D>, IoError>,
+    // `None` if the data is entirely after the end of the time window.
+    result: Result<Option<DataSend<D>>, IoError>,
 }
 
 impl<D> LoadDataResult<D>
 where
     D: NpyDTyped + Clone,
 {
-    pub fn ok(key: String, data: Data<D>) -> Self {
+    pub fn new(key: String, result: Result<Option<Data<D>>, IoError>) -> Self {
         Self {
             key,
-            result: Ok(DataSend(data)),
+            result: result.map(|data| data.map(DataSend)),
         }
     }
+}
 
-    pub fn err(key: String, error: IoError) -> Self {
-        Self {
-            key,
-            result: Err(error),
-        }
-    }
+// The file and the chunk number from which the chunk is loaded. A columnar file is kept open with
+// its chunk index.
+enum ChunkSource {
+    Npz(String, usize),
+    Columnar(Arc<Mutex<ColumnarReader>>, usize),
 }
 
+// Requests the chunk with the chunk number to be loaded for the key.
+type ChunkRequest = (String, usize);
+
 /// A builder for constructing [`Reader`].
 pub struct ReaderBuilder<D>
 where
     D: NpyDTyped + POD + Clone,
 {
     data_key_list: Vec<String>,
+    columnar_files: HashSet<String>,
     cache: Cache<D>,
     temporary_data: HashMap<String, Data<D>>,
     parallel_load: bool,
+    mmap: bool,
+    chunk_size: usize,
     preprocessor: Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
+    start: i64,
+    end: i64,
+    time_window: Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
 }
 
 impl<D> Default for ReaderBuilder<D>
@@ -225,10 +256,16 @@ where
     fn default() -> Self {
         Self {
             data_key_list: Default::default(),
+            columnar_files: Default::default(),
             cache: Default::default(),
             temporary_data: Default::default(),
             parallel_load: false,
+            mmap: false,
+            chunk_size: 0,
             preprocessor: None,
+            start: i64::MIN,
+            end: i64::MAX,
+            time_window: None,
         }
     }
 }
@@ -255,6 +292,26 @@ where
         }
     }
 
+    /// Sets whether to memory-map `npy` files instead of reading them into memory. The data is
+    /// paged in as it is read, and the pages are shared through the page cache with other processes
+    /// backtesting the same files. Modifications made by a [`DataPreprocess`] are copied on write.
+    ///
+    /// The default value is `false`.
+    pub fn mmap(self, mmap: bool) -> Self {
+        Self { mmap, ..self }
+    }
+
+    /// Sets the number of rows into which `npz` files are split, so that each file is decompressed
+    /// in a streaming manner and only a sliding window of chunks is held in memory. A
+    /// [`DataPreprocess`] is applied to each chunk separately, so one that is not
+    /// [chunk-safe](DataPreprocess::is_chunk_safe) is rejected.
+    ///
+    /// The number of rows in each file is read when the [`Reader`] is built. The default value is
+    /// `0`, indicating that each file is loaded entirely.
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        Self { chunk_size, ..self }
+    }
+
     /// Sets a [`DataPreprocess`].
     pub fn preprocessor<Preprocessor>(self, preprocessor: Preprocessor) -> Self
     where
@@ -270,12 +327,17 @@ where
     /// the chronological order.
     pub fn data(self, data: Vec<DataSource<D>>) -> Self {
         let mut data_key_list = self.data_key_list;
+        let mut columnar_files = self.columnar_files;
         let mut temporary_data = self.temporary_data;
         for item in data {
             match item {
                 DataSource::File(filepath) => {
                     data_key_list.push(filepath);
                 }
+                DataSource::Columnar(filepath) => {
+                    columnar_files.insert(filepath.clone());
+                    data_key_list.push(filepath);
+                }
                 DataSource::Data(data) => {
                     let key = Uuid::new_v4().to_string();
                     data_key_list.push(key.clone());
@@ -285,34 +347,137 @@ where
         }
         Self {
             data_key_list,
+            columnar_files,
             temporary_data,
             ..self
         }
     }
 
     /// Builds a [`Reader`].
-    pub fn build(self) -> Result<Reader<D>, IoError> {
+    pub fn build(self) -> Result<Reader<D>, IoError>
+    where
+        D: 'static,
+    {
+        let mut data_key_list = Vec::with_capacity(self.data_key_list.len());
+        let mut chunks = HashMap::new();
+        for key in self.data_key_list {
+            if self.columnar_files.contains(&key) {
+                let reader = ColumnarReader::open(&key)?;
+                let num_chunks = reader
+                    .index()
+                    .iter()
+                    // Chunks entirely after the end of the time window are skipped.
+                    .take_while(|entry| {
+                        entry.min_exch_ts <= self.end || entry.min_local_ts <= self.end
+                    })
+                    .count();
+                let reader = Arc::new(Mutex::new(reader));
+                for chunk_num in 0..num_chunks {
+                    let chunk_key = format!("{key}#{chunk_num}");
+                    data_key_list.push(chunk_key.clone());
+                    chunks.insert(chunk_key, ChunkSource::Columnar(reader.clone(), chunk_num));
+                }
+            } else if self.chunk_size > 0 && key.ends_with(".npz") && !key.starts_with("s3://") {
+                let mut archive = zip::ZipArchive::new(File::open(&key)?)?;
+                let len = NpyChunkReader::<_, D>::new(archive.by_name("data.npy")?)?.len();
+                for chunk_num in 0..len.div_ceil(self.chunk_size) {
+                    let chunk_key = format!("{key}#{chunk_num}");
+                    data_key_list.push(chunk_key.clone());
+                    chunks.insert(chunk_key, ChunkSource::Npz(key.clone(), chunk_num));
+                }
+            } else {
+                data_key_list.push(key);
+            }
+        }
+
+        if !chunks.is_empty()
+            && self
+                .preprocessor
+                .as_ref()
+                .is_some_and(|p| !p.is_chunk_safe())
+        {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "the preprocessor needs the entire file, so it cannot be used with the files \
+                read in chunks",
+            ));
+        }
+
+        // The time window is applied after the preprocessor, so that it bounds the adjusted
+        // timestamps.
+        let preprocessor = match (self.preprocessor, self.time_window) {
+            (Some(preprocessor), Some(time_window)) => {
+                Some(Arc::new(Box::new((preprocessor, time_window))
+                    as Box<dyn DataPreprocess<D> + Sync + Send + 'static>))
+            }
+            (preprocessor, time_window) => preprocessor.or(time_window),
+        };
+
         let mut cache = self.cache.clone();
-        for (key, mut data) in self.temporary_data {
-            if let Some(p) = &self.preprocessor {
-                p.preprocess(&mut data)?;
+        let mut past_end = HashSet::new();
+        for (key, data) in self.temporary_data {
+            match preprocess(&preprocessor, data)? {
+                Some(data) => cache.insert(key, data),
+                None => {
+                    cache.insert(key.clone(), Data::empty());
+                    past_end.insert(key);
+                }
             }
-            cache.insert(key, data)
         }
 
         let (tx, rx) = channel();
         Ok(Reader {
-            data_key_list: self.data_key_list.clone(),
+            data_key_list,
             cache,
             data_num: 0,
             tx,
             rx: Rc::new(rx),
             parallel_load: self.parallel_load,
-            preprocessor: self.preprocessor.clone(),
+            mmap: self.mmap,
+            chunk_size: self.chunk_size,
+            chunks: Rc::new(chunks),
+            streams: Default::default(),
+            preprocessor,
+            past_end: Rc::new(RefCell::new(past_end)),
         })
     }
 }
 
+impl ReaderBuilder<Event> {
+    /// Sets the start of the time window. Events before the start are only applied to build the
+    /// market depth; see [`TimeWindow`].
+    ///
+    /// The default value is `i64::MIN`, indicating that the data is read from the beginning.
+    pub fn start(self, start: i64) -> Self {
+        Self {
+            start,
+            time_window: time_window(start, self.end),
+            ..self
+        }
+    }
+
+    /// Sets the end of the time window. Events after the end are dropped, and the chunks of
+    /// columnar files entirely after the end are not loaded.
+    ///
+    /// The default value is `i64::MAX`, indicating that the data is read to the end.
+    pub fn end(self, end: i64) -> Self {
+        Self {
+            end,
+            time_window: time_window(self.start, end),
+            ..self
+        }
+    }
+}
+
+// The time window is installed only if it bounds the data.
+fn time_window(
+    start: i64,
+    end: i64,
+) -> Option<Arc<Box<dyn DataPreprocess<Event> + Sync + Send + 'static>>> {
+    (start != i64::MIN || end != i64::MAX)
+        .then(|| Arc::new(Box::new(TimeWindow::new(start, end)) as Box<_>))
+}
+
 /// Provides `Data` reading based on the given sequence of data through `Cache`.
 #[derive(Clone)]
 pub struct Reader<D>
@@ -325,7 +490,15 @@ where
     tx: Sender<LoadDataResult<D>>,
     rx: Rc<Receiver<LoadDataResult<D>>>,
     parallel_load: bool,
+    mmap: bool,
+    chunk_size: usize,
+    // Maps the chunk keys to the files and the chunk numbers.
+    chunks: Rc<HashMap<String, ChunkSource>>,
+    // Sends the chunk requests to the threads streaming the `npz` files.
+    streams: Rc<RefCell<HashMap<String, Sender<ChunkRequest>>>>,
     preprocessor: Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
+    // The keys of the data entirely after the end of the time window.
+    past_end: Rc<RefCell<HashSet<String>>>,
 }
 
 impl<D> Reader<D>
@@ -337,6 +510,17 @@ where
         ReaderBuilder::default()
     }
 
+    /// Returns the number of [`Data`] retrieved so far.
+    pub fn cursor(&self) -> usize {
+        self.data_num
+    }
+
+    /// Moves the cursor so that the next call to [`next_data`](Reader::next_data) retrieves the
+    /// [`Data`] at the given index, in the order of your additions.
+    pub fn seek(&mut self, cursor: usize) {
+        self.data_num = cursor;
+    }
+
     /// Releases this [`Data`] from the `Cache`. The `Cache` will delete the [`Data`] if there are
     /// no readers accessing it.
     pub fn release(&mut self, data: Data<D>) {
@@ -360,10 +544,17 @@ where
                 match self.rx.recv().unwrap() {
                     LoadDataResult {
                         key,
-                        result: Ok(data),
+                        result: Ok(Some(data)),
                     } => {
                         self.cache.set(&key, data.unwrap());
                     }
+                    LoadDataResult {
+                        key,
+                        result: Ok(None),
+                    } => {
+                        self.cache.set(&key, Data::empty());
+                        self.past_end.borrow_mut().insert(key);
+                    }
                     LoadDataResult {
                         result: Err(err), ..
                     } => {
@@ -375,6 +566,13 @@ where
                 }
             }
 
+            // The following data is not read once the data is entirely after the end of the time
+            // window.
+            if self.past_end.borrow().contains(&key) {
+                self.data_num = self.data_key_list.len();
+                return Err(BacktestError::EndOfData);
+            }
+
             let data = self.cache.get(&key);
             self.data_num += 1;
             Ok(data)
@@ -387,29 +585,54 @@ where
         if !self.cache.contains(key) {
             self.cache.prepare(key.to_string());
 
-            if key.ends_with(".npy") {
+            if let Some(ChunkSource::Columnar(reader, chunk_num)) = self.chunks.get(key) {
+                let tx = self.tx.clone();
+                let key = key.to_string();
+                let reader = reader.clone();
+                let chunk_num = *chunk_num;
+                let preprocessor = self.preprocessor.clone();
+
+                let _ = thread::spawn(move || {
+                    let result = read_columnar_chunk::<D>(&reader, chunk_num)
+                        .and_then(|data| preprocess(&preprocessor, data));
+                    // SendError occurs only if Reader is already destroyed. Since no data is needed
+                    // once the Reader is destroyed, SendError is safely suppressed.
+                    let _ = tx.send(LoadDataResult::new(key, result));
+                });
+            } else if let Some(ChunkSource::Npz(filepath, chunk_num)) = self.chunks.get(key) {
+                let mut streams = self.streams.borrow_mut();
+                let requests = streams.entry(filepath.clone()).or_insert_with(|| {
+                    let (requests, rx) = channel();
+                    let tx = self.tx.clone();
+                    let filepath = filepath.clone();
+                    let chunk_size = self.chunk_size;
+                    let preprocessor = self.preprocessor.clone();
+                    let _ = thread::spawn(move || {
+                        stream_npz_chunks(filepath, chunk_size, rx, tx, preprocessor)
+                    });
+                    requests
+                });
+                requests.send((key.to_string(), *chunk_num)).map_err(|_| {
+                    BacktestError::DataError(IoError::other(format!(
+                        "Failed to stream file '{filepath}'"
+                    )))
+                })?;
+            } else if key.ends_with(".npy") {
                 let tx = self.tx.clone();
                 let filepath = key.to_string();
+                let mmap = self.mmap && !key.starts_with("s3://");
                 let preprocessor = self.preprocessor.clone();
 
                 let _ = thread::spawn(move || {
-                    let load_data = |filepath: &str| {
-                        let mut data = read_npy_file::<D>(filepath)?;
-                        if let Some(preprocessor) = &preprocessor {
-                            preprocessor.preprocess(&mut data)?;
-                        }
-                        Ok(data)
+                    let data = if mmap {
+                        mmap_npy_file::<D>(&filepath)
+                    } else {
+                        read_npy_file::<D>(&filepath)
                     };
+                    let result = data.and_then(|data| preprocess(&preprocessor, data));
                     // SendError occurs only if Reader is already destroyed. Since no data is needed
                     // once the Reader is destroyed, SendError is safely suppressed.
-                    match load_data(&filepath) {
-                        Ok(data) => {
-                            let _ = tx.send(LoadDataResult::ok(filepath, data));
-                        }
-                        Err(err) => {
-                            let _ = tx.send(LoadDataResult::err(filepath, err));
-                        }
-                    }
+                    let _ = tx.send(LoadDataResult::new(filepath, result));
                 });
             } else if key.ends_with(".npz") {
                 let tx = self.tx.clone();
@@ -417,23 +640,11 @@ where
                 let preprocessor = self.preprocessor.clone();
 
                 let _ = thread::spawn(move || {
-                    let load_data = |filepath: &str| {
-                        let mut data = read_npz_file::<D>(filepath, "data")?;
-                        if let Some(preprocessor) = &preprocessor {
-                            preprocessor.preprocess(&mut data)?;
-                        }
-                        Ok(data)
-                    };
+                    let result = read_npz_file::<D>(&filepath, "data")
+                        .and_then(|data| preprocess(&preprocessor, data));
                     // SendError occurs only if Reader is already destroyed. Since no data is needed
                     // once the Reader is destroyed, SendError is safely suppressed.
-                    match load_data(&filepath) {
-                        Ok(data) => {
-                            let _ = tx.send(LoadDataResult::ok(filepath, data));
-                        }
-                        Err(err) => {
-                            let _ = tx.send(LoadDataResult::err(filepath, err));
-                        }
-                    }
+                    let _ = tx.send(LoadDataResult::new(filepath, result));
                 });
             } else {
                 return Err(BacktestError::DataError(IoError::new(
@@ -446,6 +657,64 @@ where
     }
 }
 
+/// Preprocesses the loaded data, or returns `None` if the data is entirely after the end of the time
+/// window; see [`DataPreprocess::is_past_end`].
+fn preprocess<D>(
+    preprocessor: &Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
+    mut data: Data<D>,
+) -> Result<Option<Data<D>>, IoError>
+where
+    D: POD + Clone,
+{
+    if let Some(preprocessor) = preprocessor {
+        if preprocessor.is_past_end(&data) {
+            return Ok(None);
+        }
+        preprocessor.preprocess(&mut data)?;
+    }
+    Ok(Some(data))
+}
+
+/// Streams the chunks of the array in the `npz` file as they are requested. The file is decompressed
+/// only once as long as the chunks are requested in order; it is reopened to seek backward.
+fn stream_npz_chunks<D>(
+    filepath: String,
+    chunk_size: usize,
+    requests: Receiver<ChunkRequest>,
+    tx: Sender<LoadDataResult<D>>,
+    preprocessor: Option<Arc<Box<dyn DataPreprocess<D> + Sync + Send + 'static>>>,
+) where
+    D: NpyDTyped + Clone,
+{
+    let mut request = requests.recv().ok();
+    while let Some(mut current) = request.take() {
+        let result = (|| -> Result<(), IoError> {
+            let mut archive = zip::ZipArchive::new(File::open(&filepath)?)?;
+            let mut chunks = NpyChunkReader::<_, D>::new(archive.by_name("data.npy")?)?;
+            loop {
+                let (key, chunk_num) = &current;
+                chunks.skip(chunk_num * chunk_size - chunks.position())?;
+                let data = preprocess(&preprocessor, chunks.read_chunk(chunk_size)?)?;
+                // SendError occurs only if Reader is already destroyed.
+                let _ = tx.send(LoadDataResult::new(key.clone(), Ok(data)));
+                match requests.recv() {
+                    Ok(next) if next.1 * chunk_size >= chunks.position() => {
+                        current = next;
+                    }
+                    next => {
+                        request = next.ok();
+                        return Ok(());
+                    }
+                }
+            }
+        })();
+        if let Err(err) = result {
+            let _ = tx.send(LoadDataResult::new(current.0, Err(err)));
+            request = requests.recv().ok();
+        }
+    }
+}
+
 /// `DataPreprocess` offers a function to preprocess data before it is fed into the backtesting.
 /// This feature is primarily introduced to adjust timestamps, making it particularly useful when
 /// backtesting the market from a location different from where your order latency was originally
@@ -461,6 +730,41 @@ where
     D: POD + Clone,
 {
     fn preprocess(&self, data: &mut Data<D>) -> Result<(), IoError>;
+
+    /// Returns whether preprocessing each chunk of a file separately gives the same result as
+    /// preprocessing the entire file. [`ReaderBuilder::build`] rejects the preprocessor otherwise
+    /// if the files are read in chunks.
+    fn is_chunk_safe(&self) -> bool {
+        true
+    }
+
+    /// Returns whether the data, before preprocessing, is entirely after the end of the time
+    /// window. If so, [`Reader`] stops reading the data and the data that follows it.
+    fn is_past_end(&self, _data: &Data<D>) -> bool {
+        false
+    }
+}
+
+/// Chains two [`DataPreprocess`], applying them in order, so that a [`Reader`] can apply more than
+/// one.
+impl<D, P1, P2> DataPreprocess<D> for (P1, P2)
+where
+    D: POD + Clone,
+    P1: DataPreprocess<D>,
+    P2: DataPreprocess<D>,
+{
+    fn preprocess(&self, data: &mut Data<D>) -> Result<(), IoError> {
+        self.0.preprocess(data)?;
+        self.1.preprocess(data)
+    }
+
+    fn is_chunk_safe(&self) -> bool {
+        self.0.is_chunk_safe() && self.1.is_chunk_safe()
+    }
+
+    fn is_past_end(&self, data: &Data<D>) -> bool {
+        self.0.is_past_end(data) || self.1.is_past_end(data)
+    }
 }
 
 /// Pre-processes the feed data to adjust for latency. `local_ts` is offset by the specified latency
@@ -492,3 +796,433 @@ impl DataPreprocess<Event> for FeedLatencyAdjustment {
         Ok(())
     }
 }
+
+impl<D, P> DataPreprocess<D> for Box<P>
+where
+    D: POD + Clone,
+    P: DataPreprocess<D> + ?Sized,
+{
+    fn preprocess(&self, data: &mut Data<D>) -> Result<(), IoError> {
+        (**self).preprocess(data)
+    }
+
+    fn is_chunk_safe(&self) -> bool {
+        (**self).is_chunk_safe()
+    }
+
+    fn is_past_end(&self, data: &Data<D>) -> bool {
+        (**self).is_past_end(data)
+    }
+}
+
+impl<D, P> DataPreprocess<D> for Arc<P>
+where
+    D: POD + Clone,
+    P: DataPreprocess<D> + ?Sized,
+{
+    fn preprocess(&self, data: &mut Data<D>) -> Result<(), IoError> {
+        (**self).preprocess(data)
+    }
+
+    fn is_chunk_safe(&self) -> bool {
+        (**self).is_chunk_safe()
+    }
+
+    fn is_past_end(&self, data: &Data<D>) -> bool {
+        (**self).is_past_end(data)
+    }
+}
+
+// The number of rows in a block of `EventIndex`.
+const INDEX_BLOCK_ROWS: usize = 1024;
+
+/// Indexes the exchange events and the local events of the data, so that the first event at or after
+/// a timestamp is found using a binary search, however sparse the events are.
+///
+/// For each block of rows, the index holds the row and the timestamp of the first event at or after
+/// the start of the block. A search finds the block using a binary search and then scans at most a
+/// block of rows.
+///
+/// The data must be in the valid event order; see
+/// [`validate_event_order`](crate::backtest::data::validate_event_order).
+pub struct EventIndex {
+    exch: Vec<(usize, i64)>,
+    local: Vec<(usize, i64)>,
+}
+
+impl EventIndex {
+    /// Constructs an `EventIndex` of the data.
+    pub fn new(data: &[Event]) -> Self {
+        Self {
+            exch: Self::index(data, EXCH_EVENT, |ev| ev.exch_ts),
+            local: Self::index(data, LOCAL_EVENT, |ev| ev.local_ts),
+        }
+    }
+
+    fn index(data: &[Event], flag: u64, ts: impl Fn(&Event) -> i64) -> Vec<(usize, i64)> {
+        let mut blocks = vec![(data.len(), i64::MAX); data.len().div_ceil(INDEX_BLOCK_ROWS)];
+        let mut next = (data.len(), i64::MAX);
+        for (row, event) in data.iter().enumerate().rev() {
+            if event.ev & flag == flag {
+                next = (row, ts(event));
+            }
+            if row % INDEX_BLOCK_ROWS == 0 {
+                blocks[row / INDEX_BLOCK_ROWS] = next;
+            }
+        }
+        blocks
+    }
+
+    fn seek(
+        blocks: &[(usize, i64)],
+        data: &[Event],
+        flag: u64,
+        timestamp: i64,
+        ts: impl Fn(&Event) -> i64,
+    ) -> usize {
+        // The first block whose first event is at or after the timestamp.
+        let block = blocks.partition_point(|&(_, block_ts)| block_ts < timestamp);
+        let end = blocks.get(block).map_or(data.len(), |&(row, _)| row);
+        if block == 0 {
+            return end;
+        }
+        // The event is after the first event of the previous block and, as there is no event
+        // between the start of the block and its first event, either within the previous block or
+        // the first event of the block.
+        let from = blocks[block - 1].0 + 1;
+        let to = (block * INDEX_BLOCK_ROWS).min(end);
+        (from..to)
+            .find(|&row| data[row].ev & flag == flag && ts(&data[row]) >= timestamp)
+            .unwrap_or(end)
+    }
+
+    /// Returns the position of the first exchange event whose exchange timestamp is equal to or
+    /// greater than the given timestamp, or the length of the data if there is no such event. All
+    /// exchange events before the position have earlier exchange timestamps.
+    ///
+    /// `data` must be the data from which the index is constructed.
+    pub fn seek_exch_ts(&self, data: &[Event], exch_ts: i64) -> usize {
+        Self::seek(&self.exch, data, EXCH_EVENT, exch_ts, |ev| ev.exch_ts)
+    }
+
+    /// Returns the position of the first local event whose local timestamp is equal to or greater
+    /// than the given timestamp, or the length of the data if there is no such event. All local
+    /// events before the position have earlier local timestamps.
+    ///
+    /// `data` must be the data from which the index is constructed.
+    pub fn seek_local_ts(&self, data: &[Event], local_ts: i64) -> usize {
+        Self::seek(&self.local, data, LOCAL_EVENT, local_ts, |ev| ev.local_ts)
+    }
+}
+
+/// Pre-processes the feed data to bound it to the time window between `start` and `end`,
+/// inclusive, so that a part of a file can be backtested without slicing the file.
+///
+/// Events before the start are only applied to build the market depth: the depth and order events
+/// are kept with their timestamps moved to the start, so that they are replayed at once when the
+/// backtest begins, while the other events such as trades are dropped. Events after the end are
+/// dropped. An event is kept only for the side, exchange or local, whose timestamp is within the
+/// window.
+///
+/// The boundaries are found using an [`EventIndex`], so the data must be in the valid event order.
+#[derive(Clone)]
+pub struct TimeWindow {
+    start: i64,
+    end: i64,
+}
+
+impl TimeWindow {
+    /// Constructs a `TimeWindow`.
+    pub fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+}
+
+impl DataPreprocess<Event> for TimeWindow {
+    fn is_past_end(&self, data: &Data<Event>) -> bool {
+        data.as_slice()
+            .iter()
+            .find(|event| event.ev & EXCH_EVENT == EXCH_EVENT)
+            .is_some_and(|event| event.exch_ts > self.end)
+    }
+
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        let events = data.as_slice();
+        let index = EventIndex::new(events);
+        let after_end = self.end.saturating_add(1);
+        let exch_end = index.seek_exch_ts(events, after_end);
+        let local_end = index.seek_local_ts(events, after_end);
+        let end_row = exch_end.max(local_end);
+        let start_row = index
+            .seek_exch_ts(events, self.start)
+            .max(index.seek_local_ts(events, self.start))
+            .min(end_row);
+        if start_row == 0 && exch_end.min(local_end) == events.len() {
+            return Ok(());
+        }
+
+        let mut window = Vec::with_capacity(end_row);
+        for (row, event) in events[..end_row].iter().enumerate() {
+            let mut event = event.clone();
+            if row < start_row {
+                let builds_depth = matches!(
+                    event.ev & 0xff,
+                    DEPTH_EVENT
+                        | DEPTH_CLEAR_EVENT
+                        | DEPTH_SNAPSHOT_EVENT
+                        | DEPTH_BBO_EVENT
+                        | ADD_ORDER_EVENT
+                        | CANCEL_ORDER_EVENT
+                        | MODIFY_ORDER_EVENT
+                        | FILL_EVENT
+                );
+                if builds_depth {
+                    event.exch_ts = event.exch_ts.max(self.start);
+                    event.local_ts = event.local_ts.max(self.start);
+                } else {
+                    if event.exch_ts < self.start {
+                        event.ev &= !EXCH_EVENT;
+                    }
+                    if event.local_ts < self.start {
+                        event.ev &= !LOCAL_EVENT;
+                    }
+                }
+            }
+            if event.exch_ts > self.end {
+                event.ev &= !EXCH_EVENT;
+            }
+            if event.local_ts > self.end {
+                event.ev &= !LOCAL_EVENT;
+            }
+            if event.ev & (EXCH_EVENT | LOCAL_EVENT) != 0 {
+                window.push(event);
+            }
+        }
+        *data = if window.is_empty() {
+            Data::empty()
+        } else {
+            Data::from_data(&window)
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use crate::{
+        backtest::{
+            BacktestError,
+            data::{
+                Data,
+                DataSource,
+                EventIndex,
+                LocalTimestampCorrection,
+                Reader,
+                write_npy,
+                write_npz_file,
+            },
+        },
+        types::{BUY_EVENT, DEPTH_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, TRADE_EVENT},
+    };
+
+    fn events(len: usize) -> Vec<Event> {
+        (0..len)
+            .map(|i| Event {
+                ev: DEPTH_EVENT | BUY_EVENT,
+                exch_ts: i as i64,
+                local_ts: i as i64 + 1,
+                px: i as f64,
+                qty: 1.0,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_mmap_and_chunked_npz() {
+        let evs = events(10);
+        let npy = std::env::temp_dir().join("hftbacktest_test_reader_mmap.npy");
+        let npz = std::env::temp_dir().join("hftbacktest_test_reader_chunk.npz");
+        write_npy(&mut File::create(&npy).unwrap(), &evs).unwrap();
+        write_npz_file(&npz, "data", &evs).unwrap();
+
+        let mut reader = Reader::<Event>::builder()
+            .mmap(true)
+            .data(vec![DataSource::File(npy.to_str().unwrap().to_string())])
+            .build()
+            .unwrap();
+        let data = reader.next_data().unwrap();
+        assert_eq!(data.len(), 10);
+        assert_eq!(data[9].px, 9.0);
+        reader.release(data);
+
+        let mut reader = Reader::<Event>::builder()
+            .chunk_size(4)
+            .parallel_load(true)
+            .data(vec![DataSource::File(npz.to_str().unwrap().to_string())])
+            .build()
+            .unwrap();
+        let mut px = Vec::new();
+        let mut lens = Vec::new();
+        while let Ok(data) = reader.next_data() {
+            lens.push(data.len());
+            px.extend((0..data.len()).map(|i| data[i].px));
+            reader.release(data);
+        }
+        assert_eq!(lens, vec![4, 4, 2]);
+        assert_eq!(px, (0..10).map(|i| i as f64).collect::<Vec<_>>());
+
+        // Seeking backward reopens the file.
+        reader.seek(1);
+        let data = reader.next_data().unwrap();
+        assert_eq!(data[0].px, 4.0);
+        reader.release(data);
+
+        // The offset of the local timestamp correction depends on the entire file.
+        let result = Reader::<Event>::builder()
+            .chunk_size(4)
+            .preprocessor(LocalTimestampCorrection::new(0))
+            .data(vec![DataSource::File(npz.to_str().unwrap().to_string())])
+            .build();
+        assert!(result.is_err());
+
+        std::fs::remove_file(npy).unwrap();
+        std::fs::remove_file(npz).unwrap();
+    }
+
+    fn event(ev: u64, exch_ts: i64, local_ts: i64) -> Event {
+        Event {
+            ev,
+            exch_ts,
+            local_ts,
+            px: 0.0,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_seek() {
+        let both = EXCH_EVENT | LOCAL_EVENT;
+        let evs = vec![
+            event(DEPTH_EVENT | both, 10, 15),
+            // Split by the event order correction.
+            event(DEPTH_EVENT | EXCH_EVENT, 20, 40),
+            event(DEPTH_EVENT | both, 30, 35),
+            event(DEPTH_EVENT | LOCAL_EVENT, 20, 40),
+            event(DEPTH_EVENT | both, 50, 55),
+        ];
+        let index = EventIndex::new(&evs);
+        assert_eq!(index.seek_exch_ts(&evs, 0), 0);
+        assert_eq!(index.seek_exch_ts(&evs, 20), 1);
+        assert_eq!(index.seek_exch_ts(&evs, 21), 2);
+        assert_eq!(index.seek_exch_ts(&evs, 50), 4);
+        assert_eq!(index.seek_exch_ts(&evs, 51), 5);
+        assert_eq!(index.seek_local_ts(&evs, 16), 2);
+        assert_eq!(index.seek_local_ts(&evs, 36), 3);
+        assert_eq!(index.seek_local_ts(&evs, 41), 4);
+        assert_eq!(EventIndex::new(&[]).seek_local_ts(&[], 0), 0);
+    }
+
+    #[test]
+    fn test_seek_sparse() {
+        // The local events are sparse and spread over many blocks.
+        let evs: Vec<_> = (0..10_000)
+            .map(|i| {
+                let flag = if i % 3000 == 7 {
+                    LOCAL_EVENT
+                } else {
+                    EXCH_EVENT
+                };
+                event(DEPTH_EVENT | flag, i, i + 1)
+            })
+            .collect();
+        let index = EventIndex::new(&evs);
+        for ts in [0, 8, 9, 3008, 6000, 9008, 9009] {
+            let expected = (0..evs.len())
+                .find(|&i| evs[i].ev & LOCAL_EVENT != 0 && evs[i].local_ts >= ts)
+                .unwrap_or(evs.len());
+            assert_eq!(index.seek_local_ts(&evs, ts), expected, "{ts}");
+            let expected = (0..evs.len())
+                .find(|&i| evs[i].ev & EXCH_EVENT != 0 && evs[i].exch_ts >= ts)
+                .unwrap_or(evs.len());
+            assert_eq!(index.seek_exch_ts(&evs, ts), expected, "{ts}");
+        }
+    }
+
+    #[test]
+    fn test_time_window() {
+        let both = EXCH_EVENT | LOCAL_EVENT;
+        let evs = vec![
+            event(DEPTH_EVENT | BUY_EVENT | both, 10, 15),
+            event(TRADE_EVENT | BUY_EVENT | both, 20, 25),
+            // Received by the local after the start.
+            event(TRADE_EVENT | BUY_EVENT | both, 95, 105),
+            event(DEPTH_EVENT | BUY_EVENT | both, 110, 115),
+            // Received by the local after the end.
+            event(DEPTH_EVENT | BUY_EVENT | both, 195, 205),
+            event(DEPTH_EVENT | BUY_EVENT | both, 210, 215),
+        ];
+        let mut reader = Reader::builder()
+            .start(100)
+            .end(200)
+            .data(vec![DataSource::Data(Data::from_data(&evs))])
+            .build()
+            .unwrap();
+        let data = reader.next_data().unwrap();
+        let window: Vec<_> = data
+            .as_slice()
+            .iter()
+            .map(|ev| (ev.ev, ev.exch_ts, ev.local_ts))
+            .collect();
+        assert_eq!(
+            window,
+            vec![
+                (DEPTH_EVENT | BUY_EVENT | both, 100, 100),
+                (TRADE_EVENT | BUY_EVENT | LOCAL_EVENT, 95, 105),
+                (DEPTH_EVENT | BUY_EVENT | both, 110, 115),
+                (DEPTH_EVENT | BUY_EVENT | EXCH_EVENT, 195, 205),
+            ]
+        );
+        reader.release(data);
+
+        // The data entirely before the start is dropped except for the depth events, and the data
+        // entirely after the end is dropped.
+        let mut reader = Reader::builder()
+            .start(300)
+            .data(vec![DataSource::Data(Data::from_data(&evs))])
+            .build()
+            .unwrap();
+        let data = reader.next_data().unwrap();
+        assert_eq!(data.len(), 4);
+        assert!(data.as_slice().iter().all(|ev| ev.exch_ts == 300));
+        reader.release(data);
+
+        // The data entirely after the end and the data that follows it are not read.
+        let mut reader = Reader::builder()
+            .end(50)
+            .data(vec![
+                DataSource::Data(Data::from_data(&evs[..2])),
+                DataSource::Data(Data::from_data(&evs[2..])),
+                DataSource::Data(Data::from_data(&evs[..2])),
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(reader.next_data().unwrap().len(), 2);
+        assert!(matches!(reader.next_data(), Err(BacktestError::EndOfData)));
+        assert!(matches!(reader.next_data(), Err(BacktestError::EndOfData)));
+    }
+
+    #[test]
+    fn test_time_window_unset() {
+        let builder = Reader::<Event>::builder().start(i64::MIN).end(i64::MAX);
+        assert!(builder.time_window.is_none());
+        assert!(Reader::<Event>::builder().end(0).time_window.is_some());
+    }
+}
@@ -0,0 +1,163 @@
+use std::io::{Error, ErrorKind};
+
+use crate::{
+    backtest::data::{Data, DataSource, read_columnar_file, read_npy_file, read_npz_file},
+    depth::{ApplySnapshot, L2MarketDepth},
+    types::{
+        Event,
+        LOCAL_ASK_DEPTH_BBO_EVENT,
+        LOCAL_ASK_DEPTH_CLEAR_EVENT,
+        LOCAL_ASK_DEPTH_EVENT,
+        LOCAL_ASK_DEPTH_SNAPSHOT_EVENT,
+        LOCAL_BID_DEPTH_BBO_EVENT,
+        LOCAL_BID_DEPTH_CLEAR_EVENT,
+        LOCAL_BID_DEPTH_EVENT,
+        LOCAL_BID_DEPTH_SNAPSHOT_EVENT,
+        LOCAL_DEPTH_CLEAR_EVENT,
+        Side,
+    },
+};
+
+/// Replays the depth events in the data on the market depth as the local receives them, up to and
+/// including the given local timestamp. Returns `true` if the data reaches beyond the timestamp,
+/// so that the following data doesn't need to be replayed.
+pub fn replay_depth<MD>(depth: &mut MD, data: &Data<Event>, timestamp: i64) -> bool
+where
+    MD: L2MarketDepth,
+{
+    for row_num in 0..data.len() {
+        let ev = &data[row_num];
+        // The data is sorted by the exchange timestamp, and the local timestamp cannot precede it.
+        if ev.exch_ts > timestamp {
+            return true;
+        }
+        if ev.local_ts > timestamp {
+            continue;
+        }
+        if ev.is(LOCAL_BID_DEPTH_CLEAR_EVENT) {
+            depth.clear_depth(Side::Buy, ev.px);
+        } else if ev.is(LOCAL_ASK_DEPTH_CLEAR_EVENT) {
+            depth.clear_depth(Side::Sell, ev.px);
+        } else if ev.is(LOCAL_DEPTH_CLEAR_EVENT) {
+            depth.clear_depth(Side::None, 0.0);
+        } else if ev.is(LOCAL_BID_DEPTH_EVENT) || ev.is(LOCAL_BID_DEPTH_SNAPSHOT_EVENT) {
+            depth.update_bid_depth(ev.px, ev.qty, ev.exch_ts);
+        } else if ev.is(LOCAL_ASK_DEPTH_EVENT) || ev.is(LOCAL_ASK_DEPTH_SNAPSHOT_EVENT) {
+            depth.update_ask_depth(ev.px, ev.qty, ev.exch_ts);
+        } else if ev.is(LOCAL_BID_DEPTH_BBO_EVENT) {
+            depth.update_best_bid(ev.px, ev.qty, ev.exch_ts);
+        } else if ev.is(LOCAL_ASK_DEPTH_BBO_EVENT) {
+            depth.update_best_ask(ev.px, ev.qty, ev.exch_ts);
+        }
+    }
+    false
+}
+
+/// Replays the data in order on the market depth up to and including the given local timestamp,
+/// and returns the market depth snapshot at that time, which can be used as the initial snapshot.
+/// To start from a snapshot, apply it to the market depth beforehand. With [`i64::MAX`] as the
+/// timestamp, this returns the snapshot at the end of the data, such as an End-Of-Day snapshot.
+pub fn create_snapshot<MD>(
+    depth: &mut MD,
+    data: &[DataSource<Event>],
+    timestamp: i64,
+) -> std::io::Result<Vec<Event>>
+where
+    MD: L2MarketDepth + ApplySnapshot,
+{
+    for source in data {
+        let data = match source {
+            DataSource::File(filepath) if filepath.ends_with(".npz") => {
+                read_npz_file(filepath, "data")?
+            }
+            DataSource::File(filepath) if filepath.ends_with(".npy") => read_npy_file(filepath)?,
+            DataSource::File(filepath) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unsupported data type: {filepath}"),
+                ));
+            }
+            DataSource::Columnar(filepath) => read_columnar_file(filepath)?,
+            DataSource::Data(data) => data.clone(),
+        };
+        if replay_depth(depth, &data, timestamp) {
+            break;
+        }
+    }
+    Ok(depth.snapshot())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::{Data, DataSource, create_snapshot, read_npz_file, write_npz_file},
+        depth::{ApplySnapshot, HashMapMarketDepth, MarketDepth},
+        types::{
+            BUY_EVENT,
+            DEPTH_CLEAR_EVENT,
+            DEPTH_EVENT,
+            EXCH_EVENT,
+            Event,
+            LOCAL_EVENT,
+            SELL_EVENT,
+        },
+    };
+
+    fn event(ev: u64, exch_ts: i64, local_ts: i64, px: f64, qty: f64) -> Event {
+        Event {
+            ev: EXCH_EVENT | LOCAL_EVENT | ev,
+            exch_ts,
+            local_ts,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_create_snapshot() {
+        let data1 = Data::from_data(&[
+            event(DEPTH_EVENT | BUY_EVENT, 10, 15, 100.0, 1.0),
+            event(DEPTH_EVENT | SELL_EVENT, 20, 25, 101.0, 2.0),
+        ]);
+        let data2 = Data::from_data(&[
+            event(DEPTH_EVENT | BUY_EVENT, 30, 35, 100.0, 3.0),
+            // Received by the local after the timestamp.
+            event(DEPTH_EVENT | BUY_EVENT, 38, 45, 99.0, 1.0),
+            event(DEPTH_CLEAR_EVENT | SELL_EVENT, 50, 55, 102.0, 0.0),
+        ]);
+        let data = [DataSource::Data(data1), DataSource::Data(data2)];
+
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let snapshot = create_snapshot(&mut depth, &data, 40).unwrap();
+        assert_eq!(depth.best_bid_qty(), 3.0);
+        assert_eq!(depth.bid_qty_at_tick(99), 0.0);
+        assert_eq!(snapshot.len(), 2);
+
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let snapshot = create_snapshot(&mut depth, &data, i64::MAX).unwrap();
+        assert_eq!(depth.bid_qty_at_tick(99), 1.0);
+        assert!(depth.best_ask().is_nan());
+        assert_eq!(snapshot.len(), 2);
+
+        // Continues from the snapshot.
+        let mut restored = HashMapMarketDepth::new(1.0, 1.0);
+        restored.apply_snapshot(&Data::from_data(&snapshot));
+        assert_eq!(restored.best_bid_tick(), 100);
+        assert_eq!(restored.bid_qty_at_tick(99), 1.0);
+    }
+
+    #[test]
+    fn test_write_npz_file() {
+        let events = vec![event(DEPTH_EVENT | BUY_EVENT, 10, 15, 100.0, 1.0); 3];
+        let filepath = std::env::temp_dir().join("hftbacktest_test_write_npz_file.npz");
+        write_npz_file(&filepath, "data", &events).unwrap();
+        let data = read_npz_file::<Event>(filepath.to_str().unwrap(), "data").unwrap();
+        std::fs::remove_file(&filepath).unwrap();
+        assert_eq!(data.len(), 3);
+        assert_eq!(data[2].px, 100.0);
+        assert_eq!(data[2].local_ts, 15);
+    }
+}
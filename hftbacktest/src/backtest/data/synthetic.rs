@@ -0,0 +1,690 @@
+//! Generates synthetic market data to test strategies against controlled scenarios.
+//!
+//! The order flow arrives following a Hawkes process, which reproduces the clustering of market
+//! activity, around a mid-price following a random walk. Each arrival is either a trade at the best
+//! price or a quantity update of a price level. On top of this, [`Scenario`] templates script
+//! market conditions such as flash crashes, spread blowouts, feed gaps, crossed books and
+//! one-sided markets for a period of time.
+//!
+//! The generation is deterministic for the given seed, and the generated events are in the valid
+//! event order, so they can be directly fed into the backtest through
+//! [`DataSource::Data`](crate::backtest::data::DataSource::Data).
+
+use std::{collections::BTreeMap, path::Path};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    backtest::data::{Data, write_npz_file},
+    types::{
+        BUY_EVENT,
+        BuildError,
+        DEPTH_EVENT,
+        EXCH_EVENT,
+        Event,
+        LOCAL_EVENT,
+        SELL_EVENT,
+        Side,
+        TRADE_EVENT,
+    },
+};
+
+/// A self-exciting point process, whose intensity at time `t` is
+/// `baseline + Σ alpha * exp(-beta * (t - t_i))` over the past arrivals `t_i`. The rates are per
+/// second. The branching ratio `alpha / beta` must be less than `1` for the process to be
+/// stationary.
+#[derive(Clone, Copy, Debug)]
+pub struct Hawkes {
+    /// The baseline intensity.
+    pub baseline: f64,
+    /// The jump in the intensity at each arrival.
+    pub alpha: f64,
+    /// The decay rate of the excitation.
+    pub beta: f64,
+}
+
+impl Hawkes {
+    /// Constructs a `Hawkes` process.
+    pub fn new(baseline: f64, alpha: f64, beta: f64) -> Self {
+        Self {
+            baseline,
+            alpha,
+            beta,
+        }
+    }
+}
+
+/// Feed latency added to the exchange timestamp to obtain the local timestamp, in nanoseconds.
+#[derive(Clone, Copy, Debug)]
+pub enum FeedLatency {
+    /// A constant latency.
+    Constant(i64),
+    /// The minimum latency plus an exponentially distributed delay, such that the mean latency is
+    /// `mean`.
+    Exponential { min: i64, mean: i64 },
+}
+
+/// A scripted market condition lasting for `duration` from the exchange timestamp `at`.
+#[derive(Clone, Copy, Debug)]
+pub enum Scenario {
+    /// The mid-price drops by `drop` ticks over the duration with aggressive selling, and then
+    /// recovers over `recovery`.
+    FlashCrash {
+        at: i64,
+        duration: i64,
+        drop: i64,
+        recovery: i64,
+    },
+    /// The spread widens to `spread` ticks.
+    SpreadBlowout { at: i64, duration: i64, spread: i64 },
+    /// The feed stops. The book is updated at once when the feed resumes.
+    Gap { at: i64, duration: i64 },
+    /// The best ask is `cross` ticks below the best bid.
+    CrossedBook { at: i64, duration: i64, cross: i64 },
+    /// All price levels of the given side are gone.
+    OneSided { at: i64, duration: i64, side: Side },
+}
+
+impl Scenario {
+    fn period(&self) -> (i64, i64) {
+        match *self {
+            Scenario::FlashCrash {
+                at,
+                duration,
+                recovery,
+                ..
+            } => (at, at + duration + recovery),
+            Scenario::SpreadBlowout { at, duration, .. }
+            | Scenario::Gap { at, duration }
+            | Scenario::CrossedBook { at, duration, .. }
+            | Scenario::OneSided { at, duration, .. } => (at, at + duration),
+        }
+    }
+}
+
+/// The market conditions at a point in time, as scripted by the scenarios.
+#[derive(Default)]
+struct Conditions {
+    gap: bool,
+    mid_offset: f64,
+    spread: Option<i64>,
+    cross: Option<i64>,
+    no_bids: bool,
+    no_asks: bool,
+    sell_pressure: bool,
+}
+
+/// Generates synthetic [`Event`] data.
+///
+/// # Example
+/// ```
+/// use hftbacktest::backtest::data::{Hawkes, MarketGenerator, Scenario};
+///
+/// let data = MarketGenerator::new(42)
+///     .end(10_000_000_000)
+///     .tick_size(0.1)
+///     .lot_size(0.001)
+///     .order_flow(Hawkes::new(20.0, 10.0, 20.0))
+///     .scenario(Scenario::Gap {
+///         at: 5_000_000_000,
+///         duration: 1_000_000_000,
+///     })
+///     .generate()
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct MarketGenerator {
+    seed: u64,
+    start: i64,
+    end: i64,
+    tick_size: f64,
+    lot_size: f64,
+    initial_price: f64,
+    spread: i64,
+    levels: usize,
+    volatility: f64,
+    order_flow: Hawkes,
+    trade_ratio: f64,
+    depth_qty: f64,
+    trade_qty: f64,
+    feed_latency: FeedLatency,
+    scenarios: Vec<Scenario>,
+}
+
+impl MarketGenerator {
+    /// Constructs a `MarketGenerator` with the seed of the random number generator.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            start: 0,
+            end: 60_000_000_000,
+            tick_size: 0.1,
+            lot_size: 0.001,
+            initial_price: 100.0,
+            spread: 1,
+            levels: 10,
+            volatility: 0.05,
+            order_flow: Hawkes::new(50.0, 20.0, 40.0),
+            trade_ratio: 0.1,
+            depth_qty: 1.0,
+            trade_qty: 0.1,
+            feed_latency: FeedLatency::Constant(1_000_000),
+            scenarios: Vec::new(),
+        }
+    }
+
+    /// Sets the exchange timestamp of the first event. The default value is `0`.
+    pub fn start(self, start: i64) -> Self {
+        Self { start, ..self }
+    }
+
+    /// Sets the exchange timestamp after which no event is generated. The default value is 60
+    /// seconds.
+    pub fn end(self, end: i64) -> Self {
+        Self { end, ..self }
+    }
+
+    /// Sets the tick size. The default value is `0.1`.
+    pub fn tick_size(self, tick_size: f64) -> Self {
+        Self { tick_size, ..self }
+    }
+
+    /// Sets the lot size. The default value is `0.001`.
+    pub fn lot_size(self, lot_size: f64) -> Self {
+        Self { lot_size, ..self }
+    }
+
+    /// Sets the initial mid-price. The default value is `100.0`.
+    pub fn initial_price(self, initial_price: f64) -> Self {
+        Self {
+            initial_price,
+            ..self
+        }
+    }
+
+    /// Sets the spread in ticks. The default value is `1`.
+    pub fn spread(self, spread: i64) -> Self {
+        Self { spread, ..self }
+    }
+
+    /// Sets the number of price levels on each side. The default value is `10`.
+    pub fn levels(self, levels: usize) -> Self {
+        Self { levels, ..self }
+    }
+
+    /// Sets the standard deviation of the mid-price change over one second. The default value is
+    /// `0.05`.
+    pub fn volatility(self, volatility: f64) -> Self {
+        Self { volatility, ..self }
+    }
+
+    /// Sets the arrival process of the order flow. The default value is
+    /// `Hawkes::new(50.0, 20.0, 40.0)`.
+    pub fn order_flow(self, order_flow: Hawkes) -> Self {
+        Self { order_flow, ..self }
+    }
+
+    /// Sets the probability that an arrival is a trade rather than a depth update. The default
+    /// value is `0.1`.
+    pub fn trade_ratio(self, trade_ratio: f64) -> Self {
+        Self {
+            trade_ratio,
+            ..self
+        }
+    }
+
+    /// Sets the mean quantity of a price level. The default value is `1.0`.
+    pub fn depth_qty(self, depth_qty: f64) -> Self {
+        Self { depth_qty, ..self }
+    }
+
+    /// Sets the mean quantity of a trade. The default value is `0.1`.
+    pub fn trade_qty(self, trade_qty: f64) -> Self {
+        Self { trade_qty, ..self }
+    }
+
+    /// Sets the feed latency. The default value is a constant 1ms.
+    pub fn feed_latency(self, feed_latency: FeedLatency) -> Self {
+        Self {
+            feed_latency,
+            ..self
+        }
+    }
+
+    /// Adds a [`Scenario`]. If scenarios overlap, all of them apply.
+    pub fn scenario(self, scenario: Scenario) -> Self {
+        let mut scenarios = self.scenarios;
+        scenarios.push(scenario);
+        Self { scenarios, ..self }
+    }
+
+    fn validate(&self) -> Result<(), BuildError> {
+        if self.start > self.end {
+            return Err(BuildError::InvalidArgument("`start` is after `end`"));
+        }
+        if self.tick_size <= 0.0 || self.lot_size <= 0.0 {
+            return Err(BuildError::InvalidArgument(
+                "`tick_size` and `lot_size` must be positive",
+            ));
+        }
+        if self.initial_price <= 0.0 || self.spread < 1 || self.levels == 0 {
+            return Err(BuildError::InvalidArgument(
+                "`initial_price`, `spread` and `levels` must be positive",
+            ));
+        }
+        let Hawkes {
+            baseline,
+            alpha,
+            beta,
+        } = self.order_flow;
+        if baseline <= 0.0 || alpha < 0.0 || alpha >= beta {
+            return Err(BuildError::InvalidArgument(
+                "the order flow must have a positive baseline and a branching ratio less than 1",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.trade_ratio) {
+            return Err(BuildError::InvalidArgument(
+                "`trade_ratio` must be between 0 and 1",
+            ));
+        }
+        match self.feed_latency {
+            FeedLatency::Constant(latency) if latency < 0 => Err(BuildError::InvalidArgument(
+                "the feed latency cannot be negative",
+            )),
+            FeedLatency::Exponential { min, mean } if min < 0 || mean < min => Err(
+                BuildError::InvalidArgument("the feed latency cannot be negative or below `min`"),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    fn conditions(&self, timestamp: i64) -> Conditions {
+        let mut conditions = Conditions::default();
+        for scenario in &self.scenarios {
+            let (from, to) = scenario.period();
+            if timestamp < from || timestamp >= to {
+                continue;
+            }
+            match *scenario {
+                Scenario::FlashCrash {
+                    at, duration, drop, ..
+                } => {
+                    let elapsed = (timestamp - at) as f64;
+                    if timestamp < at + duration {
+                        conditions.mid_offset -= drop as f64 * elapsed / duration as f64;
+                        conditions.sell_pressure = true;
+                    } else {
+                        let recovered = (elapsed - duration as f64) / (to - at - duration) as f64;
+                        conditions.mid_offset -= drop as f64 * (1.0 - recovered);
+                    }
+                }
+                Scenario::SpreadBlowout { spread, .. } => {
+                    conditions.spread = Some(spread);
+                }
+                Scenario::Gap { .. } => {
+                    conditions.gap = true;
+                }
+                Scenario::CrossedBook { cross, .. } => {
+                    conditions.cross = Some(cross);
+                }
+                Scenario::OneSided { side, .. } => match side {
+                    Side::Buy => conditions.no_bids = true,
+                    Side::Sell => conditions.no_asks = true,
+                    _ => {}
+                },
+            }
+        }
+        conditions
+    }
+
+    /// Generates the events.
+    pub fn generate(&self) -> Result<Data<Event>, BuildError> {
+        self.validate()?;
+        let mut sim = Simulation {
+            generator: self,
+            rng: StdRng::seed_from_u64(self.seed),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_local_ts: i64::MIN,
+            events: Vec::new(),
+        };
+
+        let mut mid = self.initial_price / self.tick_size;
+        sim.step(self.start, mid, false);
+
+        // Simulates the Hawkes process by Ogata's thinning, with the intensity right after the
+        // last candidate as the upper bound since the intensity only decays until the next arrival.
+        let Hawkes {
+            baseline,
+            alpha,
+            beta,
+        } = self.order_flow;
+        let mut elapsed = 0.0;
+        let mut excitation = 0.0;
+        loop {
+            let bound = baseline + excitation;
+            let wait = sim.exponential(1.0 / bound);
+            elapsed += wait;
+            excitation *= (-beta * wait).exp();
+            mid += self.volatility / self.tick_size * wait.sqrt() * sim.normal();
+
+            let timestamp = self
+                .start
+                .saturating_add((elapsed * 1_000_000_000.0) as i64);
+            if timestamp > self.end {
+                break;
+            }
+            if sim.rng.random::<f64>() * bound <= baseline + excitation {
+                excitation += alpha;
+                sim.step(timestamp, mid, true);
+            }
+        }
+
+        Ok(if sim.events.is_empty() {
+            Data::empty()
+        } else {
+            Data::from_data(&sim.events)
+        })
+    }
+
+    /// Generates the events and writes them into an `npz` file.
+    pub fn write_npz<P: AsRef<Path>>(&self, path: P) -> Result<(), BuildError> {
+        let data = self.generate()?;
+        write_npz_file(path, "data", data.as_slice()).map_err(|err| BuildError::Error(err.into()))
+    }
+}
+
+struct Simulation<'a> {
+    generator: &'a MarketGenerator,
+    rng: StdRng,
+    // The quantities by the price in ticks.
+    bids: BTreeMap<i64, f64>,
+    asks: BTreeMap<i64, f64>,
+    last_local_ts: i64,
+    events: Vec<Event>,
+}
+
+impl Simulation<'_> {
+    fn exponential(&mut self, mean: f64) -> f64 {
+        -(1.0 - self.rng.random::<f64>()).ln() * mean
+    }
+
+    fn normal(&mut self) -> f64 {
+        // Box-Muller transform.
+        let r = (-2.0 * (1.0 - self.rng.random::<f64>()).ln()).sqrt();
+        r * (std::f64::consts::TAU * self.rng.random::<f64>()).cos()
+    }
+
+    fn qty(&mut self, mean: f64) -> f64 {
+        let lot_size = self.generator.lot_size;
+        let lots = (self.exponential(mean / lot_size)).ceil().max(1.0);
+        lots * lot_size
+    }
+
+    fn push(&mut self, ev: u64, exch_ts: i64, px: i64, qty: f64) {
+        let latency = match self.generator.feed_latency {
+            FeedLatency::Constant(latency) => latency,
+            FeedLatency::Exponential { min, mean } => {
+                min + self.exponential((mean - min) as f64) as i64
+            }
+        };
+        // The local receives the events in order.
+        let local_ts = exch_ts.saturating_add(latency).max(self.last_local_ts);
+        self.last_local_ts = local_ts;
+        self.events.push(Event {
+            ev: ev | EXCH_EVENT | LOCAL_EVENT,
+            exch_ts,
+            local_ts,
+            px: px as f64 * self.generator.tick_size,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        });
+    }
+
+    fn set_level(&mut self, side: u64, timestamp: i64, px: i64, qty: f64) {
+        let book = if side == BUY_EVENT {
+            &mut self.bids
+        } else {
+            &mut self.asks
+        };
+        if qty > 0.0 {
+            book.insert(px, qty);
+        } else {
+            book.remove(&px);
+        }
+        self.push(DEPTH_EVENT | side, timestamp, px, qty);
+    }
+
+    /// Removes the price levels outside `range` and fills the missing ones inside it.
+    fn reshape(&mut self, side: u64, timestamp: i64, range: std::ops::RangeInclusive<i64>) {
+        let book = if side == BUY_EVENT {
+            &self.bids
+        } else {
+            &self.asks
+        };
+        let stale: Vec<i64> = book
+            .keys()
+            .copied()
+            .filter(|px| !range.contains(px))
+            .collect();
+        for px in stale {
+            self.set_level(side, timestamp, px, 0.0);
+        }
+        // Prices cannot go below a tick.
+        for px in (*range.start()).max(1)..=*range.end() {
+            let book = if side == BUY_EVENT {
+                &self.bids
+            } else {
+                &self.asks
+            };
+            if !book.contains_key(&px) {
+                let qty = self.qty(self.generator.depth_qty);
+                self.set_level(side, timestamp, px, qty);
+            }
+        }
+    }
+
+    /// Moves the book to the mid-price under the scripted conditions, and then generates an order
+    /// flow arrival if `arrival` is `true`.
+    fn step(&mut self, timestamp: i64, mid: f64, arrival: bool) {
+        let generator = self.generator;
+        let conditions = generator.conditions(timestamp);
+        if conditions.gap {
+            return;
+        }
+
+        let mid = mid + conditions.mid_offset;
+        let spread = conditions.spread.unwrap_or(generator.spread);
+        let best_bid = (mid - spread as f64 / 2.0).round() as i64;
+        let best_ask = match conditions.cross {
+            Some(cross) => best_bid - cross,
+            None => best_bid + spread,
+        };
+        // A side without levels has an empty range.
+        let levels = |gone: bool| if gone { 0 } else { generator.levels as i64 };
+        let bid_range = best_bid - levels(conditions.no_bids) + 1..=best_bid;
+        let ask_range = best_ask..=best_ask + levels(conditions.no_asks) - 1;
+        self.reshape(BUY_EVENT, timestamp, bid_range);
+        self.reshape(SELL_EVENT, timestamp, ask_range);
+
+        if !arrival {
+            return;
+        }
+        let trade = conditions.sell_pressure || self.rng.random_bool(generator.trade_ratio);
+        let side = if conditions.sell_pressure || self.rng.random_bool(0.5) {
+            SELL_EVENT
+        } else {
+            BUY_EVENT
+        };
+        if trade {
+            // A buy trade takes the best ask, and a sell trade takes the best bid.
+            let best = if side == BUY_EVENT {
+                self.asks.first_key_value()
+            } else {
+                self.bids.last_key_value()
+            };
+            let Some((&px, &depth)) = best else {
+                return;
+            };
+            let qty = self.qty(generator.trade_qty).min(depth);
+            self.push(TRADE_EVENT | side, timestamp, px, qty);
+            let book_side = if side == BUY_EVENT {
+                SELL_EVENT
+            } else {
+                BUY_EVENT
+            };
+            let remaining = ((depth - qty) / generator.lot_size).round() * generator.lot_size;
+            self.set_level(book_side, timestamp, px, remaining);
+        } else {
+            let book = if side == BUY_EVENT {
+                &self.bids
+            } else {
+                &self.asks
+            };
+            if book.is_empty() {
+                return;
+            }
+            let index = self.rng.random_range(0..book.len());
+            let px = *book.keys().nth(index).unwrap();
+            let qty = self.qty(generator.depth_qty);
+            self.set_level(side, timestamp, px, qty);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::{
+            FeedLatency,
+            MarketGenerator,
+            Scenario,
+            replay_depth,
+            validate_event_order,
+        },
+        depth::{HashMapMarketDepth, MarketDepth},
+        types::{EXCH_ASK_DEPTH_EVENT, Side, TRADE_EVENT},
+    };
+
+    const SECOND: i64 = 1_000_000_000;
+
+    fn best_prices(generator: &MarketGenerator, timestamp: i64) -> (f64, f64) {
+        let data = generator.generate().unwrap();
+        let mut depth = HashMapMarketDepth::new(0.1, 0.001);
+        replay_depth(&mut depth, &data, timestamp);
+        (depth.best_bid(), depth.best_ask())
+    }
+
+    #[test]
+    fn test_generate() {
+        let generator = MarketGenerator::new(1)
+            .start(SECOND)
+            .end(11 * SECOND)
+            .feed_latency(FeedLatency::Exponential {
+                min: 1_000_000,
+                mean: 3_000_000,
+            });
+        let data = generator.generate().unwrap();
+        assert!(data.len() > 500);
+        validate_event_order(data.as_slice()).unwrap();
+        let events = data.as_slice();
+        assert!(
+            events
+                .iter()
+                .all(|ev| ev.exch_ts >= SECOND && ev.exch_ts <= 11 * SECOND)
+        );
+        assert!(
+            events
+                .iter()
+                .all(|ev| ev.local_ts - ev.exch_ts >= 1_000_000)
+        );
+        assert!(events.iter().any(|ev| ev.ev & 0xff == TRADE_EVENT));
+
+        // Deterministic for the seed.
+        assert_eq!(events, generator.generate().unwrap().as_slice());
+        assert_ne!(
+            events,
+            MarketGenerator::new(2)
+                .start(SECOND)
+                .end(11 * SECOND)
+                .generate()
+                .unwrap()
+                .as_slice()
+        );
+
+        let (best_bid, best_ask) = best_prices(&generator, 11 * SECOND);
+        assert!(((best_ask - best_bid) / 0.1 - 1.0).abs() < 1e-9);
+
+        assert!(MarketGenerator::new(1).levels(0).generate().is_err());
+    }
+
+    #[test]
+    fn test_scenarios() {
+        let generator = MarketGenerator::new(2).end(10 * SECOND).volatility(0.0);
+
+        let (best_bid, best_ask) = best_prices(
+            &generator.clone().scenario(Scenario::SpreadBlowout {
+                at: 2 * SECOND,
+                duration: 5 * SECOND,
+                spread: 20,
+            }),
+            5 * SECOND,
+        );
+        assert!(((best_ask - best_bid) / 0.1 - 20.0).abs() < 1e-9);
+
+        // The market depth doesn't keep a crossed book, so the events are checked instead.
+        let data = generator
+            .clone()
+            .scenario(Scenario::CrossedBook {
+                at: 2 * SECOND,
+                duration: 5 * SECOND,
+                cross: 3,
+            })
+            .generate()
+            .unwrap();
+        assert!(data.as_slice().iter().any(|ev| {
+            ev.is(EXCH_ASK_DEPTH_EVENT)
+                && ev.exch_ts >= 2 * SECOND
+                && ev.qty > 0.0
+                && (ev.px - 99.7).abs() < 1e-9
+        }));
+
+        let (best_bid, best_ask) = best_prices(
+            &generator.clone().scenario(Scenario::OneSided {
+                at: 2 * SECOND,
+                duration: 5 * SECOND,
+                side: Side::Sell,
+            }),
+            5 * SECOND,
+        );
+        assert!(!best_bid.is_nan());
+        assert!(best_ask.is_nan());
+
+        let (best_bid, _) = best_prices(
+            &generator.clone().scenario(Scenario::FlashCrash {
+                at: 2 * SECOND,
+                duration: 2 * SECOND,
+                drop: 100,
+                recovery: 5 * SECOND,
+            }),
+            4 * SECOND,
+        );
+        assert!(best_bid < 92.0);
+
+        let data = generator
+            .clone()
+            .scenario(Scenario::Gap {
+                at: 2 * SECOND,
+                duration: 5 * SECOND,
+            })
+            .generate()
+            .unwrap();
+        assert!(
+            data.as_slice()
+                .iter()
+                .all(|ev| ev.exch_ts < 2 * SECOND || ev.exch_ts >= 7 * SECOND)
+        );
+    }
+}
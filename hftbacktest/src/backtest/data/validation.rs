@@ -0,0 +1,346 @@
+use std::{
+    fmt::{Display, Formatter},
+    io::{Error as IoError, ErrorKind},
+};
+
+use thiserror::Error;
+
+use crate::{
+    backtest::data::{Data, DataPreprocess},
+    types::{EXCH_EVENT, Event, LOCAL_EVENT},
+};
+
+// Returns whether the event is processed by the exchange and by the local, respectively. An event
+// without both flags, such as the raw output of a converter, is regarded as both.
+fn event_kind(ev: &Event) -> (bool, bool) {
+    let (exch, local) = (ev.is(EXCH_EVENT), ev.is(LOCAL_EVENT));
+    if !exch && !local {
+        (true, true)
+    } else {
+        (exch, local)
+    }
+}
+
+/// Adjusts the local timestamp in place if the feed latency is negative by offsetting it by the
+/// maximum negative latency value as follows:
+///
+/// ```text
+/// feed_latency = local_ts - exch_ts
+/// adjusted_local_ts = local_ts + -min(feed_latency, 0) + base_latency
+/// ```
+///
+/// Due to discrepancies in system time between the exchange and the local machine, latency may be
+/// measured inaccurately, resulting in negative latency values. Since this adjustment may still
+/// produce zero latency, `base_latency` can be added to obtain more realistic values. Returns the
+/// offset applied to the local timestamp, which is `0` if no adjustment is needed.
+pub fn correct_local_timestamp(data: &mut [Event], base_latency: i64) -> i64 {
+    let latency = data
+        .iter()
+        .map(|ev| ev.local_ts - ev.exch_ts)
+        .min()
+        .unwrap_or(i64::MAX);
+
+    if latency < 0 {
+        let local_ts_offset = -latency + base_latency;
+        for ev in data.iter_mut() {
+            ev.local_ts += local_ts_offset;
+        }
+        local_ts_offset
+    } else {
+        0
+    }
+}
+
+/// Corrects the event order when the exchange timestamps are reversed with respect to the local
+/// timestamps, by splitting such an event into an exchange event and a local event, so that the
+/// exchange events are ordered by the exchange timestamp and the local events are ordered by the
+/// local timestamp. See the [data](https://hftbacktest.readthedocs.io/en/latest/data.html) for
+/// details.
+pub fn correct_event_order(data: &[Event]) -> Vec<Event> {
+    let mut exch_index = Vec::with_capacity(data.len());
+    let mut local_index = Vec::with_capacity(data.len());
+    for (row_num, ev) in data.iter().enumerate() {
+        let (exch, local) = event_kind(ev);
+        if exch {
+            exch_index.push(row_num);
+        }
+        if local {
+            local_index.push(row_num);
+        }
+    }
+    // The row number breaks ties so that an event processed by both sides stays intact unless it
+    // needs to be split.
+    let key = |row_num: usize| (data[row_num].exch_ts, data[row_num].local_ts, row_num);
+    exch_index.sort_unstable_by_key(|&row_num| key(row_num));
+    local_index
+        .sort_unstable_by_key(|&row_num| (data[row_num].local_ts, data[row_num].exch_ts, row_num));
+
+    let mut sorted = Vec::with_capacity(exch_index.len() + local_index.len());
+    let (mut exch_rn, mut local_rn) = (0, 0);
+    loop {
+        match (exch_index.get(exch_rn), local_index.get(local_rn)) {
+            (Some(&exch), Some(&local)) if exch == local => {
+                let mut ev = data[exch].clone();
+                ev.ev |= EXCH_EVENT | LOCAL_EVENT;
+                sorted.push(ev);
+                exch_rn += 1;
+                local_rn += 1;
+            }
+            (Some(&exch), Some(&local)) if key(exch) < key(local) => {
+                let mut ev = data[exch].clone();
+                ev.ev = (ev.ev & !LOCAL_EVENT) | EXCH_EVENT;
+                sorted.push(ev);
+                exch_rn += 1;
+            }
+            (_, Some(&local)) => {
+                let mut ev = data[local].clone();
+                ev.ev = (ev.ev & !EXCH_EVENT) | LOCAL_EVENT;
+                sorted.push(ev);
+                local_rn += 1;
+            }
+            (Some(&exch), None) => {
+                let mut ev = data[exch].clone();
+                ev.ev = (ev.ev & !LOCAL_EVENT) | EXCH_EVENT;
+                sorted.push(ev);
+                exch_rn += 1;
+            }
+            (None, None) => break,
+        }
+    }
+    sorted
+}
+
+/// Reports the rows that violate the event order, returned by [`validate_event_order`].
+#[derive(Error, Debug, Default)]
+pub struct ValidationError {
+    /// The row numbers and the feed latencies of the local events whose local timestamp precedes
+    /// the exchange timestamp.
+    pub negative_latency: Vec<(usize, i64)>,
+    /// The row numbers of the exchange events whose exchange timestamp precedes that of the
+    /// previous exchange event.
+    pub exch_out_of_order: Vec<usize>,
+    /// The row numbers of the local events whose local timestamp precedes that of the previous
+    /// local event.
+    pub local_out_of_order: Vec<usize>,
+}
+
+impl ValidationError {
+    /// Returns `true` if no violation is found.
+    pub fn is_empty(&self) -> bool {
+        self.negative_latency.is_empty()
+            && self.exch_out_of_order.is_empty()
+            && self.local_out_of_order.is_empty()
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut violations = Vec::new();
+        if let Some((row_num, latency)) = self.negative_latency.first() {
+            violations.push(format!(
+                "{} local events have negative latency (first at row {row_num}: {latency})",
+                self.negative_latency.len()
+            ));
+        }
+        if let Some(row_num) = self.exch_out_of_order.first() {
+            violations.push(format!(
+                "{} exchange events are out of order (first at row {row_num})",
+                self.exch_out_of_order.len()
+            ));
+        }
+        if let Some(row_num) = self.local_out_of_order.first() {
+            violations.push(format!(
+                "{} local events are out of order (first at row {row_num})",
+                self.local_out_of_order.len()
+            ));
+        }
+        write!(f, "{}", violations.join(", "))
+    }
+}
+
+/// Validates that the exchange events are ordered by the exchange timestamp, the local events are
+/// ordered by the local timestamp, and no local event has negative feed latency.
+pub fn validate_event_order(data: &[Event]) -> Result<(), ValidationError> {
+    let mut error = ValidationError::default();
+    let mut last_exch_ts = i64::MIN;
+    let mut last_local_ts = i64::MIN;
+    for (row_num, ev) in data.iter().enumerate() {
+        let (exch, local) = event_kind(ev);
+        if exch {
+            if ev.exch_ts < last_exch_ts {
+                error.exch_out_of_order.push(row_num);
+            }
+            last_exch_ts = ev.exch_ts;
+        }
+        if local {
+            if ev.local_ts < last_local_ts {
+                error.local_out_of_order.push(row_num);
+            }
+            if ev.local_ts < ev.exch_ts {
+                error
+                    .negative_latency
+                    .push((row_num, ev.local_ts - ev.exch_ts));
+            }
+            last_local_ts = ev.local_ts;
+        }
+    }
+    if error.is_empty() { Ok(()) } else { Err(error) }
+}
+
+/// Pre-processes the feed data by [`correct_local_timestamp`]. The offset is determined for each
+/// piece of data, such as a daily file, separately, so it cannot be used with the files read in
+/// chunks.
+#[derive(Clone)]
+pub struct LocalTimestampCorrection {
+    base_latency: i64,
+}
+
+impl LocalTimestampCorrection {
+    /// Constructs a `LocalTimestampCorrection`.
+    pub fn new(base_latency: i64) -> Self {
+        Self { base_latency }
+    }
+}
+
+impl DataPreprocess<Event> for LocalTimestampCorrection {
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        correct_local_timestamp(data.as_mut_slice(), self.base_latency);
+        Ok(())
+    }
+
+    fn is_chunk_safe(&self) -> bool {
+        // The offset depends on the entire file.
+        false
+    }
+}
+
+/// Pre-processes the feed data by [`correct_event_order`]. Events can be moved across files, so it
+/// cannot be used with the files read in chunks.
+#[derive(Clone, Default)]
+pub struct EventOrderCorrection;
+
+impl EventOrderCorrection {
+    /// Constructs an `EventOrderCorrection`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DataPreprocess<Event> for EventOrderCorrection {
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        *data = Data::from_data(&correct_event_order(data.as_slice()));
+        Ok(())
+    }
+
+    fn is_chunk_safe(&self) -> bool {
+        // Events can be reordered across the chunk boundaries.
+        false
+    }
+}
+
+/// Pre-processes the feed data by [`validate_event_order`], failing the load if any violation is
+/// found.
+#[derive(Clone, Default)]
+pub struct EventOrderValidation;
+
+impl EventOrderValidation {
+    /// Constructs an `EventOrderValidation`.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl DataPreprocess<Event> for EventOrderValidation {
+    fn preprocess(&self, data: &mut Data<Event>) -> Result<(), IoError> {
+        validate_event_order(data.as_slice())
+            .map_err(|error| IoError::new(ErrorKind::InvalidData, error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::{
+            Data,
+            DataPreprocess,
+            EventOrderCorrection,
+            EventOrderValidation,
+            LocalTimestampCorrection,
+            correct_event_order,
+            validate_event_order,
+        },
+        types::{BUY_EVENT, DEPTH_EVENT, EXCH_EVENT, Event, LOCAL_EVENT},
+    };
+
+    fn event(exch_ts: i64, local_ts: i64, px: f64) -> Event {
+        Event {
+            ev: DEPTH_EVENT | BUY_EVENT,
+            exch_ts,
+            local_ts,
+            px,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_correct_event_order() {
+        // The second event is received by the local before the first event.
+        let data = [event(10, 30, 1.0), event(20, 25, 2.0), event(40, 50, 3.0)];
+        let error = validate_event_order(&data).unwrap_err();
+        assert_eq!(error.local_out_of_order, vec![1]);
+
+        let sorted = correct_event_order(&data);
+        assert_eq!(sorted.len(), 4);
+        let order: Vec<_> = (0..sorted.len())
+            .map(|i| {
+                (
+                    sorted[i].px,
+                    sorted[i].is(EXCH_EVENT),
+                    sorted[i].is(LOCAL_EVENT),
+                )
+            })
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (1.0, true, false),
+                (2.0, true, true),
+                (1.0, false, true),
+                (3.0, true, true),
+            ]
+        );
+        assert!(validate_event_order(&sorted).is_ok());
+
+        // Correcting the corrected data doesn't change it.
+        let resorted = correct_event_order(&sorted);
+        assert_eq!(resorted.len(), sorted.len());
+        for i in 0..sorted.len() {
+            assert_eq!(resorted[i].ev, sorted[i].ev);
+            assert_eq!(resorted[i].px, sorted[i].px);
+        }
+    }
+
+    #[test]
+    fn test_preprocess() {
+        let mut data = Data::from_data(&[event(10, 5, 1.0), event(20, 12, 2.0)]);
+        let error = EventOrderValidation.preprocess(&mut data).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("2 local events have negative latency")
+        );
+
+        let preprocessor = (
+            LocalTimestampCorrection::new(1),
+            (EventOrderCorrection::new(), EventOrderValidation::new()),
+        );
+        preprocessor.preprocess(&mut data).unwrap();
+        assert_eq!(data.len(), 2);
+        // Offset by the maximum negative latency, 8, and the base latency.
+        assert_eq!(data[0].local_ts, 14);
+        assert_eq!(data[1].local_ts, 21);
+    }
+}
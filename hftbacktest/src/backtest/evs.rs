@@ -39,6 +39,16 @@ impl EventSet {
         Self { timestamp }
     }
 
+    /// Returns the event timestamps.
+    pub fn timestamps(&self) -> &[i64] {
+        &self.timestamp
+    }
+
+    /// Sets the event timestamps, which must have the same length as the current ones.
+    pub fn set_timestamps(&mut self, timestamps: &[i64]) {
+        self.timestamp.copy_from_slice(timestamps);
+    }
+
     /// Returns the next event to be processed, which has the earliest timestamp.
     pub fn next(&self) -> Option<EventIntent> {
         let mut evst_no = 0;
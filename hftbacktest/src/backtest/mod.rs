@@ -11,11 +11,12 @@ use thiserror::Error;
 
 pub use crate::backtest::{
     models::L3QueueModel,
-    proc::{L3Local, L3NoPartialFillExchange},
+    proc::{L3Local, L3NoPartialFillExchange, L3PartialFillExchange},
 };
 use crate::{
     backtest::{
         assettype::AssetType,
+        checkpoint::{AssetCheckpoint, Checkpoint, ProcessorCheckpoint, load_depth, save_depth},
         data::{Data, FeedLatencyAdjustment, NpyDTyped},
         evs::{EventIntentKind, EventSet},
         models::{LatencyModel, QueueModel},
@@ -23,7 +24,8 @@ use crate::{
         proc::{Local, LocalProcessor, NoPartialFillExchange, PartialFillExchange, Processor},
         state::State,
     },
-    depth::{L2MarketDepth, L3MarketDepth, MarketDepth},
+    depth::{ApplySnapshot, DepthAnalytics, L2MarketDepth, L3MarketDepth, MarketDepth},
+    journal::OrderJournal,
     prelude::{
         Bot,
         OrdType,
@@ -56,6 +58,15 @@ pub mod state;
 /// Recorder for a bot's trading statistics.
 pub mod recorder;
 
+/// Performance statistics computed from the recorded trading statistics.
+pub mod stats;
+
+/// Parallel parameter sweep of backtests.
+pub mod sweep;
+
+/// Checkpointing and resuming of backtests.
+pub mod checkpoint;
+
 pub mod data;
 mod evs;
 
@@ -76,6 +87,12 @@ pub enum BacktestError {
     EndOfData,
     #[error("data error: {0:?}")]
     DataError(#[from] IoError),
+    #[error("checkpoint is not supported by the {0}")]
+    CheckpointUnsupported(&'static str),
+    #[error("invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
+    #[error("order request is rejected during the warm-up period")]
+    WarmingUp,
 }
 
 /// Backtesting Asset
@@ -112,7 +129,7 @@ impl<L, E, D: NpyDTyped + Clone> Asset<L, E, D> {
     pub fn l3_builder<LM, AT, QM, MD, FM>() -> L3AssetBuilder<LM, AT, QM, MD, FM>
     where
         AT: AssetType + Clone + 'static,
-        MD: MarketDepth + L3MarketDepth + 'static,
+        MD: MarketDepth + L3MarketDepth + DepthAnalytics + 'static,
         QM: L3QueueModel<MD> + 'static,
         LM: LatencyModel + Clone + 'static,
         FM: FeeModel + Clone + 'static,
@@ -124,9 +141,11 @@ impl<L, E, D: NpyDTyped + Clone> Asset<L, E, D> {
 
 /// Exchange model kind.
 pub enum ExchangeKind {
-    /// Uses [NoPartialFillExchange](`NoPartialFillExchange`).
+    /// Uses [NoPartialFillExchange](`NoPartialFillExchange`), or [`L3NoPartialFillExchange`] for
+    /// [`L3AssetBuilder`].
     NoPartialFillExchange,
-    /// Uses [PartialFillExchange](`PartialFillExchange`).
+    /// Uses [PartialFillExchange](`PartialFillExchange`), or [`L3PartialFillExchange`] for
+    /// [`L3AssetBuilder`].
     PartialFillExchange,
 }
 
@@ -136,12 +155,17 @@ pub struct L2AssetBuilder<LM, AT, QM, MD, FM> {
     asset_type: Option<AT>,
     data: Vec<DataSource<Event>>,
     parallel_load: bool,
+    mmap: bool,
+    chunk_size: usize,
+    start: i64,
+    end: i64,
     latency_offset: i64,
     fee_model: Option<FM>,
     exch_kind: ExchangeKind,
     last_trades_cap: usize,
     queue_model: Option<QM>,
     depth_builder: Option<Box<dyn Fn() -> MD>>,
+    journal: Option<OrderJournal>,
 }
 
 impl<LM, AT, QM, MD, FM> L2AssetBuilder<LM, AT, QM, MD, FM>
@@ -159,12 +183,17 @@ where
             asset_type: None,
             data: vec![],
             parallel_load: false,
+            mmap: false,
+            chunk_size: 0,
+            start: i64::MIN,
+            end: i64::MAX,
             latency_offset: 0,
             fee_model: None,
             exch_kind: ExchangeKind::NoPartialFillExchange,
             last_trades_cap: 0,
             queue_model: None,
             depth_builder: None,
+            journal: None,
         }
     }
 
@@ -183,6 +212,33 @@ where
         }
     }
 
+    /// Sets whether to memory-map `npy` feed data files instead of reading them into memory. See
+    /// [`ReaderBuilder::mmap`](crate::backtest::data::ReaderBuilder::mmap).
+    /// The default value is `false`.
+    pub fn mmap(self, mmap: bool) -> Self {
+        Self { mmap, ..self }
+    }
+
+    /// Sets the number of rows into which `npz` feed data files are split to be streamed. See
+    /// [`ReaderBuilder::chunk_size`](crate::backtest::data::ReaderBuilder::chunk_size).
+    /// The default value is `0`, indicating that each file is loaded entirely.
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        Self { chunk_size, ..self }
+    }
+
+    /// Sets the start of the time window to backtest. Events before the start are only applied to
+    /// build the market depth. See [`TimeWindow`](crate::backtest::data::TimeWindow).
+    /// The default value is `i64::MIN`, indicating that the data is backtested from the beginning.
+    pub fn start(self, start: i64) -> Self {
+        Self { start, ..self }
+    }
+
+    /// Sets the end of the time window to backtest. Events after the end are dropped.
+    /// The default value is `i64::MAX`, indicating that the data is backtested to the end.
+    pub fn end(self, end: i64) -> Self {
+        Self { end, ..self }
+    }
+
     /// Sets the latency offset to adjust the feed latency by the specified amount. This is
     /// particularly useful in cross-exchange backtesting, where the feed data is collected from a
     /// different site than the one where the strategy is intended to run.
@@ -239,7 +295,9 @@ where
         }
     }
 
-    /// Sets a market depth builder.
+    /// Sets a market depth builder. To build the market depth from multiple streams with different
+    /// update frequencies and ranges, such as the depth diff, the partial depth snapshot, and the
+    /// BBO streams, use [`FusedHashMapMarketDepth`](crate::depth::FusedHashMapMarketDepth).
     pub fn depth<Builder>(self, builder: Builder) -> Self
     where
         Builder: Fn() -> MD + 'static,
@@ -250,17 +308,34 @@ where
         }
     }
 
+    /// Sets an order journal that records every order state transition observed by both the local
+    /// and the exchange. The default value is `None`, indicating that no transitions are recorded.
+    pub fn journal(self, journal: OrderJournal) -> Self {
+        Self {
+            journal: Some(journal),
+            ..self
+        }
+    }
+
     /// Builds an `Asset`.
     pub fn build(self) -> Result<Asset<dyn LocalProcessor<MD>, dyn Processor, Event>, BuildError> {
         let reader = if self.latency_offset == 0 {
             Reader::builder()
                 .parallel_load(self.parallel_load)
+                .mmap(self.mmap)
+                .chunk_size(self.chunk_size)
+                .start(self.start)
+                .end(self.end)
                 .data(self.data)
                 .build()
                 .map_err(|err| BuildError::Error(err.into()))?
         } else {
             Reader::builder()
                 .parallel_load(self.parallel_load)
+                .mmap(self.mmap)
+                .chunk_size(self.chunk_size)
+                .start(self.start)
+                .end(self.end)
                 .data(self.data)
                 .preprocessor(FeedLatencyAdjustment::new(self.latency_offset))
                 .build()
@@ -286,12 +361,15 @@ where
 
         let (order_e2l, order_l2e) = order_bus(order_latency);
 
-        let local = Local::new(
+        let mut local = Local::new(
             create_depth(),
             State::new(asset_type, fee_model),
             self.last_trades_cap,
             order_l2e,
         );
+        if let Some(journal) = &self.journal {
+            local.set_journal(journal.clone());
+        }
 
         let queue_model = self
             .queue_model
@@ -307,12 +385,15 @@ where
 
         match self.exch_kind {
             ExchangeKind::NoPartialFillExchange => {
-                let exch = NoPartialFillExchange::new(
+                let mut exch = NoPartialFillExchange::new(
                     create_depth(),
                     State::new(asset_type, fee_model),
                     queue_model,
                     order_e2l,
                 );
+                if let Some(journal) = self.journal {
+                    exch.set_journal(journal);
+                }
 
                 Ok(Asset {
                     local: Box::new(local),
@@ -321,12 +402,15 @@ where
                 })
             }
             ExchangeKind::PartialFillExchange => {
-                let exch = PartialFillExchange::new(
+                let mut exch = PartialFillExchange::new(
                     create_depth(),
                     State::new(asset_type, fee_model),
                     queue_model,
                     order_e2l,
                 );
+                if let Some(journal) = self.journal {
+                    exch.set_journal(journal);
+                }
 
                 Ok(Asset {
                     local: Box::new(local),
@@ -357,18 +441,23 @@ pub struct L3AssetBuilder<LM, AT, QM, MD, FM> {
     asset_type: Option<AT>,
     data: Vec<DataSource<Event>>,
     parallel_load: bool,
+    mmap: bool,
+    chunk_size: usize,
+    start: i64,
+    end: i64,
     latency_offset: i64,
     fee_model: Option<FM>,
     exch_kind: ExchangeKind,
     last_trades_cap: usize,
     queue_model: Option<QM>,
     depth_builder: Option<Box<dyn Fn() -> MD>>,
+    journal: Option<OrderJournal>,
 }
 
 impl<LM, AT, QM, MD, FM> L3AssetBuilder<LM, AT, QM, MD, FM>
 where
     AT: AssetType + Clone + 'static,
-    MD: MarketDepth + L3MarketDepth + 'static,
+    MD: MarketDepth + L3MarketDepth + DepthAnalytics + 'static,
     QM: L3QueueModel<MD> + 'static,
     LM: LatencyModel + Clone + 'static,
     FM: FeeModel + Clone + 'static,
@@ -381,12 +470,17 @@ where
             asset_type: None,
             data: vec![],
             parallel_load: false,
+            mmap: false,
+            chunk_size: 0,
+            start: i64::MIN,
+            end: i64::MAX,
             latency_offset: 0,
             fee_model: None,
             exch_kind: ExchangeKind::NoPartialFillExchange,
             last_trades_cap: 0,
             queue_model: None,
             depth_builder: None,
+            journal: None,
         }
     }
 
@@ -405,6 +499,33 @@ where
         }
     }
 
+    /// Sets whether to memory-map `npy` feed data files instead of reading them into memory. See
+    /// [`ReaderBuilder::mmap`](crate::backtest::data::ReaderBuilder::mmap).
+    /// The default value is `false`.
+    pub fn mmap(self, mmap: bool) -> Self {
+        Self { mmap, ..self }
+    }
+
+    /// Sets the number of rows into which `npz` feed data files are split to be streamed. See
+    /// [`ReaderBuilder::chunk_size`](crate::backtest::data::ReaderBuilder::chunk_size).
+    /// The default value is `0`, indicating that each file is loaded entirely.
+    pub fn chunk_size(self, chunk_size: usize) -> Self {
+        Self { chunk_size, ..self }
+    }
+
+    /// Sets the start of the time window to backtest. Events before the start are only applied to
+    /// build the market depth. See [`TimeWindow`](crate::backtest::data::TimeWindow).
+    /// The default value is `i64::MIN`, indicating that the data is backtested from the beginning.
+    pub fn start(self, start: i64) -> Self {
+        Self { start, ..self }
+    }
+
+    /// Sets the end of the time window to backtest. Events after the end are dropped.
+    /// The default value is `i64::MAX`, indicating that the data is backtested to the end.
+    pub fn end(self, end: i64) -> Self {
+        Self { end, ..self }
+    }
+
     /// Sets the latency offset to adjust the feed latency by the specified amount. This is
     /// particularly useful in cross-exchange backtesting, where the feed data is collected from a
     /// different site than the one where the strategy is intended to run.
@@ -439,7 +560,7 @@ where
         }
     }
 
-    /// Sets an exchange model. The default value is [`NoPartialFillExchange`].
+    /// Sets an exchange model. The default value is [`L3NoPartialFillExchange`].
     pub fn exchange(self, exch_kind: ExchangeKind) -> Self {
         Self { exch_kind, ..self }
     }
@@ -472,17 +593,34 @@ where
         }
     }
 
+    /// Sets an order journal that records every order state transition observed by both the local
+    /// and the exchange. The default value is `None`, indicating that no transitions are recorded.
+    pub fn journal(self, journal: OrderJournal) -> Self {
+        Self {
+            journal: Some(journal),
+            ..self
+        }
+    }
+
     /// Builds an `Asset`.
     pub fn build(self) -> Result<Asset<dyn LocalProcessor<MD>, dyn Processor, Event>, BuildError> {
         let reader = if self.latency_offset == 0 {
             Reader::builder()
                 .parallel_load(self.parallel_load)
+                .mmap(self.mmap)
+                .chunk_size(self.chunk_size)
+                .start(self.start)
+                .end(self.end)
                 .data(self.data)
                 .build()
                 .map_err(|err| BuildError::Error(err.into()))?
         } else {
             Reader::builder()
                 .parallel_load(self.parallel_load)
+                .mmap(self.mmap)
+                .chunk_size(self.chunk_size)
+                .start(self.start)
+                .end(self.end)
                 .data(self.data)
                 .preprocessor(FeedLatencyAdjustment::new(self.latency_offset))
                 .build()
@@ -508,12 +646,15 @@ where
 
         let (order_e2l, order_l2e) = order_bus(order_latency);
 
-        let local = L3Local::new(
+        let mut local = L3Local::new(
             create_depth(),
             State::new(asset_type, fee_model),
             self.last_trades_cap,
             order_l2e,
         );
+        if let Some(journal) = &self.journal {
+            local.set_journal(journal.clone());
+        }
 
         let queue_model = self
             .queue_model
@@ -529,12 +670,15 @@ where
 
         match self.exch_kind {
             ExchangeKind::NoPartialFillExchange => {
-                let exch = L3NoPartialFillExchange::new(
+                let mut exch = L3NoPartialFillExchange::new(
                     create_depth(),
                     State::new(asset_type, fee_model),
                     queue_model,
                     order_e2l,
                 );
+                if let Some(journal) = self.journal {
+                    exch.set_journal(journal);
+                }
 
                 Ok(Asset {
                     local: Box::new(local),
@@ -543,7 +687,21 @@ where
                 })
             }
             ExchangeKind::PartialFillExchange => {
-                unimplemented!();
+                let mut exch = L3PartialFillExchange::new(
+                    create_depth(),
+                    State::new(asset_type, fee_model),
+                    queue_model,
+                    order_e2l,
+                );
+                if let Some(journal) = self.journal {
+                    exch.set_journal(journal);
+                }
+
+                Ok(Asset {
+                    local: Box::new(local),
+                    exch: Box::new(exch),
+                    reader,
+                })
             }
         }
     }
@@ -552,7 +710,7 @@ where
 impl<LM, AT, QM, MD, FM> Default for L3AssetBuilder<LM, AT, QM, MD, FM>
 where
     AT: AssetType + Clone + 'static,
-    MD: MarketDepth + L3MarketDepth + 'static,
+    MD: MarketDepth + L3MarketDepth + DepthAnalytics + 'static,
     QM: L3QueueModel<MD> + 'static,
     LM: LatencyModel + Clone + 'static,
     FM: FeeModel + Clone + 'static,
@@ -567,8 +725,12 @@ where
 pub struct BacktestBuilder<MD> {
     local: Vec<BacktestProcessorState<Box<dyn LocalProcessor<MD>>>>,
     exch: Vec<BacktestProcessorState<Box<dyn Processor>>>,
+    resume: Option<ResumeFn<MD>>,
+    warm_up_end: Option<i64>,
 }
 
+type ResumeFn<MD> = Box<dyn FnOnce(&mut Backtest<MD>) -> Result<(), BacktestError>>;
+
 impl<MD> BacktestBuilder<MD> {
     /// Adds [`Asset`], which will undergo simulation within the backtester.
     pub fn add_asset(self, asset: Asset<dyn LocalProcessor<MD>, dyn Processor, Event>) -> Self {
@@ -583,18 +745,51 @@ impl<MD> BacktestBuilder<MD> {
         self_
     }
 
+    /// Sets the [`Checkpoint`] from which the backtest resumes. The assets must be added with the
+    /// same configuration, including the data, as the backtest from which the checkpoint was
+    /// taken.
+    pub fn resume_from(self, checkpoint: Checkpoint) -> Self
+    where
+        MD: MarketDepth + ApplySnapshot + 'static,
+    {
+        Self {
+            resume: Some(Box::new(move |backtest| backtest.restore(&checkpoint))),
+            ..self
+        }
+    }
+
+    /// Sets the warm-up period, which lasts until the given timestamp. During the warm-up period,
+    /// events are replayed so that the market depth and the strategy's indicators can be built, but
+    /// order requests are rejected with [`BacktestError::WarmingUp`] without placing any order, and
+    /// [`Bot::is_warming_up`] returns `true`, which excludes the period from the
+    /// [`BacktestRecorder`](recorder::BacktestRecorder) output.
+    pub fn warm_up(self, until: i64) -> Self {
+        Self {
+            warm_up_end: Some(until),
+            ..self
+        }
+    }
+
     /// Builds [`Backtest`].
-    pub fn build(self) -> Result<Backtest<MD>, BuildError> {
+    pub fn build(self) -> Result<Backtest<MD>, BuildError>
+    where
+        MD: MarketDepth,
+    {
         let num_assets = self.local.len();
         if self.local.len() != num_assets || self.exch.len() != num_assets {
             panic!();
         }
-        Ok(Backtest {
+        let mut backtest = Backtest {
             cur_ts: i64::MAX,
             evs: EventSet::new(num_assets),
             local: self.local,
             exch: self.exch,
-        })
+            warm_up_end: self.warm_up_end,
+        };
+        if let Some(resume) = self.resume {
+            resume(&mut backtest).map_err(|err| BuildError::Error(err.into()))?;
+        }
+        Ok(backtest)
     }
 }
 
@@ -606,6 +801,7 @@ pub struct Backtest<MD> {
     evs: EventSet,
     local: Vec<BacktestProcessorState<Box<dyn LocalProcessor<MD>>>>,
     exch: Vec<BacktestProcessorState<Box<dyn Processor>>>,
+    warm_up_end: Option<i64>,
 }
 
 impl<P: Processor> Deref for BacktestProcessorState<P> {
@@ -650,6 +846,66 @@ impl<P: Processor> BacktestProcessorState<P> {
         self.row.ok_or(BacktestError::EndOfData)
     }
 
+    fn checkpoint<MD>(&self) -> Result<ProcessorCheckpoint, BacktestError>
+    where
+        MD: MarketDepth + ApplySnapshot + 'static,
+    {
+        let depth = match self.processor.market_depth() {
+            Some(depth) => {
+                Some(save_depth(depth.downcast_ref::<MD>().ok_or(
+                    BacktestError::CheckpointUnsupported("market depth"),
+                )?))
+            }
+            None => None,
+        };
+        Ok(ProcessorCheckpoint {
+            cursor: self.reader.cursor(),
+            row: self.row,
+            depth,
+            state: self.processor.save_state()?,
+        })
+    }
+
+    fn restore<MD>(&mut self, checkpoint: &ProcessorCheckpoint) -> Result<(), BacktestError>
+    where
+        MD: ApplySnapshot + 'static,
+    {
+        // The depth needs to be restored first, since the exchange's orders are indexed against it.
+        match (self.processor.market_depth_mut(), &checkpoint.depth) {
+            (Some(depth), Some(events)) => load_depth(
+                depth
+                    .downcast_mut::<MD>()
+                    .ok_or(BacktestError::CheckpointUnsupported("market depth"))?,
+                events,
+            ),
+            (None, None) => {}
+            _ => {
+                return Err(BacktestError::InvalidCheckpoint(
+                    "the market depth doesn't match the processor".to_string(),
+                ));
+            }
+        }
+        self.processor.load_state(&checkpoint.state)?;
+        // Reloads the data that was being processed.
+        let data = if checkpoint.cursor > 0 {
+            self.reader.seek(checkpoint.cursor - 1);
+            self.reader.next_data()?
+        } else {
+            self.reader.seek(0);
+            Data::empty()
+        };
+        self.reader.release(std::mem::replace(&mut self.data, data));
+        if let Some(row) = checkpoint.row
+            && row >= self.data.len()
+        {
+            return Err(BacktestError::InvalidCheckpoint(format!(
+                "row {row} is out of the data"
+            )));
+        }
+        self.row = checkpoint.row;
+        Ok(())
+    }
+
     /// Advance the state of this processor to the next available event and return the
     /// timestamp it occurred at, if any.
     fn advance(&mut self) -> Result<i64, BacktestError> {
@@ -679,6 +935,8 @@ where
         BacktestBuilder {
             local: vec![],
             exch: vec![],
+            resume: None,
+            warm_up_end: None,
         }
     }
 
@@ -708,9 +966,64 @@ where
             exch,
             cur_ts: i64::MAX,
             evs: EventSet::new(num_assets),
+            warm_up_end: None,
         }
     }
 
+    /// Captures the complete backtest state at the current timestamp as a [`Checkpoint`].
+    ///
+    /// All local and exchange processors must support checkpointing; see
+    /// [`Processor::save_state`]. The orders recorded by an [`OrderJournal`] are not included.
+    pub fn checkpoint(&self) -> Result<Checkpoint, BacktestError>
+    where
+        MD: ApplySnapshot + 'static,
+    {
+        let assets = self
+            .local
+            .iter()
+            .zip(self.exch.iter())
+            .map(|(local, exch)| {
+                Ok(AssetCheckpoint {
+                    local: local.checkpoint::<MD>()?,
+                    exch: exch.checkpoint::<MD>()?,
+                })
+            })
+            .collect::<Result<Vec<_>, BacktestError>>()?;
+        Ok(Checkpoint::new(
+            self.cur_ts,
+            self.evs.timestamps().to_vec(),
+            assets,
+        ))
+    }
+
+    /// Restores the backtest state from the [`Checkpoint`], so that the backtest resumes at the
+    /// timestamp at which the checkpoint was taken. The backtest must be constructed with the same
+    /// configuration, including the data, as the backtest from which the checkpoint was taken.
+    pub fn restore(&mut self, checkpoint: &Checkpoint) -> Result<(), BacktestError>
+    where
+        MD: ApplySnapshot + 'static,
+    {
+        if checkpoint.num_assets() != self.local.len() {
+            return Err(BacktestError::InvalidCheckpoint(format!(
+                "the checkpoint has {} assets, but the backtest has {}",
+                checkpoint.num_assets(),
+                self.local.len()
+            )));
+        }
+        for ((local, exch), asset) in self
+            .local
+            .iter_mut()
+            .zip(self.exch.iter_mut())
+            .zip(checkpoint.assets.iter())
+        {
+            local.restore::<MD>(&asset.local)?;
+            exch.restore::<MD>(&asset.exch)?;
+        }
+        self.evs.set_timestamps(&checkpoint.evs);
+        self.cur_ts = checkpoint.timestamp;
+        Ok(())
+    }
+
     fn initialize_evs(&mut self) -> Result<(), BacktestError> {
         for (asset_no, local) in self.local.iter_mut().enumerate() {
             match local.advance() {
@@ -879,6 +1192,12 @@ where
         self.local.len()
     }
 
+    #[inline]
+    fn is_warming_up(&self) -> bool {
+        self.warm_up_end
+            .is_some_and(|end| self.cur_ts == i64::MAX || self.cur_ts < end)
+    }
+
     #[inline]
     fn position(&self, asset_no: usize) -> f64 {
         self.local.get(asset_no).unwrap().position()
@@ -928,6 +1247,9 @@ where
         order_type: OrdType,
         wait: bool,
     ) -> Result<ElapseResult, Self::Error> {
+        if self.is_warming_up() {
+            return Err(BacktestError::WarmingUp);
+        }
         let local = self.local.get_mut(asset_no).unwrap();
         local.submit_order(
             order_id,
@@ -959,6 +1281,9 @@ where
         order_type: OrdType,
         wait: bool,
     ) -> Result<ElapseResult, Self::Error> {
+        if self.is_warming_up() {
+            return Err(BacktestError::WarmingUp);
+        }
         let local = self.local.get_mut(asset_no).unwrap();
         local.submit_order(
             order_id,
@@ -985,6 +1310,9 @@ where
         order: OrderRequest,
         wait: bool,
     ) -> Result<ElapseResult, Self::Error> {
+        if self.is_warming_up() {
+            return Err(BacktestError::WarmingUp);
+        }
         let local = self.local.get_mut(asset_no).unwrap();
         local.submit_order(
             order.order_id,
@@ -1017,6 +1345,9 @@ where
         qty: f64,
         wait: bool,
     ) -> Result<ElapseResult, Self::Error> {
+        if self.is_warming_up() {
+            return Err(BacktestError::WarmingUp);
+        }
         let local = self.local.get_mut(asset_no).unwrap();
         local.modify(order_id, price, qty, self.cur_ts)?;
 
@@ -1036,6 +1367,9 @@ where
         order_id: OrderId,
         wait: bool,
     ) -> Result<ElapseResult, Self::Error> {
+        if self.is_warming_up() {
+            return Err(BacktestError::WarmingUp);
+        }
         let local = self.local.get_mut(asset_no).unwrap();
         local.cancel(order_id, self.cur_ts)?;
 
@@ -1146,10 +1480,12 @@ mod test {
     use crate::{
         backtest::{
             Backtest,
+            BacktestError,
             DataSource,
             ExchangeKind::NoPartialFillExchange,
             L2AssetBuilder,
             assettype::LinearAsset,
+            checkpoint::Checkpoint,
             data::Data,
             models::{
                 CommonFees,
@@ -1158,10 +1494,24 @@ mod test {
                 ProbQueueModel,
                 TradingValueFeeModel,
             },
+            recorder::BacktestRecorder,
         },
-        depth::HashMapMarketDepth,
+        depth::{HashMapMarketDepth, MarketDepth},
+        journal::{JournalSource, OrderJournal, OrderTransition, TransitionReason},
         prelude::{Bot, Event},
-        types::{EXCH_EVENT, LOCAL_EVENT},
+        types::{
+            BUY_EVENT,
+            DEPTH_EVENT,
+            EXCH_EVENT,
+            LOCAL_EVENT,
+            OrdType,
+            Recorder,
+            SELL_EVENT,
+            Side,
+            Status,
+            TRADE_EVENT,
+            TimeInForce,
+        },
     };
 
     #[test]
@@ -1238,4 +1588,306 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn journals_order_transitions() -> Result<(), Box<dyn Error>> {
+        let event = |ev: u64, ts: i64, px: f64| Event {
+            ev,
+            exch_ts: ts,
+            local_ts: ts,
+            px,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        };
+        let data = Data::from_data(&[
+            event(EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_EVENT, 0, 100.0),
+            event(
+                EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_EVENT,
+                0,
+                100.1,
+            ),
+            event(EXCH_EVENT | LOCAL_EVENT, 1000, 0.0),
+        ]);
+
+        let journal = OrderJournal::new();
+        let mut backtester = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(0.1, 1.0))
+                    .journal(journal.clone())
+                    .build()?,
+            )
+            .build()?;
+
+        backtester.elapse(10)?;
+        backtester.submit_buy_order(0, 1, 99.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)?;
+        backtester.submit_buy_order(0, 2, 100.1, 1.0, TimeInForce::GTX, OrdType::Limit, false)?;
+        backtester.elapse(200)?;
+        backtester.cancel(0, 1, false)?;
+        backtester.elapse(200)?;
+
+        let entries: Vec<_> = journal
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.timestamp,
+                    entry.source,
+                    entry.order_id,
+                    entry.transition,
+                    entry.reason,
+                )
+            })
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    10,
+                    JournalSource::Local,
+                    1,
+                    OrderTransition::Submitted,
+                    TransitionReason::None
+                ),
+                (
+                    10,
+                    JournalSource::Local,
+                    2,
+                    OrderTransition::Submitted,
+                    TransitionReason::None
+                ),
+                (
+                    60,
+                    JournalSource::Exchange,
+                    1,
+                    OrderTransition::Acked,
+                    TransitionReason::None
+                ),
+                (
+                    60,
+                    JournalSource::Exchange,
+                    2,
+                    OrderTransition::Expired,
+                    TransitionReason::PostOnlyCrossed
+                ),
+                (
+                    110,
+                    JournalSource::Local,
+                    1,
+                    OrderTransition::Acked,
+                    TransitionReason::None
+                ),
+                (
+                    110,
+                    JournalSource::Local,
+                    2,
+                    OrderTransition::Expired,
+                    TransitionReason::PostOnlyCrossed
+                ),
+                (
+                    210,
+                    JournalSource::Local,
+                    1,
+                    OrderTransition::CancelRequested,
+                    TransitionReason::None
+                ),
+                (
+                    260,
+                    JournalSource::Exchange,
+                    1,
+                    OrderTransition::Canceled,
+                    TransitionReason::None
+                ),
+                (
+                    310,
+                    JournalSource::Local,
+                    1,
+                    OrderTransition::Canceled,
+                    TransitionReason::None
+                ),
+            ]
+        );
+        assert_eq!(journal.entries()[0].side, Side::Buy);
+
+        Ok(())
+    }
+
+    #[test]
+    fn resumes_from_checkpoint() -> Result<(), Box<dyn Error>> {
+        let event = |ev: u64, ts: i64, px: f64, qty: f64| Event {
+            ev,
+            exch_ts: ts,
+            local_ts: ts + 5,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        };
+        let data1 = Data::from_data(&[
+            event(
+                EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_EVENT,
+                0,
+                100.0,
+                5.0,
+            ),
+            event(
+                EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_EVENT,
+                0,
+                100.1,
+                5.0,
+            ),
+        ]);
+        let data2 = Data::from_data(&[
+            event(
+                EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | TRADE_EVENT,
+                300,
+                100.0,
+                3.0,
+            ),
+            event(
+                EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | TRADE_EVENT,
+                400,
+                100.0,
+                3.0,
+            ),
+            event(EXCH_EVENT | LOCAL_EVENT, 1000, 0.0, 0.0),
+        ]);
+        let build = |checkpoint: Option<Checkpoint>| -> Result<Backtest<_>, Box<dyn Error>> {
+            let builder = Backtest::builder().add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![
+                        DataSource::Data(data1.clone()),
+                        DataSource::Data(data2.clone()),
+                    ])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(0.1, 1.0))
+                    .last_trades_capacity(10)
+                    .build()?,
+            );
+            Ok(match checkpoint {
+                Some(checkpoint) => builder.resume_from(checkpoint).build()?,
+                None => builder.build()?,
+            })
+        };
+
+        let mut backtester = build(None)?;
+        backtester.elapse(10)?;
+        backtester.submit_buy_order(0, 1, 100.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)?;
+        backtester.elapse(40)?;
+        // The order is in transit to the exchange.
+        let in_transit = backtester.checkpoint()?;
+        backtester.elapse(300)?;
+        // The order is resting in the queue and the first trade has been processed.
+        let in_queue = backtester.checkpoint()?;
+        let depth_timestamp = backtester.depth(0).timestamp();
+        backtester.goto_end()?;
+        assert_eq!(backtester.position(0), 1.0);
+
+        let path = std::env::temp_dir().join(format!("hbt-checkpoint-{}", std::process::id()));
+        in_transit.save(&path)?;
+        let loaded = Checkpoint::load(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(loaded.timestamp(), 50);
+
+        for checkpoint in [loaded, in_queue] {
+            let mut resumed = build(Some(checkpoint.clone()))?;
+            assert_eq!(resumed.current_timestamp(), checkpoint.timestamp());
+            assert_eq!(resumed.depth(0).best_bid_tick(), 1000);
+            assert_eq!(resumed.depth(0).timestamp(), depth_timestamp);
+            resumed.goto_end()?;
+
+            assert_eq!(resumed.state_values(0), backtester.state_values(0));
+            assert_eq!(resumed.orders(0)[&1].status, Status::Filled);
+            assert_eq!(
+                resumed.last_trades(0).len(),
+                backtester.last_trades(0).len()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn ignores_orders_during_warm_up() -> Result<(), Box<dyn Error>> {
+        let event = |ev: u64, ts: i64, px: f64, qty: f64| Event {
+            ev,
+            exch_ts: ts,
+            local_ts: ts + 5,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        };
+        let data = Data::from_data(&[
+            event(
+                EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_EVENT,
+                0,
+                100.0,
+                5.0,
+            ),
+            event(
+                EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_EVENT,
+                0,
+                100.1,
+                5.0,
+            ),
+            event(EXCH_EVENT | LOCAL_EVENT, 1000, 0.0, 0.0),
+        ]);
+        let mut backtester = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(0.1, 1.0))
+                    .build()?,
+            )
+            .warm_up(200)
+            .build()?;
+        let mut recorder = BacktestRecorder::new(&backtester);
+
+        assert!(backtester.is_warming_up());
+        backtester.elapse(100)?;
+        assert!(backtester.is_warming_up());
+        // The depth is built during the warm-up period.
+        assert_eq!(backtester.depth(0).best_bid_tick(), 1000);
+        assert!(matches!(
+            backtester.submit_buy_order(0, 1, 100.0, 1.0, TimeInForce::GTC, OrdType::Limit, true),
+            Err(BacktestError::WarmingUp)
+        ));
+        assert!(backtester.orders(0).is_empty());
+        assert_eq!(backtester.current_timestamp(), 100);
+        assert!(matches!(
+            backtester.cancel(0, 1, false),
+            Err(BacktestError::WarmingUp)
+        ));
+        recorder.record(&backtester)?;
+        assert!(recorder.records(0).is_empty());
+
+        backtester.elapse(100)?;
+        assert!(!backtester.is_warming_up());
+        backtester.submit_buy_order(0, 1, 100.0, 1.0, TimeInForce::GTC, OrdType::Limit, false)?;
+        recorder.record(&backtester)?;
+        assert_eq!(recorder.records(0).len(), 1);
+        assert_eq!(recorder.records(0)[0].timestamp, 200);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,341 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Error as IoError, ErrorKind},
+    path::Path,
+};
+
+use bincode::config;
+
+use crate::{
+    backtest::{
+        BacktestError,
+        models::{L3FIFOQueueModel, L3QueueModel, PowerProbQueueFunc3, Probability},
+    },
+    depth::{HashMapMarketDepth, L3MarketDepth, MarketDepth},
+    types::{
+        BUY_EVENT,
+        EXCH_ASK_ADD_ORDER_EVENT,
+        EXCH_ASK_DEPTH_CLEAR_EVENT,
+        EXCH_BID_ADD_ORDER_EVENT,
+        EXCH_BID_DEPTH_CLEAR_EVENT,
+        EXCH_CANCEL_ORDER_EVENT,
+        EXCH_DEPTH_CLEAR_EVENT,
+        EXCH_EVENT,
+        EXCH_FILL_EVENT,
+        EXCH_MODIFY_ORDER_EVENT,
+        Event,
+        OrderId,
+        SELL_EVENT,
+        Side,
+    },
+};
+
+/// Collects the queue positions at which the market feed orders are canceled by replaying Level 3
+/// Market-By-Order data through [`L3FIFOQueueModel`], and fits a [`Probability`] from them. The
+/// fitted probability can be used by [`ProbQueueModel`](crate::backtest::models::ProbQueueModel)
+/// to estimate the queue position on the Level 2 feed of the same venue.
+///
+/// Quantity decreases caused by fills are not counted as cancellations.
+pub struct L3QueueCalibrator {
+    depth: HashMapMarketDepth,
+    queue_model: L3FIFOQueueModel,
+    // The filled quantity of the market feed order that is not yet removed from the queue.
+    filled: HashMap<OrderId, f64>,
+    // The relative queue positions of the front and the end of the canceled order, and the
+    // canceled quantity.
+    samples: Vec<(f64, f64, f64)>,
+}
+
+impl L3QueueCalibrator {
+    /// Constructs an instance of `L3QueueCalibrator`.
+    pub fn new(tick_size: f64, lot_size: f64) -> Self {
+        Self {
+            depth: HashMapMarketDepth::new(tick_size, lot_size),
+            queue_model: L3FIFOQueueModel::new(),
+            filled: Default::default(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Processes the Level 3 Market-By-Order event. Only exchange events are processed.
+    pub fn process(&mut self, event: &Event) -> Result<(), BacktestError> {
+        if !event.is(EXCH_EVENT) {
+            return Ok(());
+        }
+        if event.is(EXCH_BID_DEPTH_CLEAR_EVENT) {
+            self.clear(Side::Buy);
+        } else if event.is(EXCH_ASK_DEPTH_CLEAR_EVENT) {
+            self.clear(Side::Sell);
+        } else if event.is(EXCH_DEPTH_CLEAR_EVENT) {
+            self.clear(Side::None);
+        } else if event.is(EXCH_BID_ADD_ORDER_EVENT) {
+            self.depth
+                .add_buy_order(event.order_id, event.px, event.qty, event.exch_ts)?;
+            self.queue_model.add_market_feed_order(event, &self.depth)?;
+        } else if event.is(EXCH_ASK_ADD_ORDER_EVENT) {
+            self.depth
+                .add_sell_order(event.order_id, event.px, event.qty, event.exch_ts)?;
+            self.queue_model.add_market_feed_order(event, &self.depth)?;
+        } else if event.is(EXCH_MODIFY_ORDER_EVENT) {
+            // A quantity decrease at the same price keeps the queue position, so it is a partial
+            // cancellation.
+            let price_tick = (event.px / self.depth.tick_size()).round() as i64;
+            if let Some((front, qty, total)) = self.queue_pos(event.order_id, Some(price_tick))
+                && event.qty < qty
+            {
+                self.add_sample(event.order_id, front, qty, total, qty - event.qty);
+            }
+            self.depth
+                .modify_order(event.order_id, event.px, event.qty, event.exch_ts)?;
+            self.queue_model
+                .modify_market_feed_order(event.order_id, event, &self.depth)?;
+        } else if event.is(EXCH_CANCEL_ORDER_EVENT) {
+            if let Some((front, qty, total)) = self.queue_pos(event.order_id, None) {
+                self.add_sample(event.order_id, front, qty, total, qty);
+            }
+            self.filled.remove(&event.order_id);
+            self.depth.delete_order(event.order_id, event.exch_ts)?;
+            self.queue_model
+                .cancel_market_feed_order(event.order_id, &self.depth)?;
+        } else if event.is(EXCH_FILL_EVENT) && (event.is(BUY_EVENT) || event.is(SELL_EVENT)) {
+            *self.filled.entry(event.order_id).or_default() += event.qty;
+        }
+        Ok(())
+    }
+
+    fn clear(&mut self, side: Side) {
+        self.depth.clear_orders(side);
+        let _ = <L3FIFOQueueModel as L3QueueModel<HashMapMarketDepth>>::clear_orders(
+            &mut self.queue_model,
+            side,
+        );
+        if side == Side::None {
+            self.filled.clear();
+        }
+    }
+
+    /// Returns the quantity ahead of the order, the order's quantity, and the total quantity at
+    /// the price level, if the order is at the given price.
+    fn queue_pos(&self, order_id: OrderId, price_tick: Option<i64>) -> Option<(f64, f64, f64)> {
+        let (side, order_price_tick) = *self.queue_model.mkt_feed_orders.get(&order_id)?;
+        if price_tick.is_some_and(|price_tick| price_tick != order_price_tick) {
+            return None;
+        }
+        let queue = match side {
+            Side::Buy => self.queue_model.bid_queue.get(&order_price_tick)?,
+            Side::Sell => self.queue_model.ask_queue.get(&order_price_tick)?,
+            Side::None | Side::Unsupported => return None,
+        };
+        let mut front = 0.0;
+        let mut qty = None;
+        for order in queue {
+            if order.order_id == order_id {
+                qty = Some(order.leaves_qty);
+                break;
+            }
+            front += order.leaves_qty;
+        }
+        let total = queue.iter().map(|order| order.leaves_qty).sum();
+        Some((front, qty?, total))
+    }
+
+    fn add_sample(&mut self, order_id: OrderId, front: f64, qty: f64, total: f64, decrease: f64) {
+        // Excludes the decrease caused by fills.
+        let mut canceled = decrease;
+        if let Some(filled) = self.filled.get_mut(&order_id) {
+            let by_fill = filled.min(decrease);
+            *filled -= by_fill;
+            canceled -= by_fill;
+        }
+        if canceled > 0.0 && total > 0.0 {
+            self.samples
+                .push((front / total, (front + qty) / total, canceled));
+        }
+    }
+
+    /// Returns the number of cancellations collected.
+    pub fn num_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns the fraction of the canceled quantity behind the relative queue position `x`.
+    fn behind(&self, x: f64) -> f64 {
+        let mut behind = 0.0;
+        let mut total = 0.0;
+        for &(start, end, qty) in &self.samples {
+            let fraction = if end > start {
+                ((end - x) / (end - start)).clamp(0.0, 1.0)
+            } else if start >= x {
+                1.0
+            } else {
+                0.0
+            };
+            behind += fraction * qty;
+            total += qty;
+        }
+        behind / total
+    }
+
+    /// Fits a non-parametric [`TableProbQueueFunc`] with `num_bins` bins. Returns `None` if no
+    /// cancellation has been collected.
+    pub fn fit_table(&self, num_bins: usize) -> Option<TableProbQueueFunc> {
+        assert!(num_bins > 0);
+        if self.samples.is_empty() {
+            return None;
+        }
+        let table = (0..=num_bins)
+            .map(|i| self.behind(i as f64 / num_bins as f64))
+            .collect();
+        Some(TableProbQueueFunc::new(table))
+    }
+
+    /// Fits [`PowerProbQueueFunc3`], whose probability is `1 - x ** n` for the relative queue
+    /// position `x`, by matching the mean of the canceled positions. Returns `None` if no
+    /// cancellation has been collected.
+    pub fn fit_power(&self) -> Option<PowerProbQueueFunc3> {
+        let total: f64 = self.samples.iter().map(|&(_, _, qty)| qty).sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mean = self
+            .samples
+            .iter()
+            .map(|&(start, end, qty)| (start + end) / 2.0 * qty)
+            .sum::<f64>()
+            / total;
+        // The mean of the position distributed with the CDF `x ** n` is `n / (n + 1)`.
+        let mean = mean.clamp(1e-6, 1.0 - 1e-6);
+        Some(PowerProbQueueFunc3::new(mean / (1.0 - mean)))
+    }
+}
+
+/// This probability model looks up the probability from a table over the relative queue position
+/// `front / (front + back)`, where the table values are evenly spaced over `[0, 1]`, with linear
+/// interpolation. The table is typically fitted from Level 3 data by [`L3QueueCalibrator`].
+#[derive(Clone, Debug)]
+pub struct TableProbQueueFunc {
+    table: Vec<f64>,
+}
+
+impl TableProbQueueFunc {
+    /// Constructs an instance of `TableProbQueueFunc`. The table must have at least two values.
+    pub fn new(table: Vec<f64>) -> Self {
+        assert!(table.len() >= 2);
+        Self { table }
+    }
+
+    /// Returns the table.
+    pub fn table(&self) -> &[f64] {
+        &self.table
+    }
+
+    /// Saves the table to the file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), IoError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        bincode::encode_into_std_write(&self.table, &mut writer, config::standard())
+            .map_err(IoError::other)?;
+        Ok(())
+    }
+
+    /// Loads the table from the file saved by [`save`](TableProbQueueFunc::save).
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, IoError> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let table: Vec<f64> = bincode::decode_from_std_read(&mut reader, config::standard())
+            .map_err(|err| IoError::new(ErrorKind::InvalidData, err))?;
+        if table.len() < 2 {
+            return Err(IoError::new(
+                ErrorKind::InvalidData,
+                "the table must have at least two values",
+            ));
+        }
+        Ok(Self { table })
+    }
+}
+
+impl Probability for TableProbQueueFunc {
+    fn prob(&self, front: f64, back: f64) -> f64 {
+        let total = front + back;
+        let x = if total > 0.0 {
+            (front / total).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let pos = x * (self.table.len() - 1) as f64;
+        let i = (pos.floor() as usize).min(self.table.len() - 2);
+        let w = pos - i as f64;
+        self.table[i] * (1.0 - w) + self.table[i + 1] * w
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::models::{L3QueueCalibrator, Probability, TableProbQueueFunc},
+        types::{
+            BUY_EVENT,
+            EXCH_BID_ADD_ORDER_EVENT,
+            EXCH_CANCEL_ORDER_EVENT,
+            EXCH_FILL_EVENT,
+            EXCH_MODIFY_ORDER_EVENT,
+            Event,
+        },
+    };
+
+    fn event(ev: u64, order_id: u64, qty: f64) -> Event {
+        Event {
+            ev,
+            exch_ts: 0,
+            local_ts: 0,
+            px: 100.0,
+            qty,
+            order_id,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn fit_from_cancellations() {
+        let mut calibrator = L3QueueCalibrator::new(1.0, 0.1);
+        for order_id in 1..=4 {
+            calibrator
+                .process(&event(EXCH_BID_ADD_ORDER_EVENT, order_id, 1.0))
+                .unwrap();
+        }
+        // The last order in the queue, occupying [0.75, 1.0], is canceled.
+        calibrator
+            .process(&event(EXCH_CANCEL_ORDER_EVENT, 4, 1.0))
+            .unwrap();
+        // The first order is filled and then deleted, which is not a cancellation.
+        calibrator
+            .process(&event(EXCH_FILL_EVENT | BUY_EVENT, 1, 1.0))
+            .unwrap();
+        calibrator
+            .process(&event(EXCH_CANCEL_ORDER_EVENT, 1, 1.0))
+            .unwrap();
+        // Half of the last order in the queue, occupying [0.5, 1.0], is canceled.
+        calibrator
+            .process(&event(EXCH_MODIFY_ORDER_EVENT, 3, 0.5))
+            .unwrap();
+        assert_eq!(calibrator.num_samples(), 2);
+
+        let table = calibrator.fit_table(4).unwrap();
+        let expected = [1.0, 1.0, 1.0, 1.25 / 1.5, 0.0];
+        for (value, expected) in table.table().iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        assert!((table.prob(7.0, 1.0) - 1.25 / 3.0).abs() < 1e-9);
+        assert!((table.prob(0.0, 1.0) - 1.0).abs() < 1e-9);
+
+        // The mean of the canceled positions is 1.25 / 1.5, so n = 5.
+        let power = calibrator.fit_power().unwrap();
+        assert!((power.prob(1.0, 1.0) - (1.0 - 0.5f64.powi(5))).abs() < 1e-9);
+
+        let path = std::env::temp_dir().join(format!("hbt-prob-table-{}", std::process::id()));
+        table.save(&path).unwrap();
+        let loaded = TableProbQueueFunc::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.table(), table.table());
+    }
+}
@@ -3,10 +3,12 @@
 //! Please find more details in the documents below.
 //! * [Latency Models](https://hftbacktest.readthedocs.io/en/latest/latency_models.html)
 //! * [Order Fill](https://hftbacktest.readthedocs.io/en/latest/order_fill.html)
+mod calibration;
 mod fee;
 mod latency;
 mod queue;
 
+pub use calibration::{L3QueueCalibrator, TableProbQueueFunc};
 pub use fee::{
     CommonFees,
     DirectionalFees,
@@ -17,8 +19,12 @@ pub use fee::{
 };
 pub use latency::{ConstantLatency, IntpOrderLatency, LatencyModel, OrderLatencyRow};
 pub use queue::{
+    AllocationRounding,
     L3FIFOQueueModel,
+    L3ProRataQueueModel,
     L3QueueModel,
+    LevelAgeQueueModel,
+    LevelAgeQueuePos,
     LogProbQueueFunc,
     LogProbQueueFunc2,
     PowerProbQueueFunc,
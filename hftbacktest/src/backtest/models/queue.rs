@@ -4,9 +4,14 @@ use std::{
     marker::PhantomData,
 };
 
+use bincode::{Decode, Encode};
+
 use crate::{
-    backtest::BacktestError,
-    depth::{INVALID_MAX, INVALID_MIN, MarketDepth},
+    backtest::{
+        BacktestError,
+        checkpoint::{decode, encode},
+    },
+    depth::{INVALID_MAX, INVALID_MIN, LevelTimestamp, MarketDepth},
     types::{
         AnyClone,
         BUY_EVENT,
@@ -37,6 +42,22 @@ where
     fn depth(&self, order: &mut Order, prev_qty: f64, new_qty: f64, depth: &MD);
 
     fn is_filled(&self, order: &mut Order, depth: &MD) -> f64;
+
+    /// Encodes the queue position values stored in the order for a
+    /// [`Checkpoint`](crate::backtest::checkpoint::Checkpoint).
+    ///
+    /// The default implementation returns [`BacktestError::CheckpointUnsupported`].
+    fn save_queue_pos(&self, _order: &Order) -> Result<Vec<u8>, BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("queue model"))
+    }
+
+    /// Restores the queue position values encoded by
+    /// [`save_queue_pos`](QueueModel::save_queue_pos) to the order.
+    ///
+    /// The default implementation returns [`BacktestError::CheckpointUnsupported`].
+    fn load_queue_pos(&self, _order: &mut Order, _state: &[u8]) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("queue model"))
+    }
 }
 
 /// Provides a conservative queue position model, where your order's queue position advances only
@@ -93,6 +114,16 @@ where
             0.0
         }
     }
+
+    fn save_queue_pos(&self, order: &Order) -> Result<Vec<u8>, BacktestError> {
+        let front_q_qty = order.q.as_any().downcast_ref::<f64>().unwrap();
+        encode(front_q_qty)
+    }
+
+    fn load_queue_pos(&self, order: &mut Order, state: &[u8]) -> Result<(), BacktestError> {
+        order.q = Box::new(decode::<f64>(state)?);
+        Ok(())
+    }
 }
 
 /// Stores the values needed for queue position estimation and adjustment for [`ProbQueueModel`].
@@ -214,6 +245,20 @@ where
             0.0
         }
     }
+
+    fn save_queue_pos(&self, order: &Order) -> Result<Vec<u8>, BacktestError> {
+        let q = order.q.as_any().downcast_ref::<QueuePos>().unwrap();
+        encode(&(q.front_q_qty, q.cum_trade_qty))
+    }
+
+    fn load_queue_pos(&self, order: &mut Order, state: &[u8]) -> Result<(), BacktestError> {
+        let (front_q_qty, cum_trade_qty) = decode::<(f64, f64)>(state)?;
+        order.q = Box::new(QueuePos {
+            front_q_qty,
+            cum_trade_qty,
+        });
+        Ok(())
+    }
 }
 
 /// This probability model uses a power function `f(x) = x ** n` to adjust the probability which is
@@ -329,9 +374,195 @@ impl Probability for PowerProbQueueFunc3 {
     }
 }
 
+/// Stores the values needed for queue position estimation and adjustment for
+/// [`LevelAgeQueueModel`].
+#[derive(Clone, Debug, Default)]
+pub struct LevelAgeQueuePos {
+    front_q_qty: f64,
+    cum_trade_qty: f64,
+    // The quantities added behind the order that haven't been canceled, in the order of arrival.
+    added: Vec<f64>,
+}
+
+impl AnyClone for LevelAgeQueuePos {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// Provides a queue position model that extends [`ProbQueueModel`] with the age of the price level
+/// and the observed history of additions and cancellations at the level, which helps fill
+/// estimation on large-tick assets where queues are long-lived.
+///
+/// The quantity added behind the order is tracked from the depth changes. A decrease that matches
+/// the size of such an addition is regarded as the cancellation of that order and doesn't advance
+/// the queue position. Otherwise, the probability that the decrease occurs behind the order,
+/// obtained from the [`Probability`] model, is increased towards the share of the recently added
+/// quantity in the back queue as the level ages, since orders that have rested for a while tend to
+/// stay in the queue. The age of the level is measured by the [`MarketDepth::timestamp`] since the
+/// level was first seen, which is reset when the level empties, as provided by
+/// [`LevelTimestamp`], and `age_scale` is the age at which half of the increase applies.
+pub struct LevelAgeQueueModel<P, MD>
+where
+    P: Probability,
+{
+    prob: P,
+    age_scale: i64,
+    _md_marker: PhantomData<MD>,
+}
+
+impl<P, MD> LevelAgeQueueModel<P, MD>
+where
+    P: Probability,
+{
+    /// Constructs an instance of `LevelAgeQueueModel` with a [`Probability`] model and the age
+    /// scale in the same unit as the timestamps.
+    pub fn new(prob: P, age_scale: i64) -> Self {
+        assert!(age_scale > 0);
+        Self {
+            prob,
+            age_scale,
+            _md_marker: Default::default(),
+        }
+    }
+}
+
+impl<P, MD> QueueModel<MD> for LevelAgeQueueModel<P, MD>
+where
+    P: Probability,
+    MD: MarketDepth + LevelTimestamp,
+{
+    fn new_order(&self, order: &mut Order, depth: &MD) {
+        let front_q_qty = if order.side == Side::Buy {
+            depth.bid_qty_at_tick(order.price_tick)
+        } else {
+            depth.ask_qty_at_tick(order.price_tick)
+        };
+        order.q = Box::new(LevelAgeQueuePos {
+            front_q_qty,
+            cum_trade_qty: 0.0,
+            added: Vec::new(),
+        });
+    }
+
+    fn trade(&self, order: &mut Order, qty: f64, _depth: &MD) {
+        let q = order
+            .q
+            .as_any_mut()
+            .downcast_mut::<LevelAgeQueuePos>()
+            .unwrap();
+        q.front_q_qty -= qty;
+        q.cum_trade_qty += qty;
+    }
+
+    fn depth(&self, order: &mut Order, prev_qty: f64, new_qty: f64, depth: &MD) {
+        let mut chg = prev_qty - new_qty;
+        let q = order
+            .q
+            .as_any_mut()
+            .downcast_mut::<LevelAgeQueuePos>()
+            .unwrap();
+        // In order to avoid duplicate order queue position adjustment, subtract queue position
+        // change by trades.
+        chg -= q.cum_trade_qty;
+        q.cum_trade_qty = 0.0;
+        // An increase of the quantity is added behind the order.
+        if chg < 0.0 {
+            q.added.push(-chg);
+            q.front_q_qty = q.front_q_qty.min(new_qty);
+            return;
+        }
+
+        // A decrease matching an addition behind the order is the cancellation of that order.
+        let lot_size = depth.lot_size();
+        if let Some(i) = q
+            .added
+            .iter()
+            .rposition(|&added| ((added - chg) / lot_size).round() as i64 == 0)
+        {
+            q.added.remove(i);
+            q.front_q_qty = q.front_q_qty.min(new_qty);
+            return;
+        }
+
+        let front = q.front_q_qty;
+        let back = prev_qty - front;
+
+        let mut prob = self.prob.prob(front, back);
+        if prob.is_infinite() {
+            prob = 1.0;
+        }
+        let first_seen = if order.side == Side::Buy {
+            depth.bid_first_seen_at_tick(order.price_tick)
+        } else {
+            depth.ask_first_seen_at_tick(order.price_tick)
+        };
+        if let Some(first_seen) = first_seen
+            && back > 0.0
+        {
+            let age = (depth.timestamp() - first_seen).max(0) as f64;
+            let age_weight = age / (age + self.age_scale as f64);
+            let recent = q.added.iter().sum::<f64>().min(back);
+            prob += (1.0 - prob) * age_weight * recent / back;
+        }
+
+        let est_front = front - (1.0 - prob) * chg + (back - prob * chg).min(0.0);
+        q.front_q_qty = est_front.min(new_qty);
+
+        // The decrease behind the order is taken from the most recent additions.
+        let mut behind = prob * chg;
+        while behind > 0.0 {
+            let Some(last) = q.added.last_mut() else {
+                break;
+            };
+            if *last <= behind {
+                behind -= *last;
+                q.added.pop();
+            } else {
+                *last -= behind;
+                behind = 0.0;
+            }
+        }
+    }
+
+    fn is_filled(&self, order: &mut Order, depth: &MD) -> f64 {
+        let q = order
+            .q
+            .as_any_mut()
+            .downcast_mut::<LevelAgeQueuePos>()
+            .unwrap();
+        let exec = (-q.front_q_qty / depth.lot_size()).round() as i64;
+        if exec > 0 {
+            q.front_q_qty = 0.0;
+            (exec as f64) * depth.lot_size()
+        } else {
+            0.0
+        }
+    }
+
+    fn save_queue_pos(&self, order: &Order) -> Result<Vec<u8>, BacktestError> {
+        let q = order.q.as_any().downcast_ref::<LevelAgeQueuePos>().unwrap();
+        encode(&(q.front_q_qty, q.cum_trade_qty, &q.added))
+    }
+
+    fn load_queue_pos(&self, order: &mut Order, state: &[u8]) -> Result<(), BacktestError> {
+        let (front_q_qty, cum_trade_qty, added) = decode::<(f64, f64, Vec<f64>)>(state)?;
+        order.q = Box::new(LevelAgeQueuePos {
+            front_q_qty,
+            cum_trade_qty,
+            added,
+        });
+        Ok(())
+    }
+}
+
 /// Represents the order source for the Level 3 Market-By-Order queue model, which is stored in
 /// [`order.q`](crate::types::Order::q)
-#[derive(Copy, Clone, Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq, Decode, Encode)]
 enum L3OrderSource {
     /// Represents an order originating from the market feed.
     MarketFeed,
@@ -452,6 +683,29 @@ pub trait L3QueueModel<MD> {
         depth: &MD,
     ) -> Result<Vec<Order>, BacktestError>;
 
+    /// Invoked when an order is filled from the market feed, in the exchange models that simulate
+    /// partial fills. Returns the backtest orders that are executed along with their executed
+    /// quantities. A partially executed backtest order remains in the queue with its leaves
+    /// quantity reduced, while the returned order holds the leaves quantity before the execution.
+    ///
+    /// The default implementation executes the backtest orders in full, the same as
+    /// [`fill_market_feed_order`](L3QueueModel::fill_market_feed_order).
+    fn partially_fill_market_feed_order<const DELETE: bool>(
+        &mut self,
+        order_id: OrderId,
+        order: &Event,
+        depth: &MD,
+    ) -> Result<Vec<(Order, f64)>, BacktestError> {
+        let filled = self.fill_market_feed_order::<DELETE>(order_id, order, depth)?;
+        Ok(filled
+            .into_iter()
+            .map(|order| {
+                let exec_qty = order.leaves_qty;
+                (order, exec_qty)
+            })
+            .collect())
+    }
+
     /// Invoked when a clear order message is received. Returns the expired orders due to the clear
     /// message.
     ///
@@ -468,15 +722,32 @@ pub trait L3QueueModel<MD> {
     /// Due to these challenges, HftBacktest opts to clear all backtest orders upon receiving a
     /// clear message, even though this may differ from the exchange's actual behavior.
     fn clear_orders(&mut self, side: Side) -> Vec<Order>;
+
+    /// Encodes the state of the queues, including the backtest orders, for a
+    /// [`Checkpoint`](crate::backtest::checkpoint::Checkpoint).
+    ///
+    /// The default implementation returns [`BacktestError::CheckpointUnsupported`].
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("queue model"))
+    }
+
+    /// Restores the state encoded by [`save_state`](L3QueueModel::save_state), replacing the
+    /// current state.
+    ///
+    /// The default implementation returns [`BacktestError::CheckpointUnsupported`].
+    fn load_state(&mut self, _state: &[u8]) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("queue model"))
+    }
 }
 
 /// This provides a Level 3 Market-By-Order queue model for backtesting in a FIFO manner. This means
 /// that all orders, including backtest orders, are managed in a FIFO queue based on price-time
 /// priority and executed in the FIFO order. Backtest orders are assumed to be executed in the queue
 /// when the market order, order from the market feed, behind the backtest order is executed.
-/// Exchanges may have different matching algorithms, such as Pro-Rata, and may have exotic order
-/// types that aren't executed in a FIFO manner. Therefore, you should carefully choose the queue
-/// model, even when dealing with a Level 3 Market-By-Order feed.
+/// Exchanges may have different matching algorithms, such as Pro-Rata, which is modeled by
+/// [`L3ProRataQueueModel`], and may have exotic order types that aren't executed in a FIFO manner.
+/// Therefore, you should carefully choose the queue model, even when dealing with a Level 3
+/// Market-By-Order feed.
 #[derive(Default)]
 pub struct L3FIFOQueueModel {
     // Stores the location of the queue that holds the order by (side, price in ticks).
@@ -587,6 +858,51 @@ impl L3FIFOQueueModel {
         }
         filled
     }
+
+    // Executes the backtest orders placed before the filled market-feed order in the queue, up to
+    // the quantity executed on the market-feed order, since the market order would have traded
+    // through them first.
+    fn fill_queue_partially<const DELETE: bool>(
+        queue: &mut VecDeque<Order>,
+        backtest_orders: &mut HashMap<OrderId, (Side, i64)>,
+        order_id: OrderId,
+        mut qty: f64,
+        lot_size: f64,
+        filled: &mut Vec<(Order, f64)>,
+    ) {
+        let mut i = 0;
+        while i < queue.len() {
+            let order_in_q = queue.get_mut(i).unwrap();
+            match order_in_q.order_source() {
+                L3OrderSource::MarketFeed if order_in_q.order_id == order_id => {
+                    if DELETE {
+                        queue.remove(i);
+                    }
+                    break;
+                }
+                L3OrderSource::MarketFeed => {
+                    i += 1;
+                }
+                L3OrderSource::Backtest => {
+                    let exec_qty = qty.min(order_in_q.leaves_qty);
+                    if ((order_in_q.leaves_qty - exec_qty) / lot_size).round() <= 0.0 {
+                        let order = queue.remove(i).unwrap();
+                        backtest_orders.remove(&order.order_id);
+                        qty -= order.leaves_qty;
+                        let exec_qty = order.leaves_qty;
+                        filled.push((order, exec_qty));
+                    } else {
+                        if (exec_qty / lot_size).round() > 0.0 {
+                            filled.push((order_in_q.clone(), exec_qty));
+                            order_in_q.leaves_qty -= exec_qty;
+                        }
+                        qty -= exec_qty;
+                        i += 1;
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl<MD> L3QueueModel<MD> for L3FIFOQueueModel
@@ -1068,6 +1384,75 @@ where
         }
     }
 
+    fn partially_fill_market_feed_order<const DELETE: bool>(
+        &mut self,
+        order_id: OrderId,
+        order: &Event,
+        depth: &MD,
+    ) -> Result<Vec<(Order, f64)>, BacktestError> {
+        let (side, order_price_tick) = if DELETE {
+            self.mkt_feed_orders
+                .remove(&order_id)
+                .ok_or(BacktestError::OrderNotFound)?
+        } else {
+            *self
+                .mkt_feed_orders
+                .get(&order_id)
+                .ok_or(BacktestError::OrderNotFound)?
+        };
+        let exec_price_tick = (order.px / depth.tick_size()).round() as i64;
+
+        let mut filled = Vec::new();
+        match side {
+            Side::Buy => {
+                // The backtest bid orders above the price of the filled market-feed bid order are
+                // traded through, so they are fully filled.
+                if exec_price_tick < depth.best_bid_tick() {
+                    let f =
+                        self.fill_bid_between::<false>(depth.best_bid_tick(), exec_price_tick + 1);
+                    filled.extend(f.into_iter().map(|order| {
+                        let exec_qty = order.leaves_qty;
+                        (order, exec_qty)
+                    }));
+                }
+
+                let queue = self.bid_queue.get_mut(&order_price_tick).unwrap();
+                Self::fill_queue_partially::<DELETE>(
+                    queue,
+                    &mut self.backtest_orders,
+                    order_id,
+                    order.qty,
+                    depth.lot_size(),
+                    &mut filled,
+                );
+            }
+            Side::Sell => {
+                // The backtest ask orders below the price of the filled market-feed ask order are
+                // traded through, so they are fully filled.
+                if exec_price_tick > depth.best_ask_tick() {
+                    let f =
+                        self.fill_ask_between::<false>(depth.best_ask_tick(), exec_price_tick - 1);
+                    filled.extend(f.into_iter().map(|order| {
+                        let exec_qty = order.leaves_qty;
+                        (order, exec_qty)
+                    }));
+                }
+
+                let queue = self.ask_queue.get_mut(&order_price_tick).unwrap();
+                Self::fill_queue_partially::<DELETE>(
+                    queue,
+                    &mut self.backtest_orders,
+                    order_id,
+                    order.qty,
+                    depth.lot_size(),
+                    &mut filled,
+                );
+            }
+            Side::None | Side::Unsupported => unreachable!(),
+        }
+        Ok(filled)
+    }
+
     fn clear_orders(&mut self, side: Side) -> Vec<Order> {
         match side {
             Side::Buy => {
@@ -1117,12 +1502,552 @@ where
             }
         }
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        // The order locations are restored from the queues.
+        let save_queue = |queue: &HashMap<i64, VecDeque<Order>>| {
+            queue
+                .iter()
+                .map(|(&price_tick, orders)| {
+                    let orders = orders
+                        .iter()
+                        .map(|order| (order.clone(), order.order_source()))
+                        .collect::<Vec<_>>();
+                    (price_tick, orders)
+                })
+                .collect::<Vec<_>>()
+        };
+        encode(&(save_queue(&self.bid_queue), save_queue(&self.ask_queue)))
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        type Queue = Vec<(i64, Vec<(Order, L3OrderSource)>)>;
+        let (bid_queue, ask_queue) = decode::<(Queue, Queue)>(state)?;
+
+        self.backtest_orders.clear();
+        self.mkt_feed_orders.clear();
+        self.bid_queue.clear();
+        self.ask_queue.clear();
+        for (side, queue) in [(Side::Buy, bid_queue), (Side::Sell, ask_queue)] {
+            for (price_tick, orders) in queue {
+                let mut restored = VecDeque::with_capacity(orders.len());
+                for (mut order, source) in orders {
+                    let location = (side, price_tick);
+                    match source {
+                        L3OrderSource::MarketFeed => {
+                            self.mkt_feed_orders.insert(order.order_id, location)
+                        }
+                        L3OrderSource::Backtest => {
+                            self.backtest_orders.insert(order.order_id, location)
+                        }
+                    };
+                    order.q = Box::new(source);
+                    restored.push_back(order);
+                }
+                match side {
+                    Side::Buy => self.bid_queue.insert(price_tick, restored),
+                    _ => self.ask_queue.insert(price_tick, restored),
+                };
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rounding rule applied to the quantity allocated by [`L3ProRataQueueModel`].
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum AllocationRounding {
+    /// Rounds the allocation down to a multiple of the lot size, as most pro-rata exchanges do.
+    Down,
+    /// Rounds the allocation to the nearest multiple of the lot size.
+    Nearest,
+}
+
+#[derive(Clone, Copy)]
+struct Allocation {
+    lot_size: f64,
+    min_allocation: f64,
+    rounding: AllocationRounding,
+}
+
+impl Allocation {
+    fn allocate(&self, qty: f64) -> f64 {
+        let lots = qty / self.lot_size;
+        let lots = match self.rounding {
+            // Adds a small epsilon to absorb the floating-point error of the division.
+            AllocationRounding::Down => (lots + 1e-9).floor(),
+            AllocationRounding::Nearest => lots.round(),
+        };
+        let qty = lots * self.lot_size;
+        if qty < self.min_allocation { 0.0 } else { qty }
+    }
+}
+
+/// This provides a Level 3 Market-By-Order queue model for exchanges that use Pro-Rata matching.
+/// With Pro-Rata matching, an aggressive order is allocated across all resting orders at the price
+/// level in proportion to their quantities rather than in time priority.
+///
+/// The aggressive quantity that the backtest order would have received is estimated from the fills
+/// of the market feed orders. If the fill of a market feed order is `f` and the total quantity of
+/// the market feed orders at the price level is `Q`, the backtest order with the leaves quantity
+/// `q` is allocated `f * q / Q`, which is then rounded to the lot size and discarded if it is less
+/// than the minimum allocation. The backtest order is filled once the accumulated allocation
+/// reaches its leaves quantity. As with [`L3FIFOQueueModel`], the backtest order is also filled
+/// when the market trades through its price.
+///
+/// By enabling [`top_order_priority`](L3ProRataQueueModel::top_order_priority), this becomes a
+/// hybrid model in which the top order, the first order placed at an empty price level, is matched
+/// first while it remains at the front of the queue, and the remaining orders are matched in the
+/// Pro-Rata manner.
+pub struct L3ProRataQueueModel {
+    fifo: L3FIFOQueueModel,
+    // The quantity allocated to the backtest orders that are not filled yet.
+    allocated: HashMap<OrderId, f64>,
+    // The top order of the price level by price in ticks.
+    bid_top: HashMap<i64, (L3OrderSource, OrderId)>,
+    ask_top: HashMap<i64, (L3OrderSource, OrderId)>,
+    allocation: Allocation,
+    top_order_priority: bool,
+}
+
+impl L3ProRataQueueModel {
+    /// Constructs an instance of `L3ProRataQueueModel`.
+    ///
+    /// * `lot_size` - The lot size to which the allocation is rounded. It must be positive.
+    /// * `min_allocation` - The minimum quantity allocated; any smaller allocation is discarded.
+    pub fn new(lot_size: f64, min_allocation: f64) -> Self {
+        assert!(lot_size > 0.0);
+        Self {
+            fifo: L3FIFOQueueModel::new(),
+            allocated: Default::default(),
+            bid_top: Default::default(),
+            ask_top: Default::default(),
+            allocation: Allocation {
+                lot_size,
+                min_allocation,
+                rounding: AllocationRounding::Down,
+            },
+            top_order_priority: false,
+        }
+    }
+
+    /// Sets the rounding rule for the allocation. The default is [`AllocationRounding::Down`].
+    pub fn rounding(self, rounding: AllocationRounding) -> Self {
+        Self {
+            allocation: Allocation {
+                rounding,
+                ..self.allocation
+            },
+            ..self
+        }
+    }
+
+    /// Sets whether the top order at each price level is matched before the Pro-Rata allocation.
+    /// The default is `false`.
+    pub fn top_order_priority(self, top_order_priority: bool) -> Self {
+        Self {
+            top_order_priority,
+            ..self
+        }
+    }
+
+    fn set_top_order(&mut self, side: Side, price_tick: i64, source: L3OrderSource, id: OrderId) {
+        if !self.top_order_priority {
+            return;
+        }
+        let (queue, top) = match side {
+            Side::Buy => (&self.fifo.bid_queue, &mut self.bid_top),
+            Side::Sell => (&self.fifo.ask_queue, &mut self.ask_top),
+            Side::None | Side::Unsupported => unreachable!(),
+        };
+        // The order has already been pushed to the queue.
+        if queue.get(&price_tick).map(|q| q.len()).unwrap_or(0) == 1 {
+            top.insert(price_tick, (source, id));
+        }
+    }
+
+    fn release(&mut self, orders: &[Order]) {
+        for order in orders {
+            self.allocated.remove(&order.order_id);
+        }
+    }
+
+    fn fill_level<const DELETE: bool>(
+        &mut self,
+        side: Side,
+        price_tick: i64,
+        order_id: OrderId,
+        fill_qty: f64,
+    ) -> Vec<Order> {
+        let allocation = self.allocation;
+        let (queue, top) = match side {
+            Side::Buy => (
+                self.fifo.bid_queue.get_mut(&price_tick).unwrap(),
+                &mut self.bid_top,
+            ),
+            Side::Sell => (
+                self.fifo.ask_queue.get_mut(&price_tick).unwrap(),
+                &mut self.ask_top,
+            ),
+            Side::None | Side::Unsupported => unreachable!(),
+        };
+
+        // The top order loses its priority once it leaves the front of the queue.
+        let top_order = match top.get(&price_tick) {
+            Some(&(source, id))
+                if queue.front().is_some_and(|front| {
+                    front.order_source() == source && front.order_id == id
+                }) =>
+            {
+                Some((source, id))
+            }
+            Some(_) => {
+                top.remove(&price_tick);
+                None
+            }
+            None => None,
+        };
+
+        let mut filled = Vec::new();
+        match top_order {
+            Some((L3OrderSource::Backtest, _)) => {
+                // The aggressive order is matched with the top order first.
+                filled.push(queue.pop_front().unwrap());
+                top.remove(&price_tick);
+            }
+            Some((L3OrderSource::MarketFeed, id)) if id == order_id => {
+                // The fill is entirely taken by the top order.
+            }
+            _ => {
+                let is_top = |order: &Order| {
+                    top_order.is_some_and(|(source, id)| {
+                        order.order_source() == source && order.order_id == id
+                    })
+                };
+                let mkt_qty: f64 = queue
+                    .iter()
+                    .filter(|order| order.is_market_feed_order() && !is_top(order))
+                    .map(|order| order.leaves_qty)
+                    .sum();
+                if mkt_qty > 0.0 {
+                    let mut i = 0;
+                    while i < queue.len() {
+                        let order_in_q = &queue[i];
+                        if order_in_q.is_backtest_order() {
+                            let qty =
+                                allocation.allocate(fill_qty * order_in_q.leaves_qty / mkt_qty);
+                            let allocated = self.allocated.entry(order_in_q.order_id).or_default();
+                            *allocated += qty;
+                            if *allocated >= order_in_q.leaves_qty - 1e-9 {
+                                filled.push(queue.remove(i).unwrap());
+                                continue;
+                            }
+                        }
+                        i += 1;
+                    }
+                }
+            }
+        }
+
+        if DELETE
+            && let Some(i) = queue
+                .iter()
+                .position(|order| order.is_market_feed_order() && order.order_id == order_id)
+        {
+            queue.remove(i);
+        }
+
+        for order in &filled {
+            self.fifo.backtest_orders.remove(&order.order_id);
+            self.allocated.remove(&order.order_id);
+        }
+        filled
+    }
+}
+
+impl<MD> L3QueueModel<MD> for L3ProRataQueueModel
+where
+    MD: MarketDepth,
+{
+    fn contains_backtest_order(&self, order_id: OrderId) -> bool {
+        self.fifo.backtest_orders.contains_key(&order_id)
+    }
+
+    fn on_best_bid_update(
+        &mut self,
+        prev_best_tick: i64,
+        new_best_tick: i64,
+    ) -> Result<Vec<Order>, BacktestError> {
+        let filled = <L3FIFOQueueModel as L3QueueModel<MD>>::on_best_bid_update(
+            &mut self.fifo,
+            prev_best_tick,
+            new_best_tick,
+        )?;
+        self.release(&filled);
+        Ok(filled)
+    }
+
+    fn on_best_ask_update(
+        &mut self,
+        prev_best_tick: i64,
+        new_best_tick: i64,
+    ) -> Result<Vec<Order>, BacktestError> {
+        let filled = <L3FIFOQueueModel as L3QueueModel<MD>>::on_best_ask_update(
+            &mut self.fifo,
+            prev_best_tick,
+            new_best_tick,
+        )?;
+        self.release(&filled);
+        Ok(filled)
+    }
+
+    fn add_backtest_order(&mut self, order: Order, depth: &MD) -> Result<(), BacktestError> {
+        let (side, price_tick, order_id) = (order.side, order.price_tick, order.order_id);
+        self.fifo.add_backtest_order(order, depth)?;
+        self.set_top_order(side, price_tick, L3OrderSource::Backtest, order_id);
+        Ok(())
+    }
+
+    fn add_market_feed_order(&mut self, order: &Event, depth: &MD) -> Result<(), BacktestError> {
+        self.fifo.add_market_feed_order(order, depth)?;
+        let (side, price_tick) = self.fifo.mkt_feed_orders[&order.order_id];
+        self.set_top_order(side, price_tick, L3OrderSource::MarketFeed, order.order_id);
+        Ok(())
+    }
+
+    fn cancel_backtest_order(
+        &mut self,
+        order_id: OrderId,
+        depth: &MD,
+    ) -> Result<Order, BacktestError> {
+        let order = self.fifo.cancel_backtest_order(order_id, depth)?;
+        self.allocated.remove(&order_id);
+        Ok(order)
+    }
+
+    fn cancel_market_feed_order(
+        &mut self,
+        order_id: OrderId,
+        depth: &MD,
+    ) -> Result<(), BacktestError> {
+        self.fifo.cancel_market_feed_order(order_id, depth)
+    }
+
+    fn modify_backtest_order(
+        &mut self,
+        order_id: OrderId,
+        order: &mut Order,
+        depth: &MD,
+    ) -> Result<(), BacktestError> {
+        let prev_price_tick = self.fifo.backtest_orders.get(&order_id).map(|(_, p)| *p);
+        self.fifo.modify_backtest_order(order_id, order, depth)?;
+        // The allocation is only kept while the order stays at the same price level.
+        if prev_price_tick != Some(order.price_tick) {
+            self.allocated.remove(&order_id);
+        }
+        Ok(())
+    }
+
+    fn modify_market_feed_order(
+        &mut self,
+        order_id: OrderId,
+        order: &Event,
+        depth: &MD,
+    ) -> Result<(), BacktestError> {
+        self.fifo.modify_market_feed_order(order_id, order, depth)
+    }
+
+    fn fill_market_feed_order<const DELETE: bool>(
+        &mut self,
+        order_id: OrderId,
+        order: &Event,
+        depth: &MD,
+    ) -> Result<Vec<Order>, BacktestError> {
+        let (side, order_price_tick) = if DELETE {
+            self.fifo
+                .mkt_feed_orders
+                .remove(&order_id)
+                .ok_or(BacktestError::OrderNotFound)?
+        } else {
+            *self
+                .fifo
+                .mkt_feed_orders
+                .get(&order_id)
+                .ok_or(BacktestError::OrderNotFound)?
+        };
+        let exec_price_tick = (order.px / depth.tick_size()).round() as i64;
+
+        // The backtest orders at prices better than the price of the filled market-feed order are
+        // filled.
+        let mut filled = match side {
+            Side::Buy if exec_price_tick < depth.best_bid_tick() => self
+                .fifo
+                .fill_bid_between::<false>(depth.best_bid_tick(), exec_price_tick + 1),
+            Side::Sell if exec_price_tick > depth.best_ask_tick() => self
+                .fifo
+                .fill_ask_between::<false>(depth.best_ask_tick(), exec_price_tick - 1),
+            Side::None | Side::Unsupported => unreachable!(),
+            _ => Vec::new(),
+        };
+        self.release(&filled);
+
+        filled.append(&mut self.fill_level::<DELETE>(side, order_price_tick, order_id, order.qty));
+        Ok(filled)
+    }
+
+    fn clear_orders(&mut self, side: Side) -> Vec<Order> {
+        let expired = <L3FIFOQueueModel as L3QueueModel<MD>>::clear_orders(&mut self.fifo, side);
+        self.release(&expired);
+        match side {
+            Side::Buy => self.bid_top.clear(),
+            Side::Sell => self.ask_top.clear(),
+            _ => {
+                self.bid_top.clear();
+                self.ask_top.clear();
+            }
+        }
+        expired
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        let save_top = |top: &HashMap<i64, (L3OrderSource, OrderId)>| {
+            top.iter()
+                .map(|(&price_tick, &(source, id))| (price_tick, source, id))
+                .collect::<Vec<_>>()
+        };
+        encode(&(
+            <L3FIFOQueueModel as L3QueueModel<MD>>::save_state(&self.fifo)?,
+            self.allocated
+                .iter()
+                .map(|(&id, &qty)| (id, qty))
+                .collect::<Vec<_>>(),
+            save_top(&self.bid_top),
+            save_top(&self.ask_top),
+        ))
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        type Top = Vec<(i64, L3OrderSource, OrderId)>;
+        let (fifo, allocated, bid_top, ask_top) =
+            decode::<(Vec<u8>, Vec<(OrderId, f64)>, Top, Top)>(state)?;
+        <L3FIFOQueueModel as L3QueueModel<MD>>::load_state(&mut self.fifo, &fifo)?;
+        self.allocated = allocated.into_iter().collect();
+        self.bid_top = bid_top
+            .into_iter()
+            .map(|(price_tick, source, id)| (price_tick, (source, id)))
+            .collect();
+        self.ask_top = ask_top
+            .into_iter()
+            .map(|(price_tick, source, id)| (price_tick, (source, id)))
+            .collect();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::models::{
+            LevelAgeQueueModel,
+            LevelAgeQueuePos,
+            PowerProbQueueFunc3,
+            ProbQueueModel,
+            QueueModel,
+            QueuePos,
+        },
+        depth::{HashMapMarketDepth, L2MarketDepth},
+        types::{OrdType, Order, Side, TimeInForce},
+    };
+
+    fn front_q_qty(order: &Order) -> f64 {
+        order
+            .q
+            .as_any()
+            .downcast_ref::<LevelAgeQueuePos>()
+            .unwrap()
+            .front_q_qty
+    }
+
+    #[test]
+    fn level_age_queue_model() {
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let qm = LevelAgeQueueModel::new(PowerProbQueueFunc3::new(3.0), 1_000);
+        let prob_qm = ProbQueueModel::new(PowerProbQueueFunc3::new(3.0));
+
+        depth.update_bid_depth(100.0, 10.0, 0);
+        let mut order = Order::new(
+            1,
+            100,
+            1.0,
+            1.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        let mut prob_order = order.clone();
+        qm.new_order(&mut order, &depth);
+        prob_qm.new_order(&mut prob_order, &depth);
+        assert_eq!(front_q_qty(&order), 10.0);
+
+        // The order of 3 added behind is canceled.
+        depth.update_bid_depth(100.0, 13.0, 10);
+        qm.depth(&mut order, 10.0, 13.0, &depth);
+        depth.update_bid_depth(100.0, 10.0, 20);
+        qm.depth(&mut order, 13.0, 10.0, &depth);
+        assert_eq!(front_q_qty(&order), 10.0);
+
+        // On the aged level, the decrease is mostly attributed to the recent addition behind.
+        depth.update_bid_depth(100.0, 14.0, 30);
+        qm.depth(&mut order, 10.0, 14.0, &depth);
+        prob_qm.depth(&mut prob_order, 10.0, 14.0, &depth);
+        depth.update_bid_depth(100.0, 12.0, 1_000_000);
+        qm.depth(&mut order, 14.0, 12.0, &depth);
+        prob_qm.depth(&mut prob_order, 14.0, 12.0, &depth);
+
+        let prob_front = prob_order
+            .q
+            .as_any()
+            .downcast_ref::<QueuePos>()
+            .unwrap()
+            .front_q_qty;
+        assert!(front_q_qty(&order) > 9.99);
+        assert!(prob_front < 9.5);
+    }
+
+    #[test]
+    fn level_age_is_measured_from_first_seen() {
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let qm = LevelAgeQueueModel::new(PowerProbQueueFunc3::new(3.0), 1_000);
+
+        // The level has rested long before the order joins.
+        depth.update_bid_depth(100.0, 10.0, 0);
+        depth.update_bid_depth(100.0, 10.0, 1_000_000);
+        let mut order = Order::new(
+            1,
+            100,
+            1.0,
+            1.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::GTC,
+        );
+        qm.new_order(&mut order, &depth);
+
+        depth.update_bid_depth(100.0, 14.0, 1_000_010);
+        qm.depth(&mut order, 10.0, 14.0, &depth);
+        depth.update_bid_depth(100.0, 12.0, 1_000_020);
+        qm.depth(&mut order, 14.0, 12.0, &depth);
+        assert!(front_q_qty(&order) > 9.99);
+    }
 }
 
 #[cfg(test)]
 mod l3_tests {
     use crate::{
-        backtest::{L3QueueModel, models::L3FIFOQueueModel},
+        backtest::{
+            L3QueueModel,
+            models::{L3FIFOQueueModel, L3ProRataQueueModel},
+        },
         prelude::{
             Event,
             HashMapMarketDepth,
@@ -1355,4 +2280,234 @@ mod l3_tests {
             )
         );
     }
+
+    #[test]
+    fn save_and_load_state() {
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let mut qm = L3FIFOQueueModel::new();
+
+        let add_ev = |order_id| Event {
+            ev: EXCH_EVENT | BUY_EVENT | ADD_ORDER_EVENT,
+            exch_ts: 0,
+            local_ts: 0,
+            px: 100.0,
+            qty: 1.0,
+            order_id,
+            ival: 0,
+            fval: 0.0,
+        };
+
+        let ev = add_ev(1);
+        depth
+            .add_buy_order(ev.order_id, ev.px, ev.qty, ev.exch_ts)
+            .unwrap();
+        qm.add_market_feed_order(&ev, &depth).unwrap();
+
+        qm.add_backtest_order(
+            Order::new(
+                10,
+                100,
+                1.0,
+                1.0,
+                Side::Buy,
+                OrdType::Limit,
+                TimeInForce::GTC,
+            ),
+            &depth,
+        )
+        .unwrap();
+
+        let ev = add_ev(2);
+        depth
+            .add_buy_order(ev.order_id, ev.px, ev.qty, ev.exch_ts)
+            .unwrap();
+        qm.add_market_feed_order(&ev, &depth).unwrap();
+
+        let state =
+            <L3FIFOQueueModel as L3QueueModel<HashMapMarketDepth>>::save_state(&qm).unwrap();
+        let mut restored = L3FIFOQueueModel::new();
+        <L3FIFOQueueModel as L3QueueModel<HashMapMarketDepth>>::load_state(&mut restored, &state)
+            .unwrap();
+
+        assert!(
+            <L3FIFOQueueModel as L3QueueModel<HashMapMarketDepth>>::contains_backtest_order(
+                &restored, 10
+            )
+        );
+        assert_eq!(restored.bid_queue[&100].len(), 3);
+        assert_eq!(restored.mkt_feed_orders.len(), 2);
+
+        // The backtest order is filled when the market feed order behind it is filled.
+        let ev = Event {
+            ev: EXCH_EVENT | BUY_EVENT | FILL_EVENT,
+            ..add_ev(2)
+        };
+        let filled = restored
+            .fill_market_feed_order::<false>(2, &ev, &depth)
+            .unwrap();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].order_id, 10);
+    }
+
+    #[test]
+    fn pro_rata_allocation() {
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let mut qm = L3ProRataQueueModel::new(1.0, 1.0);
+
+        let ev = |ev, order_id, qty| Event {
+            ev: EXCH_EVENT | BUY_EVENT | ev,
+            exch_ts: 0,
+            local_ts: 0,
+            px: 100.0,
+            qty,
+            order_id,
+            ival: 0,
+            fval: 0.0,
+        };
+
+        for (order_id, qty) in [(1, 6.0), (2, 4.0)] {
+            let ev = ev(ADD_ORDER_EVENT, order_id, qty);
+            depth
+                .add_buy_order(ev.order_id, ev.px, ev.qty, ev.exch_ts)
+                .unwrap();
+            qm.add_market_feed_order(&ev, &depth).unwrap();
+        }
+        qm.add_backtest_order(
+            Order::new(
+                10,
+                100,
+                1.0,
+                4.0,
+                Side::Buy,
+                OrdType::Limit,
+                TimeInForce::GTC,
+            ),
+            &depth,
+        )
+        .unwrap();
+
+        // 5 * 4 / 10 = 2 is allocated.
+        let filled = qm
+            .fill_market_feed_order::<false>(1, &ev(FILL_EVENT, 1, 5.0), &depth)
+            .unwrap();
+        assert!(filled.is_empty());
+
+        // 3 * 4 / 10 = 1.2 is rounded down to 1.
+        let filled = qm
+            .fill_market_feed_order::<false>(2, &ev(FILL_EVENT, 2, 3.0), &depth)
+            .unwrap();
+        assert!(filled.is_empty());
+
+        // The accumulated allocation reaches the order quantity.
+        let filled = qm
+            .fill_market_feed_order::<false>(1, &ev(FILL_EVENT, 1, 5.0), &depth)
+            .unwrap();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].order_id, 10);
+        assert!(
+            !<L3ProRataQueueModel as L3QueueModel<HashMapMarketDepth>>::contains_backtest_order(
+                &qm, 10
+            )
+        );
+
+        // The allocation less than the minimum allocation is discarded.
+        let mut qm = L3ProRataQueueModel::new(1.0, 2.0);
+        let ev = ev(ADD_ORDER_EVENT, 1, 10.0);
+        qm.add_market_feed_order(&ev, &depth).unwrap();
+        qm.add_backtest_order(
+            Order::new(
+                10,
+                100,
+                1.0,
+                2.0,
+                Side::Buy,
+                OrdType::Limit,
+                TimeInForce::GTC,
+            ),
+            &depth,
+        )
+        .unwrap();
+        for _ in 0..5 {
+            let ev = Event {
+                ev: EXCH_EVENT | BUY_EVENT | FILL_EVENT,
+                qty: 5.0,
+                ..ev
+            };
+            let filled = qm.fill_market_feed_order::<false>(1, &ev, &depth).unwrap();
+            assert!(filled.is_empty());
+        }
+    }
+
+    #[test]
+    fn top_order_priority() {
+        let mut depth = HashMapMarketDepth::new(1.0, 1.0);
+        let mut qm = L3ProRataQueueModel::new(1.0, 1.0).top_order_priority(true);
+
+        let ev = |ev, order_id, px, qty| Event {
+            ev: EXCH_EVENT | BUY_EVENT | ev,
+            exch_ts: 0,
+            local_ts: 0,
+            px,
+            qty,
+            order_id,
+            ival: 0,
+            fval: 0.0,
+        };
+        let add_backtest_order =
+            |qm: &mut L3ProRataQueueModel, order_id, price_tick, depth: &HashMapMarketDepth| {
+                qm.add_backtest_order(
+                    Order::new(
+                        order_id,
+                        price_tick,
+                        1.0,
+                        1.0,
+                        Side::Buy,
+                        OrdType::Limit,
+                        TimeInForce::GTC,
+                    ),
+                    depth,
+                )
+                .unwrap();
+            };
+
+        // The backtest order is the top order at 100.
+        add_backtest_order(&mut qm, 10, 100, &depth);
+        // The market feed order is the top order at 99.
+        for (order_id, px) in [(1, 100.0), (2, 99.0)] {
+            let ev = ev(ADD_ORDER_EVENT, order_id, px, 5.0);
+            depth
+                .add_buy_order(ev.order_id, ev.px, ev.qty, ev.exch_ts)
+                .unwrap();
+            qm.add_market_feed_order(&ev, &depth).unwrap();
+        }
+        add_backtest_order(&mut qm, 11, 99, &depth);
+        let ev3 = ev(ADD_ORDER_EVENT, 3, 99.0, 1.0);
+        depth
+            .add_buy_order(ev3.order_id, ev3.px, ev3.qty, ev3.exch_ts)
+            .unwrap();
+        qm.add_market_feed_order(&ev3, &depth).unwrap();
+
+        // The top order is matched first regardless of its quantity.
+        let filled = qm
+            .fill_market_feed_order::<false>(1, &ev(FILL_EVENT, 1, 100.0, 1.0), &depth)
+            .unwrap();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].order_id, 10);
+
+        depth.delete_order(1, 0).unwrap();
+        qm.cancel_market_feed_order(1, &depth).unwrap();
+
+        // The fill of the top order is not allocated to the other orders.
+        let filled = qm
+            .fill_market_feed_order::<false>(2, &ev(FILL_EVENT, 2, 99.0, 5.0), &depth)
+            .unwrap();
+        assert!(filled.is_empty());
+
+        // The top order is excluded from the Pro-Rata allocation; 1 * 1 / 1 = 1 is allocated.
+        let filled = qm
+            .fill_market_feed_order::<false>(3, &ev(FILL_EVENT, 3, 99.0, 1.0), &depth)
+            .unwrap();
+        assert_eq!(filled.len(), 1);
+        assert_eq!(filled[0].order_id, 11);
+    }
 }
@@ -2,6 +2,9 @@ use std::{cell::UnsafeCell, collections::VecDeque, rc::Rc};
 
 use crate::{backtest::models::LatencyModel, types::Order};
 
+/// Orders in an [`OrderBus`] along with their receipt timestamps.
+pub type TimestampedOrders = Vec<(Order, i64)>;
+
 /// Provides a bus for transporting backtesting orders between the exchange and the local model
 /// based on the given timestamp.
 #[derive(Clone, Debug, Default)]
@@ -65,6 +68,11 @@ impl OrderBus {
     pub fn pop_front(&mut self) -> Option<(Order, i64)> {
         unsafe { &mut *self.order_list.get() }.pop_front()
     }
+
+    /// Returns the orders in the bus and their timestamps.
+    pub fn to_vec(&self) -> TimestampedOrders {
+        unsafe { &*self.order_list.get() }.iter().cloned().collect()
+    }
 }
 
 /// Provides a bidirectional order bus connecting the exchange to the local.
@@ -155,6 +163,29 @@ where
         }
     }
 
+    /// Returns the orders in transit to the exchange and to the local, respectively, along with
+    /// their receipt timestamps.
+    pub fn orders_in_transit(&self) -> (TimestampedOrders, TimestampedOrders) {
+        (self.to_exch.to_vec(), self.to_local.to_vec())
+    }
+
+    /// Replaces the orders in transit to the exchange and to the local, respectively. Since the
+    /// buses are shared with the [`ExchToLocal`], this also applies to the exchange side.
+    pub fn set_orders_in_transit(
+        &mut self,
+        to_exch: TimestampedOrders,
+        to_local: TimestampedOrders,
+    ) {
+        self.to_exch.reset();
+        for (order, timestamp) in to_exch {
+            self.to_exch.append(order, timestamp);
+        }
+        self.to_local.reset();
+        for (order, timestamp) in to_local {
+            self.to_local.append(order, timestamp);
+        }
+    }
+
     /// Receives the order response from the exchange, which is expected to be received at
     /// `receipt_timestamp`.
     pub fn receive(&mut self, receipt_timestamp: i64) -> Option<Order> {
@@ -1,15 +1,19 @@
 use std::collections::{HashMap, hash_map::Entry};
 
+use bincode::{Decode, Encode};
+
 use crate::{
     backtest::{
         BacktestError,
         assettype::AssetType,
+        checkpoint::{L3DepthCheckpoint, decode, encode, load_l3_depth, save_l3_depth},
         models::{FeeModel, LatencyModel},
         order::LocalToExch,
         proc::{LocalProcessor, Processor},
         state::State,
     },
     depth::L3MarketDepth,
+    journal::{JournalSource, OrderJournal, OrderTransition, TransitionReason},
     types::{
         Event,
         LOCAL_ASK_ADD_ORDER_EVENT,
@@ -31,6 +35,20 @@ use crate::{
     },
 };
 
+/// The state of [`L3Local`] stored in a checkpoint.
+#[derive(Decode, Encode)]
+struct L3LocalState {
+    orders: Vec<Order>,
+    depth: L3DepthCheckpoint,
+    state_values: StateValues,
+    trades: Vec<Event>,
+    last_feed_latency: Option<(i64, i64)>,
+    last_order_latency: Option<(i64, i64, i64)>,
+    // The order bus is shared with the exchange, so only the local stores the orders in transit.
+    to_exch: Vec<(Order, i64)>,
+    to_local: Vec<(Order, i64)>,
+}
+
 /// The Level3 Market-By-Order local model.
 pub struct L3Local<AT, LM, MD, FM>
 where
@@ -46,6 +64,7 @@ where
     trades: Vec<Event>,
     last_feed_latency: Option<(i64, i64)>,
     last_order_latency: Option<(i64, i64, i64)>,
+    journal: Option<OrderJournal>,
 }
 
 impl<AT, LM, MD, FM> L3Local<AT, LM, MD, FM>
@@ -70,8 +89,14 @@ where
             trades: Vec::with_capacity(trade_len),
             last_feed_latency: None,
             last_order_latency: None,
+            journal: None,
         }
     }
+
+    /// Sets the journal that records the order state transitions observed by the local.
+    pub fn set_journal(&mut self, journal: OrderJournal) {
+        self.journal = Some(journal);
+    }
 }
 
 impl<AT, LM, MD, FM> LocalProcessor<MD> for L3Local<AT, LM, MD, FM>
@@ -108,6 +133,15 @@ where
         );
         order.req = Status::New;
         order.local_timestamp = current_timestamp;
+        if let Some(journal) = &self.journal {
+            journal.record_order(
+                current_timestamp,
+                JournalSource::Local,
+                OrderTransition::Submitted,
+                TransitionReason::None,
+                &order,
+            );
+        }
         self.orders.insert(order.order_id, order.clone());
 
         self.order_l2e.request(order, |order| {
@@ -142,6 +176,15 @@ where
 
         order.req = Status::Replaced;
         order.local_timestamp = current_timestamp;
+        if let Some(journal) = &self.journal {
+            journal.record_order(
+                current_timestamp,
+                JournalSource::Local,
+                OrderTransition::ModifyRequested,
+                TransitionReason::None,
+                order,
+            );
+        }
 
         self.order_l2e.request(order.clone(), |order| {
             order.req = Status::Rejected;
@@ -164,6 +207,15 @@ where
 
         order.req = Status::Canceled;
         order.local_timestamp = current_timestamp;
+        if let Some(journal) = &self.journal {
+            journal.record_order(
+                current_timestamp,
+                JournalSource::Local,
+                OrderTransition::CancelRequested,
+                TransitionReason::None,
+                order,
+            );
+        }
 
         self.order_l2e.request(order.clone(), |order| {
             order.req = Status::Rejected;
@@ -282,6 +334,13 @@ where
             if order.status == Status::Filled {
                 self.state.apply_fill(&order);
             }
+            if let Some(journal) = &self.journal {
+                let req = self
+                    .orders
+                    .get(&order.order_id)
+                    .map_or(Status::None, |local_order| local_order.req);
+                journal.record_update(timestamp, JournalSource::Local, req, &order);
+            }
             // Applies the received order response to the local orders.
             match self.orders.entry(order.order_id) {
                 Entry::Occupied(mut entry) => {
@@ -320,4 +379,37 @@ where
             .earliest_send_order_timestamp()
             .unwrap_or(i64::MAX)
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        let (to_exch, to_local) = self.order_l2e.orders_in_transit();
+        encode(&L3LocalState {
+            orders: self.orders.values().cloned().collect(),
+            depth: save_l3_depth(&self.depth),
+            state_values: self.state.state_values.clone(),
+            trades: self.trades.clone(),
+            last_feed_latency: self.last_feed_latency,
+            last_order_latency: self.last_order_latency,
+            to_exch,
+            to_local,
+        })
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        let state: L3LocalState = decode(state)?;
+        self.orders = state
+            .orders
+            .into_iter()
+            .map(|order| (order.order_id, order))
+            .collect();
+        load_l3_depth(&mut self.depth, state.depth)?;
+        self.state.state_values = state.state_values;
+        // Keeps the capacity, which determines whether the last trades are stored.
+        self.trades.clear();
+        self.trades.extend(state.trades);
+        self.last_feed_latency = state.last_feed_latency;
+        self.last_order_latency = state.last_order_latency;
+        self.order_l2e
+            .set_orders_in_transit(state.to_exch, state.to_local);
+        Ok(())
+    }
 }
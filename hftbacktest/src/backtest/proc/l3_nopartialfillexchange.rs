@@ -1,13 +1,17 @@
+use bincode::{Decode, Encode};
+
 use crate::{
     backtest::{
         BacktestError,
         assettype::AssetType,
+        checkpoint::{L3DepthCheckpoint, decode, encode, load_l3_depth, save_l3_depth},
         models::{FeeModel, L3QueueModel, LatencyModel},
         order::ExchToLocal,
         proc::Processor,
         state::State,
     },
     depth::L3MarketDepth,
+    journal::{JournalSource, OrderJournal},
     prelude::OrdType,
     types::{
         BUY_EVENT,
@@ -25,11 +29,21 @@ use crate::{
         OrderId,
         SELL_EVENT,
         Side,
+        StateValues,
         Status,
         TimeInForce,
     },
 };
 
+/// The state of [`L3NoPartialFillExchange`] stored in a checkpoint.
+#[derive(Decode, Encode)]
+struct L3ExchangeState {
+    depth: L3DepthCheckpoint,
+    // The queues, including the backtest orders, encoded by the queue model.
+    queue: Vec<u8>,
+    state_values: StateValues,
+}
+
 /// The exchange model without partial fills.
 ///
 /// Support order types: [OrdType::Limit](crate::types::OrdType::Limit)
@@ -67,6 +81,7 @@ where
     state: State<AT, FM>,
     queue_model: QM,
     order_e2l: ExchToLocal<LM>,
+    journal: Option<OrderJournal>,
 }
 
 impl<AT, LM, QM, MD, FM> L3NoPartialFillExchange<AT, LM, QM, MD, FM>
@@ -90,15 +105,24 @@ where
             state,
             queue_model,
             order_e2l,
+            journal: None,
         }
     }
 
+    /// Sets the journal that records the order state transitions observed by the exchange.
+    pub fn set_journal(&mut self, journal: OrderJournal) {
+        self.journal = Some(journal);
+    }
+
     fn expired(&mut self, mut order: Order, timestamp: i64) -> Result<(), BacktestError> {
         order.exec_qty = 0.0;
         order.leaves_qty = 0.0;
         order.status = Status::Expired;
         order.exch_timestamp = timestamp;
 
+        if let Some(journal) = &self.journal {
+            journal.record_update(timestamp, JournalSource::Exchange, Status::None, &order);
+        }
         self.order_e2l.respond(order);
         Ok(())
     }
@@ -132,6 +156,9 @@ where
         self.state.apply_fill(order);
 
         if MAKE_RESPONSE {
+            if let Some(journal) = &self.journal {
+                journal.record_update(timestamp, JournalSource::Exchange, Status::None, order);
+            }
             self.order_e2l.respond(order.clone());
         }
         Ok(())
@@ -413,6 +440,7 @@ where
         _wait_resp_order_id: Option<OrderId>,
     ) -> Result<bool, BacktestError> {
         while let Some(mut order) = self.order_e2l.receive(timestamp) {
+            let req = order.req;
             // Processes a new order.
             if order.req == Status::New {
                 order.req = Status::None;
@@ -430,6 +458,9 @@ where
             } else {
                 return Err(BacktestError::InvalidOrderRequest);
             }
+            if let Some(journal) = &self.journal {
+                journal.record_update(timestamp, JournalSource::Exchange, req, &order);
+            }
             // Makes the response.
             self.order_e2l.respond(order);
         }
@@ -447,4 +478,20 @@ where
             .earliest_send_order_timestamp()
             .unwrap_or(i64::MAX)
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        encode(&L3ExchangeState {
+            depth: save_l3_depth(&self.depth),
+            queue: self.queue_model.save_state()?,
+            state_values: self.state.state_values.clone(),
+        })
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        let state: L3ExchangeState = decode(state)?;
+        load_l3_depth(&mut self.depth, state.depth)?;
+        self.queue_model.load_state(&state.queue)?;
+        self.state.state_values = state.state_values;
+        Ok(())
+    }
 }
@@ -0,0 +1,710 @@
+use bincode::{Decode, Encode};
+
+use crate::{
+    backtest::{
+        BacktestError,
+        assettype::AssetType,
+        checkpoint::{L3DepthCheckpoint, decode, encode, load_l3_depth, save_l3_depth},
+        models::{FeeModel, L3QueueModel, LatencyModel},
+        order::ExchToLocal,
+        proc::Processor,
+        state::State,
+    },
+    depth::{DepthAnalytics, INVALID_MAX, INVALID_MIN, L3MarketDepth},
+    journal::{JournalSource, OrderJournal},
+    prelude::OrdType,
+    types::{
+        BUY_EVENT,
+        EXCH_ASK_ADD_ORDER_EVENT,
+        EXCH_ASK_DEPTH_CLEAR_EVENT,
+        EXCH_BID_ADD_ORDER_EVENT,
+        EXCH_BID_DEPTH_CLEAR_EVENT,
+        EXCH_CANCEL_ORDER_EVENT,
+        EXCH_DEPTH_CLEAR_EVENT,
+        EXCH_EVENT,
+        EXCH_FILL_EVENT,
+        EXCH_MODIFY_ORDER_EVENT,
+        Event,
+        Order,
+        OrderId,
+        SELL_EVENT,
+        Side,
+        StateValues,
+        Status,
+        TimeInForce,
+    },
+};
+
+/// The state of [`L3PartialFillExchange`] stored in a checkpoint.
+#[derive(Decode, Encode)]
+struct L3ExchangeState {
+    depth: L3DepthCheckpoint,
+    // The queues, including the backtest orders, encoded by the queue model.
+    queue: Vec<u8>,
+    state_values: StateValues,
+}
+
+/// The exchange model with partial fills for the Level-3 Market-By-Order feed.
+///
+/// * Support order types: [OrdType::Limit](crate::types::OrdType::Limit),
+///   [OrdType::Market](crate::types::OrdType::Market)
+/// * Support time-in-force: [`TimeInForce::GTC`], [`TimeInForce::FOK`], [`TimeInForce::IOC`],
+///   [`TimeInForce::GTX`]
+///
+/// **Conditions for Full Execution**
+///
+/// Buy order in the order book
+///
+/// - Your order price >= the best ask price
+/// - Your order price > sell trade price
+///
+/// Sell order in the order book
+///
+/// - Your order price <= the best bid price
+/// - Your order price < buy trade price
+///
+/// **Conditions for Partial Execution**
+///
+/// Buy order in the order book
+///
+/// - Filled by the executed quantity of the market-feed orders behind your order in the queue:
+///   your order price == sell trade price
+///
+/// Sell order in the order book
+///
+/// - Filled by the executed quantity of the market-feed orders behind your order in the queue:
+///   your order price == buy trade price
+///
+/// **Liquidity-Taking Order**
+///
+/// Liquidity-taking orders walk the price levels of the order book, and each level is executed up
+/// to the total quantity of the orders at that level, even though the order book does not change
+/// due to your execution. Be aware that this may cause unrealistic fill simulations if you attempt
+/// to execute a large quantity.
+///
+pub struct L3PartialFillExchange<AT, LM, QM, MD, FM>
+where
+    AT: AssetType,
+    LM: LatencyModel,
+    QM: L3QueueModel<MD>,
+    MD: L3MarketDepth + DepthAnalytics,
+    FM: FeeModel,
+{
+    depth: MD,
+    state: State<AT, FM>,
+    queue_model: QM,
+    order_e2l: ExchToLocal<LM>,
+    journal: Option<OrderJournal>,
+}
+
+impl<AT, LM, QM, MD, FM> L3PartialFillExchange<AT, LM, QM, MD, FM>
+where
+    AT: AssetType,
+    LM: LatencyModel,
+    QM: L3QueueModel<MD>,
+    MD: L3MarketDepth + DepthAnalytics,
+    FM: FeeModel,
+    BacktestError: From<<MD as L3MarketDepth>::Error>,
+{
+    /// Constructs an instance of `L3PartialFillExchange`.
+    pub fn new(
+        depth: MD,
+        state: State<AT, FM>,
+        queue_model: QM,
+        order_e2l: ExchToLocal<LM>,
+    ) -> Self {
+        Self {
+            depth,
+            state,
+            queue_model,
+            order_e2l,
+            journal: None,
+        }
+    }
+
+    /// Sets the journal that records the order state transitions observed by the exchange.
+    pub fn set_journal(&mut self, journal: OrderJournal) {
+        self.journal = Some(journal);
+    }
+
+    fn expired(&mut self, mut order: Order, timestamp: i64) -> Result<(), BacktestError> {
+        order.exec_qty = 0.0;
+        order.leaves_qty = 0.0;
+        order.status = Status::Expired;
+        order.exch_timestamp = timestamp;
+
+        if let Some(journal) = &self.journal {
+            journal.record_update(timestamp, JournalSource::Exchange, Status::None, &order);
+        }
+        self.order_e2l.respond(order);
+        Ok(())
+    }
+
+    fn fill<const MAKE_RESPONSE: bool>(
+        &mut self,
+        order: &mut Order,
+        timestamp: i64,
+        maker: bool,
+        exec_price_tick: i64,
+        exec_qty: f64,
+    ) -> Result<(), BacktestError> {
+        if order.status == Status::Expired
+            || order.status == Status::Canceled
+            || order.status == Status::Filled
+        {
+            return Err(BacktestError::InvalidOrderStatus);
+        }
+
+        order.maker = maker;
+        if maker {
+            order.exec_price_tick = order.price_tick;
+        } else {
+            order.exec_price_tick = exec_price_tick;
+        }
+
+        order.exec_qty = exec_qty;
+        order.leaves_qty -= exec_qty;
+        if (order.leaves_qty / self.depth.lot_size()).round() > 0f64 {
+            order.status = Status::PartiallyFilled;
+        } else {
+            order.leaves_qty = 0.0;
+            order.status = Status::Filled;
+        }
+        order.exch_timestamp = timestamp;
+
+        self.state.apply_fill(order);
+
+        if MAKE_RESPONSE {
+            if let Some(journal) = &self.journal {
+                journal.record_update(timestamp, JournalSource::Exchange, Status::None, order);
+            }
+            self.order_e2l.respond(order.clone());
+        }
+        Ok(())
+    }
+
+    fn fill_ask_orders_by_crossing(
+        &mut self,
+        prev_best_tick: i64,
+        new_best_tick: i64,
+        timestamp: i64,
+    ) -> Result<(), BacktestError> {
+        let filled = self
+            .queue_model
+            .on_best_bid_update(prev_best_tick, new_best_tick)?;
+        for mut order in filled {
+            let (price_tick, leaves_qty) = (order.price_tick, order.leaves_qty);
+            self.fill::<true>(&mut order, timestamp, true, price_tick, leaves_qty)?;
+        }
+        Ok(())
+    }
+
+    fn fill_bid_orders_by_crossing(
+        &mut self,
+        prev_best_tick: i64,
+        new_best_tick: i64,
+        timestamp: i64,
+    ) -> Result<(), BacktestError> {
+        let filled = self
+            .queue_model
+            .on_best_ask_update(prev_best_tick, new_best_tick)?;
+        for mut order in filled {
+            let (price_tick, leaves_qty) = (order.price_tick, order.leaves_qty);
+            self.fill::<true>(&mut order, timestamp, true, price_tick, leaves_qty)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the price levels, as (price in ticks, quantity), that the liquidity-taking order can
+    /// execute against, ordered from the best. The levels are walked from the best until they
+    /// cover the order's leaves quantity.
+    fn taker_levels(&self, order: &Order) -> Vec<(i64, f64)> {
+        let mut levels = Vec::new();
+        let mut cum_qty = 0.0;
+        let mut add_level = |price_tick, qty| {
+            levels.push((price_tick, qty));
+            cum_qty += qty;
+            cum_qty < order.leaves_qty
+        };
+        // Market orders can walk every level.
+        let market_depth_ticks = u32::MAX as usize;
+        match order.side {
+            Side::Buy => {
+                let best_ask_tick = self.depth.best_ask_tick();
+                if best_ask_tick != INVALID_MAX {
+                    let depth_ticks = if order.order_type == OrdType::Market {
+                        market_depth_ticks
+                    } else {
+                        (order.price_tick - best_ask_tick + 1).max(0) as usize
+                    };
+                    self.depth.for_each_ask_level(depth_ticks, &mut add_level);
+                }
+            }
+            Side::Sell => {
+                let best_bid_tick = self.depth.best_bid_tick();
+                if best_bid_tick != INVALID_MIN {
+                    let depth_ticks = if order.order_type == OrdType::Market {
+                        market_depth_ticks
+                    } else {
+                        (best_bid_tick - order.price_tick + 1).max(0) as usize
+                    };
+                    self.depth.for_each_bid_level(depth_ticks, &mut add_level);
+                }
+            }
+            Side::None | Side::Unsupported => {}
+        }
+        levels
+    }
+
+    /// Executes the liquidity-taking order against the price levels until it is filled.
+    fn take(
+        &mut self,
+        order: &mut Order,
+        levels: &[(i64, f64)],
+        timestamp: i64,
+    ) -> Result<(), BacktestError> {
+        for &(price_tick, qty) in levels {
+            let exec_qty = qty.min(order.leaves_qty);
+            self.fill::<false>(order, timestamp, false, price_tick, exec_qty)?;
+            if order.status == Status::Filled {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    fn ack_taker(&mut self, order: &mut Order, timestamp: i64) -> Result<(), BacktestError> {
+        let levels = self.taker_levels(order);
+        match (order.order_type, order.time_in_force) {
+            (OrdType::Limit, TimeInForce::GTX) => {
+                order.status = Status::Expired;
+                order.exch_timestamp = timestamp;
+                Ok(())
+            }
+            (OrdType::Limit, TimeInForce::FOK) => {
+                // The order must be executed immediately in its entirety; otherwise, the entire
+                // order will be cancelled.
+                let lot_size = self.depth.lot_size();
+                let cum_qty: f64 = levels.iter().map(|(_, qty)| qty).sum();
+                if (cum_qty / lot_size).round() >= (order.qty / lot_size).round() {
+                    self.take(order, &levels, timestamp)
+                } else {
+                    order.status = Status::Expired;
+                    order.exch_timestamp = timestamp;
+                    Ok(())
+                }
+            }
+            (OrdType::Limit, TimeInForce::GTC) => {
+                self.take(order, &levels, timestamp)?;
+                if order.status != Status::Filled {
+                    // The order cannot remain in the opposite side of the book, as it cannot
+                    // affect the market depth during backtesting based on market-data replay. So,
+                    // the remaining quantity is executed at the order price.
+                    let (price_tick, leaves_qty) = (order.price_tick, order.leaves_qty);
+                    self.fill::<false>(order, timestamp, false, price_tick, leaves_qty)?;
+                }
+                Ok(())
+            }
+            (OrdType::Limit, TimeInForce::IOC) | (OrdType::Market, _) => {
+                // The order must be executed immediately, and the remaining quantity expires.
+                self.take(order, &levels, timestamp)?;
+                if order.status != Status::Filled {
+                    order.status = Status::Expired;
+                    order.exch_timestamp = timestamp;
+                }
+                Ok(())
+            }
+            _ => Err(BacktestError::InvalidOrderRequest),
+        }
+    }
+
+    fn ack_new(&mut self, order: &mut Order, timestamp: i64) -> Result<(), BacktestError> {
+        if self.queue_model.contains_backtest_order(order.order_id) {
+            return Err(BacktestError::OrderIdExist);
+        }
+
+        let takes = match (order.order_type, order.side) {
+            (OrdType::Market, _) => true,
+            (OrdType::Limit, Side::Buy) => order.price_tick >= self.depth.best_ask_tick(),
+            (OrdType::Limit, Side::Sell) => order.price_tick <= self.depth.best_bid_tick(),
+            _ => return Err(BacktestError::InvalidOrderRequest),
+        };
+        if takes {
+            return self.ack_taker(order, timestamp);
+        }
+
+        match order.time_in_force {
+            TimeInForce::GTC | TimeInForce::GTX => {
+                // Initializes the order's queue position.
+                order.status = Status::New;
+                order.exch_timestamp = timestamp;
+
+                self.queue_model
+                    .add_backtest_order(order.clone(), &self.depth)?;
+                Ok(())
+            }
+            TimeInForce::FOK | TimeInForce::IOC => {
+                order.status = Status::Expired;
+                order.exch_timestamp = timestamp;
+                Ok(())
+            }
+            TimeInForce::Unsupported => Err(BacktestError::InvalidOrderRequest),
+        }
+    }
+
+    fn ack_cancel(&mut self, order: &mut Order, timestamp: i64) -> Result<(), BacktestError> {
+        match self
+            .queue_model
+            .cancel_backtest_order(order.order_id, &self.depth)
+        {
+            Ok(exch_order) => {
+                let _ = std::mem::replace(order, exch_order);
+
+                order.status = Status::Canceled;
+                order.exch_timestamp = timestamp;
+                Ok(())
+            }
+            Err(BacktestError::OrderNotFound) => {
+                order.req = Status::Rejected;
+                order.exch_timestamp = timestamp;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn ack_modify<const RESET_QUEUE_POS: bool>(
+        &mut self,
+        order: &mut Order,
+        timestamp: i64,
+    ) -> Result<(), BacktestError> {
+        match self
+            .queue_model
+            .modify_backtest_order(order.order_id, order, &self.depth)
+        {
+            Ok(()) => {
+                order.leaves_qty = order.qty;
+                order.exch_timestamp = timestamp;
+                Ok(())
+            }
+            Err(BacktestError::OrderNotFound) => {
+                order.req = Status::Rejected;
+                order.exch_timestamp = timestamp;
+                Ok(())
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<AT, LM, QM, MD, FM> Processor for L3PartialFillExchange<AT, LM, QM, MD, FM>
+where
+    AT: AssetType,
+    LM: LatencyModel,
+    QM: L3QueueModel<MD>,
+    MD: L3MarketDepth + DepthAnalytics,
+    FM: FeeModel,
+    BacktestError: From<<MD as L3MarketDepth>::Error>,
+{
+    fn event_seen_timestamp(&self, event: &Event) -> Option<i64> {
+        event.is(EXCH_EVENT).then_some(event.exch_ts)
+    }
+
+    fn process(&mut self, event: &Event) -> Result<(), BacktestError> {
+        if event.is(EXCH_BID_DEPTH_CLEAR_EVENT) {
+            self.depth.clear_orders(Side::Buy);
+            let expired = self.queue_model.clear_orders(Side::Buy);
+            for order in expired {
+                self.expired(order, event.exch_ts)?;
+            }
+        } else if event.is(EXCH_ASK_DEPTH_CLEAR_EVENT) {
+            self.depth.clear_orders(Side::Sell);
+            let expired = self.queue_model.clear_orders(Side::Sell);
+            for order in expired {
+                self.expired(order, event.exch_ts)?;
+            }
+        } else if event.is(EXCH_DEPTH_CLEAR_EVENT) {
+            self.depth.clear_orders(Side::None);
+            let expired = self.queue_model.clear_orders(Side::None);
+            for order in expired {
+                self.expired(order, event.exch_ts)?;
+            }
+        } else if event.is(EXCH_BID_ADD_ORDER_EVENT) {
+            let (prev_best_bid_tick, best_bid_tick) =
+                self.depth
+                    .add_buy_order(event.order_id, event.px, event.qty, event.exch_ts)?;
+            self.queue_model.add_market_feed_order(event, &self.depth)?;
+            if best_bid_tick > prev_best_bid_tick {
+                self.fill_ask_orders_by_crossing(prev_best_bid_tick, best_bid_tick, event.exch_ts)?;
+            }
+        } else if event.is(EXCH_ASK_ADD_ORDER_EVENT) {
+            let (prev_best_ask_tick, best_ask_tick) =
+                self.depth
+                    .add_sell_order(event.order_id, event.px, event.qty, event.exch_ts)?;
+            self.queue_model.add_market_feed_order(event, &self.depth)?;
+            if best_ask_tick < prev_best_ask_tick {
+                self.fill_bid_orders_by_crossing(prev_best_ask_tick, best_ask_tick, event.exch_ts)?;
+            }
+        } else if event.is(EXCH_MODIFY_ORDER_EVENT) {
+            let (side, prev_best_tick, best_tick) =
+                self.depth
+                    .modify_order(event.order_id, event.px, event.qty, event.exch_ts)?;
+            self.queue_model
+                .modify_market_feed_order(event.order_id, event, &self.depth)?;
+            if side == Side::Buy {
+                if best_tick > prev_best_tick {
+                    self.fill_ask_orders_by_crossing(prev_best_tick, best_tick, event.exch_ts)?;
+                }
+            } else if best_tick < prev_best_tick {
+                self.fill_bid_orders_by_crossing(prev_best_tick, best_tick, event.exch_ts)?;
+            }
+        } else if event.is(EXCH_CANCEL_ORDER_EVENT) {
+            let order_id = event.order_id;
+            self.depth.delete_order(order_id, event.exch_ts)?;
+            self.queue_model
+                .cancel_market_feed_order(event.order_id, &self.depth)?;
+        } else if event.is(EXCH_FILL_EVENT) {
+            // todo: handle properly if no side is provided.
+            if event.is(BUY_EVENT) || event.is(SELL_EVENT) {
+                let filled = self.queue_model.partially_fill_market_feed_order::<false>(
+                    event.order_id,
+                    event,
+                    &self.depth,
+                )?;
+                let timestamp = event.exch_ts;
+                for (mut order, exec_qty) in filled {
+                    let price_tick = order.price_tick;
+                    self.fill::<true>(&mut order, timestamp, true, price_tick, exec_qty)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn process_recv_order(
+        &mut self,
+        timestamp: i64,
+        _wait_resp_order_id: Option<OrderId>,
+    ) -> Result<bool, BacktestError> {
+        while let Some(mut order) = self.order_e2l.receive(timestamp) {
+            let req = order.req;
+            // Processes a new order.
+            if order.req == Status::New {
+                order.req = Status::None;
+                self.ack_new(&mut order, timestamp)?;
+            }
+            // Processes a cancel order.
+            else if order.req == Status::Canceled {
+                order.req = Status::None;
+                self.ack_cancel(&mut order, timestamp)?;
+            }
+            // Processes a modify order.
+            else if order.req == Status::Replaced {
+                order.req = Status::None;
+                self.ack_modify::<false>(&mut order, timestamp)?;
+            } else {
+                return Err(BacktestError::InvalidOrderRequest);
+            }
+            if let Some(journal) = &self.journal {
+                journal.record_update(timestamp, JournalSource::Exchange, req, &order);
+            }
+            // Makes the response.
+            self.order_e2l.respond(order);
+        }
+        Ok(false)
+    }
+
+    fn earliest_recv_order_timestamp(&self) -> i64 {
+        self.order_e2l
+            .earliest_recv_order_timestamp()
+            .unwrap_or(i64::MAX)
+    }
+
+    fn earliest_send_order_timestamp(&self) -> i64 {
+        self.order_e2l
+            .earliest_send_order_timestamp()
+            .unwrap_or(i64::MAX)
+    }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        encode(&L3ExchangeState {
+            depth: save_l3_depth(&self.depth),
+            queue: self.queue_model.save_state()?,
+            state_values: self.state.state_values.clone(),
+        })
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        let state: L3ExchangeState = decode(state)?;
+        load_l3_depth(&mut self.depth, state.depth)?;
+        self.queue_model.load_state(&state.queue)?;
+        self.state.state_values = state.state_values;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::{
+            assettype::LinearAsset,
+            models::{CommonFees, ConstantLatency, L3FIFOQueueModel, TradingValueFeeModel},
+            order::{LocalToExch, order_bus},
+            proc::{L3PartialFillExchange, Processor},
+            state::State,
+        },
+        depth::HashMapMarketDepth,
+        types::{
+            BUY_EVENT,
+            EXCH_ASK_ADD_ORDER_EVENT,
+            EXCH_BID_ADD_ORDER_EVENT,
+            EXCH_CANCEL_ORDER_EVENT,
+            EXCH_FILL_EVENT,
+            Event,
+            OrdType,
+            Order,
+            Side,
+            Status,
+            TimeInForce,
+        },
+    };
+
+    type Exchange = L3PartialFillExchange<
+        LinearAsset,
+        ConstantLatency,
+        L3FIFOQueueModel,
+        HashMapMarketDepth,
+        TradingValueFeeModel<CommonFees>,
+    >;
+
+    fn event(ev: u64, order_id: u64, px: f64, qty: f64) -> Event {
+        Event {
+            ev,
+            exch_ts: 0,
+            local_ts: 0,
+            px,
+            qty,
+            order_id,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    fn setup() -> (Exchange, LocalToExch<ConstantLatency>) {
+        let (order_e2l, order_l2e) = order_bus(ConstantLatency::new(0, 0));
+        let mut exch = L3PartialFillExchange::new(
+            HashMapMarketDepth::new(1.0, 1.0),
+            State::new(
+                LinearAsset::new(1.0),
+                TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)),
+            ),
+            L3FIFOQueueModel::new(),
+            order_e2l,
+        );
+        exch.process(&event(EXCH_ASK_ADD_ORDER_EVENT, 1, 101.0, 2.0))
+            .unwrap();
+        exch.process(&event(EXCH_ASK_ADD_ORDER_EVENT, 2, 102.0, 3.0))
+            .unwrap();
+        exch.process(&event(EXCH_BID_ADD_ORDER_EVENT, 3, 99.0, 2.0))
+            .unwrap();
+        (exch, order_l2e)
+    }
+
+    fn submit(
+        exch: &mut Exchange,
+        order_l2e: &mut LocalToExch<ConstantLatency>,
+        mut order: Order,
+    ) -> Order {
+        order.req = Status::New;
+        order_l2e.request(order, |_| unreachable!());
+        exch.process_recv_order(0, None).unwrap();
+        order_l2e.receive(0).unwrap()
+    }
+
+    #[test]
+    fn test_taker_walks_levels() {
+        let (mut exch, mut order_l2e) = setup();
+
+        let order = Order::new(
+            1,
+            102,
+            1.0,
+            4.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::IOC,
+        );
+        let order = submit(&mut exch, &mut order_l2e, order);
+        assert_eq!(order.status, Status::Filled);
+        assert_eq!(order.exec_price_tick, 102);
+        assert_eq!(exch.state.state_values.position, 4.0);
+        assert_eq!(
+            exch.state.state_values.balance,
+            -(2.0 * 101.0 + 2.0 * 102.0)
+        );
+
+        // Only 2 is available up to 101.
+        let order = Order::new(
+            2,
+            101,
+            1.0,
+            3.0,
+            Side::Buy,
+            OrdType::Limit,
+            TimeInForce::FOK,
+        );
+        let order = submit(&mut exch, &mut order_l2e, order);
+        assert_eq!(order.status, Status::Expired);
+        assert_eq!(exch.state.state_values.position, 4.0);
+
+        let order = Order::new(
+            3,
+            0,
+            1.0,
+            3.0,
+            Side::Sell,
+            OrdType::Market,
+            TimeInForce::GTC,
+        );
+        let order = submit(&mut exch, &mut order_l2e, order);
+        assert_eq!(order.status, Status::Expired);
+        assert_eq!(order.leaves_qty, 1.0);
+        assert_eq!(exch.state.state_values.position, 2.0);
+    }
+
+    #[test]
+    fn test_partial_fill_in_queue() {
+        let (mut exch, mut order_l2e) = setup();
+
+        let order = Order::new(1, 99, 1.0, 3.0, Side::Buy, OrdType::Limit, TimeInForce::GTC);
+        let order = submit(&mut exch, &mut order_l2e, order);
+        assert_eq!(order.status, Status::New);
+        exch.process(&event(EXCH_BID_ADD_ORDER_EVENT, 4, 99.0, 5.0))
+            .unwrap();
+
+        // The market-feed order ahead of the backtest order is filled.
+        exch.process(&event(EXCH_FILL_EVENT | BUY_EVENT, 3, 99.0, 2.0))
+            .unwrap();
+        exch.process(&event(EXCH_CANCEL_ORDER_EVENT, 3, 99.0, 0.0))
+            .unwrap();
+        assert!(order_l2e.receive(0).is_none());
+
+        // The market-feed order behind the backtest order is filled, so the backtest order is
+        // executed by the same quantity.
+        exch.process(&event(EXCH_FILL_EVENT | BUY_EVENT, 4, 99.0, 2.0))
+            .unwrap();
+        let order = order_l2e.receive(0).unwrap();
+        assert_eq!(order.status, Status::PartiallyFilled);
+        assert_eq!(order.exec_qty, 2.0);
+        assert_eq!(order.leaves_qty, 1.0);
+
+        exch.process(&event(EXCH_FILL_EVENT | BUY_EVENT, 4, 99.0, 2.0))
+            .unwrap();
+        let order = order_l2e.receive(0).unwrap();
+        assert_eq!(order.status, Status::Filled);
+        assert_eq!(order.exec_qty, 1.0);
+        assert_eq!(order.leaves_qty, 0.0);
+        assert_eq!(exch.state.state_values.position, 3.0);
+    }
+}
@@ -1,20 +1,29 @@
-use std::collections::{HashMap, hash_map::Entry};
+use std::{
+    any::Any,
+    collections::{HashMap, hash_map::Entry},
+};
+
+use bincode::{Decode, Encode};
 
 use crate::{
     backtest::{
         BacktestError,
         assettype::AssetType,
+        checkpoint::{decode, encode},
         models::{FeeModel, LatencyModel},
         order::LocalToExch,
         proc::{LocalProcessor, Processor},
         state::State,
     },
     depth::{L2MarketDepth, MarketDepth},
+    journal::{JournalSource, OrderJournal, OrderTransition, TransitionReason},
     types::{
         Event,
+        LOCAL_ASK_DEPTH_BBO_EVENT,
         LOCAL_ASK_DEPTH_CLEAR_EVENT,
         LOCAL_ASK_DEPTH_EVENT,
         LOCAL_ASK_DEPTH_SNAPSHOT_EVENT,
+        LOCAL_BID_DEPTH_BBO_EVENT,
         LOCAL_BID_DEPTH_CLEAR_EVENT,
         LOCAL_BID_DEPTH_EVENT,
         LOCAL_BID_DEPTH_SNAPSHOT_EVENT,
@@ -31,6 +40,19 @@ use crate::{
     },
 };
 
+/// The state of [`Local`] stored in a checkpoint.
+#[derive(Decode, Encode)]
+struct LocalState {
+    orders: Vec<Order>,
+    state_values: StateValues,
+    trades: Vec<Event>,
+    last_feed_latency: Option<(i64, i64)>,
+    last_order_latency: Option<(i64, i64, i64)>,
+    // The order bus is shared with the exchange, so only the local stores the orders in transit.
+    to_exch: Vec<(Order, i64)>,
+    to_local: Vec<(Order, i64)>,
+}
+
 /// The local model.
 pub struct Local<AT, LM, MD, FM>
 where
@@ -46,6 +68,7 @@ where
     trades: Vec<Event>,
     last_feed_latency: Option<(i64, i64)>,
     last_order_latency: Option<(i64, i64, i64)>,
+    journal: Option<OrderJournal>,
 }
 
 impl<AT, LM, MD, FM> Local<AT, LM, MD, FM>
@@ -70,9 +93,15 @@ where
             trades: Vec::with_capacity(last_trades_cap),
             last_feed_latency: None,
             last_order_latency: None,
+            journal: None,
         }
     }
 
+    /// Sets the journal that records the order state transitions observed by the local.
+    pub fn set_journal(&mut self, journal: OrderJournal) {
+        self.journal = Some(journal);
+    }
+
     pub fn process_recv_order_<const USE_HANDLER: bool, Handler>(
         &mut self,
         timestamp: i64,
@@ -102,6 +131,13 @@ where
             if order.status == Status::Filled {
                 self.state.apply_fill(&order);
             }
+            if let Some(journal) = &self.journal {
+                let req = self
+                    .orders
+                    .get(&order.order_id)
+                    .map_or(Status::None, |local_order| local_order.req);
+                journal.record_update(timestamp, JournalSource::Local, req, &order);
+            }
             // Applies the received order response to the local orders.
             match self.orders.entry(order.order_id) {
                 Entry::Occupied(mut entry) => {
@@ -140,7 +176,7 @@ impl<AT, LM, MD, FM> LocalProcessor<MD> for Local<AT, LM, MD, FM>
 where
     AT: AssetType,
     LM: LatencyModel,
-    MD: MarketDepth + L2MarketDepth,
+    MD: MarketDepth + L2MarketDepth + 'static,
     FM: FeeModel,
 {
     fn submit_order(
@@ -169,6 +205,15 @@ where
         );
         order.req = Status::New;
         order.local_timestamp = current_timestamp;
+        if let Some(journal) = &self.journal {
+            journal.record_order(
+                current_timestamp,
+                JournalSource::Local,
+                OrderTransition::Submitted,
+                TransitionReason::None,
+                &order,
+            );
+        }
         self.orders.insert(order.order_id, order.clone());
 
         self.order_l2e.request(order, |order| {
@@ -203,6 +248,15 @@ where
 
         order.req = Status::Replaced;
         order.local_timestamp = current_timestamp;
+        if let Some(journal) = &self.journal {
+            journal.record_order(
+                current_timestamp,
+                JournalSource::Local,
+                OrderTransition::ModifyRequested,
+                TransitionReason::None,
+                order,
+            );
+        }
 
         self.order_l2e.request(order.clone(), |order| {
             order.req = Status::Rejected;
@@ -225,6 +279,15 @@ where
 
         order.req = Status::Canceled;
         order.local_timestamp = current_timestamp;
+        if let Some(journal) = &self.journal {
+            journal.record_order(
+                current_timestamp,
+                JournalSource::Local,
+                OrderTransition::CancelRequested,
+                TransitionReason::None,
+                order,
+            );
+        }
 
         self.order_l2e.request(order.clone(), |order| {
             order.req = Status::Rejected;
@@ -278,7 +341,7 @@ impl<AT, LM, MD, FM> Processor for Local<AT, LM, MD, FM>
 where
     AT: AssetType,
     LM: LatencyModel,
-    MD: MarketDepth + L2MarketDepth,
+    MD: MarketDepth + L2MarketDepth + 'static,
     FM: FeeModel,
 {
     fn event_seen_timestamp(&self, event: &Event) -> Option<i64> {
@@ -286,6 +349,13 @@ where
     }
 
     fn process(&mut self, ev: &Event) -> Result<(), BacktestError> {
+        // The market depth that fuses multiple streams orders the updates by the exchange
+        // timestamp.
+        let depth_ts = if self.depth.uses_exch_timestamp() {
+            ev.exch_ts
+        } else {
+            ev.local_ts
+        };
         // Processes a depth event
         if ev.is(LOCAL_BID_DEPTH_CLEAR_EVENT) {
             self.depth.clear_depth(Side::Buy, ev.px);
@@ -294,9 +364,13 @@ where
         } else if ev.is(LOCAL_DEPTH_CLEAR_EVENT) {
             self.depth.clear_depth(Side::None, 0.0);
         } else if ev.is(LOCAL_BID_DEPTH_EVENT) || ev.is(LOCAL_BID_DEPTH_SNAPSHOT_EVENT) {
-            self.depth.update_bid_depth(ev.px, ev.qty, ev.local_ts);
+            self.depth.update_bid_depth(ev.px, ev.qty, depth_ts);
         } else if ev.is(LOCAL_ASK_DEPTH_EVENT) || ev.is(LOCAL_ASK_DEPTH_SNAPSHOT_EVENT) {
-            self.depth.update_ask_depth(ev.px, ev.qty, ev.local_ts);
+            self.depth.update_ask_depth(ev.px, ev.qty, depth_ts);
+        } else if ev.is(LOCAL_BID_DEPTH_BBO_EVENT) {
+            self.depth.update_best_bid(ev.px, ev.qty, depth_ts);
+        } else if ev.is(LOCAL_ASK_DEPTH_BBO_EVENT) {
+            self.depth.update_best_ask(ev.px, ev.qty, depth_ts);
         }
         // Processes a trade event
         else if ev.is(LOCAL_TRADE_EVENT) && self.trades.capacity() > 0 {
@@ -328,4 +402,43 @@ where
             .earliest_send_order_timestamp()
             .unwrap_or(i64::MAX)
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        let (to_exch, to_local) = self.order_l2e.orders_in_transit();
+        encode(&LocalState {
+            orders: self.orders.values().cloned().collect(),
+            state_values: self.state.state_values.clone(),
+            trades: self.trades.clone(),
+            last_feed_latency: self.last_feed_latency,
+            last_order_latency: self.last_order_latency,
+            to_exch,
+            to_local,
+        })
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        let state: LocalState = decode(state)?;
+        self.orders = state
+            .orders
+            .into_iter()
+            .map(|order| (order.order_id, order))
+            .collect();
+        self.state.state_values = state.state_values;
+        // Keeps the capacity, which determines whether the last trades are stored.
+        self.trades.clear();
+        self.trades.extend(state.trades);
+        self.last_feed_latency = state.last_feed_latency;
+        self.last_order_latency = state.last_order_latency;
+        self.order_l2e
+            .set_orders_in_transit(state.to_exch, state.to_local);
+        Ok(())
+    }
+
+    fn market_depth(&self) -> Option<&dyn Any> {
+        Some(&self.depth)
+    }
+
+    fn market_depth_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(&mut self.depth)
+    }
 }
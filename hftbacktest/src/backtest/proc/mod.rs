@@ -2,7 +2,7 @@ mod local;
 mod nopartialfillexchange;
 mod partialfillexchange;
 
-use std::collections::HashMap;
+use std::{any::Any, collections::HashMap};
 
 pub use local::Local;
 pub use nopartialfillexchange::NoPartialFillExchange;
@@ -11,9 +11,11 @@ pub use partialfillexchange::PartialFillExchange;
 mod l3_local;
 
 mod l3_nopartialfillexchange;
+mod l3_partialfillexchange;
 
 pub use l3_local::L3Local;
 pub use l3_nopartialfillexchange::L3NoPartialFillExchange;
+pub use l3_partialfillexchange::L3PartialFillExchange;
 
 use crate::{
     backtest::BacktestError,
@@ -124,6 +126,22 @@ impl<P: Processor + ?Sized> Processor for Box<P> {
     fn earliest_send_order_timestamp(&self) -> i64 {
         P::earliest_send_order_timestamp(self)
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        P::save_state(self)
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        P::load_state(self, state)
+    }
+
+    fn market_depth(&self) -> Option<&dyn Any> {
+        P::market_depth(self)
+    }
+
+    fn market_depth_mut(&mut self) -> Option<&mut dyn Any> {
+        P::market_depth_mut(self)
+    }
 }
 /// Processes the historical feed data and the order interaction.
 pub trait Processor {
@@ -153,4 +171,40 @@ pub trait Processor {
     /// Returns the foremost timestamp at which an order sent by this processor is to be received by
     /// the corresponding processor.
     fn earliest_send_order_timestamp(&self) -> i64;
+
+    /// Encodes the state of this processor for a
+    /// [`Checkpoint`](crate::backtest::checkpoint::Checkpoint). The configuration, such as the
+    /// models and the asset type, is not included.
+    ///
+    /// The default implementation returns [`BacktestError::CheckpointUnsupported`].
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("processor"))
+    }
+
+    /// Restores the state encoded by [`save_state`](Processor::save_state), replacing the current
+    /// state.
+    ///
+    /// The default implementation returns [`BacktestError::CheckpointUnsupported`].
+    fn load_state(&mut self, _state: &[u8]) -> Result<(), BacktestError> {
+        Err(BacktestError::CheckpointUnsupported("processor"))
+    }
+
+    /// Returns the market depth, which is stored in a
+    /// [`Checkpoint`](crate::backtest::checkpoint::Checkpoint) as a depth snapshot, separately from
+    /// [`save_state`](Processor::save_state). The market depth must be of the backtest's market
+    /// depth type.
+    ///
+    /// The default implementation returns `None`, in which case the market depth, if any, needs to
+    /// be included in the processor's own state.
+    fn market_depth(&self) -> Option<&dyn Any> {
+        None
+    }
+
+    /// Returns the market depth to be restored from a
+    /// [`Checkpoint`](crate::backtest::checkpoint::Checkpoint) before
+    /// [`load_state`](Processor::load_state) is invoked. See
+    /// [`market_depth`](Processor::market_depth).
+    fn market_depth_mut(&mut self) -> Option<&mut dyn Any> {
+        None
+    }
 }
@@ -1,25 +1,32 @@
 use std::{
+    any::Any,
     cell::RefCell,
     cmp::Ordering,
     collections::{HashMap, HashSet},
     rc::Rc,
 };
 
+use bincode::{Decode, Encode};
+
 use crate::{
     backtest::{
         BacktestError,
         assettype::AssetType,
+        checkpoint::{decode, encode},
         models::{FeeModel, LatencyModel, QueueModel},
         order::ExchToLocal,
         proc::Processor,
         state::State,
     },
     depth::{INVALID_MAX, INVALID_MIN, L2MarketDepth, MarketDepth},
+    journal::{JournalSource, OrderJournal},
     prelude::OrdType,
     types::{
+        EXCH_ASK_DEPTH_BBO_EVENT,
         EXCH_ASK_DEPTH_CLEAR_EVENT,
         EXCH_ASK_DEPTH_EVENT,
         EXCH_ASK_DEPTH_SNAPSHOT_EVENT,
+        EXCH_BID_DEPTH_BBO_EVENT,
         EXCH_BID_DEPTH_CLEAR_EVENT,
         EXCH_BID_DEPTH_EVENT,
         EXCH_BID_DEPTH_SNAPSHOT_EVENT,
@@ -31,11 +38,20 @@ use crate::{
         Order,
         OrderId,
         Side,
+        StateValues,
         Status,
         TimeInForce,
     },
 };
 
+/// The state of [`PartialFillExchange`] stored in a checkpoint.
+#[derive(Decode, Encode)]
+struct ExchangeState {
+    // The orders and their queue position values encoded by the queue model.
+    orders: Vec<(Order, Vec<u8>)>,
+    state_values: StateValues,
+}
+
 /// The exchange model with partial fills.
 ///
 /// * Support order types: [OrdType::Limit](crate::types::OrdType::Limit)
@@ -96,6 +112,7 @@ where
     queue_model: QM,
 
     filled_orders: Vec<OrderId>,
+    journal: Option<OrderJournal>,
 }
 
 impl<AT, LM, QM, MD, FM> PartialFillExchange<AT, LM, QM, MD, FM>
@@ -122,9 +139,15 @@ where
             state,
             queue_model,
             filled_orders: Default::default(),
+            journal: None,
         }
     }
 
+    /// Sets the journal that records the order state transitions observed by the exchange.
+    pub fn set_journal(&mut self, journal: OrderJournal) {
+        self.journal = Some(journal);
+    }
+
     fn check_if_sell_filled(
         &mut self,
         order: &mut Order,
@@ -239,6 +262,9 @@ where
         self.state.apply_fill(order);
 
         if MAKE_RESPONSE {
+            if let Some(journal) = &self.journal {
+                journal.record_update(timestamp, JournalSource::Exchange, Status::None, order);
+            }
             self.order_e2l.respond(order.clone());
         }
         Ok(())
@@ -732,7 +758,7 @@ where
     AT: AssetType,
     LM: LatencyModel,
     QM: QueueModel<MD>,
-    MD: MarketDepth + L2MarketDepth,
+    MD: MarketDepth + L2MarketDepth + 'static,
     FM: FeeModel,
 {
     fn event_seen_timestamp(&self, event: &Event) -> Option<i64> {
@@ -762,6 +788,40 @@ where
             if best_ask_tick < prev_best_ask_tick {
                 self.on_best_ask_update(prev_best_ask_tick, best_ask_tick, timestamp)?;
             }
+        } else if event.is(EXCH_BID_DEPTH_BBO_EVENT) {
+            if let Some((
+                price_tick,
+                prev_best_bid_tick,
+                best_bid_tick,
+                prev_qty,
+                new_qty,
+                timestamp,
+            )) = self
+                .depth
+                .update_best_bid(event.px, event.qty, event.exch_ts)
+            {
+                self.on_bid_qty_chg(price_tick, prev_qty, new_qty);
+                if best_bid_tick > prev_best_bid_tick {
+                    self.on_best_bid_update(prev_best_bid_tick, best_bid_tick, timestamp)?;
+                }
+            }
+        } else if event.is(EXCH_ASK_DEPTH_BBO_EVENT) {
+            if let Some((
+                price_tick,
+                prev_best_ask_tick,
+                best_ask_tick,
+                prev_qty,
+                new_qty,
+                timestamp,
+            )) = self
+                .depth
+                .update_best_ask(event.px, event.qty, event.exch_ts)
+            {
+                self.on_ask_qty_chg(price_tick, prev_qty, new_qty);
+                if best_ask_tick < prev_best_ask_tick {
+                    self.on_best_ask_update(prev_best_ask_tick, best_ask_tick, timestamp)?;
+                }
+            }
         } else if event.is(EXCH_BUY_TRADE_EVENT) {
             let price_tick = (event.px / self.depth.tick_size()).round() as i64;
             let qty = event.qty;
@@ -825,6 +885,7 @@ where
         _wait_resp_order_id: Option<OrderId>,
     ) -> Result<bool, BacktestError> {
         while let Some(mut order) = self.order_e2l.receive(timestamp) {
+            let req = order.req;
             // Processes a new order.
             if order.req == Status::New {
                 order.req = Status::None;
@@ -842,6 +903,9 @@ where
             } else {
                 return Err(BacktestError::InvalidOrderRequest);
             }
+            if let Some(journal) = &self.journal {
+                journal.record_update(timestamp, JournalSource::Exchange, req, &order);
+            }
             // Makes the response.
             self.order_e2l.respond(order);
         }
@@ -859,4 +923,50 @@ where
             .earliest_send_order_timestamp()
             .unwrap_or(i64::MAX)
     }
+
+    fn save_state(&self) -> Result<Vec<u8>, BacktestError> {
+        let orders = self
+            .orders
+            .borrow()
+            .values()
+            .map(|order| Ok((order.clone(), self.queue_model.save_queue_pos(order)?)))
+            .collect::<Result<Vec<_>, BacktestError>>()?;
+        encode(&ExchangeState {
+            orders,
+            state_values: self.state.state_values.clone(),
+        })
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), BacktestError> {
+        let state: ExchangeState = decode(state)?;
+        self.state.state_values = state.state_values;
+
+        let mut orders = self.orders.borrow_mut();
+        orders.clear();
+        self.buy_orders.clear();
+        self.sell_orders.clear();
+        self.filled_orders.clear();
+        for (mut order, q) in state.orders {
+            self.queue_model.load_queue_pos(&mut order, &q)?;
+            let price_orders = if order.side == Side::Buy {
+                &mut self.buy_orders
+            } else {
+                &mut self.sell_orders
+            };
+            price_orders
+                .entry(order.price_tick)
+                .or_default()
+                .insert(order.order_id);
+            orders.insert(order.order_id, order);
+        }
+        Ok(())
+    }
+
+    fn market_depth(&self) -> Option<&dyn Any> {
+        Some(&self.depth)
+    }
+
+    fn market_depth_mut(&mut self) -> Option<&mut dyn Any> {
+        Some(&mut self.depth)
+    }
 }
@@ -13,17 +13,19 @@ use crate::{
     types::{Bot, Recorder},
 };
 
+/// The state values of an asset recorded at a timestamp. The trading statistics are cumulative.
 #[repr(C)]
-#[derive(NpyDTyped)]
-struct Record {
-    timestamp: i64,
-    price: f64,
-    position: f64,
-    balance: f64,
-    fee: f64,
-    num_trades: i64,
-    trading_volume: f64,
-    trading_value: f64,
+#[derive(NpyDTyped, Clone, Debug)]
+pub struct Record {
+    pub timestamp: i64,
+    /// The mid-price at the timestamp.
+    pub price: f64,
+    pub position: f64,
+    pub balance: f64,
+    pub fee: f64,
+    pub num_trades: i64,
+    pub trading_volume: f64,
+    pub trading_value: f64,
 }
 
 unsafe impl POD for Record {}
@@ -42,6 +44,9 @@ impl Recorder for BacktestRecorder {
         MD: MarketDepth,
         I: Bot<MD>,
     {
+        if hbt.is_warming_up() {
+            return Ok(());
+        }
         let timestamp = hbt.current_timestamp();
         for asset_no in 0..hbt.num_assets() {
             let depth = hbt.depth(asset_no);
@@ -81,6 +86,16 @@ impl BacktestRecorder {
         }
     }
 
+    /// Returns the number of assets.
+    pub fn num_assets(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns the records of the asset.
+    pub fn records(&self, asset_no: usize) -> &[Record] {
+        &self.values[asset_no]
+    }
+
     /// Saves record data into a CSV file at the specified path. It creates a separate CSV file for
     /// each asset, with the filename `{prefix}_{asset_no}.csv`.
     /// The columns are `timestamp`, `mid`, `balance`, `position`, `fee`, `trade_num`,
@@ -0,0 +1,572 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::{
+    assettype::AssetType,
+    recorder::{BacktestRecorder, Record},
+};
+
+const NANOS_PER_HOUR: i64 = 60 * 60 * 1_000_000_000;
+const NANOS_PER_DAY: i64 = 24 * NANOS_PER_HOUR;
+
+/// Partitioning of the period for which the statistics are additionally computed.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Partition {
+    /// Computes the statistics for the entire period only.
+    None,
+    /// Splits the period by UTC hour.
+    Hourly,
+    /// Splits the period by UTC day.
+    Daily,
+    /// Splits the period by UTC month.
+    Monthly,
+}
+
+/// The performance metrics for a period.
+///
+/// If the book size is set, [`ret`](Self::ret), [`annual_ret`](Self::annual_ret),
+/// [`max_drawdown`](Self::max_drawdown), [`trading_value`](Self::trading_value), and
+/// [`daily_trading_value`](Self::daily_trading_value) are divided by the book size to express
+/// them as a ratio of the book size; otherwise, they are in raw units.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Summary {
+    /// The first timestamp of the period.
+    pub start: i64,
+    /// The last timestamp of the period.
+    pub end: i64,
+    /// Sharpe ratio without considering a benchmark.
+    pub sr: f64,
+    /// Sortino ratio without considering a benchmark.
+    pub sortino: f64,
+    /// Return.
+    pub ret: f64,
+    /// Annualised return.
+    pub annual_ret: f64,
+    /// Maximum drawdown.
+    pub max_drawdown: f64,
+    /// Return over maximum drawdown.
+    pub return_over_mdd: f64,
+    /// Return over trading value, which represents the profit made per unit of trading value.
+    pub return_over_trade: f64,
+    pub num_trades: i64,
+    pub daily_num_trades: f64,
+    /// The number of shares or contracts traded.
+    pub trading_volume: f64,
+    pub daily_trading_volume: f64,
+    /// Trading value, or turnover if the book size is set.
+    pub trading_value: f64,
+    /// Daily trading value, or daily turnover if the book size is set.
+    pub daily_trading_value: f64,
+    pub max_position_value: f64,
+    pub mean_position_value: f64,
+    pub median_position_value: f64,
+    /// Maximum open position value divided by the book size, only available when the book size is
+    /// set.
+    pub max_leverage: Option<f64>,
+}
+
+/// The statistics of an asset, or of the aggregate of all assets.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Stats {
+    /// The statistics for the entire period.
+    pub entire: Summary,
+    /// The statistics for each partition of the period.
+    pub splits: Vec<Summary>,
+}
+
+/// The statistics of a backtest.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BacktestStats {
+    /// The statistics of each asset.
+    pub assets: Vec<Stats>,
+    /// The statistics of the portfolio combining all assets.
+    pub aggregate: Stats,
+}
+
+/// A resampled record. Trading statistics are the values accrued during the sampling interval
+/// instead of the cumulative values.
+#[derive(Clone, Debug)]
+struct Sample {
+    timestamp: i64,
+    equity_wo_fee: f64,
+    fee: f64,
+    position_value: f64,
+    num_trades: i64,
+    trading_volume: f64,
+    trading_value: f64,
+}
+
+impl Sample {
+    fn equity(&self) -> f64 {
+        self.equity_wo_fee - self.fee
+    }
+}
+
+/// Computes the performance metrics from the records of [`BacktestRecorder`]. This provides the
+/// same metric set as `hftbacktest.stats` in Python.
+///
+/// All timestamps are expected to be in nanoseconds.
+///
+/// **Example**
+/// ```
+/// use hftbacktest::backtest::{
+///     assettype::LinearAsset,
+///     recorder::Record,
+///     stats::{Partition, StatsCalculator},
+/// };
+///
+/// // The records of an asset, usually obtained by `BacktestRecorder::records`. `compute` computes
+/// // the metrics of all assets in a `BacktestRecorder` at once.
+/// let records: Vec<Record> = (0..1_000)
+///     .map(|i| Record {
+///         timestamp: i * 1_000_000_000,
+///         price: 100.0 + (i % 10) as f64,
+///         position: 1.0,
+///         balance: -100.0,
+///         fee: 0.0,
+///         num_trades: 1,
+///         trading_volume: 1.0,
+///         trading_value: 100.0,
+///     })
+///     .collect();
+///
+/// let stats = StatsCalculator::new()
+///     .resample(10_000_000_000)
+///     .partition(Partition::Daily)
+///     .book_size(10_000.0)
+///     .compute_records(&records, &LinearAsset::new(1.0));
+/// println!("{:?}", stats.entire);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StatsCalculator {
+    interval: i64,
+    partition: Partition,
+    book_size: Option<f64>,
+    trading_days_per_year: f64,
+}
+
+impl Default for StatsCalculator {
+    fn default() -> Self {
+        Self {
+            interval: 10_000_000_000,
+            partition: Partition::None,
+            book_size: None,
+            trading_days_per_year: 252.0,
+        }
+    }
+}
+
+impl StatsCalculator {
+    /// Constructs an instance of `StatsCalculator`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the resampling interval in nanoseconds for downsampling the records. This affects the
+    /// metrics related to the sampling interval, such as the Sharpe ratio. `0` disables resampling.
+    /// The default value is `10_000_000_000`, which is 10 seconds.
+    pub fn resample(self, interval: i64) -> Self {
+        Self { interval, ..self }
+    }
+
+    /// Sets the partitioning of the period. The default value is [`Partition::None`].
+    pub fn partition(self, partition: Partition) -> Self {
+        Self { partition, ..self }
+    }
+
+    /// Sets the book size, or capital allocation.
+    pub fn book_size(self, book_size: f64) -> Self {
+        Self {
+            book_size: Some(book_size),
+            ..self
+        }
+    }
+
+    /// Sets the number of trading days per year to annualise. The default value is `252`, which is
+    /// common in trad-fi; `365` can be used for crypto markets, which run 24/7.
+    pub fn trading_days_per_year(self, trading_days_per_year: f64) -> Self {
+        Self {
+            trading_days_per_year,
+            ..self
+        }
+    }
+
+    /// Computes the statistics of all assets recorded by the recorder and of their aggregate.
+    ///
+    /// * `recorder` - The recorder containing the records.
+    /// * `asset_types` - The asset type of each asset, used to compute the equity and the position
+    ///   value.
+    pub fn compute(
+        &self,
+        recorder: &BacktestRecorder,
+        asset_types: &[&dyn AssetType],
+    ) -> BacktestStats {
+        assert_eq!(
+            recorder.num_assets(),
+            asset_types.len(),
+            "the number of asset types must match the number of assets"
+        );
+        let samples: Vec<_> = asset_types
+            .iter()
+            .enumerate()
+            .map(|(asset_no, asset_type)| self.samples(recorder.records(asset_no), *asset_type))
+            .collect();
+        let aggregate = aggregate(&samples);
+        BacktestStats {
+            assets: samples
+                .iter()
+                .map(|samples| self.compute_samples(samples))
+                .collect(),
+            aggregate: self.compute_samples(&aggregate),
+        }
+    }
+
+    /// Computes the statistics of an asset from its records.
+    pub fn compute_records<AT>(&self, records: &[Record], asset_type: &AT) -> Stats
+    where
+        AT: AssetType + ?Sized,
+    {
+        self.compute_samples(&self.samples(records, asset_type))
+    }
+
+    fn samples<AT>(&self, records: &[Record], asset_type: &AT) -> Vec<Sample>
+    where
+        AT: AssetType + ?Sized,
+    {
+        let mut samples: Vec<Sample> = Vec::new();
+        let mut prev: Option<&Record> = None;
+        for record in records {
+            let (num_trades, trading_volume, trading_value) = match prev {
+                Some(prev) => (
+                    record.num_trades - prev.num_trades,
+                    record.trading_volume - prev.trading_volume,
+                    record.trading_value - prev.trading_value,
+                ),
+                None => (0, 0.0, 0.0),
+            };
+            prev = Some(record);
+
+            let timestamp = if self.interval > 0 {
+                record.timestamp.div_euclid(self.interval) * self.interval
+            } else {
+                record.timestamp
+            };
+            let sample = Sample {
+                timestamp,
+                equity_wo_fee: asset_type.equity(
+                    record.price,
+                    record.balance,
+                    record.position,
+                    0.0,
+                ),
+                fee: record.fee,
+                position_value: asset_type.amount(record.price, record.position.abs()),
+                num_trades,
+                trading_volume,
+                trading_value,
+            };
+            match samples.last_mut() {
+                // Takes the last state values and the sum of the trading statistics within the
+                // resampling interval.
+                Some(last) if self.interval > 0 && last.timestamp == timestamp => {
+                    last.equity_wo_fee = sample.equity_wo_fee;
+                    last.fee = sample.fee;
+                    last.position_value = sample.position_value;
+                    last.num_trades += sample.num_trades;
+                    last.trading_volume += sample.trading_volume;
+                    last.trading_value += sample.trading_value;
+                }
+                _ => samples.push(sample),
+            }
+        }
+        samples
+    }
+
+    fn compute_samples(&self, samples: &[Sample]) -> Stats {
+        let mut splits = Vec::new();
+        if self.partition != Partition::None {
+            let mut start = 0;
+            for i in 1..=samples.len() {
+                if i == samples.len()
+                    || self.partition_key(samples[i].timestamp)
+                        != self.partition_key(samples[start].timestamp)
+                {
+                    splits.push(self.summary(&samples[start..i]));
+                    start = i;
+                }
+            }
+        }
+        Stats {
+            entire: self.summary(samples),
+            splits,
+        }
+    }
+
+    fn partition_key(&self, timestamp: i64) -> i64 {
+        match self.partition {
+            Partition::None => 0,
+            Partition::Hourly => timestamp.div_euclid(NANOS_PER_HOUR),
+            Partition::Daily => timestamp.div_euclid(NANOS_PER_DAY),
+            Partition::Monthly => {
+                let (year, month) = year_month(timestamp.div_euclid(NANOS_PER_DAY));
+                year * 12 + month
+            }
+        }
+    }
+
+    fn summary(&self, samples: &[Sample]) -> Summary {
+        let start = samples.first().map(|s| s.timestamp).unwrap_or(0);
+        let end = samples.last().map(|s| s.timestamp).unwrap_or(0);
+        let total_days = (end - start) as f64 / NANOS_PER_DAY as f64;
+
+        let equity: Vec<f64> = samples
+            .iter()
+            .map(|s| s.equity())
+            .filter(|v| !v.is_nan())
+            .collect();
+        let ret = match (equity.first(), equity.last()) {
+            (Some(first), Some(last)) => last - first,
+            _ => f64::NAN,
+        };
+
+        let pnl: Vec<f64> = samples
+            .windows(2)
+            .map(|w| w[1].equity() - w[0].equity())
+            .filter(|v| !v.is_nan())
+            .collect();
+        // The sampling interval is the average over the period, so that an irregular interval,
+        // such as a duplicate record, doesn't skew it.
+        let c = if samples.len() >= 2 && end > start {
+            let interval = (end - start) as f64 / (samples.len() - 1) as f64;
+            let samples_per_day = NANOS_PER_DAY as f64 / interval;
+            (samples_per_day * self.trading_days_per_year).sqrt()
+        } else {
+            f64::NAN
+        };
+        let pnl_mean = mean(&pnl);
+        let downside_risk =
+            mean(&pnl.iter().map(|v| v.min(0.0).powi(2)).collect::<Vec<_>>()).sqrt();
+        let sr = pnl_mean / std(&pnl) * c;
+        let sortino = pnl_mean / downside_risk * c;
+
+        let mut max_equity = f64::NAN;
+        let mut max_drawdown: f64 = 0.0;
+        for &v in &equity {
+            max_equity = max_equity.max(v);
+            max_drawdown = max_drawdown.max(max_equity - v);
+        }
+
+        let num_trades: i64 = samples.iter().map(|s| s.num_trades).sum();
+        let trading_volume: f64 = samples.iter().map(|s| s.trading_volume).sum();
+        let trading_value: f64 = samples.iter().map(|s| s.trading_value).sum();
+
+        let mut position_value: Vec<f64> = samples
+            .iter()
+            .map(|s| s.position_value)
+            .filter(|v| !v.is_nan())
+            .collect();
+        position_value.sort_by(|a, b| a.total_cmp(b));
+        let max_position_value = position_value.last().copied().unwrap_or(f64::NAN);
+
+        let book_size = self.book_size.unwrap_or(1.0);
+        Summary {
+            start,
+            end,
+            sr,
+            sortino,
+            ret: ret / book_size,
+            annual_ret: ret / book_size / total_days * self.trading_days_per_year,
+            max_drawdown: max_drawdown / book_size,
+            return_over_mdd: ret / max_drawdown,
+            return_over_trade: ret / trading_value,
+            num_trades,
+            daily_num_trades: num_trades as f64 / total_days,
+            trading_volume,
+            daily_trading_volume: trading_volume / total_days,
+            trading_value: trading_value / book_size,
+            daily_trading_value: trading_value / book_size / total_days,
+            max_position_value,
+            mean_position_value: mean(&position_value),
+            median_position_value: median(&position_value),
+            max_leverage: self
+                .book_size
+                .map(|book_size| max_position_value / book_size),
+        }
+    }
+}
+
+/// Combines the samples of all assets by timestamp. The state values of an asset without a sample
+/// at a timestamp are carried forward from its previous sample.
+fn aggregate(samples: &[Vec<Sample>]) -> Vec<Sample> {
+    let timestamps: BTreeSet<i64> = samples
+        .iter()
+        .flat_map(|samples| samples.iter().map(|s| s.timestamp))
+        .collect();
+    let mut cursors = vec![0; samples.len()];
+    timestamps
+        .into_iter()
+        .map(|timestamp| {
+            let mut combined = Sample {
+                timestamp,
+                equity_wo_fee: 0.0,
+                fee: 0.0,
+                position_value: 0.0,
+                num_trades: 0,
+                trading_volume: 0.0,
+                trading_value: 0.0,
+            };
+            for (samples, cursor) in samples.iter().zip(cursors.iter_mut()) {
+                if *cursor < samples.len() && samples[*cursor].timestamp == timestamp {
+                    let s = &samples[*cursor];
+                    combined.num_trades += s.num_trades;
+                    combined.trading_volume += s.trading_volume;
+                    combined.trading_value += s.trading_value;
+                    *cursor += 1;
+                }
+                if *cursor > 0 {
+                    let s = &samples[*cursor - 1];
+                    combined.equity_wo_fee += s.equity_wo_fee;
+                    combined.fee += s.fee;
+                    combined.position_value += s.position_value;
+                }
+            }
+            combined
+        })
+        .collect()
+}
+
+/// Converts the days since the Unix epoch into the year and the month of the civil calendar.
+fn year_month(days: i64) -> (i64, i64) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month)
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample standard deviation.
+fn std(values: &[f64]) -> f64 {
+    let mean = mean(values);
+    let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() as f64 - 1.0);
+    var.sqrt()
+}
+
+/// Median of the sorted values.
+fn median(sorted: &[f64]) -> f64 {
+    let len = sorted.len();
+    if len == 0 {
+        f64::NAN
+    } else if len % 2 == 1 {
+        sorted[len / 2]
+    } else {
+        (sorted[len / 2 - 1] + sorted[len / 2]) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::assettype::LinearAsset;
+
+    fn record(timestamp: i64, price: f64, position: f64, balance: f64, num_trades: i64) -> Record {
+        Record {
+            timestamp,
+            price,
+            position,
+            balance,
+            fee: 0.0,
+            num_trades,
+            trading_volume: num_trades as f64,
+            trading_value: num_trades as f64 * 100.0,
+        }
+    }
+
+    #[test]
+    fn test_compute_records() {
+        let sec = 1_000_000_000;
+        let records = [
+            record(0, 100.0, 0.0, 0.0, 0),
+            record(sec, 100.0, 1.0, -100.0, 1),
+            record(5 * sec, 101.0, 1.0, -100.0, 1),
+            record(10 * sec, 99.0, 1.0, -100.0, 1),
+            record(20 * sec, 102.0, 0.0, 2.0, 2),
+        ];
+        let stats = StatsCalculator::new()
+            .resample(10 * sec)
+            .book_size(1000.0)
+            .compute_records(&records, &LinearAsset::new(1.0));
+        let summary = stats.entire;
+
+        assert_eq!(summary.start, 0);
+        assert_eq!(summary.end, 20 * sec);
+        // equity: 1.0 (at 5s), -1.0 (at 10s), 2.0 (at 20s)
+        assert!((summary.ret - 0.001).abs() < 1e-12);
+        assert!((summary.max_drawdown - 0.002).abs() < 1e-12);
+        assert!((summary.return_over_mdd - 0.5).abs() < 1e-12);
+        assert_eq!(summary.num_trades, 2);
+        assert!((summary.trading_volume - 2.0).abs() < 1e-12);
+        assert!((summary.trading_value - 0.2).abs() < 1e-12);
+        assert!((summary.return_over_trade - 0.005).abs() < 1e-12);
+        assert!((summary.max_position_value - 101.0).abs() < 1e-12);
+        assert!((summary.max_leverage.unwrap() - 0.101).abs() < 1e-12);
+        assert!(stats.splits.is_empty());
+    }
+
+    #[test]
+    fn test_annualization() {
+        let sec = 1_000_000_000;
+        let asset = LinearAsset::new(1.0);
+        // The duplicate first record doesn't make the interval zero.
+        let records = [
+            record(0, 100.0, 1.0, -100.0, 0),
+            record(0, 100.0, 1.0, -100.0, 0),
+            record(sec, 101.0, 1.0, -100.0, 0),
+            record(2 * sec, 103.0, 1.0, -100.0, 0),
+        ];
+        let stats = StatsCalculator::new()
+            .resample(0)
+            .compute_records(&records, &asset);
+        assert!(stats.entire.sr.is_finite());
+        assert!(stats.entire.sr > 0.0);
+
+        // The period has no span.
+        let stats = StatsCalculator::new()
+            .resample(0)
+            .compute_records(&records[..2], &asset);
+        assert!(stats.entire.sr.is_nan());
+        assert!(stats.entire.sortino.is_nan());
+    }
+
+    #[test]
+    fn test_partition() {
+        let records: Vec<_> = (0..48)
+            .map(|hour| record(hour * NANOS_PER_HOUR, 100.0, 0.0, hour as f64, 0))
+            .collect();
+        let stats = StatsCalculator::new()
+            .partition(Partition::Daily)
+            .compute_records(&records, &LinearAsset::new(1.0));
+        assert_eq!(stats.splits.len(), 2);
+        assert_eq!(stats.splits[1].start, 24 * NANOS_PER_HOUR);
+        assert!((stats.splits[0].ret - 23.0).abs() < 1e-12);
+        assert!((stats.entire.ret - 47.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_year_month() {
+        assert_eq!(year_month(0), (1970, 1));
+        assert_eq!(year_month(31), (1970, 2));
+        // 2024-02-29
+        assert_eq!(year_month(19782), (2024, 2));
+        // 2024-03-01
+        assert_eq!(year_month(19783), (2024, 3));
+    }
+}
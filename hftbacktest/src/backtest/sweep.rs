@@ -0,0 +1,490 @@
+use std::{
+    any::Any,
+    fmt::Display,
+    fs::File,
+    io::{BufWriter, Error, Write},
+    num::NonZeroUsize,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::{
+        Mutex,
+        PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::backtest::stats::Summary;
+
+/// A combination of parameter values for a single run.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Params(Vec<(String, f64)>);
+
+impl Params {
+    /// Returns the value of the parameter.
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.0
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Returns an iterator over the names and the values of the parameters.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, f64)> {
+        self.0.iter().map(|(name, value)| (name.as_str(), *value))
+    }
+}
+
+/// A grid of parameter values. Each run of the sweep is given one combination of the Cartesian
+/// product of the values.
+#[derive(Clone, Debug, Default)]
+pub struct ParamGrid {
+    params: Vec<(String, Vec<f64>)>,
+}
+
+impl ParamGrid {
+    /// Constructs an empty `ParamGrid`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Adds a parameter with the values to try.
+    pub fn param(mut self, name: &str, values: Vec<f64>) -> Self {
+        self.params.push((name.to_string(), values));
+        self
+    }
+
+    /// Returns the number of the combinations.
+    pub fn len(&self) -> usize {
+        if self.params.is_empty() {
+            0
+        } else {
+            self.params.iter().map(|(_, values)| values.len()).product()
+        }
+    }
+
+    /// Returns `true` if there are no combinations.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns all combinations of the parameter values, where the last added parameter varies
+    /// the fastest.
+    pub fn combinations(&self) -> Vec<Params> {
+        let len = self.len();
+        let mut combinations = Vec::with_capacity(len);
+        for i in 0..len {
+            let mut rem = i;
+            let mut params = Vec::with_capacity(self.params.len());
+            for (name, values) in self.params.iter().rev() {
+                params.push((name.clone(), values[rem % values.len()]));
+                rem /= values.len();
+            }
+            params.reverse();
+            combinations.push(Params(params));
+        }
+        combinations
+    }
+}
+
+/// The result of a single run.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SweepRow {
+    pub params: Params,
+    /// The summary statistics of the run, or the error message if the run fails.
+    pub result: Result<Summary, String>,
+}
+
+/// The results of all runs of the sweep, in the order of the parameter combinations.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SweepResult {
+    pub rows: Vec<SweepRow>,
+}
+
+impl SweepResult {
+    /// Returns the successful run that has the largest value of the given key, such as
+    /// `|summary| summary.sr`. Runs whose key is `NaN` are ignored.
+    pub fn best_by<F>(&self, key: F) -> Option<(&Params, &Summary)>
+    where
+        F: Fn(&Summary) -> f64,
+    {
+        self.rows
+            .iter()
+            .filter_map(|row| row.result.as_ref().ok().map(|s| (&row.params, s)))
+            .filter(|(_, s)| !key(s).is_nan())
+            .max_by(|(_, a), (_, b)| key(a).total_cmp(&key(b)))
+    }
+
+    /// Saves the results into a CSV file at the specified path. The columns are the parameter
+    /// names followed by the fields of [`Summary`] and `error`.
+    pub fn to_csv<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = BufWriter::new(File::create(path)?);
+        if let Some(row) = self.rows.first() {
+            for (name, _) in row.params.iter() {
+                write!(file, "{name},")?;
+            }
+        }
+        writeln!(
+            file,
+            "start,end,sr,sortino,ret,annual_ret,max_drawdown,return_over_mdd,return_over_trade,\
+            num_trades,daily_num_trades,trading_volume,daily_trading_volume,trading_value,\
+            daily_trading_value,max_position_value,mean_position_value,median_position_value,\
+            max_leverage,error"
+        )?;
+        for row in &self.rows {
+            for (_, value) in row.params.iter() {
+                write!(file, "{value},")?;
+            }
+            match &row.result {
+                Ok(s) => writeln!(
+                    file,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},",
+                    s.start,
+                    s.end,
+                    s.sr,
+                    s.sortino,
+                    s.ret,
+                    s.annual_ret,
+                    s.max_drawdown,
+                    s.return_over_mdd,
+                    s.return_over_trade,
+                    s.num_trades,
+                    s.daily_num_trades,
+                    s.trading_volume,
+                    s.daily_trading_volume,
+                    s.trading_value,
+                    s.daily_trading_value,
+                    s.max_position_value,
+                    s.mean_position_value,
+                    s.median_position_value,
+                    s.max_leverage.map(|v| v.to_string()).unwrap_or_default(),
+                )?,
+                Err(error) => writeln!(
+                    file,
+                    ",,,,,,,,,,,,,,,,,,,\"{}\"",
+                    error.replace('"', "\"\"")
+                )?,
+            }
+        }
+        file.flush()
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic");
+    format!("panicked: {message}")
+}
+
+/// Runs backtests for every combination of a [`ParamGrid`] in parallel with a bounded number of
+/// worker threads.
+///
+/// Each run builds its own backtest within the strategy closure, as a backtest cannot be sent
+/// between threads. To avoid loading the same files in every run, load them once into
+/// [`SharedData`](crate::backtest::data::SharedData) and feed each run with
+/// [`DataSource::Data`](crate::backtest::DataSource::Data) referencing it. A run that modifies its
+/// data, for example with a latency offset, works on its own copy.
+///
+/// A panicking run is recorded as the error of that run only if panics unwind. With
+/// `panic = "abort"`, as in this workspace's release profile, a panic aborts the entire sweep; use
+/// a profile that unwinds to isolate the panicking runs.
+///
+/// **Example**
+/// ```no_run
+/// use hftbacktest::{
+///     backtest::{
+///         Backtest,
+///         DataSource,
+///         ExchangeKind,
+///         L2AssetBuilder,
+///         assettype::LinearAsset,
+///         data::SharedData,
+///         models::{
+///             CommonFees,
+///             ConstantLatency,
+///             PowerProbQueueFunc3,
+///             ProbQueueModel,
+///             TradingValueFeeModel,
+///         },
+///         recorder::BacktestRecorder,
+///         stats::StatsCalculator,
+///         sweep::{ParamGrid, SweepRunner},
+///     },
+///     depth::HashMapMarketDepth,
+///     prelude::{Bot, Event},
+///     types::{ElapseResult, Recorder},
+/// };
+///
+/// let data = SharedData::<Event>::load("btcusdt_20240809.npz")?;
+/// let grid = ParamGrid::new()
+///     .param("half_spread", vec![0.0001, 0.0002])
+///     .param("skew", vec![0.5, 1.0, 2.0]);
+/// let result = SweepRunner::new().num_threads(4).run(&grid, |params| {
+///     let mut hbt = Backtest::builder()
+///         .add_asset(
+///             L2AssetBuilder::default()
+///                 .data(vec![DataSource::Data(data.data())])
+///                 .latency_model(ConstantLatency::new(10_000_000, 10_000_000))
+///                 .asset_type(LinearAsset::new(1.0))
+///                 .fee_model(TradingValueFeeModel::new(CommonFees::new(-0.00005, 0.0007)))
+///                 .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+///                 .exchange(ExchangeKind::NoPartialFillExchange)
+///                 .depth(|| HashMapMarketDepth::new(0.1, 0.001))
+///                 .build()?,
+///         )
+///         .build()?;
+///     let mut recorder = BacktestRecorder::new(&hbt);
+///     let _half_spread = params.get("half_spread").unwrap();
+///     while hbt.elapse(100_000_000)? == ElapseResult::Ok {
+///         // Quotes orders using the parameters here.
+///         recorder.record(&hbt)?;
+///     }
+///     let stats = StatsCalculator::new().compute(&recorder, &[&LinearAsset::new(1.0)]);
+///     Ok::<_, Box<dyn std::error::Error>>(stats.aggregate.entire)
+/// });
+/// result.to_csv("sweep.csv")?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct SweepRunner {
+    num_threads: usize,
+}
+
+impl Default for SweepRunner {
+    fn default() -> Self {
+        Self {
+            num_threads: thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1),
+        }
+    }
+}
+
+impl SweepRunner {
+    /// Constructs an instance of `SweepRunner`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sets the number of worker threads. The default value is the available parallelism.
+    pub fn num_threads(self, num_threads: usize) -> Self {
+        Self {
+            num_threads: num_threads.max(1),
+        }
+    }
+
+    /// Runs the strategy for every combination of the grid and collects the results.
+    ///
+    /// * `grid` - The parameter grid.
+    /// * `strategy` - Runs a backtest with the given parameters and returns its summary
+    ///   statistics. If it panics, the panic is recorded as the error of that run, unless panics
+    ///   abort the process.
+    pub fn run<F, E>(&self, grid: &ParamGrid, strategy: F) -> SweepResult
+    where
+        F: Fn(&Params) -> Result<Summary, E> + Sync,
+        E: Display,
+    {
+        let combinations = grid.combinations();
+        let results: Mutex<Vec<Option<Result<Summary, String>>>> =
+            Mutex::new(vec![None; combinations.len()]);
+        let next = AtomicUsize::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..self.num_threads.min(combinations.len()) {
+                scope.spawn(|| {
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(params) = combinations.get(i) else {
+                            break;
+                        };
+                        // A panicking run is recorded as an error instead of aborting the sweep,
+                        // as long as the panic unwinds.
+                        let result = panic::catch_unwind(AssertUnwindSafe(|| strategy(params)))
+                            .map_err(|payload| panic_message(payload.as_ref()))
+                            .and_then(|result| result.map_err(|err| err.to_string()));
+                        results.lock().unwrap_or_else(PoisonError::into_inner)[i] = Some(result);
+                    }
+                });
+            }
+        });
+
+        SweepResult {
+            rows: combinations
+                .into_iter()
+                .zip(results.into_inner().unwrap_or_else(PoisonError::into_inner))
+                .map(|(params, result)| SweepRow {
+                    params,
+                    result: result.unwrap(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        backtest::{
+            Backtest,
+            BacktestError,
+            DataSource,
+            ExchangeKind::NoPartialFillExchange,
+            L2AssetBuilder,
+            assettype::LinearAsset,
+            data::{Data, SharedData},
+            models::{
+                CommonFees,
+                ConstantLatency,
+                PowerProbQueueFunc3,
+                ProbQueueModel,
+                TradingValueFeeModel,
+            },
+            recorder::BacktestRecorder,
+            stats::StatsCalculator,
+        },
+        depth::HashMapMarketDepth,
+        prelude::{Bot, Event},
+        types::{
+            BUY_EVENT,
+            DEPTH_EVENT,
+            EXCH_EVENT,
+            ElapseResult,
+            LOCAL_EVENT,
+            OrdType,
+            Recorder,
+            SELL_EVENT,
+            TimeInForce,
+        },
+    };
+
+    #[test]
+    fn test_combinations() {
+        let grid = ParamGrid::new()
+            .param("a", vec![1.0, 2.0])
+            .param("b", vec![10.0, 20.0, 30.0]);
+        let combinations = grid.combinations();
+        assert_eq!(grid.len(), 6);
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(combinations[0].get("a"), Some(1.0));
+        assert_eq!(combinations[0].get("b"), Some(10.0));
+        assert_eq!(combinations[4].get("a"), Some(2.0));
+        assert_eq!(combinations[4].get("b"), Some(20.0));
+        assert_eq!(combinations[4].get("c"), None);
+    }
+
+    #[test]
+    fn test_shared_data_copy_on_write() {
+        let events = [Event {
+            ev: EXCH_EVENT | LOCAL_EVENT | DEPTH_EVENT | BUY_EVENT,
+            exch_ts: 1,
+            local_ts: 2,
+            px: 100.0,
+            qty: 1.0,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }];
+        let shared = SharedData::new(Data::from_data(&events)).unwrap();
+        assert_eq!(shared.len(), 1);
+        assert!(!shared.is_empty());
+
+        let mut data = shared.data();
+        data[0].local_ts = 3;
+        data.as_mut_slice()[0].exch_ts = 0;
+        assert_eq!((data[0].exch_ts, data[0].local_ts), (0, 3));
+        // The shared buffer is unaffected.
+        assert_eq!(
+            (shared.data()[0].exch_ts, shared.data()[0].local_ts),
+            (1, 2)
+        );
+
+        assert!(SharedData::new(Data::<Event>::empty()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run() {
+        let sec = 1_000_000_000;
+        let mut events = Vec::new();
+        for i in 0..100 {
+            let px = 100.0 + (i % 10) as f64 * 0.1;
+            for (side, px) in [(BUY_EVENT, px), (SELL_EVENT, px + 0.1)] {
+                events.push(Event {
+                    ev: EXCH_EVENT | LOCAL_EVENT | DEPTH_EVENT | side,
+                    exch_ts: i * sec,
+                    local_ts: i * sec,
+                    px,
+                    qty: 1.0,
+                    order_id: 0,
+                    ival: 0,
+                    fval: 0.0,
+                });
+            }
+        }
+        let data = SharedData::new(Data::from_data(&events)).unwrap();
+
+        let grid = ParamGrid::new().param("qty", vec![1.0, 2.0, 3.0, -1.0, 0.0]);
+        let result = SweepRunner::new().num_threads(2).run(&grid, |params| {
+            let qty = params.get("qty").unwrap();
+            if qty < 0.0 {
+                return Err(BacktestError::InvalidOrderRequest);
+            }
+            if qty == 0.0 {
+                panic!("zero quantity");
+            }
+            let mut hbt = Backtest::builder()
+                .add_asset(
+                    L2AssetBuilder::default()
+                        .data(vec![DataSource::Data(data.data())])
+                        .latency_model(ConstantLatency::new(0, 0))
+                        .asset_type(LinearAsset::new(1.0))
+                        .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                        .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                        .exchange(NoPartialFillExchange)
+                        .depth(|| HashMapMarketDepth::new(0.1, 1.0))
+                        .build()
+                        .unwrap(),
+                )
+                .build()
+                .unwrap();
+            let mut recorder = BacktestRecorder::new(&hbt);
+            hbt.elapse(sec)?;
+            recorder.record(&hbt).unwrap();
+            hbt.submit_buy_order(0, 1, 100.1, qty, TimeInForce::GTC, OrdType::Market, false)?;
+            while hbt.elapse(sec)? == ElapseResult::Ok {
+                recorder.record(&hbt).unwrap();
+            }
+            let stats = StatsCalculator::new()
+                .resample(0)
+                .compute(&recorder, &[&LinearAsset::new(1.0)]);
+            Ok(stats.aggregate.entire)
+        });
+
+        assert_eq!(result.rows.len(), 5);
+        for (row, qty) in result.rows.iter().zip([1.0, 2.0, 3.0]) {
+            assert_eq!(row.params.get("qty"), Some(qty));
+            let summary = row.result.as_ref().unwrap();
+            assert_eq!(summary.num_trades, 1);
+            assert!(summary.max_position_value >= qty * 100.0);
+        }
+        assert!(result.rows[3].result.is_err());
+        assert_eq!(
+            result.rows[4].result.as_ref().unwrap_err(),
+            "panicked: zero quantity"
+        );
+        let (params, _) = result.best_by(|s| s.max_position_value).unwrap();
+        assert_eq!(params.get("qty"), Some(3.0));
+    }
+}
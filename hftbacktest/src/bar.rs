@@ -0,0 +1,495 @@
+use std::{collections::VecDeque, marker::PhantomData, mem};
+
+use crate::{
+    depth::MarketDepth,
+    types::{BUY_EVENT, Bot, ElapseResult, Event, SELL_EVENT},
+};
+
+/// Specifies when a bar closes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BarKind {
+    /// Closes a bar at every multiple of the given interval, so the bars are aligned to the
+    /// interval. The unit should be the same as the data's timestamp unit.
+    Time(i64),
+    /// Closes a bar once it has the given number of trades.
+    Tick(usize),
+    /// Closes a bar once its traded quantity reaches the given volume.
+    Volume(f64),
+    /// Closes a bar once its traded value, the sum of the price times the quantity, reaches the
+    /// given amount.
+    Dollar(f64),
+    /// Closes a bar once it has the given number of market feed events, which include depth
+    /// updates as well as trades.
+    Event(usize),
+}
+
+/// An aggregated bar of the market trades.
+#[derive(Clone, Debug)]
+pub struct Bar {
+    /// For time bars, the start of the interval. Otherwise, the timestamp of the first trade or
+    /// market feed event in the bar.
+    pub open_ts: i64,
+    /// For time bars, the end of the interval. Otherwise, the timestamp of the trade or market
+    /// feed event that closes the bar.
+    pub close_ts: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    /// The volume of the trades initiated by buyers.
+    pub buy_volume: f64,
+    /// The volume of the trades initiated by sellers.
+    pub sell_volume: f64,
+    /// The sum of the price times the quantity of the trades.
+    pub value: f64,
+    pub trade_count: usize,
+    /// The number of market feed events in the bar, counted only if they are fed by
+    /// [`BarAggregator::on_feed`].
+    pub event_count: usize,
+}
+
+impl Bar {
+    fn new(open_ts: i64, close_ts: i64) -> Self {
+        Self {
+            open_ts,
+            close_ts,
+            open: f64::NAN,
+            high: f64::NAN,
+            low: f64::NAN,
+            close: f64::NAN,
+            volume: 0.0,
+            buy_volume: 0.0,
+            sell_volume: 0.0,
+            value: 0.0,
+            trade_count: 0,
+            event_count: 0,
+        }
+    }
+
+    /// Returns the volume-weighted average price of the trades in the bar. If there is no trade,
+    /// it returns [`f64::NAN`].
+    pub fn vwap(&self) -> f64 {
+        if self.volume > 0.0 {
+            self.value / self.volume
+        } else {
+            f64::NAN
+        }
+    }
+}
+
+/// Aggregates the market trades into bars incrementally.
+///
+/// A bar without any trade, which can happen with time bars and event bars, has the previous
+/// bar's close as its prices, or [`f64::NAN`] if there is no previous bar.
+pub struct BarAggregator {
+    kind: BarKind,
+    bar: Bar,
+    started: bool,
+    last_close: f64,
+}
+
+impl BarAggregator {
+    /// Constructs an instance of `BarAggregator`.
+    pub fn new(kind: BarKind) -> Self {
+        match kind {
+            BarKind::Time(interval) => assert!(interval > 0, "interval must be positive"),
+            BarKind::Tick(count) | BarKind::Event(count) => {
+                assert!(count > 0, "count must be positive")
+            }
+            BarKind::Volume(amount) | BarKind::Dollar(amount) => {
+                assert!(amount > 0.0, "amount must be positive")
+            }
+        }
+        Self {
+            kind,
+            bar: Bar::new(0, 0),
+            started: false,
+            last_close: f64::NAN,
+        }
+    }
+
+    /// Returns the kind of the bars.
+    pub fn kind(&self) -> BarKind {
+        self.kind
+    }
+
+    /// Returns the bar that is being aggregated.
+    pub fn current(&self) -> &Bar {
+        &self.bar
+    }
+
+    /// Returns the timestamp at which the current time bar closes. Returns `None` if the bars
+    /// aren't time bars or no bar has been started yet.
+    pub fn next_close_ts(&self) -> Option<i64> {
+        match self.kind {
+            BarKind::Time(_) if self.started => Some(self.bar.close_ts),
+            _ => None,
+        }
+    }
+
+    /// Adds the trade and returns the bar that is closed by it. For time bars, the returned bar
+    /// is the previous one if the trade falls on or after its close.
+    pub fn on_trade(&mut self, trade: &Event) -> Option<Bar> {
+        let timestamp = trade.local_ts;
+        let closed = self.on_time(timestamp);
+        self.start(timestamp);
+
+        let bar = &mut self.bar;
+        if bar.trade_count == 0 {
+            bar.open = trade.px;
+            bar.high = trade.px;
+            bar.low = trade.px;
+        } else {
+            bar.high = bar.high.max(trade.px);
+            bar.low = bar.low.min(trade.px);
+        }
+        bar.close = trade.px;
+        bar.volume += trade.qty;
+        if trade.ev & BUY_EVENT == BUY_EVENT {
+            bar.buy_volume += trade.qty;
+        } else if trade.ev & SELL_EVENT == SELL_EVENT {
+            bar.sell_volume += trade.qty;
+        }
+        bar.value += trade.px * trade.qty;
+        bar.trade_count += 1;
+
+        let full = match self.kind {
+            BarKind::Tick(count) => bar.trade_count >= count,
+            BarKind::Volume(volume) => bar.volume >= volume,
+            BarKind::Dollar(value) => bar.value >= value,
+            BarKind::Time(_) | BarKind::Event(_) => false,
+        };
+        if full {
+            debug_assert!(closed.is_none());
+            Some(self.close(timestamp))
+        } else {
+            closed
+        }
+    }
+
+    /// Counts a market feed event received at the given timestamp and returns the bar that is
+    /// closed by it.
+    pub fn on_feed(&mut self, timestamp: i64) -> Option<Bar> {
+        let closed = self.on_time(timestamp);
+        self.start(timestamp);
+        self.bar.event_count += 1;
+        match self.kind {
+            BarKind::Event(count) if self.bar.event_count >= count => {
+                debug_assert!(closed.is_none());
+                Some(self.close(timestamp))
+            }
+            _ => closed,
+        }
+    }
+
+    /// Advances the time to the given timestamp and returns the time bar that is closed by it.
+    /// If the timestamp skips over several intervals, only the bar that was being aggregated is
+    /// returned, and a new bar starts at the interval containing the timestamp.
+    pub fn on_time(&mut self, timestamp: i64) -> Option<Bar> {
+        let BarKind::Time(interval) = self.kind else {
+            return None;
+        };
+        if !self.started {
+            self.start(timestamp);
+            return None;
+        }
+        if timestamp < self.bar.close_ts {
+            return None;
+        }
+        let close_ts = self.bar.close_ts;
+        let bar = self.close(close_ts);
+        let open_ts = timestamp.div_euclid(interval) * interval;
+        self.bar = Bar::new(open_ts, open_ts + interval);
+        self.started = true;
+        Some(bar)
+    }
+
+    fn start(&mut self, timestamp: i64) {
+        if self.started {
+            return;
+        }
+        self.bar = match self.kind {
+            BarKind::Time(interval) => {
+                let open_ts = timestamp.div_euclid(interval) * interval;
+                Bar::new(open_ts, open_ts + interval)
+            }
+            _ => Bar::new(timestamp, timestamp),
+        };
+        self.started = true;
+    }
+
+    fn close(&mut self, close_ts: i64) -> Bar {
+        let mut bar = mem::replace(&mut self.bar, Bar::new(0, 0));
+        self.started = false;
+        bar.close_ts = close_ts;
+        if bar.trade_count == 0 {
+            bar.open = self.last_close;
+            bar.high = self.last_close;
+            bar.low = self.last_close;
+            bar.close = self.last_close;
+        } else {
+            self.last_close = bar.close;
+        }
+        bar
+    }
+}
+
+/// Wraps a [`Bot`] to run a strategy on bars, such as volume bars, instead of the wall-clock time,
+/// in both backtesting and live trading.
+///
+/// The trades are read from [`Bot::last_trades`], so the last trades buffer should be enabled,
+/// and the buffer is cleared once the trades are aggregated. Trades that are evicted from the
+/// buffer while the bot is advanced directly through [`bot_mut`](Self::bot_mut) are not
+/// aggregated. For event bars, every market feed received by the bot is counted, including the
+/// ones of the other assets.
+pub struct BarBot<MD, B> {
+    bot: B,
+    asset_no: usize,
+    aggregator: BarAggregator,
+    closed: VecDeque<Bar>,
+    timeout: i64,
+    _md_marker: PhantomData<MD>,
+}
+
+impl<MD, B> BarBot<MD, B>
+where
+    MD: MarketDepth,
+    B: Bot<MD>,
+{
+    /// Constructs an instance of `BarBot` that aggregates the trades of the given asset.
+    pub fn new(bot: B, asset_no: usize, kind: BarKind) -> Self {
+        Self {
+            bot,
+            asset_no,
+            aggregator: BarAggregator::new(kind),
+            closed: VecDeque::new(),
+            timeout: 60_000_000_000,
+            _md_marker: Default::default(),
+        }
+    }
+
+    /// Sets the maximum duration to wait for the next market feed at once. The default is 60
+    /// seconds in nanoseconds.
+    pub fn timeout(self, timeout: i64) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Returns the wrapped bot.
+    pub fn bot(&self) -> &B {
+        &self.bot
+    }
+
+    /// Returns the wrapped bot as mutable, which can be used to submit orders.
+    pub fn bot_mut(&mut self) -> &mut B {
+        &mut self.bot
+    }
+
+    /// Returns the wrapped bot, discarding the bar that is being aggregated.
+    pub fn into_inner(self) -> B {
+        self.bot
+    }
+
+    /// Returns the bar aggregator.
+    pub fn aggregator(&self) -> &BarAggregator {
+        &self.aggregator
+    }
+
+    /// Advances the bot until the next bar closes and returns it. Returns `None` if the end of the
+    /// data is reached first, in which case the bar that is being aggregated is available through
+    /// [`aggregator`](Self::aggregator).
+    pub fn next_bar(&mut self) -> Result<Option<Bar>, B::Error> {
+        loop {
+            if let Some(bar) = self.closed.pop_front() {
+                return Ok(Some(bar));
+            }
+
+            let timeout = match self.aggregator.next_close_ts() {
+                Some(close_ts) => (close_ts - self.bot.current_timestamp()).clamp(1, self.timeout),
+                None => self.timeout,
+            };
+            let result = self.bot.wait_next_feed(false, timeout)?;
+            let timestamp = self.bot.current_timestamp();
+
+            for trade in self.bot.last_trades(self.asset_no) {
+                if let Some(bar) = self.aggregator.on_trade(trade) {
+                    self.closed.push_back(bar);
+                }
+            }
+            self.bot.clear_last_trades(Some(self.asset_no));
+            // Only event bars count the market feeds; otherwise, the feed that delivers the closing
+            // trade would be counted into the next bar.
+            if result == ElapseResult::MarketFeed
+                && matches!(self.aggregator.kind(), BarKind::Event(_))
+                && let Some(bar) = self.aggregator.on_feed(timestamp)
+            {
+                self.closed.push_back(bar);
+            }
+            if let Some(bar) = self.aggregator.on_time(timestamp) {
+                self.closed.push_back(bar);
+            }
+
+            if result == ElapseResult::EndOfData && self.closed.is_empty() {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bar::{BarAggregator, BarKind},
+        types::{BUY_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT, TRADE_EVENT},
+    };
+
+    fn trade(side: u64, ts: i64, px: f64, qty: f64) -> Event {
+        Event {
+            ev: EXCH_EVENT | LOCAL_EVENT | TRADE_EVENT | side,
+            exch_ts: ts,
+            local_ts: ts,
+            px,
+            qty,
+            order_id: 0,
+            ival: 0,
+            fval: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_volume_bar() {
+        let mut agg = BarAggregator::new(BarKind::Volume(3.0));
+        assert!(agg.on_trade(&trade(BUY_EVENT, 1, 100.0, 1.0)).is_none());
+        assert!(agg.on_trade(&trade(SELL_EVENT, 2, 99.0, 1.0)).is_none());
+        let bar = agg.on_trade(&trade(BUY_EVENT, 3, 102.0, 2.0)).unwrap();
+        assert_eq!(bar.open_ts, 1);
+        assert_eq!(bar.close_ts, 3);
+        assert_eq!(
+            (bar.open, bar.high, bar.low, bar.close),
+            (100.0, 102.0, 99.0, 102.0)
+        );
+        assert_eq!(bar.volume, 4.0);
+        assert_eq!(bar.buy_volume, 3.0);
+        assert_eq!(bar.sell_volume, 1.0);
+        assert_eq!(bar.trade_count, 3);
+        assert_eq!(bar.vwap(), 100.75);
+
+        assert!(agg.on_trade(&trade(BUY_EVENT, 4, 101.0, 1.0)).is_none());
+        assert_eq!(agg.current().open_ts, 4);
+        assert_eq!(agg.current().volume, 1.0);
+    }
+
+    #[test]
+    fn test_tick_and_dollar_bar() {
+        let mut agg = BarAggregator::new(BarKind::Tick(2));
+        assert!(agg.on_trade(&trade(BUY_EVENT, 1, 100.0, 1.0)).is_none());
+        assert_eq!(
+            agg.on_trade(&trade(BUY_EVENT, 2, 100.0, 1.0))
+                .unwrap()
+                .trade_count,
+            2
+        );
+
+        let mut agg = BarAggregator::new(BarKind::Dollar(250.0));
+        assert!(agg.on_trade(&trade(BUY_EVENT, 1, 100.0, 1.0)).is_none());
+        assert!(agg.on_trade(&trade(BUY_EVENT, 2, 100.0, 1.0)).is_none());
+        assert_eq!(
+            agg.on_trade(&trade(BUY_EVENT, 3, 100.0, 1.0))
+                .unwrap()
+                .value,
+            300.0
+        );
+    }
+
+    #[test]
+    fn test_time_bar() {
+        let mut agg = BarAggregator::new(BarKind::Time(10));
+        assert!(agg.on_time(3).is_none());
+        assert_eq!(agg.next_close_ts(), Some(10));
+        assert!(agg.on_trade(&trade(BUY_EVENT, 5, 100.0, 1.0)).is_none());
+        assert!(agg.on_trade(&trade(SELL_EVENT, 9, 101.0, 1.0)).is_none());
+
+        // The trade at the close belongs to the next bar.
+        let bar = agg.on_trade(&trade(BUY_EVENT, 10, 102.0, 1.0)).unwrap();
+        assert_eq!((bar.open_ts, bar.close_ts), (0, 10));
+        assert_eq!((bar.open, bar.close, bar.volume), (100.0, 101.0, 2.0));
+        assert_eq!(agg.current().trade_count, 1);
+
+        let bar = agg.on_time(25).unwrap();
+        assert_eq!((bar.open_ts, bar.close_ts, bar.close), (10, 20, 102.0));
+        assert_eq!(agg.next_close_ts(), Some(30));
+
+        // An empty bar carries the previous close.
+        let bar = agg.on_time(30).unwrap();
+        assert_eq!((bar.open_ts, bar.close_ts), (20, 30));
+        assert_eq!((bar.open, bar.close, bar.volume), (102.0, 102.0, 0.0));
+    }
+
+    #[test]
+    fn test_event_bar() {
+        let mut agg = BarAggregator::new(BarKind::Event(3));
+        assert!(agg.on_feed(1).is_none());
+        assert!(agg.on_trade(&trade(BUY_EVENT, 2, 100.0, 1.0)).is_none());
+        assert!(agg.on_feed(2).is_none());
+        let bar = agg.on_feed(3).unwrap();
+        assert_eq!((bar.open_ts, bar.close_ts), (1, 3));
+        assert_eq!((bar.event_count, bar.trade_count), (3, 1));
+    }
+
+    #[cfg(feature = "backtest")]
+    #[test]
+    fn test_bar_bot() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::{
+            backtest::{
+                Backtest,
+                DataSource,
+                ExchangeKind::NoPartialFillExchange,
+                L2AssetBuilder,
+                assettype::LinearAsset,
+                data::Data,
+                models::{
+                    CommonFees,
+                    ConstantLatency,
+                    PowerProbQueueFunc3,
+                    ProbQueueModel,
+                    TradingValueFeeModel,
+                },
+            },
+            bar::BarBot,
+            depth::HashMapMarketDepth,
+        };
+
+        let data = Data::from_data(&[
+            trade(BUY_EVENT, 100, 100.0, 1.0),
+            trade(SELL_EVENT, 200, 99.0, 1.0),
+            trade(BUY_EVENT, 300, 101.0, 1.5),
+            trade(BUY_EVENT, 400, 102.0, 0.5),
+            trade(SELL_EVENT, 500, 100.0, 1.0),
+        ]);
+        let hbt = Backtest::builder()
+            .add_asset(
+                L2AssetBuilder::default()
+                    .data(vec![DataSource::Data(data)])
+                    .latency_model(ConstantLatency::new(50, 50))
+                    .asset_type(LinearAsset::new(1.0))
+                    .fee_model(TradingValueFeeModel::new(CommonFees::new(0.0, 0.0)))
+                    .queue_model(ProbQueueModel::new(PowerProbQueueFunc3::new(3.0)))
+                    .exchange(NoPartialFillExchange)
+                    .depth(|| HashMapMarketDepth::new(0.1, 1.0))
+                    .last_trades_capacity(10)
+                    .build()?,
+            )
+            .build()?;
+
+        let mut bot = BarBot::new(hbt, 0, BarKind::Volume(2.0));
+        let bar = bot.next_bar()?.unwrap();
+        assert_eq!((bar.open_ts, bar.close_ts, bar.volume), (100, 200, 2.0));
+        assert_eq!((bar.buy_volume, bar.sell_volume), (1.0, 1.0));
+        let bar = bot.next_bar()?.unwrap();
+        assert_eq!((bar.open_ts, bar.close_ts, bar.volume), (300, 400, 2.0));
+        assert_eq!(bar.vwap(), 101.25);
+        assert!(bot.next_bar()?.is_none());
+        assert_eq!(bot.aggregator().current().volume, 1.0);
+        Ok(())
+    }
+}
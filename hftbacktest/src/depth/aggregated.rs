@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+
+use crate::{
+    backtest::data::Data,
+    depth::{ApplySnapshot, DepthAnalytics, INVALID_MAX, INVALID_MIN, L2MarketDepth, MarketDepth},
+    types::{BUY_EVENT, Event, SELL_EVENT, Side},
+};
+
+#[derive(Clone, Copy, Default)]
+struct Bucket {
+    qty: f64,
+    // The number of non-empty price levels in the bucket. The quantity is reset once this becomes
+    // zero so that floating-point errors don't accumulate across the incremental updates.
+    levels: usize,
+}
+
+/// Wraps a market depth and aggregates it into price buckets of a fixed width, such as 1.0 for an
+/// asset whose tick size is 0.1. Bid levels are grouped into the bucket at or below them, and ask
+/// levels into the bucket at or above them, so the bucket prices are conservative on both sides.
+///
+/// The bucket quantities are maintained incrementally on each depth update, so reading the
+/// bucketed ladders only costs the number of levels requested.
+///
+/// This doesn't take the BBO feed, since the BBO feed alone cannot tell which price levels are
+/// removed.
+pub struct AggregatedMarketDepth<MD> {
+    depth: MD,
+    bucket_ticks: i64,
+    num_levels: usize,
+    bid_buckets: HashMap<i64, Bucket>,
+    ask_buckets: HashMap<i64, Bucket>,
+}
+
+impl<MD> AggregatedMarketDepth<MD>
+where
+    MD: MarketDepth,
+{
+    /// Constructs an instance of `AggregatedMarketDepth` over the given market depth, which should
+    /// be empty. `bucket_size` is rounded to a multiple of the tick size, and `num_levels` is the
+    /// number of buckets in the ladders.
+    pub fn new(depth: MD, bucket_size: f64, num_levels: usize) -> Self {
+        let bucket_ticks = (bucket_size / depth.tick_size()).round() as i64;
+        assert!(
+            bucket_ticks > 0,
+            "bucket_size must be at least the tick size"
+        );
+        Self {
+            depth,
+            bucket_ticks,
+            num_levels,
+            bid_buckets: HashMap::new(),
+            ask_buckets: HashMap::new(),
+        }
+    }
+
+    /// Returns the wrapped market depth.
+    pub fn inner(&self) -> &MD {
+        &self.depth
+    }
+
+    /// Returns the bucket width in ticks.
+    pub fn bucket_ticks(&self) -> i64 {
+        self.bucket_ticks
+    }
+
+    /// Returns the number of buckets in the ladders.
+    pub fn num_levels(&self) -> usize {
+        self.num_levels
+    }
+
+    /// Returns the bid ladder as (the bucket price, the quantity) from the bucket of the best bid
+    /// downward, which has `num_levels` buckets including the empty ones. The ladder is empty if
+    /// there is no best bid.
+    pub fn bid_ladder(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let best_bid_tick = self.depth.best_bid_tick();
+        let (top, num_levels) = if best_bid_tick == INVALID_MIN {
+            (0, 0)
+        } else {
+            (best_bid_tick.div_euclid(self.bucket_ticks), self.num_levels)
+        };
+        (0..num_levels as i64).map(move |i| {
+            let key = top - i;
+            let mut qty = self.bid_buckets.get(&key).map(|b| b.qty).unwrap_or(0.0);
+            if i == 0 {
+                // Excludes the levels above the best bid, which some market depth implementations
+                // keep after the ask side crosses them.
+                for t in (best_bid_tick + 1)..(key + 1) * self.bucket_ticks {
+                    qty -= level_qty(self.depth.bid_qty_at_tick(t));
+                }
+            }
+            (
+                (key * self.bucket_ticks) as f64 * self.depth.tick_size(),
+                qty,
+            )
+        })
+    }
+
+    /// Returns the ask ladder as (the bucket price, the quantity) from the bucket of the best ask
+    /// upward, which has `num_levels` buckets including the empty ones. The ladder is empty if
+    /// there is no best ask.
+    pub fn ask_ladder(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        let best_ask_tick = self.depth.best_ask_tick();
+        let (top, num_levels) = if best_ask_tick == INVALID_MAX {
+            (0, 0)
+        } else {
+            (ask_key(best_ask_tick, self.bucket_ticks), self.num_levels)
+        };
+        (0..num_levels as i64).map(move |i| {
+            let key = top + i;
+            let mut qty = self.ask_buckets.get(&key).map(|b| b.qty).unwrap_or(0.0);
+            if i == 0 {
+                // Excludes the levels below the best ask, which some market depth implementations
+                // keep after the bid side crosses them.
+                for t in ((key - 1) * self.bucket_ticks + 1)..best_ask_tick {
+                    qty -= level_qty(self.depth.ask_qty_at_tick(t));
+                }
+            }
+            (
+                (key * self.bucket_ticks) as f64 * self.depth.tick_size(),
+                qty,
+            )
+        })
+    }
+
+    fn resync_bid_bucket(&mut self, key: i64) {
+        let bucket = sum_bucket(key * self.bucket_ticks, self.bucket_ticks, |t| {
+            self.depth.bid_qty_at_tick(t)
+        });
+        set_bucket(&mut self.bid_buckets, key, bucket);
+    }
+
+    fn resync_ask_bucket(&mut self, key: i64) {
+        let bucket = sum_bucket((key - 1) * self.bucket_ticks + 1, self.bucket_ticks, |t| {
+            self.depth.ask_qty_at_tick(t)
+        });
+        set_bucket(&mut self.ask_buckets, key, bucket);
+    }
+}
+
+#[inline(always)]
+fn ask_key(price_tick: i64, bucket_ticks: i64) -> i64 {
+    -(-price_tick).div_euclid(bucket_ticks)
+}
+
+#[inline(always)]
+fn level_qty(qty: f64) -> f64 {
+    // Some market depth implementations return NaN for the price levels they don't track.
+    if qty > 0.0 { qty } else { 0.0 }
+}
+
+fn sum_bucket<F>(lo_tick: i64, bucket_ticks: i64, qty_at_tick: F) -> Bucket
+where
+    F: Fn(i64) -> f64,
+{
+    let mut bucket = Bucket::default();
+    for t in lo_tick..lo_tick + bucket_ticks {
+        let qty = level_qty(qty_at_tick(t));
+        if qty > 0.0 {
+            bucket.qty += qty;
+            bucket.levels += 1;
+        }
+    }
+    bucket
+}
+
+fn set_bucket(buckets: &mut HashMap<i64, Bucket>, key: i64, bucket: Bucket) {
+    if bucket.levels == 0 {
+        buckets.remove(&key);
+    } else {
+        buckets.insert(key, bucket);
+    }
+}
+
+fn apply_level_change(buckets: &mut HashMap<i64, Bucket>, key: i64, prev_qty: f64, qty: f64) {
+    match (prev_qty > 0.0, qty > 0.0) {
+        (false, false) => {}
+        (false, true) => {
+            let bucket = buckets.entry(key).or_default();
+            bucket.qty += qty;
+            bucket.levels += 1;
+        }
+        (true, false) => {
+            if let Some(bucket) = buckets.get_mut(&key) {
+                bucket.levels -= 1;
+                if bucket.levels == 0 {
+                    buckets.remove(&key);
+                } else {
+                    bucket.qty -= prev_qty;
+                }
+            }
+        }
+        (true, true) => {
+            if let Some(bucket) = buckets.get_mut(&key) {
+                bucket.qty += qty - prev_qty;
+            }
+        }
+    }
+}
+
+impl<MD> L2MarketDepth for AggregatedMarketDepth<MD>
+where
+    MD: MarketDepth + L2MarketDepth,
+{
+    fn update_bid_depth(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> (i64, i64, i64, f64, f64, i64) {
+        let price_tick = (price / self.depth.tick_size()).round() as i64;
+        let prev_qty = level_qty(self.depth.bid_qty_at_tick(price_tick));
+        let result = self.depth.update_bid_depth(price, qty, timestamp);
+        // The wrapped market depth may round or ignore the update, so the applied quantity is read
+        // back from it.
+        let qty = level_qty(self.depth.bid_qty_at_tick(price_tick));
+        apply_level_change(
+            &mut self.bid_buckets,
+            price_tick.div_euclid(self.bucket_ticks),
+            prev_qty,
+            qty,
+        );
+        result
+    }
+
+    fn update_ask_depth(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> (i64, i64, i64, f64, f64, i64) {
+        let price_tick = (price / self.depth.tick_size()).round() as i64;
+        let prev_qty = level_qty(self.depth.ask_qty_at_tick(price_tick));
+        let result = self.depth.update_ask_depth(price, qty, timestamp);
+        let qty = level_qty(self.depth.ask_qty_at_tick(price_tick));
+        apply_level_change(
+            &mut self.ask_buckets,
+            ask_key(price_tick, self.bucket_ticks),
+            prev_qty,
+            qty,
+        );
+        result
+    }
+
+    fn uses_exch_timestamp(&self) -> bool {
+        self.depth.uses_exch_timestamp()
+    }
+
+    fn clear_depth(&mut self, side: Side, clear_upto_price: f64) {
+        self.depth.clear_depth(side, clear_upto_price);
+        let clear_upto = (clear_upto_price / self.depth.tick_size()).round() as i64;
+        match side {
+            Side::Buy if clear_upto_price.is_finite() => {
+                // Resynchronizes every bucket that overlaps the cleared range.
+                let keys: Vec<_> = self
+                    .bid_buckets
+                    .keys()
+                    .copied()
+                    .filter(|&key| (key + 1) * self.bucket_ticks > clear_upto)
+                    .collect();
+                for key in keys {
+                    self.resync_bid_bucket(key);
+                }
+            }
+            Side::Sell if clear_upto_price.is_finite() => {
+                let keys: Vec<_> = self
+                    .ask_buckets
+                    .keys()
+                    .copied()
+                    .filter(|&key| (key - 1) * self.bucket_ticks < clear_upto)
+                    .collect();
+                for key in keys {
+                    self.resync_ask_bucket(key);
+                }
+            }
+            Side::Buy => self.bid_buckets.clear(),
+            Side::Sell => self.ask_buckets.clear(),
+            Side::None => {
+                self.bid_buckets.clear();
+                self.ask_buckets.clear();
+            }
+            Side::Unsupported => unreachable!(),
+        }
+    }
+}
+
+impl<MD> ApplySnapshot for AggregatedMarketDepth<MD>
+where
+    MD: MarketDepth + ApplySnapshot,
+{
+    fn apply_snapshot(&mut self, data: &Data<Event>) {
+        self.depth.apply_snapshot(data);
+        self.bid_buckets.clear();
+        self.ask_buckets.clear();
+        for ev in self.depth.snapshot() {
+            let price_tick = (ev.px / self.depth.tick_size()).round() as i64;
+            if ev.ev & BUY_EVENT == BUY_EVENT {
+                let key = price_tick.div_euclid(self.bucket_ticks);
+                apply_level_change(&mut self.bid_buckets, key, 0.0, ev.qty);
+            } else if ev.ev & SELL_EVENT == SELL_EVENT {
+                let key = ask_key(price_tick, self.bucket_ticks);
+                apply_level_change(&mut self.ask_buckets, key, 0.0, ev.qty);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<Event> {
+        self.depth.snapshot()
+    }
+}
+
+impl<MD> MarketDepth for AggregatedMarketDepth<MD>
+where
+    MD: MarketDepth,
+{
+    #[inline(always)]
+    fn best_bid(&self) -> f64 {
+        self.depth.best_bid()
+    }
+
+    #[inline(always)]
+    fn best_ask(&self) -> f64 {
+        self.depth.best_ask()
+    }
+
+    #[inline(always)]
+    fn best_bid_tick(&self) -> i64 {
+        self.depth.best_bid_tick()
+    }
+
+    #[inline(always)]
+    fn best_ask_tick(&self) -> i64 {
+        self.depth.best_ask_tick()
+    }
+
+    #[inline(always)]
+    fn best_bid_qty(&self) -> f64 {
+        self.depth.best_bid_qty()
+    }
+
+    #[inline(always)]
+    fn best_ask_qty(&self) -> f64 {
+        self.depth.best_ask_qty()
+    }
+
+    #[inline(always)]
+    fn tick_size(&self) -> f64 {
+        self.depth.tick_size()
+    }
+
+    #[inline(always)]
+    fn lot_size(&self) -> f64 {
+        self.depth.lot_size()
+    }
+
+    #[inline(always)]
+    fn timestamp(&self) -> i64 {
+        self.depth.timestamp()
+    }
+
+    #[inline(always)]
+    fn bid_qty_at_tick(&self, price_tick: i64) -> f64 {
+        self.depth.bid_qty_at_tick(price_tick)
+    }
+
+    #[inline(always)]
+    fn ask_qty_at_tick(&self, price_tick: i64) -> f64 {
+        self.depth.ask_qty_at_tick(price_tick)
+    }
+}
+
+impl<MD> DepthAnalytics for AggregatedMarketDepth<MD> where MD: MarketDepth {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::Data,
+        depth::{
+            AggregatedMarketDepth,
+            ApplySnapshot,
+            HashMapMarketDepth,
+            L2MarketDepth,
+            ROIVectorMarketDepth,
+        },
+        types::{Event, Side},
+    };
+
+    #[test]
+    fn test_ladder() {
+        let mut depth = AggregatedMarketDepth::new(HashMapMarketDepth::new(0.1, 0.001), 1.0, 3);
+        assert_eq!(depth.bid_ladder().count(), 0);
+        assert_eq!(depth.ask_ladder().count(), 0);
+
+        depth.update_bid_depth(100.5, 1.0, 0);
+        depth.update_bid_depth(100.0, 2.0, 0);
+        depth.update_bid_depth(99.9, 3.0, 0);
+        depth.update_bid_depth(97.2, 4.0, 0);
+        depth.update_ask_depth(100.6, 1.0, 0);
+        depth.update_ask_depth(101.0, 2.0, 0);
+        depth.update_ask_depth(101.1, 3.0, 0);
+
+        let bids: Vec<_> = depth.bid_ladder().collect();
+        assert_eq!(bids.len(), 3);
+        assert!((bids[0].0 - 100.0).abs() < 1e-9 && bids[0].1 == 3.0);
+        assert!((bids[1].0 - 99.0).abs() < 1e-9 && bids[1].1 == 3.0);
+        assert!((bids[2].0 - 98.0).abs() < 1e-9 && bids[2].1 == 0.0);
+
+        let asks: Vec<_> = depth.ask_ladder().collect();
+        assert!((asks[0].0 - 101.0).abs() < 1e-9 && asks[0].1 == 3.0);
+        assert!((asks[1].0 - 102.0).abs() < 1e-9 && asks[1].1 == 3.0);
+        assert!((asks[2].0 - 103.0).abs() < 1e-9 && asks[2].1 == 0.0);
+
+        depth.update_bid_depth(100.5, 0.5, 1);
+        depth.update_bid_depth(100.0, 0.0, 1);
+        depth.update_ask_depth(101.1, 0.0, 1);
+        let bids: Vec<_> = depth.bid_ladder().map(|(_, qty)| qty).collect();
+        assert_eq!(bids, vec![0.5, 3.0, 0.0]);
+        let asks: Vec<_> = depth.ask_ladder().map(|(_, qty)| qty).collect();
+        assert_eq!(asks, vec![3.0, 0.0, 0.0]);
+
+        // The bid at 100.5 stays in the wrapped market depth after the ask side crosses it, but is
+        // no longer part of the ladder.
+        depth.update_ask_depth(100.5, 1.0, 2);
+        let bids: Vec<_> = depth.bid_ladder().collect();
+        assert!((bids[0].0 - 99.0).abs() < 1e-9 && bids[0].1 == 3.0);
+        let asks: Vec<_> = depth.ask_ladder().map(|(_, qty)| qty).collect();
+        assert_eq!(asks, vec![4.0, 0.0, 0.0]);
+
+        depth.clear_depth(Side::Sell, 100.6);
+        let asks: Vec<_> = depth.ask_ladder().map(|(_, qty)| qty).collect();
+        assert_eq!(asks, vec![2.0, 0.0, 0.0]);
+
+        depth.clear_depth(Side::None, 0.0);
+        assert_eq!(depth.bid_ladder().count(), 0);
+        assert_eq!(depth.ask_ladder().count(), 0);
+    }
+
+    #[test]
+    fn test_outside_roi() {
+        let mut depth =
+            AggregatedMarketDepth::new(ROIVectorMarketDepth::new(1.0, 1.0, 90.0, 110.0), 5.0, 2);
+        depth.update_bid_depth(100.0, 1.0, 0);
+        depth.update_bid_depth(80.0, 1.0, 0);
+        depth.update_ask_depth(101.0, 1.0, 0);
+        depth.update_ask_depth(120.0, 1.0, 0);
+        depth.update_bid_depth(80.0, 0.0, 0);
+
+        let bids: Vec<_> = depth.bid_ladder().collect();
+        assert_eq!(bids, vec![(100.0, 1.0), (95.0, 0.0)]);
+        let asks: Vec<_> = depth.ask_ladder().collect();
+        assert_eq!(asks, vec![(105.0, 1.0), (110.0, 0.0)]);
+    }
+
+    #[test]
+    fn test_apply_snapshot() {
+        let mut source = HashMapMarketDepth::new(1.0, 1.0);
+        source.update_bid_depth(99.0, 1.0, 0);
+        source.update_bid_depth(98.0, 2.0, 0);
+        source.update_ask_depth(101.0, 3.0, 0);
+        let snapshot = source.snapshot();
+
+        let mut depth = AggregatedMarketDepth::new(HashMapMarketDepth::new(1.0, 1.0), 2.0, 2);
+        depth.update_ask_depth(110.0, 1.0, 0);
+        depth.apply_snapshot(&Data::<Event>::from_data(&snapshot));
+
+        let bids: Vec<_> = depth.bid_ladder().collect();
+        assert_eq!(bids, vec![(98.0, 3.0), (96.0, 0.0)]);
+        let asks: Vec<_> = depth.ask_ladder().collect();
+        assert_eq!(asks, vec![(102.0, 3.0), (104.0, 0.0)]);
+    }
+}
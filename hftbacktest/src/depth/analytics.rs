@@ -0,0 +1,323 @@
+use super::{
+    BTreeMarketDepth,
+    FusedHashMapMarketDepth,
+    HashMapMarketDepth,
+    INVALID_MAX,
+    INVALID_MIN,
+    MarketDepth,
+    ROIVectorMarketDepth,
+    RingBufferMarketDepth,
+};
+
+/// Provides the order book analytics commonly used by strategies, computed from the market depth
+/// within `depth_ticks` price levels from the best bid and ask, including the best ones.
+///
+/// The default implementations work with any [`MarketDepth`] by looking up the quantity at each
+/// price level. Implementations can override [`for_each_bid_level`](Self::for_each_bid_level) and
+/// [`for_each_ask_level`](Self::for_each_ask_level) to iterate over their storage directly.
+pub trait DepthAnalytics: MarketDepth {
+    /// Calls `f` with the price in ticks and the quantity of each non-empty bid level, from the
+    /// best bid downward within `depth_ticks`, until `f` returns `false`.
+    fn for_each_bid_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        let best_bid_tick = self.best_bid_tick();
+        if best_bid_tick == INVALID_MIN {
+            return;
+        }
+        for price_tick in (0..depth_ticks as i64).map(|i| best_bid_tick - i) {
+            let qty = self.bid_qty_at_tick(price_tick);
+            if qty > 0.0 && !f(price_tick, qty) {
+                break;
+            }
+        }
+    }
+
+    /// Calls `f` with the price in ticks and the quantity of each non-empty ask level, from the
+    /// best ask upward within `depth_ticks`, until `f` returns `false`.
+    fn for_each_ask_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        let best_ask_tick = self.best_ask_tick();
+        if best_ask_tick == INVALID_MAX {
+            return;
+        }
+        for price_tick in (0..depth_ticks as i64).map(|i| best_ask_tick + i) {
+            let qty = self.ask_qty_at_tick(price_tick);
+            if qty > 0.0 && !f(price_tick, qty) {
+                break;
+            }
+        }
+    }
+
+    /// Returns the cumulative bid quantity within `depth_ticks` from the best bid.
+    fn cum_bid_qty(&self, depth_ticks: usize) -> f64 {
+        let mut cum_qty = 0.0;
+        self.for_each_bid_level(depth_ticks, |_, qty| {
+            cum_qty += qty;
+            true
+        });
+        cum_qty
+    }
+
+    /// Returns the cumulative ask quantity within `depth_ticks` from the best ask.
+    fn cum_ask_qty(&self, depth_ticks: usize) -> f64 {
+        let mut cum_qty = 0.0;
+        self.for_each_ask_level(depth_ticks, |_, qty| {
+            cum_qty += qty;
+            true
+        });
+        cum_qty
+    }
+
+    /// Returns the order book imbalance, `(bid_qty - ask_qty) / (bid_qty + ask_qty)`, of the
+    /// cumulative quantities within `depth_ticks`. It ranges from -1 to 1, and is [`f64::NAN`] if
+    /// both sides are empty.
+    fn imbalance(&self, depth_ticks: usize) -> f64 {
+        let bid_qty = self.cum_bid_qty(depth_ticks);
+        let ask_qty = self.cum_ask_qty(depth_ticks);
+        if bid_qty + ask_qty == 0.0 {
+            f64::NAN
+        } else {
+            (bid_qty - ask_qty) / (bid_qty + ask_qty)
+        }
+    }
+
+    /// Returns the mid-price weighted by the cumulative quantities within `depth_ticks` of the
+    /// opposite sides, `(best_bid * ask_qty + best_ask * bid_qty) / (bid_qty + ask_qty)`. With
+    /// `depth_ticks` of 1, this is the micro-price. It is [`f64::NAN`] if either side is empty.
+    fn weighted_mid(&self, depth_ticks: usize) -> f64 {
+        let bid_qty = self.cum_bid_qty(depth_ticks);
+        let ask_qty = self.cum_ask_qty(depth_ticks);
+        if bid_qty == 0.0 || ask_qty == 0.0 {
+            f64::NAN
+        } else {
+            (self.best_bid() * ask_qty + self.best_ask() * bid_qty) / (bid_qty + ask_qty)
+        }
+    }
+
+    /// Returns the volume-weighted average price at which a sell order of the given quantity
+    /// would be filled by sweeping the bid side within `depth_ticks`. It is [`f64::NAN`] if the
+    /// quantity cannot be filled within `depth_ticks`.
+    fn bid_vwap(&self, qty: f64, depth_ticks: usize) -> f64 {
+        let mut leaves_qty = qty;
+        let mut value = 0.0;
+        self.for_each_bid_level(depth_ticks, |price_tick, level_qty| {
+            let exec_qty = leaves_qty.min(level_qty);
+            value += price_tick as f64 * exec_qty;
+            leaves_qty -= exec_qty;
+            leaves_qty > 0.0
+        });
+        if leaves_qty > 0.0 {
+            f64::NAN
+        } else {
+            value / qty * self.tick_size()
+        }
+    }
+
+    /// Returns the volume-weighted average price at which a buy order of the given quantity would
+    /// be filled by sweeping the ask side within `depth_ticks`. It is [`f64::NAN`] if the quantity
+    /// cannot be filled within `depth_ticks`.
+    fn ask_vwap(&self, qty: f64, depth_ticks: usize) -> f64 {
+        let mut leaves_qty = qty;
+        let mut value = 0.0;
+        self.for_each_ask_level(depth_ticks, |price_tick, level_qty| {
+            let exec_qty = leaves_qty.min(level_qty);
+            value += price_tick as f64 * exec_qty;
+            leaves_qty -= exec_qty;
+            leaves_qty > 0.0
+        });
+        if leaves_qty > 0.0 {
+            f64::NAN
+        } else {
+            value / qty * self.tick_size()
+        }
+    }
+}
+
+impl DepthAnalytics for HashMapMarketDepth {
+    fn for_each_bid_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        if self.best_bid_tick == INVALID_MIN || depth_ticks == 0 {
+            return;
+        }
+        // There are no levels below the lowest bid seen.
+        let lo = (self.best_bid_tick - depth_ticks as i64 + 1).max(self.low_bid_tick);
+        for price_tick in (lo..=self.best_bid_tick).rev() {
+            let qty = self.bid_qty_at_tick(price_tick);
+            if qty > 0.0 && !f(price_tick, qty) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_ask_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        if self.best_ask_tick == INVALID_MAX || depth_ticks == 0 {
+            return;
+        }
+        // There are no levels above the highest ask seen.
+        let hi = (self.best_ask_tick + depth_ticks as i64 - 1).min(self.high_ask_tick);
+        for price_tick in self.best_ask_tick..=hi {
+            let qty = self.ask_qty_at_tick(price_tick);
+            if qty > 0.0 && !f(price_tick, qty) {
+                break;
+            }
+        }
+    }
+}
+
+impl DepthAnalytics for FusedHashMapMarketDepth {}
+
+impl DepthAnalytics for RingBufferMarketDepth {}
+
+impl DepthAnalytics for ROIVectorMarketDepth {
+    fn for_each_bid_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        if self.best_bid_tick == INVALID_MIN || depth_ticks == 0 {
+            return;
+        }
+        // Levels outside the range of interest are not tracked.
+        let hi = self.best_bid_tick.min(self.roi_ub);
+        let lo = (self.best_bid_tick - depth_ticks as i64 + 1).max(self.roi_lb);
+        if lo > hi {
+            return;
+        }
+        let levels = &self.bid_depth[(lo - self.roi_lb) as usize..=(hi - self.roi_lb) as usize];
+        for (i, &qty) in levels.iter().enumerate().rev() {
+            if qty > 0.0 && !f(lo + i as i64, qty) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_ask_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        if self.best_ask_tick == INVALID_MAX || depth_ticks == 0 {
+            return;
+        }
+        // Levels outside the range of interest are not tracked.
+        let lo = self.best_ask_tick.max(self.roi_lb);
+        let hi = (self.best_ask_tick + depth_ticks as i64 - 1).min(self.roi_ub);
+        if lo > hi {
+            return;
+        }
+        let levels = &self.ask_depth[(lo - self.roi_lb) as usize..=(hi - self.roi_lb) as usize];
+        for (i, &qty) in levels.iter().enumerate() {
+            if qty > 0.0 && !f(lo + i as i64, qty) {
+                break;
+            }
+        }
+    }
+}
+
+impl DepthAnalytics for BTreeMarketDepth {
+    fn for_each_bid_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        if self.best_bid_tick == INVALID_MIN || depth_ticks == 0 {
+            return;
+        }
+        let lo = self.best_bid_tick - depth_ticks as i64 + 1;
+        for (&price_tick, &qty) in self.bid_depth.range(lo..=self.best_bid_tick).rev() {
+            if qty > 0.0 && !f(price_tick, qty) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_ask_level<F>(&self, depth_ticks: usize, mut f: F)
+    where
+        F: FnMut(i64, f64) -> bool,
+    {
+        if self.best_ask_tick == INVALID_MAX || depth_ticks == 0 {
+            return;
+        }
+        let hi = self.best_ask_tick + depth_ticks as i64 - 1;
+        for (&price_tick, &qty) in self.ask_depth.range(self.best_ask_tick..=hi) {
+            if qty > 0.0 && !f(price_tick, qty) {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::depth::{
+        BTreeMarketDepth,
+        DepthAnalytics,
+        HashMapMarketDepth,
+        L2MarketDepth,
+        MarketDepth,
+        ROIVectorMarketDepth,
+        RingBufferMarketDepth,
+    };
+
+    fn assert_analytics<MD>(mut depth: MD)
+    where
+        MD: MarketDepth + L2MarketDepth + DepthAnalytics,
+    {
+        assert!(depth.imbalance(5).is_nan());
+        assert!(depth.weighted_mid(1).is_nan());
+
+        depth.update_bid_depth(100.0, 1.0, 0);
+        depth.update_bid_depth(99.0, 2.0, 0);
+        depth.update_bid_depth(97.0, 3.0, 0);
+        depth.update_ask_depth(101.0, 3.0, 0);
+        depth.update_ask_depth(103.0, 1.0, 0);
+
+        assert_eq!(depth.cum_bid_qty(1), 1.0);
+        assert_eq!(depth.cum_bid_qty(3), 3.0);
+        assert_eq!(depth.cum_bid_qty(4), 6.0);
+        assert_eq!(depth.cum_ask_qty(2), 3.0);
+        assert_eq!(depth.cum_ask_qty(3), 4.0);
+
+        assert_eq!(depth.imbalance(1), -0.5);
+        assert_eq!(depth.imbalance(4), 0.2);
+
+        // (100 * 3 + 101 * 1) / 4
+        assert_eq!(depth.weighted_mid(1), 100.25);
+
+        // (100 * 1 + 99 * 2 + 97 * 1) / 4
+        assert_eq!(depth.bid_vwap(4.0, 4), 98.75);
+        assert!(depth.bid_vwap(4.0, 3).is_nan());
+        // (101 * 3 + 103 * 1) / 4
+        assert_eq!(depth.ask_vwap(4.0, 3), 101.5);
+        assert_eq!(depth.ask_vwap(2.0, 1), 101.0);
+    }
+
+    #[test]
+    fn test_depth_analytics() {
+        assert_analytics(HashMapMarketDepth::new(1.0, 1.0));
+        assert_analytics(BTreeMarketDepth::new(1.0, 1.0));
+        assert_analytics(ROIVectorMarketDepth::new(1.0, 1.0, 0.0, 200.0));
+        assert_analytics(RingBufferMarketDepth::new(1.0, 1.0));
+    }
+
+    #[test]
+    fn test_roi_vector_outside_roi() {
+        let mut depth = ROIVectorMarketDepth::new(1.0, 1.0, 98.0, 102.0);
+        depth.update_bid_depth(100.0, 1.0, 0);
+        depth.update_bid_depth(98.0, 2.0, 0);
+        depth.update_bid_depth(97.0, 3.0, 0);
+        depth.update_ask_depth(102.0, 4.0, 0);
+        depth.update_ask_depth(103.0, 5.0, 0);
+
+        // Only the levels within the range of interest are counted.
+        assert_eq!(depth.cum_bid_qty(10), 3.0);
+        assert_eq!(depth.cum_ask_qty(10), 4.0);
+        assert!(depth.bid_vwap(4.0, 10).is_nan());
+    }
+}
@@ -12,7 +12,7 @@ use super::{
 use crate::{
     backtest::{BacktestError, data::Data},
     prelude::{OrderId, Side},
-    types::{BUY_EVENT, Event, SELL_EVENT},
+    types::{BUY_EVENT, DEPTH_SNAPSHOT_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT},
 };
 
 /// L2 Market depth implementation based on a B-Tree map.
@@ -68,6 +68,7 @@ impl L2MarketDepth for BTreeMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
         let price_tick = (price / self.tick_size).round() as i64;
         let prev_best_bid_tick = *self.bid_depth.keys().last().unwrap_or(&INVALID_MIN);
         let prev_qty = *self.bid_depth.get(&prev_best_bid_tick).unwrap_or(&0.0);
@@ -94,6 +95,7 @@ impl L2MarketDepth for BTreeMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
         let price_tick = (price / self.tick_size).round() as i64;
         let prev_best_ask_tick = *self.ask_depth.keys().next().unwrap_or(&INVALID_MAX);
         let prev_qty = *self.ask_depth.get(&prev_best_ask_tick).unwrap_or(&0.0);
@@ -210,6 +212,11 @@ impl MarketDepth for BTreeMarketDepth {
         self.lot_size
     }
 
+    #[inline(always)]
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
     #[inline(always)]
     fn bid_qty_at_tick(&self, price_tick: i64) -> f64 {
         *self.bid_depth.get(&price_tick).unwrap_or(&0.0)
@@ -223,11 +230,13 @@ impl MarketDepth for BTreeMarketDepth {
 
 impl ApplySnapshot for BTreeMarketDepth {
     fn apply_snapshot(&mut self, data: &Data<Event>) {
+        self.timestamp = 0;
         self.bid_depth.clear();
         self.ask_depth.clear();
         for row_num in 0..data.len() {
             let price = data[row_num].px;
             let qty = data[row_num].qty;
+            self.timestamp = self.timestamp.max(data[row_num].exch_ts);
 
             let price_tick = (price / self.tick_size).round() as i64;
             if data[row_num].ev & BUY_EVENT == BUY_EVENT {
@@ -241,7 +250,35 @@ impl ApplySnapshot for BTreeMarketDepth {
     }
 
     fn snapshot(&self) -> Vec<Event> {
-        todo!()
+        let mut events = Vec::new();
+
+        for (&px_tick, &qty) in self.bid_depth.range(..=self.best_bid_tick).rev() {
+            events.push(Event {
+                ev: EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_SNAPSHOT_EVENT,
+                exch_ts: self.timestamp,
+                local_ts: self.timestamp,
+                px: px_tick as f64 * self.tick_size,
+                qty,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            });
+        }
+
+        for (&px_tick, &qty) in self.ask_depth.range(self.best_ask_tick..) {
+            events.push(Event {
+                ev: EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_SNAPSHOT_EVENT,
+                exch_ts: self.timestamp,
+                local_ts: self.timestamp,
+                px: px_tick as f64 * self.tick_size,
+                qty,
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            });
+        }
+
+        events
     }
 }
 
@@ -255,6 +292,7 @@ impl L3MarketDepth for BTreeMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let price_tick = (px / self.tick_size).round() as i64;
         self.add(L3Order {
             order_id,
@@ -277,6 +315,7 @@ impl L3MarketDepth for BTreeMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let price_tick = (px / self.tick_size).round() as i64;
         self.add(L3Order {
             order_id,
@@ -295,8 +334,9 @@ impl L3MarketDepth for BTreeMarketDepth {
     fn delete_order(
         &mut self,
         order_id: OrderId,
-        _timestamp: i64,
+        timestamp: i64,
     ) -> Result<(Side, i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let order = self
             .orders
             .remove(&order_id)
@@ -335,6 +375,7 @@ impl L3MarketDepth for BTreeMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(Side, i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let order = self
             .orders
             .get_mut(&order_id)
@@ -439,6 +480,10 @@ impl L3MarketDepth for BTreeMarketDepth {
     fn orders(&self) -> &HashMap<OrderId, L3Order> {
         &self.orders
     }
+
+    fn set_timestamp(&mut self, timestamp: i64) {
+        self.timestamp = timestamp;
+    }
 }
 
 #[cfg(test)]
@@ -1,11 +1,10 @@
 use std::collections::{HashMap, hash_map::Entry};
 
 //use tracing::debug;
-
 use crate::{
     backtest::data::Data,
-    depth::{ApplySnapshot, INVALID_MAX, INVALID_MIN, MarketDepth},
-    prelude::{DEPTH_SNAPSHOT_EVENT, EXCH_EVENT, LOCAL_EVENT},
+    depth::{ApplySnapshot, INVALID_MAX, INVALID_MIN, L2MarketDepth, MarketDepth},
+    prelude::{DEPTH_BBO_EVENT, DEPTH_SNAPSHOT_EVENT, EXCH_EVENT, LOCAL_EVENT},
     types::{BUY_EVENT, DEPTH_EVENT, Event, SELL_EVENT, Side},
 };
 
@@ -20,6 +19,13 @@ impl Default for QtyTimestamp {
     }
 }
 
+/// A HashMap-based market depth that fuses the feeds from multiple streams with different update
+/// frequencies and ranges, such as the full-depth diff stream, the partial depth snapshot stream,
+/// and the BBO stream. An update is applied only if it isn't older than what the market depth
+/// already holds for the price level or the BBO, so the depth always reflects the most recent
+/// information across the streams.
+///
+/// The exchange timestamp is used to determine which update is more recent.
 pub struct FusedHashMapMarketDepth {
     pub tick_size: f64,
     pub lot_size: f64,
@@ -91,6 +97,7 @@ impl FusedHashMapMarketDepth {
             // );
             return result;
         }
+        self.timestamp = self.timestamp.max(ev.exch_ts);
 
         match self.bid_depth.entry(price_tick) {
             Entry::Occupied(mut entry) => {
@@ -207,6 +214,7 @@ impl FusedHashMapMarketDepth {
             // );
             return result;
         }
+        self.timestamp = self.timestamp.max(ev.exch_ts);
 
         match self.ask_depth.entry(price_tick) {
             Entry::Occupied(mut entry) => {
@@ -305,6 +313,7 @@ impl FusedHashMapMarketDepth {
     }
 
     pub fn clear_depth(&mut self, side: Side, clear_upto_price: f64, timestamp: i64) {
+        self.timestamp = self.timestamp.max(timestamp);
         let clear_upto = (clear_upto_price / self.tick_size).round() as i64;
         if side == Side::Buy {
             if self.best_bid_tick != INVALID_MIN {
@@ -361,6 +370,7 @@ impl FusedHashMapMarketDepth {
             // );
             return result;
         }
+        self.timestamp = self.timestamp.max(ev.exch_ts);
 
         match self.bid_depth.entry(price_tick) {
             Entry::Occupied(mut entry) => {
@@ -469,6 +479,7 @@ impl FusedHashMapMarketDepth {
             // );
             return result;
         }
+        self.timestamp = self.timestamp.max(ev.exch_ts);
 
         match self.ask_depth.entry(price_tick) {
             Entry::Occupied(mut entry) => {
@@ -561,8 +572,125 @@ impl FusedHashMapMarketDepth {
     }
 }
 
+fn depth_event(ev: u64, price: f64, qty: f64, timestamp: i64) -> Event {
+    Event {
+        ev,
+        exch_ts: timestamp,
+        local_ts: timestamp,
+        px: price,
+        qty,
+        order_id: 0,
+        ival: 0,
+        fval: 0.0,
+    }
+}
+
+impl L2MarketDepth for FusedHashMapMarketDepth {
+    fn update_bid_depth(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> (i64, i64, i64, f64, f64, i64) {
+        let price_tick = (price / self.tick_size).round() as i64;
+        let prev_best_bid_tick = self.best_bid_tick;
+        let prev_qty = self.bid_qty_at_tick(price_tick);
+        FusedHashMapMarketDepth::update_bid_depth(
+            self,
+            depth_event(BUY_EVENT | DEPTH_EVENT, price, qty, timestamp),
+        );
+        (
+            price_tick,
+            prev_best_bid_tick,
+            self.best_bid_tick,
+            prev_qty,
+            self.bid_qty_at_tick(price_tick),
+            timestamp,
+        )
+    }
+
+    fn update_ask_depth(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> (i64, i64, i64, f64, f64, i64) {
+        let price_tick = (price / self.tick_size).round() as i64;
+        let prev_best_ask_tick = self.best_ask_tick;
+        let prev_qty = self.ask_qty_at_tick(price_tick);
+        FusedHashMapMarketDepth::update_ask_depth(
+            self,
+            depth_event(SELL_EVENT | DEPTH_EVENT, price, qty, timestamp),
+        );
+        (
+            price_tick,
+            prev_best_ask_tick,
+            self.best_ask_tick,
+            prev_qty,
+            self.ask_qty_at_tick(price_tick),
+            timestamp,
+        )
+    }
+
+    fn uses_exch_timestamp(&self) -> bool {
+        true
+    }
+
+    fn clear_depth(&mut self, side: Side, clear_upto_price: f64) {
+        // The clear event doesn't carry its own timestamp, so it's regarded as the most recent one.
+        FusedHashMapMarketDepth::clear_depth(self, side, clear_upto_price, self.timestamp);
+    }
+
+    fn update_best_bid(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> Option<(i64, i64, i64, f64, f64, i64)> {
+        let price_tick = (price / self.tick_size).round() as i64;
+        let prev_best_bid_tick = self.best_bid_tick;
+        let prev_qty = self.bid_qty_at_tick(price_tick);
+        FusedHashMapMarketDepth::update_best_bid(
+            self,
+            depth_event(BUY_EVENT | DEPTH_BBO_EVENT, price, qty, timestamp),
+        );
+        Some((
+            price_tick,
+            prev_best_bid_tick,
+            self.best_bid_tick,
+            prev_qty,
+            self.bid_qty_at_tick(price_tick),
+            timestamp,
+        ))
+    }
+
+    fn update_best_ask(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> Option<(i64, i64, i64, f64, f64, i64)> {
+        let price_tick = (price / self.tick_size).round() as i64;
+        let prev_best_ask_tick = self.best_ask_tick;
+        let prev_qty = self.ask_qty_at_tick(price_tick);
+        FusedHashMapMarketDepth::update_best_ask(
+            self,
+            depth_event(SELL_EVENT | DEPTH_BBO_EVENT, price, qty, timestamp),
+        );
+        Some((
+            price_tick,
+            prev_best_ask_tick,
+            self.best_ask_tick,
+            prev_qty,
+            self.ask_qty_at_tick(price_tick),
+            timestamp,
+        ))
+    }
+}
+
 impl ApplySnapshot for FusedHashMapMarketDepth {
     fn apply_snapshot(&mut self, data: &Data<Event>) {
+        self.timestamp = 0;
         self.best_bid_tick = INVALID_MIN;
         self.best_ask_tick = INVALID_MAX;
         self.low_bid_tick = INVALID_MAX;
@@ -573,6 +701,7 @@ impl ApplySnapshot for FusedHashMapMarketDepth {
             let price = data[row_num].px;
             let qty = data[row_num].qty;
             let ts = data[row_num].exch_ts;
+            self.timestamp = self.timestamp.max(ts);
 
             let price_tick = (price / self.tick_size).round() as i64;
             if data[row_num].ev & BUY_EVENT == BUY_EVENT {
@@ -604,8 +733,7 @@ impl ApplySnapshot for FusedHashMapMarketDepth {
             events.push(Event {
                 ev: EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_SNAPSHOT_EVENT,
                 exch_ts: qty_ts.ts,
-                // todo: it's not a problem now, but it would be better to have valid timestamps.
-                local_ts: 0,
+                local_ts: qty_ts.ts,
                 px: px_tick as f64 * self.tick_size,
                 qty: qty_ts.qty,
                 order_id: 0,
@@ -624,8 +752,7 @@ impl ApplySnapshot for FusedHashMapMarketDepth {
             events.push(Event {
                 ev: EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_SNAPSHOT_EVENT,
                 exch_ts: qty_ts.ts,
-                // todo: it's not a problem now, but it would be better to have valid timestamps.
-                local_ts: 0,
+                local_ts: qty_ts.ts,
                 px: px_tick as f64 * self.tick_size,
                 qty: qty_ts.qty,
                 order_id: 0,
@@ -693,6 +820,11 @@ impl MarketDepth for FusedHashMapMarketDepth {
         self.lot_size
     }
 
+    #[inline(always)]
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
     #[inline(always)]
     fn bid_qty_at_tick(&self, price_tick: i64) -> f64 {
         self.bid_depth
@@ -713,8 +845,8 @@ impl MarketDepth for FusedHashMapMarketDepth {
 #[cfg(test)]
 mod tests {
     use crate::{
-        depth::{FusedHashMapMarketDepth, MarketDepth},
-        types::{BUY_EVENT, DEPTH_EVENT, Event, SELL_EVENT},
+        depth::{FusedHashMapMarketDepth, INVALID_MIN, L2MarketDepth, MarketDepth},
+        types::{BUY_EVENT, DEPTH_EVENT, Event, SELL_EVENT, Side},
     };
 
     #[test]
@@ -1358,4 +1490,35 @@ mod tests {
         assert_eq!(depth.low_bid_tick, 100);
         assert_eq!(depth.high_ask_tick, 104);
     }
+
+    #[test]
+    fn test_l2_market_depth() {
+        let mut depth = FusedHashMapMarketDepth::new(1.0, 1.0);
+
+        assert_eq!(
+            L2MarketDepth::update_bid_depth(&mut depth, 100.0, 5.0, 10),
+            (100, INVALID_MIN, 100, 0.0, 5.0, 10)
+        );
+        // An outdated update from a slower stream is discarded.
+        assert_eq!(
+            L2MarketDepth::update_bid_depth(&mut depth, 100.0, 3.0, 5),
+            (100, 100, 100, 5.0, 5.0, 5)
+        );
+
+        assert_eq!(
+            L2MarketDepth::update_best_bid(&mut depth, 101.0, 2.0, 20),
+            Some((101, 100, 101, 0.0, 2.0, 20))
+        );
+        // The best bid backoff removes the price levels above it.
+        assert_eq!(
+            L2MarketDepth::update_best_bid(&mut depth, 99.0, 1.0, 30),
+            Some((99, 101, 99, 0.0, 1.0, 30))
+        );
+        assert_eq!(depth.bid_qty_at_tick(100), 0.0);
+        assert_eq!(depth.bid_qty_at_tick(101), 0.0);
+        assert_eq!(depth.timestamp(), 30);
+
+        L2MarketDepth::clear_depth(&mut depth, Side::None, 0.0);
+        assert_eq!(depth.best_bid_tick(), INVALID_MIN);
+    }
 }
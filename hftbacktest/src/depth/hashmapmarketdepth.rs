@@ -1,6 +1,14 @@
 use std::collections::{HashMap, hash_map::Entry};
 
-use super::{ApplySnapshot, INVALID_MAX, INVALID_MIN, L3MarketDepth, L3Order, MarketDepth};
+use super::{
+    ApplySnapshot,
+    INVALID_MAX,
+    INVALID_MIN,
+    L3MarketDepth,
+    L3Order,
+    LevelTimestamp,
+    MarketDepth,
+};
 use crate::{
     backtest::{BacktestError, data::Data},
     prelude::{L2MarketDepth, OrderId, Side},
@@ -29,6 +37,10 @@ pub struct HashMapMarketDepth {
     pub low_bid_tick: i64,
     pub high_ask_tick: i64,
     pub orders: HashMap<OrderId, L3Order>,
+    // The timestamps at which the price levels were first seen. An entry is overwritten when its
+    // level is created again, so it's valid only while the level exists in the depth.
+    bid_first_seen: HashMap<i64, i64>,
+    ask_first_seen: HashMap<i64, i64>,
 }
 
 #[inline(always)]
@@ -65,6 +77,8 @@ impl HashMapMarketDepth {
             low_bid_tick: INVALID_MAX,
             high_ask_tick: INVALID_MIN,
             orders: HashMap::new(),
+            bid_first_seen: HashMap::new(),
+            ask_first_seen: HashMap::new(),
         }
     }
 
@@ -74,8 +88,16 @@ impl HashMapMarketDepth {
             Entry::Vacant(entry) => entry.insert(order),
         };
         if order.side == Side::Buy {
+            if !self.bid_depth.contains_key(&order.price_tick) {
+                self.bid_first_seen
+                    .insert(order.price_tick, order.timestamp);
+            }
             *self.bid_depth.entry(order.price_tick).or_insert(0.0) += order.qty;
         } else {
+            if !self.ask_depth.contains_key(&order.price_tick) {
+                self.ask_first_seen
+                    .insert(order.price_tick, order.timestamp);
+            }
             *self.ask_depth.entry(order.price_tick).or_insert(0.0) += order.qty;
         }
         Ok(())
@@ -89,6 +111,7 @@ impl L2MarketDepth for HashMapMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
         let price_tick = (price / self.tick_size).round() as i64;
         let qty_lot = (qty / self.lot_size).round() as i64;
         let prev_best_bid_tick = self.best_bid_tick;
@@ -106,6 +129,7 @@ impl L2MarketDepth for HashMapMarketDepth {
                 prev_qty = 0f64;
                 if qty_lot > 0 {
                     entry.insert(qty);
+                    self.bid_first_seen.insert(price_tick, timestamp);
                 }
             }
         }
@@ -144,6 +168,7 @@ impl L2MarketDepth for HashMapMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
         let price_tick = (price / self.tick_size).round() as i64;
         let qty_lot = (qty / self.lot_size).round() as i64;
         let prev_best_ask_tick = self.best_ask_tick;
@@ -161,6 +186,7 @@ impl L2MarketDepth for HashMapMarketDepth {
                 prev_qty = 0f64;
                 if qty_lot > 0 {
                     entry.insert(qty);
+                    self.ask_first_seen.insert(price_tick, timestamp);
                 }
             }
         }
@@ -209,6 +235,7 @@ impl L2MarketDepth for HashMapMarketDepth {
                         depth_below(&self.bid_depth, clear_upto - 1, self.low_bid_tick);
                 } else {
                     self.bid_depth.clear();
+                    self.bid_first_seen.clear();
                     self.best_bid_tick = INVALID_MIN;
                 }
                 if self.best_bid_tick == INVALID_MIN {
@@ -229,6 +256,7 @@ impl L2MarketDepth for HashMapMarketDepth {
                         depth_above(&self.ask_depth, clear_upto + 1, self.high_ask_tick);
                 } else {
                     self.ask_depth.clear();
+                    self.ask_first_seen.clear();
                     self.best_ask_tick = INVALID_MAX;
                 }
                 if self.best_ask_tick == INVALID_MAX {
@@ -238,6 +266,8 @@ impl L2MarketDepth for HashMapMarketDepth {
             Side::None => {
                 self.bid_depth.clear();
                 self.ask_depth.clear();
+                self.bid_first_seen.clear();
+                self.ask_first_seen.clear();
                 self.best_bid_tick = INVALID_MIN;
                 self.best_ask_tick = INVALID_MAX;
                 self.low_bid_tick = INVALID_MAX;
@@ -299,6 +329,11 @@ impl MarketDepth for HashMapMarketDepth {
         self.lot_size
     }
 
+    #[inline(always)]
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
     #[inline(always)]
     fn bid_qty_at_tick(&self, price_tick: i64) -> f64 {
         *self.bid_depth.get(&price_tick).unwrap_or(&0.0)
@@ -312,25 +347,33 @@ impl MarketDepth for HashMapMarketDepth {
 
 impl ApplySnapshot for HashMapMarketDepth {
     fn apply_snapshot(&mut self, data: &Data<Event>) {
+        self.timestamp = 0;
         self.best_bid_tick = INVALID_MIN;
         self.best_ask_tick = INVALID_MAX;
         self.low_bid_tick = INVALID_MAX;
         self.high_ask_tick = INVALID_MIN;
         self.bid_depth.clear();
         self.ask_depth.clear();
+        self.bid_first_seen.clear();
+        self.ask_first_seen.clear();
         for row_num in 0..data.len() {
             let price = data[row_num].px;
             let qty = data[row_num].qty;
+            self.timestamp = self.timestamp.max(data[row_num].exch_ts);
 
             let price_tick = (price / self.tick_size).round() as i64;
             if data[row_num].ev & BUY_EVENT == BUY_EVENT {
                 self.best_bid_tick = self.best_bid_tick.max(price_tick);
                 self.low_bid_tick = self.low_bid_tick.min(price_tick);
                 *self.bid_depth.entry(price_tick).or_insert(0f64) = qty;
+                self.bid_first_seen
+                    .insert(price_tick, data[row_num].exch_ts);
             } else if data[row_num].ev & SELL_EVENT == SELL_EVENT {
                 self.best_ask_tick = self.best_ask_tick.min(price_tick);
                 self.high_ask_tick = self.high_ask_tick.max(price_tick);
                 *self.ask_depth.entry(price_tick).or_insert(0f64) = qty;
+                self.ask_first_seen
+                    .insert(price_tick, data[row_num].exch_ts);
             }
         }
     }
@@ -348,9 +391,8 @@ impl ApplySnapshot for HashMapMarketDepth {
         for (px_tick, qty) in bid_depth {
             events.push(Event {
                 ev: EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_SNAPSHOT_EVENT,
-                // todo: it's not a problem now, but it would be better to have valid timestamps.
-                exch_ts: 0,
-                local_ts: 0,
+                exch_ts: self.timestamp,
+                local_ts: self.timestamp,
                 px: px_tick as f64 * self.tick_size,
                 qty,
                 order_id: 0,
@@ -369,9 +411,8 @@ impl ApplySnapshot for HashMapMarketDepth {
         for (px_tick, qty) in ask_depth {
             events.push(Event {
                 ev: EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_SNAPSHOT_EVENT,
-                // todo: it's not a problem now, but it would be better to have valid timestamps.
-                exch_ts: 0,
-                local_ts: 0,
+                exch_ts: self.timestamp,
+                local_ts: self.timestamp,
                 px: px_tick as f64 * self.tick_size,
                 qty,
                 order_id: 0,
@@ -384,6 +425,24 @@ impl ApplySnapshot for HashMapMarketDepth {
     }
 }
 
+impl LevelTimestamp for HashMapMarketDepth {
+    fn bid_first_seen_at_tick(&self, price_tick: i64) -> Option<i64> {
+        if self.bid_depth.contains_key(&price_tick) {
+            self.bid_first_seen.get(&price_tick).copied()
+        } else {
+            None
+        }
+    }
+
+    fn ask_first_seen_at_tick(&self, price_tick: i64) -> Option<i64> {
+        if self.ask_depth.contains_key(&price_tick) {
+            self.ask_first_seen.get(&price_tick).copied()
+        } else {
+            None
+        }
+    }
+}
+
 impl L3MarketDepth for HashMapMarketDepth {
     type Error = BacktestError;
 
@@ -394,6 +453,7 @@ impl L3MarketDepth for HashMapMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let price_tick = (px / self.tick_size).round() as i64;
         self.add(L3Order {
             order_id,
@@ -421,6 +481,7 @@ impl L3MarketDepth for HashMapMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let price_tick = (px / self.tick_size).round() as i64;
         self.add(L3Order {
             order_id,
@@ -444,8 +505,9 @@ impl L3MarketDepth for HashMapMarketDepth {
     fn delete_order(
         &mut self,
         order_id: OrderId,
-        _timestamp: i64,
+        timestamp: i64,
     ) -> Result<(Side, i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let order = self
             .orders
             .remove(&order_id)
@@ -492,6 +554,7 @@ impl L3MarketDepth for HashMapMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(Side, i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let order = self
             .orders
             .get_mut(&order_id)
@@ -517,6 +580,9 @@ impl L3MarketDepth for HashMapMarketDepth {
                 order.qty = qty;
                 order.timestamp = timestamp;
 
+                if !self.bid_depth.contains_key(&price_tick) {
+                    self.bid_first_seen.insert(price_tick, timestamp);
+                }
                 *self.bid_depth.entry(order.price_tick).or_insert(0.0) += order.qty;
 
                 if price_tick > self.best_bid_tick {
@@ -555,6 +621,9 @@ impl L3MarketDepth for HashMapMarketDepth {
                 order.qty = qty;
                 order.timestamp = timestamp;
 
+                if !self.ask_depth.contains_key(&price_tick) {
+                    self.ask_first_seen.insert(price_tick, timestamp);
+                }
                 *self.ask_depth.entry(order.price_tick).or_insert(0.0) += order.qty;
 
                 if price_tick < self.best_ask_tick {
@@ -614,12 +683,24 @@ impl L3MarketDepth for HashMapMarketDepth {
     fn orders(&self) -> &HashMap<OrderId, L3Order> {
         &self.orders
     }
+
+    fn set_timestamp(&mut self, timestamp: i64) {
+        self.timestamp = timestamp;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        depth::{HashMapMarketDepth, INVALID_MAX, INVALID_MIN, L3MarketDepth, MarketDepth},
+        depth::{
+            HashMapMarketDepth,
+            INVALID_MAX,
+            INVALID_MIN,
+            L2MarketDepth,
+            L3MarketDepth,
+            LevelTimestamp,
+            MarketDepth,
+        },
         types::Side,
     };
 
@@ -843,4 +924,24 @@ mod tests {
         assert_eq_qty!(depth.ask_qty_at_tick(4981), 0.0, lot_size);
         assert_eq_qty!(depth.ask_qty_at_tick(5002), 0.002, lot_size);
     }
+    #[test]
+    fn test_level_first_seen() {
+        let mut depth = HashMapMarketDepth::new(0.1, 1.0);
+        depth.update_bid_depth(100.0, 5.0, 10);
+        depth.update_bid_depth(100.0, 3.0, 20);
+        assert_eq!(depth.bid_first_seen_at_tick(1000), Some(10));
+
+        // The first-seen timestamp is reset once the level empties.
+        depth.update_bid_depth(100.0, 0.0, 30);
+        assert_eq!(depth.bid_first_seen_at_tick(1000), None);
+        depth.update_bid_depth(100.0, 2.0, 40);
+        assert_eq!(depth.bid_first_seen_at_tick(1000), Some(40));
+
+        depth.add_sell_order(1, 100.1, 1.0, 50).unwrap();
+        depth.add_sell_order(2, 100.1, 1.0, 60).unwrap();
+        assert_eq!(depth.ask_first_seen_at_tick(1001), Some(50));
+        depth.delete_order(1, 70).unwrap();
+        depth.delete_order(2, 80).unwrap();
+        assert_eq!(depth.ask_first_seen_at_tick(1001), None);
+    }
 }
@@ -1,15 +1,21 @@
 use std::collections::HashMap;
 
+pub use aggregated::AggregatedMarketDepth;
+pub use analytics::DepthAnalytics;
 pub use btreemarketdepth::BTreeMarketDepth;
 pub use fuse::FusedHashMapMarketDepth;
 pub use hashmapmarketdepth::HashMapMarketDepth;
+pub use ringbuffermarketdepth::RingBufferMarketDepth;
 pub use roivectormarketdepth::ROIVectorMarketDepth;
 
 use crate::prelude::Side;
 
+mod aggregated;
+mod analytics;
 mod btreemarketdepth;
 mod fuse;
 mod hashmapmarketdepth;
+mod ringbuffermarketdepth;
 mod roivectormarketdepth;
 
 use crate::{
@@ -53,6 +59,12 @@ pub trait MarketDepth {
     /// Returns the lot size.
     fn lot_size(&self) -> f64;
 
+    /// Returns the timestamp of the last update to the market depth. The default implementation
+    /// returns `0` for a market depth that doesn't track it.
+    fn timestamp(&self) -> i64 {
+        0
+    }
+
     /// Returns the quantity at the bid market depth for a given price in ticks.
     fn bid_qty_at_tick(&self, price_tick: i64) -> f64;
 
@@ -91,6 +103,38 @@ pub trait L2MarketDepth {
     /// Clears the market depth. If the side is [Side::None], both sides are cleared. In this case,
     /// `clear_upto_price` is ignored.
     fn clear_depth(&mut self, side: Side, clear_upto_price: f64);
+
+    /// Returns `true` if the market depth determines which update is more recent by the exchange
+    /// timestamp, in which case the local processor passes the exchange timestamp of the feed
+    /// instead of the local timestamp. The default is `false`.
+    fn uses_exch_timestamp(&self) -> bool {
+        false
+    }
+
+    /// Updates the best bid from the BBO feed and returns the same tuple as
+    /// [`update_bid_depth`](L2MarketDepth::update_bid_depth). Returns `None` if the market depth
+    /// doesn't take the BBO feed, which is the default, since the BBO feed alone cannot tell which
+    /// price levels are removed.
+    fn update_best_bid(
+        &mut self,
+        _price: f64,
+        _qty: f64,
+        _timestamp: i64,
+    ) -> Option<(i64, i64, i64, f64, f64, i64)> {
+        None
+    }
+
+    /// Updates the best ask from the BBO feed and returns the same tuple as
+    /// [`update_ask_depth`](L2MarketDepth::update_ask_depth). Returns `None` if the market depth
+    /// doesn't take the BBO feed, which is the default.
+    fn update_best_ask(
+        &mut self,
+        _price: f64,
+        _qty: f64,
+        _timestamp: i64,
+    ) -> Option<(i64, i64, i64, f64, f64, i64)> {
+        None
+    }
 }
 
 /// Provides a method to initialize the `MarketDepth` from the given snapshot data, such as
@@ -103,8 +147,21 @@ pub trait ApplySnapshot {
     fn snapshot(&self) -> Vec<Event>;
 }
 
+/// Provides the timestamp at which each price level was first seen, which is reset when the level
+/// empties, so that the age of the price level can be measured.
+pub trait LevelTimestamp {
+    /// Returns the timestamp at which the bid price level, in ticks, was first seen since it was
+    /// last empty. If the level is empty, it returns `None`.
+    fn bid_first_seen_at_tick(&self, price_tick: i64) -> Option<i64>;
+
+    /// Returns the timestamp at which the ask price level, in ticks, was first seen since it was
+    /// last empty. If the level is empty, it returns `None`.
+    fn ask_first_seen_at_tick(&self, price_tick: i64) -> Option<i64>;
+}
+
 /// Level3 order from the market feed.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[repr(C)]
 pub struct L3Order {
     pub order_id: OrderId,
     pub side: Side,
@@ -159,6 +216,10 @@ pub trait L3MarketDepth: MarketDepth {
 
     /// Returns the orders held in the order book.
     fn orders(&self) -> &HashMap<OrderId, L3Order>;
+
+    /// Sets the timestamp of the last update to the market depth, such as when the market depth is
+    /// restored. The default implementation does nothing for a market depth that doesn't track it.
+    fn set_timestamp(&mut self, _timestamp: i64) {}
 }
 
 /// Provides Level1-specific market depth functions.
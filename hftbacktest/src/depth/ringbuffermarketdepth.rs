@@ -0,0 +1,861 @@
+use super::{ApplySnapshot, INVALID_MAX, INVALID_MIN, L2MarketDepth, MarketDepth};
+use crate::{
+    backtest::data::Data,
+    types::{BUY_EVENT, DEPTH_SNAPSHOT_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT, Side},
+};
+
+const MIN_CAPACITY: usize = 64 * 64;
+const DEFAULT_MAX_CAPACITY: usize = 1 << 20;
+
+/// Returns the highest set bit within `lo..=hi`.
+#[inline(always)]
+fn highest_set(bits: &[u64], lo: usize, hi: usize) -> Option<usize> {
+    let (lw, hw) = (lo >> 6, hi >> 6);
+    let mut w = hw;
+    loop {
+        let mut word = bits[w];
+        if w == hw {
+            word &= u64::MAX >> (63 - (hi & 63));
+        }
+        if w == lw {
+            word &= u64::MAX << (lo & 63);
+        }
+        if word != 0 {
+            return Some((w << 6) + 63 - word.leading_zeros() as usize);
+        }
+        if w == lw {
+            return None;
+        }
+        w -= 1;
+    }
+}
+
+/// Returns the lowest set bit within `lo..=hi`.
+#[inline(always)]
+fn lowest_set(bits: &[u64], lo: usize, hi: usize) -> Option<usize> {
+    let (lw, hw) = (lo >> 6, hi >> 6);
+    let mut w = lw;
+    loop {
+        let mut word = bits[w];
+        if w == lw {
+            word &= u64::MAX << (lo & 63);
+        }
+        if w == hw {
+            word &= u64::MAX >> (63 - (hi & 63));
+        }
+        if word != 0 {
+            return Some((w << 6) + word.trailing_zeros() as usize);
+        }
+        if w == hw {
+            return None;
+        }
+        w += 1;
+    }
+}
+
+/// The quantities on one side of the book, indexed by the slot in the ring buffer. Occupancy is
+/// tracked by a two-level bitmap, one bit per price level and one bit per 64 price levels, so the
+/// next occupied level is found by a few bit scans regardless of the distance.
+struct Levels {
+    qty: Vec<f64>,
+    occupied: Vec<u64>,
+    summary: Vec<u64>,
+}
+
+impl Levels {
+    fn new(capacity: usize) -> Self {
+        Self {
+            qty: vec![0.0; capacity],
+            occupied: vec![0; capacity / 64],
+            summary: vec![0; capacity.div_ceil(64 * 64)],
+        }
+    }
+
+    #[inline(always)]
+    fn set(&mut self, slot: usize, qty: f64) {
+        self.qty[slot] = qty;
+        let w = slot >> 6;
+        self.occupied[w] |= 1 << (slot & 63);
+        self.summary[w >> 6] |= 1 << (w & 63);
+    }
+
+    #[inline(always)]
+    fn remove(&mut self, slot: usize) {
+        self.qty[slot] = 0.0;
+        let w = slot >> 6;
+        self.occupied[w] &= !(1 << (slot & 63));
+        if self.occupied[w] == 0 {
+            self.summary[w >> 6] &= !(1 << (w & 63));
+        }
+    }
+
+    fn clear(&mut self) {
+        self.qty.fill(0.0);
+        self.occupied.fill(0);
+        self.summary.fill(0);
+    }
+
+    /// Returns the highest occupied slot within `lo..=hi`.
+    #[inline(always)]
+    fn highest(&self, lo: usize, hi: usize) -> Option<usize> {
+        let (lw, hw) = (lo >> 6, hi >> 6);
+        if lw == hw {
+            return highest_set(&self.occupied, lo, hi);
+        }
+        highest_set(&self.occupied, hw << 6, hi)
+            .or_else(|| {
+                if hw > lw + 1 {
+                    highest_set(&self.summary, lw + 1, hw - 1)
+                        .and_then(|w| highest_set(&self.occupied, w << 6, (w << 6) + 63))
+                } else {
+                    None
+                }
+            })
+            .or_else(|| highest_set(&self.occupied, lo, (lw << 6) + 63))
+    }
+
+    /// Returns the lowest occupied slot within `lo..=hi`.
+    #[inline(always)]
+    fn lowest(&self, lo: usize, hi: usize) -> Option<usize> {
+        let (lw, hw) = (lo >> 6, hi >> 6);
+        if lw == hw {
+            return lowest_set(&self.occupied, lo, hi);
+        }
+        lowest_set(&self.occupied, lo, (lw << 6) + 63)
+            .or_else(|| {
+                if hw > lw + 1 {
+                    lowest_set(&self.summary, lw + 1, hw - 1)
+                        .and_then(|w| lowest_set(&self.occupied, w << 6, (w << 6) + 63))
+                } else {
+                    None
+                }
+            })
+            .or_else(|| lowest_set(&self.occupied, hw << 6, hi))
+    }
+}
+
+/// L2 market depth implementation based on a ring buffer with bitmap occupancy indexes.
+///
+/// Like [`ROIVectorMarketDepth`](crate::depth::ROIVectorMarketDepth), the quantities are stored in
+/// vectors indexed by the price in ticks, but the covered range follows the market: it is
+/// re-centered on the mid-price when an update falls outside of it, and the buffer grows when the
+/// book no longer fits, up to [`max_capacity`](Self::max_capacity). Beyond that, the levels
+/// farthest from the best bid and ask are dropped, like the levels outside the range of interest
+/// of `ROIVectorMarketDepth`. The best bid and ask are recomputed by bit scans over the occupancy
+/// bitmaps instead of tick-by-tick scans.
+pub struct RingBufferMarketDepth {
+    pub tick_size: f64,
+    pub lot_size: f64,
+    pub timestamp: i64,
+    pub best_bid_tick: i64,
+    pub best_ask_tick: i64,
+    bid_depth: Levels,
+    ask_depth: Levels,
+    lb_tick: i64,
+    mask: i64,
+    max_capacity: usize,
+}
+
+impl RingBufferMarketDepth {
+    /// Constructs an instance of `RingBufferMarketDepth`.
+    pub fn new(tick_size: f64, lot_size: f64) -> Self {
+        Self {
+            tick_size,
+            lot_size,
+            timestamp: 0,
+            best_bid_tick: INVALID_MIN,
+            best_ask_tick: INVALID_MAX,
+            bid_depth: Levels::new(MIN_CAPACITY),
+            ask_depth: Levels::new(MIN_CAPACITY),
+            lb_tick: 0,
+            mask: MIN_CAPACITY as i64 - 1,
+            max_capacity: DEFAULT_MAX_CAPACITY,
+        }
+    }
+
+    /// Sets the maximum number of price levels the ring buffer can cover, rounded up to a power
+    /// of two. The default is 2^20.
+    pub fn max_capacity(self, max_capacity: usize) -> Self {
+        Self {
+            max_capacity: max_capacity.max(MIN_CAPACITY).next_power_of_two(),
+            ..self
+        }
+    }
+
+    /// Returns the lower and the upper bound of the price range currently covered by the ring
+    /// buffer, in ticks.
+    pub fn range_tick(&self) -> (i64, i64) {
+        (self.lb_tick, self.ub_tick())
+    }
+
+    #[inline(always)]
+    fn ub_tick(&self) -> i64 {
+        self.lb_tick + self.mask
+    }
+
+    #[inline(always)]
+    fn contains(&self, price_tick: i64) -> bool {
+        price_tick >= self.lb_tick && price_tick <= self.ub_tick()
+    }
+
+    #[inline(always)]
+    fn slot(&self, price_tick: i64) -> usize {
+        (price_tick & self.mask) as usize
+    }
+
+    /// Returns the highest occupied price in ticks within `lo..=hi`, which must be in the range.
+    #[inline(always)]
+    fn highest_tick(&self, levels: &Levels, lo: i64, hi: i64) -> i64 {
+        if lo > hi {
+            return INVALID_MIN;
+        }
+        let (sl, sh) = (self.slot(lo), self.slot(hi));
+        let found = if sl <= sh {
+            levels.highest(sl, sh)
+        } else {
+            levels
+                .highest(0, sh)
+                .or_else(|| levels.highest(sl, self.mask as usize))
+        };
+        found
+            .map(|s| hi - ((sh as i64 - s as i64) & self.mask))
+            .unwrap_or(INVALID_MIN)
+    }
+
+    /// Returns the lowest occupied price in ticks within `lo..=hi`, which must be in the range.
+    #[inline(always)]
+    fn lowest_tick(&self, levels: &Levels, lo: i64, hi: i64) -> i64 {
+        if lo > hi {
+            return INVALID_MAX;
+        }
+        let (sl, sh) = (self.slot(lo), self.slot(hi));
+        let found = if sl <= sh {
+            levels.lowest(sl, sh)
+        } else {
+            levels
+                .lowest(sl, self.mask as usize)
+                .or_else(|| levels.lowest(0, sh))
+        };
+        found
+            .map(|s| lo + ((s as i64 - sl as i64) & self.mask))
+            .unwrap_or(INVALID_MAX)
+    }
+
+    /// Makes the range cover the given price, re-centering it on the mid-price and growing the
+    /// buffer if the book doesn't fit. Once the buffer reaches the maximum capacity, the range is
+    /// re-centered on the best bid and ask, given as they will be after the update, and the levels
+    /// falling outside of it are dropped. Returns `false` if the price is still out of the range.
+    fn ensure_range(&mut self, price_tick: i64, best_bid_tick: i64, best_ask_tick: i64) -> bool {
+        if self.contains(price_tick) {
+            return true;
+        }
+
+        let (lb, ub) = self.range_tick();
+        let mut low = price_tick;
+        let mut high = price_tick;
+        for tick in [
+            self.lowest_tick(&self.bid_depth, lb, ub),
+            self.lowest_tick(&self.ask_depth, lb, ub),
+        ] {
+            if tick != INVALID_MAX {
+                low = low.min(tick);
+            }
+        }
+        for tick in [
+            self.highest_tick(&self.bid_depth, lb, ub),
+            self.highest_tick(&self.ask_depth, lb, ub),
+        ] {
+            if tick != INVALID_MIN {
+                high = high.max(tick);
+            }
+        }
+
+        let span = high - low + 1;
+        if span > self.mask + 1 && self.mask + 1 < self.max_capacity as i64 {
+            self.grow(((2 * span) as usize).min(self.max_capacity));
+        }
+
+        let capacity = self.mask + 1;
+        let center = match (best_bid_tick != INVALID_MIN, best_ask_tick != INVALID_MAX) {
+            (true, true) if best_ask_tick - best_bid_tick < capacity => {
+                (best_bid_tick + best_ask_tick) / 2
+            }
+            (true, false) => best_bid_tick,
+            (false, true) => best_ask_tick,
+            _ => price_tick,
+        };
+        if span <= capacity {
+            // Every occupied level stays within the new range, so the slots leaving the range are
+            // already empty and nothing needs to be moved.
+            self.lb_tick = (center - capacity / 2).min(low).max(high - capacity + 1);
+        } else {
+            self.shift_range(center - capacity / 2);
+        }
+        self.contains(price_tick)
+    }
+
+    /// Moves the range to start at `lb_tick`, dropping the levels falling outside of it.
+    fn shift_range(&mut self, lb_tick: i64) {
+        let (lb, ub) = self.range_tick();
+        let (new_lb, new_ub) = (lb_tick, lb_tick + self.mask);
+        for (lo, hi) in [(lb, ub.min(new_lb - 1)), (lb.max(new_ub + 1), ub)] {
+            let mut t = self.lowest_tick(&self.bid_depth, lo, hi);
+            while t != INVALID_MAX {
+                let slot = self.slot(t);
+                self.bid_depth.remove(slot);
+                t = self.lowest_tick(&self.bid_depth, t + 1, hi);
+            }
+            let mut t = self.lowest_tick(&self.ask_depth, lo, hi);
+            while t != INVALID_MAX {
+                let slot = self.slot(t);
+                self.ask_depth.remove(slot);
+                t = self.lowest_tick(&self.ask_depth, t + 1, hi);
+            }
+        }
+        self.lb_tick = lb_tick;
+        let (lb, ub) = self.range_tick();
+        if !self.contains(self.best_bid_tick) {
+            self.best_bid_tick = self.highest_tick(&self.bid_depth, lb, ub);
+        }
+        if !self.contains(self.best_ask_tick) {
+            self.best_ask_tick = self.lowest_tick(&self.ask_depth, lb, ub);
+        }
+    }
+
+    fn grow(&mut self, capacity: usize) {
+        let capacity = capacity.next_power_of_two();
+        let (lb, ub) = self.range_tick();
+        let mut bid_depth = Levels::new(capacity);
+        let mut ask_depth = Levels::new(capacity);
+        let mask = capacity as i64 - 1;
+        for t in lb..=ub {
+            let slot = self.slot(t);
+            let new_slot = (t & mask) as usize;
+            let qty = self.bid_depth.qty[slot];
+            if qty > 0.0 {
+                bid_depth.set(new_slot, qty);
+            }
+            let qty = self.ask_depth.qty[slot];
+            if qty > 0.0 {
+                ask_depth.set(new_slot, qty);
+            }
+        }
+        self.bid_depth = bid_depth;
+        self.ask_depth = ask_depth;
+        self.mask = mask;
+    }
+}
+
+impl L2MarketDepth for RingBufferMarketDepth {
+    fn update_bid_depth(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
+        let price_tick = (price / self.tick_size).round() as i64;
+        let qty_lot = (qty / self.lot_size).round() as i64;
+        let prev_best_bid_tick = self.best_bid_tick;
+        let mut prev_qty = 0.0;
+        if qty_lot > 0 {
+            if !self.ensure_range(
+                price_tick,
+                self.best_bid_tick.max(price_tick),
+                self.best_ask_tick,
+            ) {
+                // This is too far from the best bid and ask to be kept.
+                return (
+                    price_tick,
+                    prev_best_bid_tick,
+                    self.best_bid_tick,
+                    0.0,
+                    qty,
+                    timestamp,
+                );
+            }
+            let slot = self.slot(price_tick);
+            prev_qty = self.bid_depth.qty[slot];
+            self.bid_depth.set(slot, qty);
+        } else if self.contains(price_tick) {
+            let slot = self.slot(price_tick);
+            prev_qty = self.bid_depth.qty[slot];
+            self.bid_depth.remove(slot);
+        }
+
+        if qty_lot == 0 {
+            if price_tick == self.best_bid_tick {
+                self.best_bid_tick =
+                    self.highest_tick(&self.bid_depth, self.lb_tick, price_tick - 1);
+            }
+        } else if price_tick > self.best_bid_tick {
+            self.best_bid_tick = price_tick;
+            if self.best_bid_tick >= self.best_ask_tick {
+                self.best_ask_tick =
+                    self.lowest_tick(&self.ask_depth, self.best_bid_tick + 1, self.ub_tick());
+            }
+        }
+        (
+            price_tick,
+            prev_best_bid_tick,
+            self.best_bid_tick,
+            prev_qty,
+            qty,
+            timestamp,
+        )
+    }
+
+    fn update_ask_depth(
+        &mut self,
+        price: f64,
+        qty: f64,
+        timestamp: i64,
+    ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
+        let price_tick = (price / self.tick_size).round() as i64;
+        let qty_lot = (qty / self.lot_size).round() as i64;
+        let prev_best_ask_tick = self.best_ask_tick;
+        let mut prev_qty = 0.0;
+        if qty_lot > 0 {
+            if !self.ensure_range(
+                price_tick,
+                self.best_bid_tick,
+                self.best_ask_tick.min(price_tick),
+            ) {
+                // This is too far from the best bid and ask to be kept.
+                return (
+                    price_tick,
+                    prev_best_ask_tick,
+                    self.best_ask_tick,
+                    0.0,
+                    qty,
+                    timestamp,
+                );
+            }
+            let slot = self.slot(price_tick);
+            prev_qty = self.ask_depth.qty[slot];
+            self.ask_depth.set(slot, qty);
+        } else if self.contains(price_tick) {
+            let slot = self.slot(price_tick);
+            prev_qty = self.ask_depth.qty[slot];
+            self.ask_depth.remove(slot);
+        }
+
+        if qty_lot == 0 {
+            if price_tick == self.best_ask_tick {
+                self.best_ask_tick =
+                    self.lowest_tick(&self.ask_depth, price_tick + 1, self.ub_tick());
+            }
+        } else if price_tick < self.best_ask_tick {
+            self.best_ask_tick = price_tick;
+            if self.best_bid_tick >= self.best_ask_tick {
+                self.best_bid_tick =
+                    self.highest_tick(&self.bid_depth, self.lb_tick, self.best_ask_tick - 1);
+            }
+        }
+        (
+            price_tick,
+            prev_best_ask_tick,
+            self.best_ask_tick,
+            prev_qty,
+            qty,
+            timestamp,
+        )
+    }
+
+    fn clear_depth(&mut self, side: Side, clear_upto_price: f64) {
+        match side {
+            Side::Buy => {
+                if clear_upto_price.is_finite() {
+                    let clear_upto = (clear_upto_price / self.tick_size).round() as i64;
+                    let lo = clear_upto.max(self.lb_tick);
+                    let mut t = self.best_bid_tick;
+                    while t != INVALID_MIN && t >= lo {
+                        let slot = self.slot(t);
+                        self.bid_depth.remove(slot);
+                        t = self.highest_tick(&self.bid_depth, lo, t - 1);
+                    }
+                    self.best_bid_tick = self.highest_tick(
+                        &self.bid_depth,
+                        self.lb_tick,
+                        (clear_upto - 1).min(self.ub_tick()),
+                    );
+                } else {
+                    self.bid_depth.clear();
+                    self.best_bid_tick = INVALID_MIN;
+                }
+            }
+            Side::Sell => {
+                if clear_upto_price.is_finite() {
+                    let clear_upto = (clear_upto_price / self.tick_size).round() as i64;
+                    let hi = clear_upto.min(self.ub_tick());
+                    let mut t = self.best_ask_tick;
+                    while t != INVALID_MAX && t <= hi {
+                        let slot = self.slot(t);
+                        self.ask_depth.remove(slot);
+                        t = self.lowest_tick(&self.ask_depth, t + 1, hi);
+                    }
+                    self.best_ask_tick = self.lowest_tick(
+                        &self.ask_depth,
+                        (clear_upto + 1).max(self.lb_tick),
+                        self.ub_tick(),
+                    );
+                } else {
+                    self.ask_depth.clear();
+                    self.best_ask_tick = INVALID_MAX;
+                }
+            }
+            Side::None => {
+                self.bid_depth.clear();
+                self.ask_depth.clear();
+                self.best_bid_tick = INVALID_MIN;
+                self.best_ask_tick = INVALID_MAX;
+            }
+            Side::Unsupported => {
+                unreachable!();
+            }
+        }
+    }
+}
+
+impl MarketDepth for RingBufferMarketDepth {
+    #[inline(always)]
+    fn best_bid(&self) -> f64 {
+        if self.best_bid_tick == INVALID_MIN {
+            f64::NAN
+        } else {
+            self.best_bid_tick as f64 * self.tick_size
+        }
+    }
+
+    #[inline(always)]
+    fn best_ask(&self) -> f64 {
+        if self.best_ask_tick == INVALID_MAX {
+            f64::NAN
+        } else {
+            self.best_ask_tick as f64 * self.tick_size
+        }
+    }
+
+    #[inline(always)]
+    fn best_bid_tick(&self) -> i64 {
+        self.best_bid_tick
+    }
+
+    #[inline(always)]
+    fn best_ask_tick(&self) -> i64 {
+        self.best_ask_tick
+    }
+
+    #[inline(always)]
+    fn best_bid_qty(&self) -> f64 {
+        self.bid_qty_at_tick(self.best_bid_tick)
+    }
+
+    #[inline(always)]
+    fn best_ask_qty(&self) -> f64 {
+        self.ask_qty_at_tick(self.best_ask_tick)
+    }
+
+    #[inline(always)]
+    fn tick_size(&self) -> f64 {
+        self.tick_size
+    }
+
+    #[inline(always)]
+    fn lot_size(&self) -> f64 {
+        self.lot_size
+    }
+
+    #[inline(always)]
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
+    #[inline(always)]
+    fn bid_qty_at_tick(&self, price_tick: i64) -> f64 {
+        if self.contains(price_tick) {
+            self.bid_depth.qty[self.slot(price_tick)]
+        } else {
+            0.0
+        }
+    }
+
+    #[inline(always)]
+    fn ask_qty_at_tick(&self, price_tick: i64) -> f64 {
+        if self.contains(price_tick) {
+            self.ask_depth.qty[self.slot(price_tick)]
+        } else {
+            0.0
+        }
+    }
+}
+
+impl ApplySnapshot for RingBufferMarketDepth {
+    fn apply_snapshot(&mut self, data: &Data<Event>) {
+        self.timestamp = 0;
+        self.bid_depth.clear();
+        self.ask_depth.clear();
+        self.best_bid_tick = INVALID_MIN;
+        self.best_ask_tick = INVALID_MAX;
+        for row_num in 0..data.len() {
+            let price = data[row_num].px;
+            let qty = data[row_num].qty;
+            self.timestamp = self.timestamp.max(data[row_num].exch_ts);
+
+            let price_tick = (price / self.tick_size).round() as i64;
+            if data[row_num].ev & BUY_EVENT == BUY_EVENT {
+                let best_bid_tick = self.best_bid_tick.max(price_tick);
+                if !self.ensure_range(price_tick, best_bid_tick, self.best_ask_tick) {
+                    continue;
+                }
+                let slot = self.slot(price_tick);
+                self.bid_depth.set(slot, qty);
+                self.best_bid_tick = self.best_bid_tick.max(price_tick);
+            } else if data[row_num].ev & SELL_EVENT == SELL_EVENT {
+                let best_ask_tick = self.best_ask_tick.min(price_tick);
+                if !self.ensure_range(price_tick, self.best_bid_tick, best_ask_tick) {
+                    continue;
+                }
+                let slot = self.slot(price_tick);
+                self.ask_depth.set(slot, qty);
+                self.best_ask_tick = self.best_ask_tick.min(price_tick);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        let mut t = self.best_bid_tick;
+        while t != INVALID_MIN {
+            events.push(Event {
+                ev: EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_SNAPSHOT_EVENT,
+                exch_ts: self.timestamp,
+                local_ts: self.timestamp,
+                px: t as f64 * self.tick_size,
+                qty: self.bid_qty_at_tick(t),
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            });
+            t = self.highest_tick(&self.bid_depth, self.lb_tick, t - 1);
+        }
+
+        let mut t = self.best_ask_tick;
+        while t != INVALID_MAX {
+            events.push(Event {
+                ev: EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_SNAPSHOT_EVENT,
+                exch_ts: self.timestamp,
+                local_ts: self.timestamp,
+                px: t as f64 * self.tick_size,
+                qty: self.ask_qty_at_tick(t),
+                order_id: 0,
+                ival: 0,
+                fval: 0.0,
+            });
+            t = self.lowest_tick(&self.ask_depth, t + 1, self.ub_tick());
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        backtest::data::Data,
+        depth::{
+            ApplySnapshot,
+            HashMapMarketDepth,
+            INVALID_MAX,
+            INVALID_MIN,
+            L2MarketDepth,
+            MarketDepth,
+            RingBufferMarketDepth,
+        },
+        types::Side,
+    };
+
+    #[test]
+    fn test_update_bid_depth() {
+        let mut depth = RingBufferMarketDepth::new(0.1, 0.01);
+
+        assert_eq!(
+            depth.update_bid_depth(100.0, 1.0, 0),
+            (1000, INVALID_MIN, 1000, 0.0, 1.0, 0)
+        );
+        depth.update_bid_depth(99.9, 2.0, 0);
+        // Far enough to be in a different bitmap summary word.
+        depth.update_bid_depth(10.0, 3.0, 0);
+        assert_eq!(depth.best_bid_tick(), 1000);
+
+        assert_eq!(
+            depth.update_bid_depth(100.0, 0.0, 1),
+            (1000, 1000, 999, 1.0, 0.0, 1)
+        );
+        depth.update_bid_depth(99.9, 0.0, 1);
+        assert_eq!(depth.best_bid_tick(), 100);
+        assert_eq!(depth.best_bid_qty(), 3.0);
+
+        depth.update_bid_depth(10.0, 0.0, 2);
+        assert_eq!(depth.best_bid_tick(), INVALID_MIN);
+        assert!(depth.best_bid().is_nan());
+    }
+
+    #[test]
+    fn test_update_ask_depth() {
+        let mut depth = RingBufferMarketDepth::new(0.1, 0.01);
+
+        depth.update_ask_depth(100.0, 1.0, 0);
+        depth.update_ask_depth(100.1, 2.0, 0);
+        depth.update_ask_depth(200.0, 3.0, 0);
+        assert_eq!(depth.best_ask_tick(), 1000);
+
+        assert_eq!(
+            depth.update_ask_depth(100.0, 0.0, 1),
+            (1000, 1000, 1001, 1.0, 0.0, 1)
+        );
+        depth.update_ask_depth(100.1, 0.0, 1);
+        assert_eq!(depth.best_ask_tick(), 2000);
+        assert_eq!(depth.best_ask_qty(), 3.0);
+
+        // The bid crossing the best ask moves the best ask up.
+        depth.update_ask_depth(200.1, 4.0, 2);
+        depth.update_bid_depth(200.0, 1.0, 2);
+        assert_eq!(depth.best_ask_tick(), 2001);
+    }
+
+    #[test]
+    fn test_recenter_and_grow() {
+        let mut depth = RingBufferMarketDepth::new(1.0, 1.0);
+
+        depth.update_bid_depth(10_000.0, 1.0, 0);
+        depth.update_ask_depth(10_001.0, 1.0, 0);
+        let (lb, ub) = depth.range_tick();
+        assert!(lb <= 10_000 && ub >= 10_001);
+
+        // The price drifts beyond the range, so the range follows it.
+        for i in 0..20_000 {
+            let px = 10_000.0 + i as f64;
+            depth.update_bid_depth(px, 1.0, i);
+            depth.update_bid_depth(px - 1.0, 0.0, i);
+            depth.update_ask_depth(px + 1.0, 1.0, i);
+            depth.update_ask_depth(px, 0.0, i);
+        }
+        assert_eq!(depth.best_bid_tick(), 29_999);
+        assert_eq!(depth.best_ask_tick(), 30_000);
+        assert_eq!(depth.range_tick().1 - depth.range_tick().0 + 1, 4096);
+
+        // The book no longer fits, so the buffer grows while keeping the levels.
+        depth.update_bid_depth(20_000.0, 2.0, 0);
+        assert!(depth.range_tick().1 - depth.range_tick().0 + 1 > 4096);
+        assert_eq!(depth.bid_qty_at_tick(20_000), 2.0);
+        assert_eq!(depth.bid_qty_at_tick(29_999), 1.0);
+        depth.update_bid_depth(29_999.0, 0.0, 0);
+        assert_eq!(depth.best_bid_tick(), 20_000);
+    }
+
+    #[test]
+    fn test_max_capacity() {
+        let mut depth = RingBufferMarketDepth::new(1.0, 1.0).max_capacity(8192);
+
+        depth.update_bid_depth(10_000.0, 1.0, 0);
+        depth.update_ask_depth(10_001.0, 1.0, 0);
+        depth.update_bid_depth(5_000.0, 2.0, 0);
+        assert_eq!(depth.range_tick().1 - depth.range_tick().0 + 1, 8192);
+        assert_eq!(depth.bid_qty_at_tick(5_000), 2.0);
+
+        // The book no longer fits within the maximum capacity, so the range is re-centered on the
+        // best bid and ask, and the farthest level is dropped.
+        depth.update_ask_depth(14_000.0, 3.0, 1);
+        let (lb, ub) = depth.range_tick();
+        assert_eq!(ub - lb + 1, 8192);
+        assert!(lb <= 10_000 && ub >= 14_000);
+        assert_eq!(depth.bid_qty_at_tick(5_000), 0.0);
+        assert_eq!(depth.ask_qty_at_tick(14_000), 3.0);
+        assert_eq!(depth.best_bid_tick(), 10_000);
+        assert_eq!(depth.best_ask_tick(), 10_001);
+
+        // An update too far from the best bid and ask is ignored.
+        assert_eq!(
+            depth.update_bid_depth(1_000.0, 4.0, 2),
+            (1_000, 10_000, 10_000, 0.0, 4.0, 2)
+        );
+        assert_eq!(depth.bid_qty_at_tick(1_000), 0.0);
+        assert_eq!(depth.snapshot().len(), 3);
+    }
+
+    #[test]
+    fn test_clear_depth() {
+        let mut depth = RingBufferMarketDepth::new(1.0, 1.0);
+        for t in 0..10 {
+            depth.update_bid_depth(100.0 - t as f64, 1.0, 0);
+            depth.update_ask_depth(101.0 + t as f64, 1.0, 0);
+        }
+
+        depth.clear_depth(Side::Buy, 97.0);
+        assert_eq!(depth.best_bid_tick(), 96);
+        assert_eq!(depth.bid_qty_at_tick(97), 0.0);
+
+        depth.clear_depth(Side::Sell, 104.0);
+        assert_eq!(depth.best_ask_tick(), 105);
+        assert_eq!(depth.ask_qty_at_tick(104), 0.0);
+
+        depth.clear_depth(Side::None, 0.0);
+        assert_eq!(depth.best_bid_tick(), INVALID_MIN);
+        assert_eq!(depth.best_ask_tick(), INVALID_MAX);
+        assert!(depth.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_same_as_hashmap_depth() {
+        let mut depth = RingBufferMarketDepth::new(1.0, 1.0);
+        let mut expected = HashMapMarketDepth::new(1.0, 1.0);
+
+        // A deterministic pseudo-random walk around the mid-price.
+        let mut seed = 42u64;
+        let mut next = || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            seed >> 33
+        };
+        let mut mid = 100_000i64;
+        for i in 0..100_000 {
+            mid += (next() % 5) as i64 - 2;
+            let offset = (next() % 300) as i64;
+            let qty = if next() % 3 == 0 {
+                0.0
+            } else {
+                (next() % 10) as f64
+            };
+            if next() % 2 == 0 {
+                let px = (mid - 1 - offset) as f64;
+                assert_eq!(
+                    depth.update_bid_depth(px, qty, i),
+                    expected.update_bid_depth(px, qty, i)
+                );
+            } else {
+                let px = (mid + 1 + offset) as f64;
+                assert_eq!(
+                    depth.update_ask_depth(px, qty, i),
+                    expected.update_ask_depth(px, qty, i)
+                );
+            }
+            assert_eq!(depth.best_bid_tick(), expected.best_bid_tick());
+            assert_eq!(depth.best_ask_tick(), expected.best_ask_tick());
+            assert_eq!(depth.best_bid_qty(), expected.best_bid_qty());
+            assert_eq!(depth.best_ask_qty(), expected.best_ask_qty());
+        }
+
+        let snapshot = depth.snapshot();
+        assert_eq!(snapshot.len(), expected.snapshot().len());
+
+        let mut restored = RingBufferMarketDepth::new(1.0, 1.0);
+        restored.apply_snapshot(&Data::from_data(&snapshot));
+        assert_eq!(restored.best_bid_tick(), expected.best_bid_tick());
+        assert_eq!(restored.best_ask_tick(), expected.best_ask_tick());
+        assert_eq!(restored.snapshot().len(), snapshot.len());
+    }
+}
@@ -4,7 +4,7 @@ use super::{ApplySnapshot, INVALID_MAX, INVALID_MIN, L3MarketDepth, L3Order, Mar
 use crate::{
     backtest::{BacktestError, data::Data},
     prelude::{L2MarketDepth, OrderId, Side},
-    types::{BUY_EVENT, Event, SELL_EVENT},
+    types::{BUY_EVENT, DEPTH_SNAPSHOT_EVENT, EXCH_EVENT, Event, LOCAL_EVENT, SELL_EVENT},
 };
 
 /// L2/L3 market depth implementation based on a vector within the range of interest.
@@ -142,6 +142,7 @@ impl L2MarketDepth for ROIVectorMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
         let price_tick = (price / self.tick_size).round() as i64;
         let qty_lot = (qty / self.lot_size).round() as i64;
         let prev_best_bid_tick = self.best_bid_tick;
@@ -209,6 +210,7 @@ impl L2MarketDepth for ROIVectorMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> (i64, i64, i64, f64, f64, i64) {
+        self.timestamp = timestamp;
         let price_tick = (price / self.tick_size).round() as i64;
         let qty_lot = (qty / self.lot_size).round() as i64;
         let prev_best_ask_tick = self.best_ask_tick;
@@ -432,6 +434,11 @@ impl MarketDepth for ROIVectorMarketDepth {
         self.lot_size
     }
 
+    #[inline(always)]
+    fn timestamp(&self) -> i64 {
+        self.timestamp
+    }
+
     #[inline(always)]
     fn bid_qty_at_tick(&self, price_tick: i64) -> f64 {
         if price_tick < self.roi_lb || price_tick > self.roi_ub {
@@ -463,6 +470,7 @@ impl MarketDepth for ROIVectorMarketDepth {
 
 impl ApplySnapshot for ROIVectorMarketDepth {
     fn apply_snapshot(&mut self, data: &Data<Event>) {
+        self.timestamp = 0;
         self.best_bid_tick = INVALID_MIN;
         self.best_ask_tick = INVALID_MAX;
         self.low_bid_tick = INVALID_MAX;
@@ -476,6 +484,7 @@ impl ApplySnapshot for ROIVectorMarketDepth {
         for row_num in 0..data.len() {
             let price = data[row_num].px;
             let qty = data[row_num].qty;
+            self.timestamp = self.timestamp.max(data[row_num].exch_ts);
 
             let price_tick = (price / self.tick_size).round() as i64;
             if price_tick < self.roi_lb || price_tick > self.roi_ub {
@@ -500,7 +509,49 @@ impl ApplySnapshot for ROIVectorMarketDepth {
     }
 
     fn snapshot(&self) -> Vec<Event> {
-        unimplemented!();
+        let mut events = Vec::new();
+
+        if self.best_bid_tick != INVALID_MIN {
+            let hi = self.best_bid_tick.min(self.roi_ub);
+            let lo = self.low_bid_tick.max(self.roi_lb);
+            for px_tick in (lo..=hi).rev() {
+                let qty = self.bid_depth[(px_tick - self.roi_lb) as usize];
+                if qty > 0.0 {
+                    events.push(Event {
+                        ev: EXCH_EVENT | LOCAL_EVENT | BUY_EVENT | DEPTH_SNAPSHOT_EVENT,
+                        exch_ts: self.timestamp,
+                        local_ts: self.timestamp,
+                        px: px_tick as f64 * self.tick_size,
+                        qty,
+                        order_id: 0,
+                        ival: 0,
+                        fval: 0.0,
+                    });
+                }
+            }
+        }
+
+        if self.best_ask_tick != INVALID_MAX {
+            let lo = self.best_ask_tick.max(self.roi_lb);
+            let hi = self.high_ask_tick.min(self.roi_ub);
+            for px_tick in lo..=hi {
+                let qty = self.ask_depth[(px_tick - self.roi_lb) as usize];
+                if qty > 0.0 {
+                    events.push(Event {
+                        ev: EXCH_EVENT | LOCAL_EVENT | SELL_EVENT | DEPTH_SNAPSHOT_EVENT,
+                        exch_ts: self.timestamp,
+                        local_ts: self.timestamp,
+                        px: px_tick as f64 * self.tick_size,
+                        qty,
+                        order_id: 0,
+                        ival: 0,
+                        fval: 0.0,
+                    });
+                }
+            }
+        }
+
+        events
     }
 }
 
@@ -514,6 +565,7 @@ impl L3MarketDepth for ROIVectorMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let price_tick = (px / self.tick_size).round() as i64;
         self.add(L3Order {
             order_id,
@@ -546,6 +598,7 @@ impl L3MarketDepth for ROIVectorMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let price_tick = (px / self.tick_size).round() as i64;
         self.add(L3Order {
             order_id,
@@ -574,8 +627,9 @@ impl L3MarketDepth for ROIVectorMarketDepth {
     fn delete_order(
         &mut self,
         order_id: OrderId,
-        _timestamp: i64,
+        timestamp: i64,
     ) -> Result<(Side, i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let order = self
             .orders
             .remove(&order_id)
@@ -638,6 +692,7 @@ impl L3MarketDepth for ROIVectorMarketDepth {
         qty: f64,
         timestamp: i64,
     ) -> Result<(Side, i64, i64), Self::Error> {
+        self.timestamp = timestamp;
         let order = self
             .orders
             .get_mut(&order_id)
@@ -800,6 +855,10 @@ impl L3MarketDepth for ROIVectorMarketDepth {
     fn orders(&self) -> &HashMap<OrderId, L3Order> {
         &self.orders
     }
+
+    fn set_timestamp(&mut self, timestamp: i64) {
+        self.timestamp = timestamp;
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,449 @@
+use std::{
+    cell::{Ref, RefCell},
+    fs::File,
+    io::{BufWriter, Error, Write},
+    path::Path,
+    rc::Rc,
+};
+
+#[cfg(feature = "backtest")]
+use hftbacktest_derive::NpyDTyped;
+
+#[cfg(feature = "backtest")]
+use crate::backtest::data::{POD, write_npy};
+use crate::types::{OrdType, Order, OrderId, Side, Status, TimeInForce};
+
+/// Indicates where an order state transition is observed.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[repr(u8)]
+pub enum JournalSource {
+    /// Observed by the local, which is what the strategy sees.
+    Local = 0,
+    /// Observed by the exchange, which is only available in backtesting.
+    Exchange = 1,
+}
+
+impl AsRef<str> for JournalSource {
+    fn as_ref(&self) -> &'static str {
+        match self {
+            JournalSource::Local => "LOCAL",
+            JournalSource::Exchange => "EXCHANGE",
+        }
+    }
+}
+
+/// Order state transition.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[repr(u8)]
+pub enum OrderTransition {
+    /// A new order request is sent.
+    Submitted = 0,
+    /// A modify request is sent.
+    ModifyRequested = 1,
+    /// A cancel request is sent.
+    CancelRequested = 2,
+    /// The new order is accepted and rests in the order book.
+    Acked = 3,
+    /// The modify request is accepted.
+    Modified = 4,
+    /// The order is canceled.
+    Canceled = 5,
+    /// The order is partially filled.
+    PartiallyFilled = 6,
+    /// The order is fully filled.
+    Filled = 7,
+    /// The request is rejected.
+    Rejected = 8,
+    /// The order is expired.
+    Expired = 9,
+}
+
+impl AsRef<str> for OrderTransition {
+    fn as_ref(&self) -> &'static str {
+        match self {
+            OrderTransition::Submitted => "SUBMITTED",
+            OrderTransition::ModifyRequested => "MODIFY_REQUESTED",
+            OrderTransition::CancelRequested => "CANCEL_REQUESTED",
+            OrderTransition::Acked => "ACKED",
+            OrderTransition::Modified => "MODIFIED",
+            OrderTransition::Canceled => "CANCELED",
+            OrderTransition::PartiallyFilled => "PARTIALLY_FILLED",
+            OrderTransition::Filled => "FILLED",
+            OrderTransition::Rejected => "REJECTED",
+            OrderTransition::Expired => "EXPIRED",
+        }
+    }
+}
+
+/// The reason for an order state transition, which is only set for rejections and expirations.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[repr(u8)]
+pub enum TransitionReason {
+    None = 0,
+    /// The request is rejected before it reaches the matching engine, as reflected in the order
+    /// entry latency.
+    EntryRejected = 1,
+    /// The order targeted by the cancel or modify request no longer exists in the exchange, as it
+    /// may have already been filled, canceled, or expired.
+    OrderNotFound = 2,
+    /// The post-only order would take liquidity.
+    PostOnlyCrossed = 3,
+    /// The market, IOC, or FOK order cannot be fully executed immediately.
+    NotFilledImmediately = 4,
+    /// The exchange expires the resting order on its own, such as when the order book is cleared.
+    ExchangeExpired = 5,
+    /// The reason does not have a corresponding value.
+    Other = 255,
+}
+
+impl AsRef<str> for TransitionReason {
+    fn as_ref(&self) -> &'static str {
+        match self {
+            TransitionReason::None => "",
+            TransitionReason::EntryRejected => "ENTRY_REJECTED",
+            TransitionReason::OrderNotFound => "ORDER_NOT_FOUND",
+            TransitionReason::PostOnlyCrossed => "POST_ONLY_CROSSED",
+            TransitionReason::NotFilledImmediately => "NOT_FILLED_IMMEDIATELY",
+            TransitionReason::ExchangeExpired => "EXCHANGE_EXPIRED",
+            TransitionReason::Other => "OTHER",
+        }
+    }
+}
+
+impl OrderTransition {
+    /// Classifies the order update into a transition and its reason. Returns `None` if the update
+    /// does not represent a state transition.
+    ///
+    /// * `req` - The request that the update responds to. This is [`Status::None`] if the update
+    ///   is not a response to a request, such as a fill of a resting order.
+    /// * `order` - The updated order.
+    pub fn classify(req: Status, order: &Order) -> Option<(Self, TransitionReason)> {
+        if order.req == Status::Rejected {
+            // A request rejected before reaching the matching engine doesn't carry a new exchange
+            // timestamp.
+            let reason = if order.exch_timestamp < order.local_timestamp {
+                TransitionReason::EntryRejected
+            } else if req == Status::Canceled || req == Status::Replaced {
+                TransitionReason::OrderNotFound
+            } else {
+                TransitionReason::Other
+            };
+            return Some((OrderTransition::Rejected, reason));
+        }
+        match order.status {
+            Status::New if req == Status::Replaced => {
+                Some((OrderTransition::Modified, TransitionReason::None))
+            }
+            Status::New => Some((OrderTransition::Acked, TransitionReason::None)),
+            Status::PartiallyFilled => {
+                Some((OrderTransition::PartiallyFilled, TransitionReason::None))
+            }
+            Status::Filled => Some((OrderTransition::Filled, TransitionReason::None)),
+            Status::Canceled => Some((OrderTransition::Canceled, TransitionReason::None)),
+            Status::Expired => {
+                let reason = if req == Status::None {
+                    TransitionReason::ExchangeExpired
+                } else if order.order_type == OrdType::Market {
+                    TransitionReason::NotFilledImmediately
+                } else {
+                    match order.time_in_force {
+                        TimeInForce::GTX => TransitionReason::PostOnlyCrossed,
+                        TimeInForce::IOC | TimeInForce::FOK => {
+                            TransitionReason::NotFilledImmediately
+                        }
+                        _ => TransitionReason::Other,
+                    }
+                };
+                Some((OrderTransition::Expired, reason))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An entry of [`OrderJournal`].
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    /// The time at which the transition is observed by the source.
+    pub timestamp: i64,
+    pub source: JournalSource,
+    pub order_id: OrderId,
+    pub transition: OrderTransition,
+    pub reason: TransitionReason,
+    pub side: Side,
+    pub price: f64,
+    pub qty: f64,
+    pub leaves_qty: f64,
+    pub exec_qty: f64,
+    pub exec_price: f64,
+    /// The local timestamp of the order at the time of the transition.
+    pub local_timestamp: i64,
+    /// The exchange timestamp of the order at the time of the transition.
+    pub exch_timestamp: i64,
+}
+
+impl JournalEntry {
+    /// Constructs an instance of `JournalEntry` from the order.
+    pub fn new(
+        timestamp: i64,
+        source: JournalSource,
+        transition: OrderTransition,
+        reason: TransitionReason,
+        order: &Order,
+    ) -> Self {
+        Self {
+            timestamp,
+            source,
+            order_id: order.order_id,
+            transition,
+            reason,
+            side: order.side,
+            price: order.price(),
+            qty: order.qty,
+            leaves_qty: order.leaves_qty,
+            exec_qty: order.exec_qty,
+            exec_price: order.exec_price(),
+            local_timestamp: order.local_timestamp,
+            exch_timestamp: order.exch_timestamp,
+        }
+    }
+}
+
+#[cfg(feature = "backtest")]
+#[repr(C)]
+#[derive(NpyDTyped)]
+struct JournalRecord {
+    timestamp: i64,
+    local_timestamp: i64,
+    exch_timestamp: i64,
+    order_id: u64,
+    price: f64,
+    qty: f64,
+    leaves_qty: f64,
+    exec_qty: f64,
+    exec_price: f64,
+    source: u8,
+    transition: u8,
+    reason: u8,
+    side: i8,
+    _padding: u32,
+}
+
+#[cfg(feature = "backtest")]
+unsafe impl POD for JournalRecord {}
+
+/// Records every order state transition, including requests, acknowledgements, modifications,
+/// cancellations, fills, rejections, and expirations, along with the local and exchange
+/// timestamps and the reason.
+///
+/// The journal is a shared handle, so a clone of it can be handed to the local and exchange
+/// processors and read after the backtest. A live bot can produce the same format by recording
+/// the order updates it receives via [`OrderJournal::record_update`].
+#[derive(Clone, Debug, Default)]
+pub struct OrderJournal {
+    entries: Rc<RefCell<Vec<JournalEntry>>>,
+}
+
+impl OrderJournal {
+    /// Constructs an instance of `OrderJournal`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Appends the entry.
+    pub fn record(&self, entry: JournalEntry) {
+        self.entries.borrow_mut().push(entry);
+    }
+
+    /// Appends an entry of the given transition of the order.
+    pub fn record_order(
+        &self,
+        timestamp: i64,
+        source: JournalSource,
+        transition: OrderTransition,
+        reason: TransitionReason,
+        order: &Order,
+    ) {
+        self.record(JournalEntry::new(
+            timestamp, source, transition, reason, order,
+        ));
+    }
+
+    /// Appends an entry of the order update if it represents a state transition. See
+    /// [`OrderTransition::classify`].
+    pub fn record_update(&self, timestamp: i64, source: JournalSource, req: Status, order: &Order) {
+        if let Some((transition, reason)) = OrderTransition::classify(req, order) {
+            self.record_order(timestamp, source, transition, reason, order);
+        }
+    }
+
+    /// Returns the recorded entries.
+    pub fn entries(&self) -> Ref<'_, [JournalEntry]> {
+        Ref::map(self.entries.borrow(), |entries| entries.as_slice())
+    }
+
+    /// Returns the number of the recorded entries.
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Returns `true` if there are no recorded entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    /// Clears the recorded entries.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Saves the entries into a CSV file at the specified path.
+    /// The columns are `timestamp`, `source`, `order_id`, `transition`, `reason`, `side`, `price`,
+    /// `qty`, `leaves_qty`, `exec_qty`, `exec_price`, `local_timestamp`, `exch_timestamp`.
+    pub fn to_csv<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(
+            file,
+            "timestamp,source,order_id,transition,reason,side,price,qty,leaves_qty,exec_qty,\
+            exec_price,local_timestamp,exch_timestamp",
+        )?;
+        for entry in self.entries.borrow().iter() {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                entry.timestamp,
+                entry.source.as_ref(),
+                entry.order_id,
+                entry.transition.as_ref(),
+                entry.reason.as_ref(),
+                entry.side as i8,
+                entry.price,
+                entry.qty,
+                entry.leaves_qty,
+                entry.exec_qty,
+                entry.exec_price,
+                entry.local_timestamp,
+                entry.exch_timestamp,
+            )?;
+        }
+        file.flush()
+    }
+
+    /// Saves the entries into an `npz` file at the specified path under the key `data`. The
+    /// `source`, `transition`, `reason`, and `side` fields are stored as their integer values.
+    #[cfg(feature = "backtest")]
+    pub fn to_npz<P>(&self, path: P) -> Result<(), Error>
+    where
+        P: AsRef<Path>,
+    {
+        use zip::{ZipWriter, write::SimpleFileOptions};
+
+        let records: Vec<JournalRecord> = self
+            .entries
+            .borrow()
+            .iter()
+            .map(|entry| JournalRecord {
+                timestamp: entry.timestamp,
+                local_timestamp: entry.local_timestamp,
+                exch_timestamp: entry.exch_timestamp,
+                order_id: entry.order_id,
+                price: entry.price,
+                qty: entry.qty,
+                leaves_qty: entry.leaves_qty,
+                exec_qty: entry.exec_qty,
+                exec_price: entry.exec_price,
+                source: entry.source as u8,
+                transition: entry.transition as u8,
+                reason: entry.reason as u8,
+                side: entry.side as i8,
+                _padding: 0,
+            })
+            .collect();
+
+        let file = File::create(path)?;
+        let mut zip = ZipWriter::new(file);
+
+        let options = SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::DEFLATE)
+            .compression_level(Some(9));
+
+        zip.start_file("data.npy", options)?;
+        write_npy(&mut zip, &records)?;
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(req: Status, status: Status, time_in_force: TimeInForce) -> Order {
+        let mut order = Order::new(1, 100, 0.1, 1.0, Side::Buy, OrdType::Limit, time_in_force);
+        order.req = req;
+        order.status = status;
+        order.local_timestamp = 10;
+        order.exch_timestamp = 20;
+        order
+    }
+
+    #[test]
+    fn classifies_order_updates() {
+        assert_eq!(
+            OrderTransition::classify(
+                Status::New,
+                &order(Status::None, Status::New, TimeInForce::GTC)
+            ),
+            Some((OrderTransition::Acked, TransitionReason::None))
+        );
+        assert_eq!(
+            OrderTransition::classify(
+                Status::Replaced,
+                &order(Status::None, Status::New, TimeInForce::GTC)
+            ),
+            Some((OrderTransition::Modified, TransitionReason::None))
+        );
+        assert_eq!(
+            OrderTransition::classify(
+                Status::New,
+                &order(Status::None, Status::Expired, TimeInForce::GTX)
+            ),
+            Some((OrderTransition::Expired, TransitionReason::PostOnlyCrossed))
+        );
+        assert_eq!(
+            OrderTransition::classify(
+                Status::New,
+                &order(Status::None, Status::Expired, TimeInForce::IOC)
+            ),
+            Some((
+                OrderTransition::Expired,
+                TransitionReason::NotFilledImmediately
+            ))
+        );
+        assert_eq!(
+            OrderTransition::classify(
+                Status::None,
+                &order(Status::None, Status::Expired, TimeInForce::GTC)
+            ),
+            Some((OrderTransition::Expired, TransitionReason::ExchangeExpired))
+        );
+        assert_eq!(
+            OrderTransition::classify(
+                Status::Canceled,
+                &order(Status::Rejected, Status::New, TimeInForce::GTC)
+            ),
+            Some((OrderTransition::Rejected, TransitionReason::OrderNotFound))
+        );
+
+        let mut rejected = order(Status::Rejected, Status::None, TimeInForce::GTC);
+        rejected.exch_timestamp = 0;
+        assert_eq!(
+            OrderTransition::classify(Status::New, &rejected),
+            Some((OrderTransition::Rejected, TransitionReason::EntryRejected))
+        );
+    }
+}
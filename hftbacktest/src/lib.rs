@@ -24,6 +24,8 @@
 //! - `backtest`: Enables backtesting features.
 //! - `live`: Enables a live trading bot.
 //! - `s3`: Enables accessing data file from S3.
+//! - `serde`: Enables serializing the types with `serde`. This is enabled by both `backtest`, for
+//!   the backtest statistics, and `live`.
 //!
 
 /// Provides backtesting features.
@@ -40,6 +42,12 @@ pub mod live;
 /// Defines HftBacktest types.
 pub mod types;
 
+/// Provides an order lifecycle journal.
+pub mod journal;
+
+/// Provides trade-based bars and event clocks.
+pub mod bar;
+
 /// Provides common types.
 pub mod prelude;
 
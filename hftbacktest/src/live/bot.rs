@@ -16,7 +16,9 @@ use crate::{
         BuildError,
         ElapseResult,
         Event,
+        LOCAL_ASK_DEPTH_BBO_EVENT,
         LOCAL_ASK_DEPTH_EVENT,
+        LOCAL_BID_DEPTH_BBO_EVENT,
         LOCAL_BID_DEPTH_EVENT,
         LOCAL_BUY_TRADE_EVENT,
         LOCAL_SELL_TRADE_EVENT,
@@ -220,6 +222,14 @@ where
                     instrument
                         .depth
                         .update_ask_depth(event.px, event.qty, event.exch_ts);
+                } else if event.is(LOCAL_BID_DEPTH_BBO_EVENT) {
+                    instrument
+                        .depth
+                        .update_best_bid(event.px, event.qty, event.exch_ts);
+                } else if event.is(LOCAL_ASK_DEPTH_BBO_EVENT) {
+                    instrument
+                        .depth
+                        .update_best_ask(event.px, event.qty, event.exch_ts);
                 } else if (event.is(LOCAL_BUY_TRADE_EVENT) || event.is(LOCAL_SELL_TRADE_EVENT))
                     && instrument.last_trades.capacity() > 0
                 {
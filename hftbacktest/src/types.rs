@@ -731,7 +731,7 @@ pub enum LiveRequest {
 /// **Note:** In a live bot, currently only `position` value is delivered correctly, and other
 /// values are invalid.
 #[repr(C)]
-#[derive(PartialEq, Clone, Debug, Default)]
+#[derive(PartialEq, Clone, Debug, Default, Decode, Encode)]
 pub struct StateValues {
     pub position: f64,
     /// Backtest only
@@ -788,6 +788,16 @@ where
     /// Returns the number of assets.
     fn num_assets(&self) -> usize;
 
+    /// Returns `true` if the bot is in the warm-up period, during which the state values are not
+    /// recorded. During the warm-up period, [`submit_buy_order`](Self::submit_buy_order),
+    /// [`submit_sell_order`](Self::submit_sell_order), [`submit_order`](Self::submit_order),
+    /// [`modify`](Self::modify), and [`cancel`](Self::cancel) are rejected with an error, such as
+    /// [`BacktestError::WarmingUp`](crate::backtest::BacktestError::WarmingUp), without sending the
+    /// request, so no order is registered and `wait` is ignored. The default is `false`.
+    fn is_warming_up(&self) -> bool {
+        false
+    }
+
     /// Returns the position you currently hold.
     ///
     /// * `asset_no` - Asset number from which the position will be retrieved.
@@ -831,6 +841,8 @@ where
     ///   the exchange model for details.
     ///
     /// * `wait` - If true, wait until the order placement response is received.
+    ///
+    /// No order is placed during the warm-up period; see [`is_warming_up`](Self::is_warming_up).
     #[allow(clippy::too_many_arguments)]
     fn submit_buy_order(
         &mut self,
@@ -857,6 +869,8 @@ where
     ///   the exchange model for details.
     ///
     /// * `wait` - If true, wait until the order placement response is received.
+    ///
+    /// No order is placed during the warm-up period; see [`is_warming_up`](Self::is_warming_up).
     #[allow(clippy::too_many_arguments)]
     fn submit_sell_order(
         &mut self,
@@ -869,7 +883,8 @@ where
         wait: bool,
     ) -> Result<ElapseResult, Self::Error>;
 
-    /// Places an order.
+    /// Places an order. No order is placed during the warm-up period; see
+    /// [`is_warming_up`](Self::is_warming_up).
     fn submit_order(
         &mut self,
         asset_no: usize,
@@ -884,6 +899,9 @@ where
     /// * `price` - Order price.
     /// * `qty` - Quantity to buy.
     /// * `wait` - If true, wait until the order modification response is received.
+    ///
+    /// The order isn't modified during the warm-up period; see
+    /// [`is_warming_up`](Self::is_warming_up).
     fn modify(
         &mut self,
         asset_no: usize,
@@ -898,6 +916,9 @@ where
     /// * `asset_no` - Asset number at which this command will be executed.
     /// * `order_id` - Order ID to cancel.
     /// * `wait` - If true, wait until the order placement response is received.
+    ///
+    /// The order isn't canceled during the warm-up period; see
+    /// [`is_warming_up`](Self::is_warming_up).
     fn cancel(
         &mut self,
         asset_no: usize,
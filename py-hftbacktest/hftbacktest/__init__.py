@@ -7,6 +7,7 @@ from ._hftbacktest import (
     BacktestAsset as BacktestAsset_,
     build_hashmap_backtest,
     build_roivec_backtest,
+    build_fused_hashmap_backtest,
     LiveInstrument
 )
 from .binding import (
@@ -14,8 +15,11 @@ from .binding import (
     HashMapMarketDepthBacktest as HashMapMarketDepthBacktest_TypeHint,
     ROIVectorMarketDepthBacktest_,
     ROIVectorMarketDepthBacktest as ROIVectorMarketDepthBacktest_TypeHint,
+    FusedHashMapMarketDepthBacktest_,
+    FusedHashMapMarketDepthBacktest as FusedHashMapMarketDepthBacktest_TypeHint,
 
-    event_dtype
+    event_dtype,
+    l3_order_dtype
 )
 from .order import (
     BUY,
@@ -51,13 +55,16 @@ from .types import (
 try:
     from ._hftbacktest import (
         build_hashmap_livebot,
-        build_roivec_livebot
+        build_roivec_livebot,
+        build_fused_hashmap_livebot
     )
     from .binding import (
         HashMapMarketDepthLiveBot_,
         HashMapMarketDepthLiveBot as HashMapMarketDepthLiveBot_TypeHint,
         ROIVectorMarketDepthLiveBot_,
         ROIVectorMarketDepthLiveBot as ROIVectorMarketDepthLiveBot_TypeHint,
+        FusedHashMapMarketDepthLiveBot_,
+        FusedHashMapMarketDepthLiveBot as FusedHashMapMarketDepthLiveBot_TypeHint,
     )
     LIVE_FEATURE = True
 except:
@@ -67,10 +74,12 @@ __all__ = (
     'BacktestAsset',
     'HashMapMarketDepthBacktest',
     'ROIVectorMarketDepthBacktest',
+    'FusedHashMapMarketDepthBacktest',
 
     'LiveInstrument',
     'HashMapMarketDepthLiveBot',
     'ROIVectorMarketDepthLiveBot',
+    'FusedHashMapMarketDepthLiveBot',
 
     'ALL_ASSETS',
 
@@ -197,6 +206,10 @@ def HashMapMarketDepthBacktest(
 
     Returns:
         A jit`ed `HashMapMarketDepthBacktest` that can be used in an ``njit`` function.
+
+    Raises:
+        ValueError: If an asset's combination of the asset type and the models is not supported.
+        OSError: If an asset's data or initial snapshot cannot be read.
     """
     ptr = build_hashmap_backtest(assets)
     return HashMapMarketDepthBacktest_(ptr)
@@ -213,11 +226,39 @@ def ROIVectorMarketDepthBacktest(
 
     Returns:
         A jit`ed `ROIVectorMarketBacktest` that can be used in an ``njit`` function.
+
+    Raises:
+        ValueError: If an asset's combination of the asset type and the models is not supported.
+        OSError: If an asset's data or initial snapshot cannot be read.
     """
     ptr = build_roivec_backtest(assets)
     return ROIVectorMarketDepthBacktest_(ptr)
 
 
+def FusedHashMapMarketDepthBacktest(
+        assets: List[BacktestAsset]
+) -> FusedHashMapMarketDepthBacktest_TypeHint:
+    """
+    Constructs an instance of `FusedHashMapMarketDepthBacktest`, whose market depth fuses the feeds from multiple
+    streams with different update frequencies and ranges, such as the full-depth diff stream, the partial depth
+    snapshot stream, and the BBO stream. Only the most recent updates across the streams are applied, based on the
+    exchange timestamp.
+
+    Args:
+        assets: A list of backtesting assets constructed using :class:`BacktestAsset`. Level-3 Market-By-Order is
+                not supported.
+
+    Returns:
+        A jit`ed `FusedHashMapMarketDepthBacktest` that can be used in an ``njit`` function.
+
+    Raises:
+        ValueError: If an asset's combination of the asset type and the models is not supported.
+        OSError: If an asset's data or initial snapshot cannot be read.
+    """
+    ptr = build_fused_hashmap_backtest(assets)
+    return FusedHashMapMarketDepthBacktest_(ptr)
+
+
 if LIVE_FEATURE:
     def ROIVectorMarketDepthLiveBot(
             assets: List[LiveInstrument]
@@ -233,3 +274,19 @@ if LIVE_FEATURE:
         """
         ptr = build_roivec_livebot(assets)
         return ROIVectorMarketDepthLiveBot_(ptr)
+
+    def FusedHashMapMarketDepthLiveBot(
+            assets: List[LiveInstrument]
+    ) -> FusedHashMapMarketDepthLiveBot_TypeHint:
+        """
+        Constructs an instance of `FusedHashMapMarketDepthLiveBot`, whose market depth fuses the feeds from multiple
+        streams, such as the depth diff stream, the partial depth snapshot stream, and the BBO stream.
+
+        Args:
+            assets: A list of live instruments constructed using :class:`LiveInstrument`.
+
+        Returns:
+            A jit`ed `FusedHashMapMarketDepthLiveBot` that can be used in an ``njit`` function.
+        """
+        ptr = build_fused_hashmap_livebot(assets)
+        return FusedHashMapMarketDepthLiveBot_(ptr)
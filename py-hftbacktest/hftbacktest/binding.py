@@ -27,7 +27,16 @@ from . import _hftbacktest
 from .intrinsic import ptr_from_val, address_as_void_pointer, val_from_ptr, is_null_ptr
 from .order import order_dtype, Order, Order_
 from .state import StateValues, StateValues_
-from .types import event_dtype, state_values_dtype, EVENT_ARRAY, DEPTH_EVENT, BUY_EVENT, SELL_EVENT
+from .types import (
+    event_dtype,
+    l3_order_dtype,
+    state_values_dtype,
+    EVENT_ARRAY,
+    L3_ORDER_ARRAY,
+    DEPTH_EVENT,
+    BUY_EVENT,
+    SELL_EVENT
+)
 
 LIVE_FEATURE = 'build_hashmap_livebot' in dir(_hftbacktest)
 
@@ -73,6 +82,30 @@ hashmapdepth_ask_qty_at_tick = lib.hashmapdepth_ask_qty_at_tick
 hashmapdepth_ask_qty_at_tick.restype = c_double
 hashmapdepth_ask_qty_at_tick.argtypes = [c_void_p, c_int64]
 
+hashmapdepth_cum_bid_qty = lib.hashmapdepth_cum_bid_qty
+hashmapdepth_cum_bid_qty.restype = c_double
+hashmapdepth_cum_bid_qty.argtypes = [c_void_p, c_uint64]
+
+hashmapdepth_cum_ask_qty = lib.hashmapdepth_cum_ask_qty
+hashmapdepth_cum_ask_qty.restype = c_double
+hashmapdepth_cum_ask_qty.argtypes = [c_void_p, c_uint64]
+
+hashmapdepth_imbalance = lib.hashmapdepth_imbalance
+hashmapdepth_imbalance.restype = c_double
+hashmapdepth_imbalance.argtypes = [c_void_p, c_uint64]
+
+hashmapdepth_weighted_mid = lib.hashmapdepth_weighted_mid
+hashmapdepth_weighted_mid.restype = c_double
+hashmapdepth_weighted_mid.argtypes = [c_void_p, c_uint64]
+
+hashmapdepth_bid_vwap = lib.hashmapdepth_bid_vwap
+hashmapdepth_bid_vwap.restype = c_double
+hashmapdepth_bid_vwap.argtypes = [c_void_p, c_double, c_uint64]
+
+hashmapdepth_ask_vwap = lib.hashmapdepth_ask_vwap
+hashmapdepth_ask_vwap.restype = c_double
+hashmapdepth_ask_vwap.argtypes = [c_void_p, c_double, c_uint64]
+
 hashmapdepth_snapshot = lib.hashmapdepth_snapshot
 hashmapdepth_snapshot.restype = c_void_p
 hashmapdepth_snapshot.argtypes = [c_void_p, POINTER(c_uint64)]
@@ -81,6 +114,22 @@ hashmapdepth_snapshot_free = lib.hashmapdepth_snapshot_free
 hashmapdepth_snapshot_free.restype = c_void_p
 hashmapdepth_snapshot_free.argtypes = [c_void_p, c_uint64]
 
+hashmapdepth_l3_order = lib.hashmapdepth_l3_order
+hashmapdepth_l3_order.restype = c_void_p
+hashmapdepth_l3_order.argtypes = [c_void_p, c_uint64]
+
+hashmapdepth_l3_orders_len = lib.hashmapdepth_l3_orders_len
+hashmapdepth_l3_orders_len.restype = c_uint64
+hashmapdepth_l3_orders_len.argtypes = [c_void_p]
+
+hashmapdepth_l3_orders = lib.hashmapdepth_l3_orders
+hashmapdepth_l3_orders.restype = c_void_p
+hashmapdepth_l3_orders.argtypes = [c_void_p, POINTER(c_uint64)]
+
+hashmapdepth_l3_orders_free = lib.hashmapdepth_l3_orders_free
+hashmapdepth_l3_orders_free.restype = c_void_p
+hashmapdepth_l3_orders_free.argtypes = [c_void_p, c_uint64]
+
 
 class HashMapMarketDepth:
     ptr: voidptr
@@ -168,6 +217,84 @@ class HashMapMarketDepth:
         """
         return hashmapdepth_ask_qty_at_tick(self.ptr, price_tick)
 
+    def cum_bid_qty(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the cumulative bid quantity within ``depth_ticks`` from the best bid.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The cumulative bid quantity.
+        """
+        return hashmapdepth_cum_bid_qty(self.ptr, depth_ticks)
+
+    def cum_ask_qty(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the cumulative ask quantity within ``depth_ticks`` from the best ask.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The cumulative ask quantity.
+        """
+        return hashmapdepth_cum_ask_qty(self.ptr, depth_ticks)
+
+    def imbalance(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the order book imbalance, ``(bid_qty - ask_qty) / (bid_qty + ask_qty)``, of the cumulative
+        quantities within ``depth_ticks``.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The imbalance ranging from -1 to 1, or ``NaN`` if both sides are empty.
+        """
+        return hashmapdepth_imbalance(self.ptr, depth_ticks)
+
+    def weighted_mid(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the mid-price weighted by the cumulative quantities within ``depth_ticks`` of the opposite
+        sides. With ``depth_ticks`` of 1, this is the micro-price.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The weighted mid-price, or ``NaN`` if either side is empty.
+        """
+        return hashmapdepth_weighted_mid(self.ptr, depth_ticks)
+
+    def bid_vwap(self, qty: float64, depth_ticks: uint64) -> float64:
+        """
+        Returns the volume-weighted average price at which a sell order of the given quantity would be
+        filled by sweeping the bid side within ``depth_ticks``.
+
+        Args:
+            qty: Quantity to fill.
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The VWAP, or ``NaN`` if the quantity cannot be filled within ``depth_ticks``.
+        """
+        return hashmapdepth_bid_vwap(self.ptr, qty, depth_ticks)
+
+    def ask_vwap(self, qty: float64, depth_ticks: uint64) -> float64:
+        """
+        Returns the volume-weighted average price at which a buy order of the given quantity would be
+        filled by sweeping the ask side within ``depth_ticks``.
+
+        Args:
+            qty: Quantity to fill.
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The VWAP, or ``NaN`` if the quantity cannot be filled within ``depth_ticks``.
+        """
+        return hashmapdepth_ask_vwap(self.ptr, qty, depth_ticks)
+
     def snapshot(self) -> EVENT_ARRAY:
         length = uint64(0)
         len_ptr = ptr_from_val(length)
@@ -181,6 +308,47 @@ class HashMapMarketDepth:
     def snapshot_free(self, arr: EVENT_ARRAY):
         hashmapdepth_snapshot_free(arr.ctypes.data, len(arr))
 
+    def l3_order(self, order_id: uint64) -> L3_ORDER_ARRAY:
+        """
+        Returns the Level-3 order in the order book. This is only available with the Level-3 Market-By-Order
+        backtesting.
+
+        Args:
+            order_id: Order ID from the market feed.
+
+        Returns:
+            An array containing the order with the specified order ID; an empty array if it does not exist. The array
+            is only valid until the next call that advances the backtest.
+        """
+        ptr = hashmapdepth_l3_order(self.ptr, order_id)
+        if is_null_ptr(ptr):
+            return numba.carray(address_as_void_pointer(ptr), 0, l3_order_dtype)
+        return numba.carray(address_as_void_pointer(ptr), 1, l3_order_dtype)
+
+    @property
+    def l3_orders_len(self) -> uint64:
+        """
+        Returns the number of the Level-3 orders in the order book.
+        """
+        return hashmapdepth_l3_orders_len(self.ptr)
+
+    def l3_orders(self) -> L3_ORDER_ARRAY:
+        """
+        Returns a copy of all Level-3 orders in the order book, in no particular order. The returned array should be
+        released by :func:`l3_orders_free`.
+        """
+        length = uint64(0)
+        len_ptr = ptr_from_val(length)
+        ptr = hashmapdepth_l3_orders(self.ptr, len_ptr)
+        return numba.carray(
+            address_as_void_pointer(ptr),
+            val_from_ptr(len_ptr),
+            l3_order_dtype
+        )
+
+    def l3_orders_free(self, arr: L3_ORDER_ARRAY):
+        hashmapdepth_l3_orders_free(arr.ctypes.data, len(arr))
+
 
 HashMapMarketDepth_ = jitclass(HashMapMarketDepth)
 
@@ -225,6 +393,30 @@ roivecdepth_ask_qty_at_tick = lib.roivecdepth_ask_qty_at_tick
 roivecdepth_ask_qty_at_tick.restype = c_double
 roivecdepth_ask_qty_at_tick.argtypes = [c_void_p, c_int64]
 
+roivecdepth_cum_bid_qty = lib.roivecdepth_cum_bid_qty
+roivecdepth_cum_bid_qty.restype = c_double
+roivecdepth_cum_bid_qty.argtypes = [c_void_p, c_uint64]
+
+roivecdepth_cum_ask_qty = lib.roivecdepth_cum_ask_qty
+roivecdepth_cum_ask_qty.restype = c_double
+roivecdepth_cum_ask_qty.argtypes = [c_void_p, c_uint64]
+
+roivecdepth_imbalance = lib.roivecdepth_imbalance
+roivecdepth_imbalance.restype = c_double
+roivecdepth_imbalance.argtypes = [c_void_p, c_uint64]
+
+roivecdepth_weighted_mid = lib.roivecdepth_weighted_mid
+roivecdepth_weighted_mid.restype = c_double
+roivecdepth_weighted_mid.argtypes = [c_void_p, c_uint64]
+
+roivecdepth_bid_vwap = lib.roivecdepth_bid_vwap
+roivecdepth_bid_vwap.restype = c_double
+roivecdepth_bid_vwap.argtypes = [c_void_p, c_double, c_uint64]
+
+roivecdepth_ask_vwap = lib.roivecdepth_ask_vwap
+roivecdepth_ask_vwap.restype = c_double
+roivecdepth_ask_vwap.argtypes = [c_void_p, c_double, c_uint64]
+
 roivecdepth_bid_depth = lib.roivecdepth_bid_depth
 roivecdepth_bid_depth.restype = c_void_p
 roivecdepth_bid_depth.argtypes = [c_void_p, POINTER(c_uint64)]
@@ -241,6 +433,22 @@ roivecdepth_roi_ub_tick = lib.roivecdepth_roi_ub_tick
 roivecdepth_roi_ub_tick.restype = c_int64
 roivecdepth_roi_ub_tick.argtypes = [c_void_p]
 
+roivecdepth_l3_order = lib.roivecdepth_l3_order
+roivecdepth_l3_order.restype = c_void_p
+roivecdepth_l3_order.argtypes = [c_void_p, c_uint64]
+
+roivecdepth_l3_orders_len = lib.roivecdepth_l3_orders_len
+roivecdepth_l3_orders_len.restype = c_uint64
+roivecdepth_l3_orders_len.argtypes = [c_void_p]
+
+roivecdepth_l3_orders = lib.roivecdepth_l3_orders
+roivecdepth_l3_orders.restype = c_void_p
+roivecdepth_l3_orders.argtypes = [c_void_p, POINTER(c_uint64)]
+
+roivecdepth_l3_orders_free = lib.roivecdepth_l3_orders_free
+roivecdepth_l3_orders_free.restype = c_void_p
+roivecdepth_l3_orders_free.argtypes = [c_void_p, c_uint64]
+
 
 class ROIVectorMarketDepth:
     ptr: voidptr
@@ -328,6 +536,84 @@ class ROIVectorMarketDepth:
         """
         return roivecdepth_ask_qty_at_tick(self.ptr, price_tick)
 
+    def cum_bid_qty(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the cumulative bid quantity within ``depth_ticks`` from the best bid.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The cumulative bid quantity.
+        """
+        return roivecdepth_cum_bid_qty(self.ptr, depth_ticks)
+
+    def cum_ask_qty(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the cumulative ask quantity within ``depth_ticks`` from the best ask.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The cumulative ask quantity.
+        """
+        return roivecdepth_cum_ask_qty(self.ptr, depth_ticks)
+
+    def imbalance(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the order book imbalance, ``(bid_qty - ask_qty) / (bid_qty + ask_qty)``, of the cumulative
+        quantities within ``depth_ticks``.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The imbalance ranging from -1 to 1, or ``NaN`` if both sides are empty.
+        """
+        return roivecdepth_imbalance(self.ptr, depth_ticks)
+
+    def weighted_mid(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the mid-price weighted by the cumulative quantities within ``depth_ticks`` of the opposite
+        sides. With ``depth_ticks`` of 1, this is the micro-price.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The weighted mid-price, or ``NaN`` if either side is empty.
+        """
+        return roivecdepth_weighted_mid(self.ptr, depth_ticks)
+
+    def bid_vwap(self, qty: float64, depth_ticks: uint64) -> float64:
+        """
+        Returns the volume-weighted average price at which a sell order of the given quantity would be
+        filled by sweeping the bid side within ``depth_ticks``.
+
+        Args:
+            qty: Quantity to fill.
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The VWAP, or ``NaN`` if the quantity cannot be filled within ``depth_ticks``.
+        """
+        return roivecdepth_bid_vwap(self.ptr, qty, depth_ticks)
+
+    def ask_vwap(self, qty: float64, depth_ticks: uint64) -> float64:
+        """
+        Returns the volume-weighted average price at which a buy order of the given quantity would be
+        filled by sweeping the ask side within ``depth_ticks``.
+
+        Args:
+            qty: Quantity to fill.
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The VWAP, or ``NaN`` if the quantity cannot be filled within ``depth_ticks``.
+        """
+        return roivecdepth_ask_vwap(self.ptr, qty, depth_ticks)
+
     @property
     def bid_depth(self) -> np.ndarray[Any, float64]:
         """
@@ -378,140 +664,438 @@ class ROIVectorMarketDepth:
         """
         return roivecdepth_roi_ub_tick(self.ptr)
 
+    def l3_order(self, order_id: uint64) -> L3_ORDER_ARRAY:
+        """
+        Returns the Level-3 order in the order book. This is only available with the Level-3 Market-By-Order
+        backtesting.
+
+        Args:
+            order_id: Order ID from the market feed.
+
+        Returns:
+            An array containing the order with the specified order ID; an empty array if it does not exist. The array
+            is only valid until the next call that advances the backtest.
+        """
+        ptr = roivecdepth_l3_order(self.ptr, order_id)
+        if is_null_ptr(ptr):
+            return numba.carray(address_as_void_pointer(ptr), 0, l3_order_dtype)
+        return numba.carray(address_as_void_pointer(ptr), 1, l3_order_dtype)
+
+    @property
+    def l3_orders_len(self) -> uint64:
+        """
+        Returns the number of the Level-3 orders in the order book.
+        """
+        return roivecdepth_l3_orders_len(self.ptr)
+
+    def l3_orders(self) -> L3_ORDER_ARRAY:
+        """
+        Returns a copy of all Level-3 orders in the order book, in no particular order. The returned array should be
+        released by :func:`l3_orders_free`.
+        """
+        length = uint64(0)
+        len_ptr = ptr_from_val(length)
+        ptr = roivecdepth_l3_orders(self.ptr, len_ptr)
+        return numba.carray(
+            address_as_void_pointer(ptr),
+            val_from_ptr(len_ptr),
+            l3_order_dtype
+        )
+
+    def l3_orders_free(self, arr: L3_ORDER_ARRAY):
+        roivecdepth_l3_orders_free(arr.ctypes.data, len(arr))
+
 
 ROIVectorMarketDepth_ = jitclass(ROIVectorMarketDepth)
 
-orders_get = lib.orders_get
-orders_get.restype = c_void_p
-orders_get.argtypes = [c_void_p, c_uint64]
 
-orders_contains = lib.orders_contains
-orders_contains.restype = c_bool
-orders_contains.argtypes = [c_void_p, c_uint64]
+fusedhashmapdepth_best_bid_tick = lib.fusedhashmapdepth_best_bid_tick
+fusedhashmapdepth_best_bid_tick.restype = c_int64
+fusedhashmapdepth_best_bid_tick.argtypes = [c_void_p]
 
-orders_len = lib.orders_len
-orders_len.restype = c_uint64
-orders_len.argtypes = [c_void_p]
+fusedhashmapdepth_best_ask_tick = lib.fusedhashmapdepth_best_ask_tick
+fusedhashmapdepth_best_ask_tick.restype = c_int64
+fusedhashmapdepth_best_ask_tick.argtypes = [c_void_p]
 
-orders_values = lib.orders_values
-orders_values.restype = c_void_p
-orders_values.argtypes = [c_void_p]
+fusedhashmapdepth_best_bid = lib.fusedhashmapdepth_best_bid
+fusedhashmapdepth_best_bid.restype = c_double
+fusedhashmapdepth_best_bid.argtypes = [c_void_p]
 
-orders_values_next = lib.orders_values_next
-orders_values_next.restype = c_void_p
-orders_values_next.argtypes = [c_void_p]
+fusedhashmapdepth_best_ask = lib.fusedhashmapdepth_best_ask
+fusedhashmapdepth_best_ask.restype = c_double
+fusedhashmapdepth_best_ask.argtypes = [c_void_p]
 
+fusedhashmapdepth_best_bid_qty = lib.fusedhashmapdepth_best_bid_qty
+fusedhashmapdepth_best_bid_qty.restype = c_double
+fusedhashmapdepth_best_bid_qty.argtypes = [c_void_p]
 
-class Values:
-    ptr: voidptr
-    order_ptr: voidptr
+fusedhashmapdepth_best_ask_qty = lib.fusedhashmapdepth_best_ask_qty
+fusedhashmapdepth_best_ask_qty.restype = c_double
+fusedhashmapdepth_best_ask_qty.argtypes = [c_void_p]
 
-    def __init__(self, ptr: voidptr):
-        self.ptr = ptr
-        self.order_ptr = 0
+fusedhashmapdepth_tick_size = lib.fusedhashmapdepth_tick_size
+fusedhashmapdepth_tick_size.restype = c_double
+fusedhashmapdepth_tick_size.argtypes = [c_void_p]
 
-    # def __next__(self) -> Order:
-    #     if is_null_ptr(self.ptr):
-    #         return None
-    #     order_ptr = orders_values_next(self.ptr)
-    #     if is_null_ptr(order_ptr):
-    #         self.ptr = 0
-    #         raise StopIteration
-    #     else:
-    #         arr = carray(
-    #             address_as_void_pointer(order_ptr),
-    #             1,
-    #             dtype=order_dtype
-    #         )
-    #         return Order_(arr)
+fusedhashmapdepth_lot_size = lib.fusedhashmapdepth_lot_size
+fusedhashmapdepth_lot_size.restype = c_double
+fusedhashmapdepth_lot_size.argtypes = [c_void_p]
 
-    def next(self) -> Order | None:
-        if is_null_ptr(self.ptr):
-            return None
-        order_ptr = orders_values_next(self.ptr)
-        if is_null_ptr(order_ptr):
-            self.ptr = 0
-            return None
-        else:
-            arr = carray(
-                address_as_void_pointer(order_ptr),
-                1,
-                dtype=order_dtype
-            )
-            return Order_(arr)
+fusedhashmapdepth_bid_qty_at_tick = lib.fusedhashmapdepth_bid_qty_at_tick
+fusedhashmapdepth_bid_qty_at_tick.restype = c_double
+fusedhashmapdepth_bid_qty_at_tick.argtypes = [c_void_p, c_int64]
 
-    def has_next(self) -> bool:
-        if is_null_ptr(self.ptr):
-            return False
-        self.order_ptr = orders_values_next(self.ptr)
-        if is_null_ptr(self.order_ptr):
-            self.ptr = 0
-            return False
-        return True
+fusedhashmapdepth_ask_qty_at_tick = lib.fusedhashmapdepth_ask_qty_at_tick
+fusedhashmapdepth_ask_qty_at_tick.restype = c_double
+fusedhashmapdepth_ask_qty_at_tick.argtypes = [c_void_p, c_int64]
 
-    def get(self) -> Order:
-        if is_null_ptr(self.order_ptr):
-            raise RuntimeError
-        arr = carray(
-            address_as_void_pointer(self.order_ptr),
-            1,
-            dtype=order_dtype
-        )
-        return Order_(arr)
+fusedhashmapdepth_cum_bid_qty = lib.fusedhashmapdepth_cum_bid_qty
+fusedhashmapdepth_cum_bid_qty.restype = c_double
+fusedhashmapdepth_cum_bid_qty.argtypes = [c_void_p, c_uint64]
 
+fusedhashmapdepth_cum_ask_qty = lib.fusedhashmapdepth_cum_ask_qty
+fusedhashmapdepth_cum_ask_qty.restype = c_double
+fusedhashmapdepth_cum_ask_qty.argtypes = [c_void_p, c_uint64]
 
-Values_ = jitclass(Values)
+fusedhashmapdepth_imbalance = lib.fusedhashmapdepth_imbalance
+fusedhashmapdepth_imbalance.restype = c_double
+fusedhashmapdepth_imbalance.argtypes = [c_void_p, c_uint64]
 
+fusedhashmapdepth_weighted_mid = lib.fusedhashmapdepth_weighted_mid
+fusedhashmapdepth_weighted_mid.restype = c_double
+fusedhashmapdepth_weighted_mid.argtypes = [c_void_p, c_uint64]
 
-class OrderDict:
-    """
-    This is a wrapper for the order dictionary. It only supports :func:`get` method, ``in`` operator through
-    :func:`__contains__`, and :func:`values` method for iterating over values. Please note the limitations of the values
-    iterator.
-    """
-    ptr: voidptr
+fusedhashmapdepth_bid_vwap = lib.fusedhashmapdepth_bid_vwap
+fusedhashmapdepth_bid_vwap.restype = c_double
+fusedhashmapdepth_bid_vwap.argtypes = [c_void_p, c_double, c_uint64]
 
-    def __init__(self, ptr: voidptr):
-        self.ptr = ptr
+fusedhashmapdepth_ask_vwap = lib.fusedhashmapdepth_ask_vwap
+fusedhashmapdepth_ask_vwap.restype = c_double
+fusedhashmapdepth_ask_vwap.argtypes = [c_void_p, c_double, c_uint64]
 
-    def values(self) -> Values:
-        """
-        Since `numba` does not support ``__next__`` method in `njit`, you need to manually iterate using the
-        ``next``, which returns the next order value if it exists; otherwise, it returns `None`.
+fusedhashmapdepth_snapshot = lib.fusedhashmapdepth_snapshot
+fusedhashmapdepth_snapshot.restype = c_void_p
+fusedhashmapdepth_snapshot.argtypes = [c_void_p, POINTER(c_uint64)]
 
-        **Example**
+fusedhashmapdepth_snapshot_free = lib.fusedhashmapdepth_snapshot_free
+fusedhashmapdepth_snapshot_free.restype = c_void_p
+fusedhashmapdepth_snapshot_free.argtypes = [c_void_p, c_uint64]
 
-        .. code-block:: python
 
-            values = order_dict.values()
-            while True:
-                order = values.next()
-                if order is None:
-                    break
-                # Do what you need with the order.
 
-        Alternatively, ``has_next`` returns ``True`` if there is a next element and ``False`` otherwise, while also
-        moving the iterator to the next element internally. ``get`` method then returns the element moved to by the
-        ``has_next`` method. Since ``has_next`` internally moves the iterator, it should not be used solely to check if
-        there is a next element.
 
-        **Example**
 
-        .. code-block:: python
 
-            values = order_dict.values()
-            while values.has_next():
-                order = values.get()
-                # Do what you need with the order.
+class FusedHashMapMarketDepth:
+    ptr: voidptr
 
-        """
-        return Values_(orders_values(self.ptr))
+    def __init__(self, ptr: voidptr):
+        self.ptr = ptr
 
-    def get(self, order_id: uint64) -> Order | None:
+    @property
+    def best_bid_tick(self) -> int64:
         """
-        Args:
-            order_id: Order ID
-
+        Returns the best bid price in ticks.
+        """
+        return fusedhashmapdepth_best_bid_tick(self.ptr)
+
+    @property
+    def best_ask_tick(self) -> int64:
+        """
+        Returns the best ask price in ticks.
+        """
+        return fusedhashmapdepth_best_ask_tick(self.ptr)
+
+    @property
+    def best_bid(self) -> float64:
+        """
+        Returns the best bid price.
+        """
+        return fusedhashmapdepth_best_bid(self.ptr)
+
+    @property
+    def best_ask(self) -> float64:
+        """
+        Returns the best ask price.
+        """
+        return fusedhashmapdepth_best_ask(self.ptr)
+
+    @property
+    def best_bid_qty(self) -> float64:
+        """
+        Returns the quantity at the best bid price.
+        """
+        return fusedhashmapdepth_best_bid_qty(self.ptr)
+
+    @property
+    def best_ask_qty(self) -> float64:
+        """
+        Returns the quantity at the best ask price.
+        """
+        return fusedhashmapdepth_best_ask_qty(self.ptr)
+
+    @property
+    def tick_size(self) -> float64:
+        """
+        Returns the tick size.
+        """
+        return fusedhashmapdepth_tick_size(self.ptr)
+
+    @property
+    def lot_size(self) -> float64:
+        """
+        Returns the lot size.
+        """
+        return fusedhashmapdepth_lot_size(self.ptr)
+
+    def bid_qty_at_tick(self, price_tick: int64) -> float64:
+        """
+        Returns the quantity at the bid market depth for a given price in ticks.
+
+        Args:
+            price_tick: Price in ticks.
+
+        Returns:
+            The quantity at the specified price.
+        """
+        return fusedhashmapdepth_bid_qty_at_tick(self.ptr, price_tick)
+
+    def ask_qty_at_tick(self, price_tick: int64) -> float64:
+        """
+        Returns the quantity at the ask market depth for a given price in ticks.
+
+        Args:
+            price_tick: Price in ticks.
+
+        Returns:
+            The quantity at the specified price.
+        """
+        return fusedhashmapdepth_ask_qty_at_tick(self.ptr, price_tick)
+
+    def cum_bid_qty(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the cumulative bid quantity within ``depth_ticks`` from the best bid.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The cumulative bid quantity.
+        """
+        return fusedhashmapdepth_cum_bid_qty(self.ptr, depth_ticks)
+
+    def cum_ask_qty(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the cumulative ask quantity within ``depth_ticks`` from the best ask.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The cumulative ask quantity.
+        """
+        return fusedhashmapdepth_cum_ask_qty(self.ptr, depth_ticks)
+
+    def imbalance(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the order book imbalance, ``(bid_qty - ask_qty) / (bid_qty + ask_qty)``, of the cumulative
+        quantities within ``depth_ticks``.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The imbalance ranging from -1 to 1, or ``NaN`` if both sides are empty.
+        """
+        return fusedhashmapdepth_imbalance(self.ptr, depth_ticks)
+
+    def weighted_mid(self, depth_ticks: uint64) -> float64:
+        """
+        Returns the mid-price weighted by the cumulative quantities within ``depth_ticks`` of the opposite
+        sides. With ``depth_ticks`` of 1, this is the micro-price.
+
+        Args:
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The weighted mid-price, or ``NaN`` if either side is empty.
+        """
+        return fusedhashmapdepth_weighted_mid(self.ptr, depth_ticks)
+
+    def bid_vwap(self, qty: float64, depth_ticks: uint64) -> float64:
+        """
+        Returns the volume-weighted average price at which a sell order of the given quantity would be
+        filled by sweeping the bid side within ``depth_ticks``.
+
+        Args:
+            qty: Quantity to fill.
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The VWAP, or ``NaN`` if the quantity cannot be filled within ``depth_ticks``.
+        """
+        return fusedhashmapdepth_bid_vwap(self.ptr, qty, depth_ticks)
+
+    def ask_vwap(self, qty: float64, depth_ticks: uint64) -> float64:
+        """
+        Returns the volume-weighted average price at which a buy order of the given quantity would be
+        filled by sweeping the ask side within ``depth_ticks``.
+
+        Args:
+            qty: Quantity to fill.
+            depth_ticks: Number of price levels from the best price, including the best price.
+
+        Returns:
+            The VWAP, or ``NaN`` if the quantity cannot be filled within ``depth_ticks``.
+        """
+        return fusedhashmapdepth_ask_vwap(self.ptr, qty, depth_ticks)
+
+    def snapshot(self) -> EVENT_ARRAY:
+        length = uint64(0)
+        len_ptr = ptr_from_val(length)
+        ptr = fusedhashmapdepth_snapshot(self.ptr, len_ptr)
+        return numba.carray(
+            address_as_void_pointer(ptr),
+            val_from_ptr(len_ptr),
+            event_dtype
+        )
+
+    def snapshot_free(self, arr: EVENT_ARRAY):
+        fusedhashmapdepth_snapshot_free(arr.ctypes.data, len(arr))
+
+FusedHashMapMarketDepth_ = jitclass(FusedHashMapMarketDepth)
+
+orders_get = lib.orders_get
+orders_get.restype = c_void_p
+orders_get.argtypes = [c_void_p, c_uint64]
+
+orders_contains = lib.orders_contains
+orders_contains.restype = c_bool
+orders_contains.argtypes = [c_void_p, c_uint64]
+
+orders_len = lib.orders_len
+orders_len.restype = c_uint64
+orders_len.argtypes = [c_void_p]
+
+orders_values = lib.orders_values
+orders_values.restype = c_void_p
+orders_values.argtypes = [c_void_p]
+
+orders_values_next = lib.orders_values_next
+orders_values_next.restype = c_void_p
+orders_values_next.argtypes = [c_void_p]
+
+
+class Values:
+    ptr: voidptr
+    order_ptr: voidptr
+
+    def __init__(self, ptr: voidptr):
+        self.ptr = ptr
+        self.order_ptr = 0
+
+    # def __next__(self) -> Order:
+    #     if is_null_ptr(self.ptr):
+    #         return None
+    #     order_ptr = orders_values_next(self.ptr)
+    #     if is_null_ptr(order_ptr):
+    #         self.ptr = 0
+    #         raise StopIteration
+    #     else:
+    #         arr = carray(
+    #             address_as_void_pointer(order_ptr),
+    #             1,
+    #             dtype=order_dtype
+    #         )
+    #         return Order_(arr)
+
+    def next(self) -> Order | None:
+        if is_null_ptr(self.ptr):
+            return None
+        order_ptr = orders_values_next(self.ptr)
+        if is_null_ptr(order_ptr):
+            self.ptr = 0
+            return None
+        else:
+            arr = carray(
+                address_as_void_pointer(order_ptr),
+                1,
+                dtype=order_dtype
+            )
+            return Order_(arr)
+
+    def has_next(self) -> bool:
+        if is_null_ptr(self.ptr):
+            return False
+        self.order_ptr = orders_values_next(self.ptr)
+        if is_null_ptr(self.order_ptr):
+            self.ptr = 0
+            return False
+        return True
+
+    def get(self) -> Order:
+        if is_null_ptr(self.order_ptr):
+            raise RuntimeError
+        arr = carray(
+            address_as_void_pointer(self.order_ptr),
+            1,
+            dtype=order_dtype
+        )
+        return Order_(arr)
+
+
+Values_ = jitclass(Values)
+
+
+class OrderDict:
+    """
+    This is a wrapper for the order dictionary. It only supports :func:`get` method, ``in`` operator through
+    :func:`__contains__`, and :func:`values` method for iterating over values. Please note the limitations of the values
+    iterator.
+    """
+    ptr: voidptr
+
+    def __init__(self, ptr: voidptr):
+        self.ptr = ptr
+
+    def values(self) -> Values:
+        """
+        Since `numba` does not support ``__next__`` method in `njit`, you need to manually iterate using the
+        ``next``, which returns the next order value if it exists; otherwise, it returns `None`.
+
+        **Example**
+
+        .. code-block:: python
+
+            values = order_dict.values()
+            while True:
+                order = values.next()
+                if order is None:
+                    break
+                # Do what you need with the order.
+
+        Alternatively, ``has_next`` returns ``True`` if there is a next element and ``False`` otherwise, while also
+        moving the iterator to the next element internally. ``get`` method then returns the element moved to by the
+        ``has_next`` method. Since ``has_next`` internally moves the iterator, it should not be used solely to check if
+        there is a next element.
+
+        **Example**
+
+        .. code-block:: python
+
+            values = order_dict.values()
+            while values.has_next():
+                order = values.get()
+                # Do what you need with the order.
+
+        """
+        return Values_(orders_values(self.ptr))
+
+    def get(self, order_id: uint64) -> Order | None:
+        """
+        Args:
+            order_id: Order ID
+
         Returns:
             Order with the specified order ID; `None` if it does not exist.
         """
@@ -1428,128 +2012,1018 @@ class ROIVectorMarketDepthBacktest:
 ROIVectorMarketDepthBacktest_ = jitclass(ROIVectorMarketDepthBacktest)
 
 
-fusemarketdepth_new = lib.fusemarketdepth_new
-fusemarketdepth_new.restype = c_void_p
-fusemarketdepth_new.argtypes = [c_double, c_double]
+fusedhashmapbt_elapse = lib.fusedhashmapbt_elapse
+fusedhashmapbt_elapse.restype = c_int64
+fusedhashmapbt_elapse.argtypes = [c_void_p, c_uint64]
 
-fusemarketdepth_free = lib.fusemarketdepth_free
-fusemarketdepth_free.restype = c_void_p
-fusemarketdepth_free.argtypes = [c_void_p]
+fusedhashmapbt_elapse_bt = lib.fusedhashmapbt_elapse_bt
+fusedhashmapbt_elapse_bt.restype = c_int64
+fusedhashmapbt_elapse_bt.argtypes = [c_void_p, c_uint64]
 
-fusemarketdepth_process_event = lib.fusemarketdepth_process_event
-fusemarketdepth_process_event.restype = c_bool
-fusemarketdepth_process_event.argtypes = [c_void_p, c_void_p, c_bool]
+fusedhashmapbt_fusedhashmapbt_wait_order_response = lib.fusedhashmapbt_wait_order_response
+fusedhashmapbt_fusedhashmapbt_wait_order_response.restype = c_int64
+fusedhashmapbt_fusedhashmapbt_wait_order_response.argtypes = [c_void_p, c_uint64, c_uint64, c_int64]
 
-fusemarketdepth_fused_events = lib.fusemarketdepth_fused_events
-fusemarketdepth_fused_events.restype = c_void_p
-fusemarketdepth_fused_events.argtypes = [c_void_p, POINTER(c_uint64)]
+fusedhashmapbt_wait_next_feed = lib.fusedhashmapbt_wait_next_feed
+fusedhashmapbt_wait_next_feed.restype = c_int64
+fusedhashmapbt_wait_next_feed.argtypes = [c_void_p, c_bool, c_int64]
 
+fusedhashmapbt_close = lib.fusedhashmapbt_close
+fusedhashmapbt_close.restype = c_int64
+fusedhashmapbt_close.argtypes = [c_void_p]
 
-class FuseMarketDepth:
-    """
-    This combines the real-time Level-1 book ticker stream with the conflated Level-2 depth stream to produce the
-    most frequent and granular depth events possible.
+fusedhashmapbt_position = lib.fusedhashmapbt_position
+fusedhashmapbt_position.restype = c_double
+fusedhashmapbt_position.argtypes = [c_void_p, c_uint64]
 
-    Args:
-        tick_size: tick size for the asset being processed.
-        lot_size: lot size for the asset being processed.
-    """
+fusedhashmapbt_current_timestamp = lib.fusedhashmapbt_current_timestamp
+fusedhashmapbt_current_timestamp.restype = c_int64
+fusedhashmapbt_current_timestamp.argtypes = [c_void_p]
+
+fusedhashmapbt_depth = lib.fusedhashmapbt_depth
+fusedhashmapbt_depth.restype = c_void_p
+fusedhashmapbt_depth.argtypes = [c_void_p, c_uint64]
+
+fusedhashmapbt_last_trades = lib.fusedhashmapbt_last_trades
+fusedhashmapbt_last_trades.restype = c_void_p
+fusedhashmapbt_last_trades.argtypes = [c_void_p, c_uint64, POINTER(c_uint64)]
+
+fusedhashmapbt_num_assets = lib.fusedhashmapbt_num_assets
+fusedhashmapbt_num_assets.restype = c_uint64
+fusedhashmapbt_num_assets.argtypes = [c_void_p]
+
+fusedhashmapbt_submit_buy_order = lib.fusedhashmapbt_submit_buy_order
+fusedhashmapbt_submit_buy_order.restype = c_int64
+fusedhashmapbt_submit_buy_order.argtypes = [
+    c_void_p,
+    c_uint64,
+    c_uint64,
+    c_double,
+    c_double,
+    c_uint8,
+    c_uint8,
+    c_bool
+]
+
+fusedhashmapbt_submit_sell_order = lib.fusedhashmapbt_submit_sell_order
+fusedhashmapbt_submit_sell_order.restype = c_int64
+fusedhashmapbt_submit_sell_order.argtypes = [
+    c_void_p,
+    c_uint64,
+    c_uint64,
+    c_double,
+    c_double,
+    c_uint8,
+    c_uint8,
+    c_bool
+]
+
+fusedhashmapbt_modify = lib.fusedhashmapbt_modify
+fusedhashmapbt_modify.restype = c_int64
+fusedhashmapbt_modify.argtypes = [c_void_p, c_uint64, c_uint64, c_double, c_double, c_bool]
+
+fusedhashmapbt_cancel = lib.fusedhashmapbt_cancel
+fusedhashmapbt_cancel.restype = c_int64
+fusedhashmapbt_cancel.argtypes = [c_void_p, c_uint64, c_uint64, c_bool]
+
+fusedhashmapbt_clear_last_trades = lib.fusedhashmapbt_clear_last_trades
+fusedhashmapbt_clear_last_trades.restype = c_void_p
+fusedhashmapbt_clear_last_trades.argtypes = [c_void_p, c_uint64]
+
+fusedhashmapbt_clear_inactive_orders = lib.fusedhashmapbt_clear_inactive_orders
+fusedhashmapbt_clear_inactive_orders.restype = c_void_p
+fusedhashmapbt_clear_inactive_orders.argtypes = [c_void_p, c_uint64]
+
+fusedhashmapbt_orders = lib.fusedhashmapbt_orders
+fusedhashmapbt_orders.restype = c_void_p
+fusedhashmapbt_orders.argtypes = [c_void_p, c_uint64]
+
+fusedhashmapbt_state_values = lib.fusedhashmapbt_state_values
+fusedhashmapbt_state_values.restype = c_void_p
+fusedhashmapbt_state_values.argtypes = [c_void_p, c_uint64]
+
+fusedhashmapbt_feed_latency = lib.fusedhashmapbt_feed_latency
+fusedhashmapbt_feed_latency.restype = c_bool
+fusedhashmapbt_feed_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64)]
+
+fusedhashmapbt_order_latency = lib.fusedhashmapbt_order_latency
+fusedhashmapbt_order_latency.restype = c_bool
+fusedhashmapbt_order_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64), POINTER(c_int64)]
+
+fusedhashmapbt_goto_end = lib.fusedhashmapbt_goto_end
+fusedhashmapbt_goto_end.restype = c_int64
+fusedhashmapbt_goto_end.argtypes = [c_void_p]
 
+
+class FusedHashMapMarketDepthBacktest:
     ptr: voidptr
-    buf: from_dtype(event_dtype)[:]
 
-    def __init__(self, tick_size: float64, lot_size: float64):
-        self.ptr = fusemarketdepth_new(tick_size, lot_size)
-        self.buf = np.zeros(1, event_dtype)
+    def __init__(self, ptr: voidptr):
+        self.ptr = ptr
 
-    # def __del__(self):
-    #     fusemarketdepth_free(self.ptr)
+    @property
+    def current_timestamp(self) -> int64:
+        """
+        In backtesting, this timestamp reflects the time at which the backtesting is conducted within the provided data.
+        """
+        return fusedhashmapbt_current_timestamp(self.ptr)
 
-    def close(self) -> None:
+    def depth(self, asset_no: uint64) -> FusedHashMapMarketDepth:
         """
-        Releases resources associated with this `FuseMarketDepth` instance.
+        Args:
+            asset_no: Asset number from which the market depth will be retrieved.
 
-        This method must be called to free the underlying memory allocated by the native implementation.
+        Returns:
+            The depth of market of the specific asset.
         """
-        fusemarketdepth_free(self.ptr)
+        return FusedHashMapMarketDepth_(fusedhashmapbt_depth(self.ptr, asset_no))
 
-    def process_event(self, ev: EVENT_ARRAY, index: uint64, add: bool) -> None:
+    @property
+    def num_assets(self) -> uint64:
         """
-        Processes a market event at the given index.
+        Returns the number of assets.
+        """
+        return fusedhashmapbt_num_assets(self.ptr)
 
+    def position(self, asset_no: uint64) -> float64:
+        """
         Args:
-            ev: The array of events to process.
-            index: The index of the event in the array to process.
-            add: If `True`, the event is added to the fused events.
-                 If `False`, the event is used to update market depth for future processing, but is not included in the
-                 fused output.
+            asset_no: Asset number from which the position will be retrieved.
+
+        Returns:
+            The quantity of the held position.
         """
-        ev_ptr = ev.ctypes.data + 64 * index
-        ok = fusemarketdepth_process_event(self.ptr, ev_ptr, add)
-        if not ok:
-            raise ValueError
+        return fusedhashmapbt_position(self.ptr, asset_no)
 
-    @property
-    def fused_events(self) -> EVENT_ARRAY:
+    def state_values(self, asset_no: uint64) -> StateValues:
         """
-        Returns the array of fused events generated so far.
+        Args:
+            asset_no: Asset number from which the state values will be retrieved.
+
+        Returns:
+            The state’s values.
+        """
+        ptr = fusedhashmapbt_state_values(self.ptr, asset_no)
+        arr = numba.carray(
+            address_as_void_pointer(ptr),
+            1,
+            state_values_dtype
+        )
+        return StateValues_(arr)
+
+    def last_trades(self, asset_no: uint64) -> EVENT_ARRAY:
+        """
+        Args:
+            asset_no: Asset number from which the trades will be retrieved.
+
+        Returns:
+            An array of `Event` representing trades occurring in the market for the specific asset.
         """
         length = uint64(0)
         len_ptr = ptr_from_val(length)
-        ptr = fusemarketdepth_fused_events(self.ptr, len_ptr)
+        ptr = fusedhashmapbt_last_trades(self.ptr, asset_no, len_ptr)
         return numba.carray(
             address_as_void_pointer(ptr),
             val_from_ptr(len_ptr),
             event_dtype
         )
 
-FuseMarketDepth_ = jitclass(FuseMarketDepth)
+    def clear_last_trades(self, asset_no: uint64) -> None:
+        """
+        Clears the last trades occurring in the market from the buffer for :func:`last_trades`.
+
+        Args:
+            asset_no: Asset number at which this command will be executed.
+                      If :const:`ALL_ASSETS <hftbacktest.types.ALL_ASSETS>`,
+                      all last trades in any assets will be cleared.
+        """
+        fusedhashmapbt_clear_last_trades(self.ptr, asset_no)
+
+    def orders(self, asset_no: uint64) -> OrderDict:
+        """
+        Args:
+            asset_no: Asset number from which orders will be retrieved.
+
+        Returns:
+            An order dictionary where the keys are order IDs and the corresponding values are
+            :class:`Order <hftbacktest.order.Order>`.
+        """
+        return OrderDict_(fusedhashmapbt_orders(self.ptr, asset_no))
+
+    def submit_buy_order(
+            self,
+            asset_no: uint64,
+            order_id: uint64,
+            price: float64,
+            qty: float64,
+            time_in_force: uint8,
+            order_type: uint8,
+            wait: bool
+    ) -> int64:
+        """
+        Submits a buy order.
+
+        Args:
+            asset_no: Asset number at which this command will be executed.
+            order_id: The unique order ID; there should not be any existing order with the same ID on both local and
+                      exchange sides.
+            price: Order price.
+            qty: Quantity to buy.
+            time_in_force: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                * :const:`GTC <hftbacktest.order.GTC>`
+                * :const:`GTX <hftbacktest.order.GTX>`
+                * :const:`FOK <hftbacktest.order.FOK>`
+                * :const:`IOC <hftbacktest.order.IOC>`
+
+            order_type: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                * :const:`LIMIT <hftbacktest.order.LIMIT>`
+                * :const:`MARKET <hftbacktest.order.MARKET>`
+
+            wait: If `True`, wait until the order placement response is received.
+
+        Returns:
+            * `0` when it successfully submits an order.
+            * `1` when it reaches the end of the data, if `wait` is `True`.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_submit_buy_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+
+    def submit_sell_order(
+            self,
+            asset_no: uint64,
+            order_id: uint64,
+            price: float64,
+            qty: float64,
+            time_in_force: uint8,
+            order_type: uint8,
+            wait: bool
+    ) -> int64:
+        """
+        Submits a sell order.
+
+        Args:
+            asset_no: Asset number at which this command will be executed.
+            order_id: The unique order ID; there should not be any existing order with the same ID on both local and
+                      exchange sides.
+            price: Order price.
+            qty: Quantity to sell.
+            time_in_force: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                * :const:`GTC <hftbacktest.order.GTC>`
+                * :const:`GTX <hftbacktest.order.GTX>`
+                * :const:`FOK <hftbacktest.order.FOK>`
+                * :const:`IOC <hftbacktest.order.IOC>`
+
+            order_type: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                * :const:`LIMIT <hftbacktest.order.LIMIT>`
+                * :const:`MARKET <hftbacktest.order.MARKET>`
+
+            wait: If `True`, wait until the order placement response is received.
+
+        Returns:
+            * `0` when it successfully submits an order.
+            * `1` when it reaches the end of the data, if `wait` is `True`.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_submit_sell_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+
+    def modify(self, asset_no: uint64, order_id: uint64, price: float, qty: float, wait: bool) -> int64:
+        """
+        Modifies the specified order.
+
+        Args:
+            asset_no: Asset number at which this command will be executed.
+            order_id: Order ID to modify.
+            price: Order price.
+            qty: Order quantity.
+            wait: If `True`, wait until the order cancel response is received.
+
+        Returns:
+            * `0` when it successfully modifies an order.
+            * `1` when it reaches the end of the data, if `wait` is `True`.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_modify(self.ptr, asset_no, order_id, price, qty, wait)
+
+    def cancel(self, asset_no: uint64, order_id: uint64, wait: bool) -> int64:
+        """
+        Cancels the specified order.
+
+        Args:
+            asset_no: Asset number at which this command will be executed.
+            order_id: Order ID to cancel.
+            wait: If `True`, wait until the order cancel response is received.
+
+        Returns:
+            * `0` when it successfully cancels an order.
+            * `1` when it reaches the end of the data, if `wait` is `True`.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_cancel(self.ptr, asset_no, order_id, wait)
+
+    def clear_inactive_orders(self, asset_no: uint64) -> None:
+        """
+        Clears inactive orders from the local order dictionary whose status is neither
+        :const:`NEW <hftbacktest.order.NEW>` nor :const:`PARTIALLY_FILLED <hftbacktest.order.PARTIALLY_FILLED>`.
+
+        Args:
+            asset_no: Asset number at which this command will be executed.
+                      If :const:`ALL_ASSETS <hftbacktest.types.ALL_ASSETS>`,
+                      all inactive orders in any assets will be cleared.
+        """
+        fusedhashmapbt_clear_inactive_orders(self.ptr, asset_no)
+
+    def wait_order_response(self, asset_no: uint64, order_id: uint64, timeout: int64) -> int64:
+        """
+        Waits for the response of the order with the given order ID until timeout.
+
+        Args:
+            asset_no: Asset number where an order with `order_id` exists.
+            order_id: Order ID to wait for the response.
+            timeout: Timeout for waiting for the order response. Nanoseconds is the default unit. However, unit should
+                     be the same as the data’s timestamp unit.
+
+        Returns:
+            * `0` when it receives an order response for the specified order ID of the specified asset number, or
+              reaches the timeout.
+            * `1` when it reaches the end of the data.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_fusedhashmapbt_wait_order_response(self.ptr, asset_no, order_id, timeout)
+
+    def wait_next_feed(self, include_order_resp: bool, timeout: int64) -> int64:
+        """
+        Waits until the next feed is received, or until timeout.
+
+        Args:
+            include_order_resp: If set to `True`, it will return when any order response is received, in addition to the
+                                next feed.
+            timeout: Timeout for waiting for the next feed or an order response. Nanoseconds is the default unit.
+                     However, unit should be the same as the data’s timestamp unit.
+
+        Returns:
+            * `0` when it reaches the timeout.
+            * `1` when it reaches the end of the data.
+            * `2` when it receives a market feed.
+            * `3` when it receives an order response if `include_order_resp` is `True`.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_wait_next_feed(self.ptr, include_order_resp, timeout)
+
+    def elapse(self, duration: uint64) -> int64:
+        """
+        Elapses the specified duration.
+
+        Args:
+            duration: Duration to elapse. Nanoseconds is the default unit. However, unit should be the same as the
+                      data’s timestamp unit.
+
+        Returns:
+            * `0` when it successfully elapses the given duration.
+            * `1` when it reaches the end of the data.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_elapse(self.ptr, duration)
+
+    def elapse_bt(self, duration: int64) -> int64:
+        """
+        Elapses time only in backtesting. In live mode, it is ignored. (Supported only in the Rust implementation)
+
+        The `elapse` method exclusively manages time during backtesting, meaning that factors such as computing time are
+        not properly accounted for. So, this method can be utilized to simulate such processing times.
+
+        Args:
+            duration: Duration to elapse. Nanoseconds is the default unit. However, unit should be the same as the
+                      data’s timestamp unit.
+
+        Returns:
+            * `0` when it successfully elapses the given duration.
+            * `1` when it reaches the end of the data.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_elapse_bt(self.ptr, duration)
+
+    def close(self) -> int64:
+        """
+        Closes this backtester or bot.
+
+        Returns:
+            * `0` when it successfully closes the bot.
+            * Otherwise, an error occurred.
+        """
+        return fusedhashmapbt_close(self.ptr)
+
+    def feed_latency(self, asset_no: uint64) -> Tuple[int64, int64] | None:
+        """
+        Args:
+            asset_no: Asset number from which the last feed latency will be retrieved.
+
+        Returns:
+            The last feed’s exchange timestamp and local receipt timestamp if a feed has been received; otherwise,
+            returns `None`.
+        """
+        exch_ts = int64(0)
+        local_ts = int64(0)
+        exch_ts_ptr = ptr_from_val(exch_ts)
+        local_ts_ptr = ptr_from_val(local_ts)
+        if fusedhashmapbt_feed_latency(self.ptr, asset_no, exch_ts_ptr, local_ts_ptr):
+            return val_from_ptr(exch_ts_ptr), val_from_ptr(local_ts_ptr)
+        return None
+
+    def order_latency(self, asset_no: uint64) -> Tuple[int64, int64, int64] | None:
+        """
+        Args:
+            asset_no: Asset number from which the last order latency will be retrieved.
+
+        Returns:
+            The last order’s request timestamp, exchange timestamp, and response receipt timestamp if there has been an
+            order submission; otherwise, returns `None`.
+        """
+        req_ts = int64(0)
+        exch_ts = int64(0)
+        resp_ts = int64(0)
+        req_ts_ptr = ptr_from_val(req_ts)
+        exch_ts_ptr = ptr_from_val(exch_ts)
+        resp_ts_ptr = ptr_from_val(resp_ts)
+        if fusedhashmapbt_order_latency(self.ptr, asset_no, req_ts_ptr, exch_ts_ptr, resp_ts_ptr):
+            return val_from_ptr(req_ts_ptr), val_from_ptr(exch_ts_ptr), val_from_ptr(resp_ts_ptr)
+        return None
+
+    def _goto_end(self) -> int64:
+        return fusedhashmapbt_goto_end(self.ptr)
+
+
+FusedHashMapMarketDepthBacktest_ = jitclass(FusedHashMapMarketDepthBacktest)
+
+
+fusemarketdepth_new = lib.fusemarketdepth_new
+fusemarketdepth_new.restype = c_void_p
+fusemarketdepth_new.argtypes = [c_double, c_double]
+
+fusemarketdepth_free = lib.fusemarketdepth_free
+fusemarketdepth_free.restype = c_void_p
+fusemarketdepth_free.argtypes = [c_void_p]
+
+fusemarketdepth_process_event = lib.fusemarketdepth_process_event
+fusemarketdepth_process_event.restype = c_bool
+fusemarketdepth_process_event.argtypes = [c_void_p, c_void_p, c_bool]
+
+fusemarketdepth_fused_events = lib.fusemarketdepth_fused_events
+fusemarketdepth_fused_events.restype = c_void_p
+fusemarketdepth_fused_events.argtypes = [c_void_p, POINTER(c_uint64)]
+
+
+class FuseMarketDepth:
+    """
+    This combines the real-time Level-1 book ticker stream with the conflated Level-2 depth stream to produce the
+    most frequent and granular depth events possible.
+
+    Args:
+        tick_size: tick size for the asset being processed.
+        lot_size: lot size for the asset being processed.
+    """
+
+    ptr: voidptr
+    buf: from_dtype(event_dtype)[:]
+
+    def __init__(self, tick_size: float64, lot_size: float64):
+        self.ptr = fusemarketdepth_new(tick_size, lot_size)
+        self.buf = np.zeros(1, event_dtype)
+
+    # def __del__(self):
+    #     fusemarketdepth_free(self.ptr)
+
+    def close(self) -> None:
+        """
+        Releases resources associated with this `FuseMarketDepth` instance.
+
+        This method must be called to free the underlying memory allocated by the native implementation.
+        """
+        fusemarketdepth_free(self.ptr)
+
+    def process_event(self, ev: EVENT_ARRAY, index: uint64, add: bool) -> None:
+        """
+        Processes a market event at the given index.
+
+        Args:
+            ev: The array of events to process.
+            index: The index of the event in the array to process.
+            add: If `True`, the event is added to the fused events.
+                 If `False`, the event is used to update market depth for future processing, but is not included in the
+                 fused output.
+        """
+        ev_ptr = ev.ctypes.data + 64 * index
+        ok = fusemarketdepth_process_event(self.ptr, ev_ptr, add)
+        if not ok:
+            raise ValueError
+
+    @property
+    def fused_events(self) -> EVENT_ARRAY:
+        """
+        Returns the array of fused events generated so far.
+        """
+        length = uint64(0)
+        len_ptr = ptr_from_val(length)
+        ptr = fusemarketdepth_fused_events(self.ptr, len_ptr)
+        return numba.carray(
+            address_as_void_pointer(ptr),
+            val_from_ptr(len_ptr),
+            event_dtype
+        )
+
+FuseMarketDepth_ = jitclass(FuseMarketDepth)
+
+
+if LIVE_FEATURE:
+    hashmaplive_elapse = lib.hashmaplive_elapse
+    hashmaplive_elapse.restype = c_int64
+    hashmaplive_elapse.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_elapse_bt = lib.hashmaplive_elapse_bt
+    hashmaplive_elapse_bt.restype = c_int64
+    hashmaplive_elapse_bt.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_hashmaplive_wait_order_response = lib.hashmaplive_wait_order_response
+    hashmaplive_hashmaplive_wait_order_response.restype = c_int64
+    hashmaplive_hashmaplive_wait_order_response.argtypes = [c_void_p, c_uint64, c_uint64, c_int64]
+
+    hashmaplive_wait_next_feed = lib.hashmaplive_wait_next_feed
+    hashmaplive_wait_next_feed.restype = c_int64
+    hashmaplive_wait_next_feed.argtypes = [c_void_p, c_bool, c_int64]
+
+    hashmaplive_close = lib.hashmaplive_close
+    hashmaplive_close.restype = c_int64
+    hashmaplive_close.argtypes = [c_void_p]
+
+    hashmaplive_position = lib.hashmaplive_position
+    hashmaplive_position.restype = c_double
+    hashmaplive_position.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_current_timestamp = lib.hashmaplive_current_timestamp
+    hashmaplive_current_timestamp.restype = c_int64
+    hashmaplive_current_timestamp.argtypes = [c_void_p]
+
+    hashmaplive_depth = lib.hashmaplive_depth
+    hashmaplive_depth.restype = c_void_p
+    hashmaplive_depth.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_last_trades = lib.hashmaplive_last_trades
+    hashmaplive_last_trades.restype = c_void_p
+    hashmaplive_last_trades.argtypes = [c_void_p, c_uint64, POINTER(c_uint64)]
+
+    hashmaplive_num_assets = lib.hashmaplive_num_assets
+    hashmaplive_num_assets.restype = c_uint64
+    hashmaplive_num_assets.argtypes = [c_void_p]
+
+    hashmaplive_submit_buy_order = lib.hashmaplive_submit_buy_order
+    hashmaplive_submit_buy_order.restype = c_int64
+    hashmaplive_submit_buy_order.argtypes = [
+        c_void_p,
+        c_uint64,
+        c_uint64,
+        c_double,
+        c_double,
+        c_uint8,
+        c_uint8,
+        c_bool
+    ]
+
+    hashmaplive_submit_sell_order = lib.hashmaplive_submit_sell_order
+    hashmaplive_submit_sell_order.restype = c_int64
+    hashmaplive_submit_sell_order.argtypes = [
+        c_void_p,
+        c_uint64,
+        c_uint64,
+        c_double,
+        c_double,
+        c_uint8,
+        c_uint8,
+        c_bool
+    ]
+
+    hashmaplive_modify = lib.hashmaplive_modify
+    hashmaplive_modify.restype = c_int64
+    hashmaplive_modify.argtypes = [c_void_p, c_uint64, c_uint64, c_double, c_double, c_bool]
+
+    hashmaplive_cancel = lib.hashmaplive_cancel
+    hashmaplive_cancel.restype = c_int64
+    hashmaplive_cancel.argtypes = [c_void_p, c_uint64, c_uint64, c_bool]
+
+    hashmaplive_clear_last_trades = lib.hashmaplive_clear_last_trades
+    hashmaplive_clear_last_trades.restype = c_void_p
+    hashmaplive_clear_last_trades.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_clear_inactive_orders = lib.hashmaplive_clear_inactive_orders
+    hashmaplive_clear_inactive_orders.restype = c_void_p
+    hashmaplive_clear_inactive_orders.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_orders = lib.hashmaplive_orders
+    hashmaplive_orders.restype = c_void_p
+    hashmaplive_orders.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_state_values = lib.hashmaplive_state_values
+    hashmaplive_state_values.restype = c_void_p
+    hashmaplive_state_values.argtypes = [c_void_p, c_uint64]
+
+    hashmaplive_feed_latency = lib.hashmaplive_feed_latency
+    hashmaplive_feed_latency.restype = c_bool
+    hashmaplive_feed_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64)]
+
+    hashmaplive_order_latency = lib.hashmaplive_order_latency
+    hashmaplive_order_latency.restype = c_bool
+    hashmaplive_order_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64), POINTER(c_int64)]
+
+
+    class HashMapMarketDepthLiveBot:
+        ptr: voidptr
+
+        def __init__(self, ptr: voidptr):
+            self.ptr = ptr
+
+        @property
+        def current_timestamp(self) -> int64:
+            """
+            In LiveBoting, this timestamp reflects the time at which the LiveBoting is conducted within the provided data.
+            """
+            return hashmaplive_current_timestamp(self.ptr)
+
+        def depth(self, asset_no: uint64) -> HashMapMarketDepth:
+            """
+            Args:
+                asset_no: Asset number from which the market depth will be retrieved.
+
+            Returns:
+                The depth of market of the specific asset.
+            """
+            return HashMapMarketDepth_(hashmaplive_depth(self.ptr, asset_no))
+
+        @property
+        def num_assets(self) -> uint64:
+            """
+            Returns the number of assets.
+            """
+            return hashmaplive_num_assets(self.ptr)
+
+        def position(self, asset_no: uint64) -> float64:
+            """
+            Args:
+                asset_no: Asset number from which the position will be retrieved.
+
+            Returns:
+                The quantity of the held position.
+            """
+            return hashmaplive_position(self.ptr, asset_no)
+
+        def state_values(self, asset_no: uint64) -> StateValues:
+            """
+            Args:
+                asset_no: Asset number from which the state values will be retrieved.
+
+            Returns:
+                The state’s values.
+            """
+            ptr = hashmaplive_state_values(self.ptr, asset_no)
+            arr = numba.carray(
+                address_as_void_pointer(ptr),
+                1,
+                state_values_dtype
+            )
+            return StateValues_(arr)
+
+        def last_trades(self, asset_no: uint64) -> EVENT_ARRAY:
+            """
+            Args:
+                asset_no: Asset number from which the trades will be retrieved.
+
+            Returns:
+                An array of `Event` representing trades occurring in the market for the specific asset.
+            """
+            length = uint64(0)
+            len_ptr = ptr_from_val(length)
+            ptr = hashmaplive_last_trades(self.ptr, asset_no, len_ptr)
+            return numba.carray(
+                address_as_void_pointer(ptr),
+                val_from_ptr(len_ptr),
+                event_dtype
+            )
+
+        def clear_last_trades(self, asset_no: uint64) -> None:
+            """
+            Clears the last trades occurring in the market from the buffer for :func:`last_trades`.
+
+            Args:
+                asset_no: Asset number at which this command will be executed.
+                          If :const:`ALL_ASSETS <hftLiveBot.types.ALL_ASSETS>`,
+                          all last trades in any assets will be cleared.
+            """
+            hashmaplive_clear_last_trades(self.ptr, asset_no)
+
+        def orders(self, asset_no: uint64) -> OrderDict:
+            """
+            Args:
+                asset_no: Asset number from which orders will be retrieved.
+
+            Returns:
+                An order dictionary where the keys are order IDs and the corresponding values are
+                :class:`Order <hftLiveBot.order.Order>`.
+            """
+            return OrderDict_(hashmaplive_orders(self.ptr, asset_no))
+
+        def submit_buy_order(
+                self,
+                asset_no: uint64,
+                order_id: uint64,
+                price: float64,
+                qty: float64,
+                time_in_force: uint8,
+                order_type: uint8,
+                wait: bool
+        ) -> int64:
+            """
+            Submits a buy order.
+
+            Args:
+                asset_no: Asset number at which this command will be executed.
+                order_id: The unique order ID; there should not be any existing order with the same ID on both local and
+                          exchange sides.
+                price: Order price.
+                qty: Quantity to buy.
+                time_in_force: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                    * :const:`GTC <hftLiveBot.order.GTC>`
+                    * :const:`GTX <hftLiveBot.order.GTX>`
+                    * :const:`FOK <hftLiveBot.order.FOK>`
+                    * :const:`IOC <hftLiveBot.order.IOC>`
+
+                order_type: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                    * :const:`LIMIT <hftLiveBot.order.LIMIT>`
+                    * :const:`MARKET <hftLiveBot.order.MARKET>`
+
+                wait: If `True`, wait until the order placement response is received.
+
+            Returns:
+                * `0` when it successfully submits an order.
+                * `1` when it reaches the end of the data, if `wait` is `True`.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_submit_buy_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+
+        def submit_sell_order(
+                self,
+                asset_no: uint64,
+                order_id: uint64,
+                price: float64,
+                qty: float64,
+                time_in_force: uint8,
+                order_type: uint8,
+                wait: bool
+        ) -> int64:
+            """
+            Submits a sell order.
+
+            Args:
+                asset_no: Asset number at which this command will be executed.
+                order_id: The unique order ID; there should not be any existing order with the same ID on both local and
+                          exchange sides.
+                price: Order price.
+                qty: Quantity to sell.
+                time_in_force: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                    * :const:`GTC <hftLiveBot.order.GTC>`
+                    * :const:`GTX <hftLiveBot.order.GTX>`
+                    * :const:`FOK <hftLiveBot.order.FOK>`
+                    * :const:`IOC <hftLiveBot.order.IOC>`
+
+                order_type: Available options vary depending on the exchange model. See to the exchange model for details.
+
+                    * :const:`LIMIT <hftLiveBot.order.LIMIT>`
+                    * :const:`MARKET <hftLiveBot.order.MARKET>`
+
+                wait: If `True`, wait until the order placement response is received.
+
+            Returns:
+                * `0` when it successfully submits an order.
+                * `1` when it reaches the end of the data, if `wait` is `True`.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_submit_sell_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+
+        def modify(self, asset_no: uint64, order_id: uint64, price: float, qty: float, wait: bool) -> int64:
+            """
+            Modifies the specified order.
+
+            Args:
+                asset_no: Asset number at which this command will be executed.
+                order_id: Order ID to modify.
+                price: Order price.
+                qty: Order quantity.
+                wait: If `True`, wait until the order cancel response is received.
+
+            Returns:
+                * `0` when it successfully modifies an order.
+                * `1` when it reaches the end of the data, if `wait` is `True`.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_modify(self.ptr, asset_no, order_id, price, qty, wait)
+
+        def cancel(self, asset_no: uint64, order_id: uint64, wait: bool) -> int64:
+            """
+            Cancels the specified order.
+
+            Args:
+                asset_no: Asset number at which this command will be executed.
+                order_id: Order ID to cancel.
+                wait: If `True`, wait until the order cancel response is received.
+
+            Returns:
+                * `0` when it successfully cancels an order.
+                * `1` when it reaches the end of the data, if `wait` is `True`.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_cancel(self.ptr, asset_no, order_id, wait)
+
+        def clear_inactive_orders(self, asset_no: uint64) -> None:
+            """
+            Clears inactive orders from the local order dictionary whose status is neither
+            :const:`NEW <hftLiveBot.order.NEW>` nor :const:`PARTIALLY_FILLED <hftLiveBot.order.PARTIALLY_FILLED>`.
+
+            Args:
+                asset_no: Asset number at which this command will be executed.
+                          If :const:`ALL_ASSETS <hftLiveBot.types.ALL_ASSETS>`,
+                          all inactive orders in any assets will be cleared.
+            """
+            hashmaplive_clear_inactive_orders(self.ptr, asset_no)
+
+        def wait_order_response(self, asset_no: uint64, order_id: uint64, timeout: int64) -> int64:
+            """
+            Waits for the response of the order with the given order ID until timeout.
+
+            Args:
+                asset_no: Asset number where an order with `order_id` exists.
+                order_id: Order ID to wait for the response.
+                timeout: Timeout for waiting for the order response. Nanoseconds is the default unit. However, unit should
+                         be the same as the data’s timestamp unit.
+
+            Returns:
+                * `0` when it receives an order response for the specified order ID of the specified asset number, or
+                  reaches the timeout.
+                * `1` when it reaches the end of the data.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_hashmaplive_wait_order_response(self.ptr, asset_no, order_id, timeout)
+
+        def wait_next_feed(self, include_order_resp: bool, timeout: int64) -> int64:
+            """
+            Waits until the next feed is received, or until timeout.
+
+            Args:
+                include_order_resp: If set to `True`, it will return when any order response is received, in addition to
+                                    the next feed.
+                timeout: Timeout for waiting for the next feed or an order response. Nanoseconds is the default unit.
+                         However, unit should be the same as the data’s timestamp unit.
+
+            Returns:
+                * `0` when it reaches the timeout.
+                * `1` when it reaches the end of the data.
+                * `2` when it receives a market feed.
+                * `3` when it receives an order response if `include_order_resp` is `True`.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_wait_next_feed(self.ptr, include_order_resp, timeout)
+
+        def elapse(self, duration: uint64) -> int64:
+            """
+            Elapses the specified duration.
+
+            Args:
+                duration: Duration to elapse. Nanoseconds is the default unit. However, unit should be the same as the
+                          data’s timestamp unit.
+
+            Returns:
+                * `0` when it successfully elapses the given duration.
+                * `1` when it reaches the end of the data.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_elapse(self.ptr, duration)
+
+        def elapse_bt(self, duration: int64) -> int64:
+            """
+            Elapses time only in LiveBoting. In live mode, it is ignored. (Supported only in the Rust implementation)
+
+            The `elapse` method exclusively manages time during LiveBoting, meaning that factors such as computing time are
+            not properly accounted for. So, this method can be utilized to simulate such processing times.
+
+            Args:
+                duration: Duration to elapse. Nanoseconds is the default unit. However, unit should be the same as the
+                          data’s timestamp unit.
+
+            Returns:
+                * `0` when it successfully elapses the given duration.
+                * `1` when it reaches the end of the data.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_elapse_bt(self.ptr, duration)
+
+        def close(self) -> int64:
+            """
+            Closes this LiveBoter or bot.
+
+            Returns:
+                * `0` when it successfully closes the bot.
+                * Otherwise, an error occurred.
+            """
+            return hashmaplive_close(self.ptr)
+
+        def feed_latency(self, asset_no: uint64) -> Tuple[int64, int64] | None:
+            """
+            Args:
+                asset_no: Asset number from which the last feed latency will be retrieved.
+
+            Returns:
+                The last feed’s exchange timestamp and local receipt timestamp if a feed has been received; otherwise,
+                returns `None`.
+            """
+            exch_ts = int64(0)
+            local_ts = int64(0)
+            exch_ts_ptr = ptr_from_val(exch_ts)
+            local_ts_ptr = ptr_from_val(local_ts)
+            if hashmaplive_feed_latency(self.ptr, asset_no, exch_ts_ptr, local_ts_ptr):
+                return val_from_ptr(exch_ts_ptr), val_from_ptr(local_ts_ptr)
+            return None
+
+        def order_latency(self, asset_no: uint64) -> Tuple[int64, int64, int64] | None:
+            """
+            Args:
+                asset_no: Asset number from which the last order latency will be retrieved.
+
+            Returns:
+                The last order’s request timestamp, exchange timestamp, and response receipt timestamp if there has been an
+                order submission; otherwise, returns `None`.
+            """
+            req_ts = int64(0)
+            exch_ts = int64(0)
+            resp_ts = int64(0)
+            req_ts_ptr = ptr_from_val(req_ts)
+            exch_ts_ptr = ptr_from_val(exch_ts)
+            resp_ts_ptr = ptr_from_val(resp_ts)
+            if hashmaplive_order_latency(self.ptr, asset_no, req_ts_ptr, exch_ts_ptr, resp_ts_ptr):
+                return val_from_ptr(req_ts_ptr), val_from_ptr(exch_ts_ptr), val_from_ptr(resp_ts_ptr)
+            return None
+
+        def _goto_end(self) -> int64:
+            return hashmaplive_goto_end(self.ptr)
+
+
+    HashMapMarketDepthLiveBot_ = jitclass(HashMapMarketDepthLiveBot)
 
 
-if LIVE_FEATURE:
-    hashmaplive_elapse = lib.hashmaplive_elapse
-    hashmaplive_elapse.restype = c_int64
-    hashmaplive_elapse.argtypes = [c_void_p, c_uint64]
+    roiveclive_elapse = lib.roiveclive_elapse
+    roiveclive_elapse.restype = c_int64
+    roiveclive_elapse.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_elapse_bt = lib.hashmaplive_elapse_bt
-    hashmaplive_elapse_bt.restype = c_int64
-    hashmaplive_elapse_bt.argtypes = [c_void_p, c_uint64]
+    roiveclive_elapse_bt = lib.roiveclive_elapse_bt
+    roiveclive_elapse_bt.restype = c_int64
+    roiveclive_elapse_bt.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_hashmaplive_wait_order_response = lib.hashmaplive_wait_order_response
-    hashmaplive_hashmaplive_wait_order_response.restype = c_int64
-    hashmaplive_hashmaplive_wait_order_response.argtypes = [c_void_p, c_uint64, c_uint64, c_int64]
+    roiveclive_roiveclive_wait_order_response = lib.roiveclive_wait_order_response
+    roiveclive_roiveclive_wait_order_response.restype = c_int64
+    roiveclive_roiveclive_wait_order_response.argtypes = [c_void_p, c_uint64, c_uint64, c_int64]
 
-    hashmaplive_wait_next_feed = lib.hashmaplive_wait_next_feed
-    hashmaplive_wait_next_feed.restype = c_int64
-    hashmaplive_wait_next_feed.argtypes = [c_void_p, c_bool, c_int64]
+    roiveclive_wait_next_feed = lib.roiveclive_wait_next_feed
+    roiveclive_wait_next_feed.restype = c_int64
+    roiveclive_wait_next_feed.argtypes = [c_void_p, c_bool, c_int64]
 
-    hashmaplive_close = lib.hashmaplive_close
-    hashmaplive_close.restype = c_int64
-    hashmaplive_close.argtypes = [c_void_p]
+    roiveclive_close = lib.roiveclive_close
+    roiveclive_close.restype = c_int64
+    roiveclive_close.argtypes = [c_void_p]
 
-    hashmaplive_position = lib.hashmaplive_position
-    hashmaplive_position.restype = c_double
-    hashmaplive_position.argtypes = [c_void_p, c_uint64]
+    roiveclive_position = lib.roiveclive_position
+    roiveclive_position.restype = c_double
+    roiveclive_position.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_current_timestamp = lib.hashmaplive_current_timestamp
-    hashmaplive_current_timestamp.restype = c_int64
-    hashmaplive_current_timestamp.argtypes = [c_void_p]
+    roiveclive_current_timestamp = lib.roiveclive_current_timestamp
+    roiveclive_current_timestamp.restype = c_int64
+    roiveclive_current_timestamp.argtypes = [c_void_p]
 
-    hashmaplive_depth = lib.hashmaplive_depth
-    hashmaplive_depth.restype = c_void_p
-    hashmaplive_depth.argtypes = [c_void_p, c_uint64]
+    roiveclive_depth = lib.roiveclive_depth
+    roiveclive_depth.restype = c_void_p
+    roiveclive_depth.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_last_trades = lib.hashmaplive_last_trades
-    hashmaplive_last_trades.restype = c_void_p
-    hashmaplive_last_trades.argtypes = [c_void_p, c_uint64, POINTER(c_uint64)]
+    roiveclive_last_trades = lib.roiveclive_last_trades
+    roiveclive_last_trades.restype = c_void_p
+    roiveclive_last_trades.argtypes = [c_void_p, c_uint64, POINTER(c_uint64)]
 
-    hashmaplive_num_assets = lib.hashmaplive_num_assets
-    hashmaplive_num_assets.restype = c_uint64
-    hashmaplive_num_assets.argtypes = [c_void_p]
+    roiveclive_num_assets = lib.roiveclive_num_assets
+    roiveclive_num_assets.restype = c_uint64
+    roiveclive_num_assets.argtypes = [c_void_p]
 
-    hashmaplive_submit_buy_order = lib.hashmaplive_submit_buy_order
-    hashmaplive_submit_buy_order.restype = c_int64
-    hashmaplive_submit_buy_order.argtypes = [
+    roiveclive_submit_buy_order = lib.roiveclive_submit_buy_order
+    roiveclive_submit_buy_order.restype = c_int64
+    roiveclive_submit_buy_order.argtypes = [
         c_void_p,
         c_uint64,
         c_uint64,
@@ -1560,9 +3034,9 @@ if LIVE_FEATURE:
         c_bool
     ]
 
-    hashmaplive_submit_sell_order = lib.hashmaplive_submit_sell_order
-    hashmaplive_submit_sell_order.restype = c_int64
-    hashmaplive_submit_sell_order.argtypes = [
+    roiveclive_submit_sell_order = lib.roiveclive_submit_sell_order
+    roiveclive_submit_sell_order.restype = c_int64
+    roiveclive_submit_sell_order.argtypes = [
         c_void_p,
         c_uint64,
         c_uint64,
@@ -1573,40 +3047,40 @@ if LIVE_FEATURE:
         c_bool
     ]
 
-    hashmaplive_modify = lib.hashmaplive_modify
-    hashmaplive_modify.restype = c_int64
-    hashmaplive_modify.argtypes = [c_void_p, c_uint64, c_uint64, c_double, c_double, c_bool]
+    roiveclive_modify = lib.roiveclive_modify
+    roiveclive_modify.restype = c_int64
+    roiveclive_modify.argtypes = [c_void_p, c_uint64, c_uint64, c_double, c_double, c_bool]
 
-    hashmaplive_cancel = lib.hashmaplive_cancel
-    hashmaplive_cancel.restype = c_int64
-    hashmaplive_cancel.argtypes = [c_void_p, c_uint64, c_uint64, c_bool]
+    roiveclive_cancel = lib.roiveclive_cancel
+    roiveclive_cancel.restype = c_int64
+    roiveclive_cancel.argtypes = [c_void_p, c_uint64, c_uint64, c_bool]
 
-    hashmaplive_clear_last_trades = lib.hashmaplive_clear_last_trades
-    hashmaplive_clear_last_trades.restype = c_void_p
-    hashmaplive_clear_last_trades.argtypes = [c_void_p, c_uint64]
+    roiveclive_clear_last_trades = lib.roiveclive_clear_last_trades
+    roiveclive_clear_last_trades.restype = c_void_p
+    roiveclive_clear_last_trades.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_clear_inactive_orders = lib.hashmaplive_clear_inactive_orders
-    hashmaplive_clear_inactive_orders.restype = c_void_p
-    hashmaplive_clear_inactive_orders.argtypes = [c_void_p, c_uint64]
+    roiveclive_clear_inactive_orders = lib.roiveclive_clear_inactive_orders
+    roiveclive_clear_inactive_orders.restype = c_void_p
+    roiveclive_clear_inactive_orders.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_orders = lib.hashmaplive_orders
-    hashmaplive_orders.restype = c_void_p
-    hashmaplive_orders.argtypes = [c_void_p, c_uint64]
+    roiveclive_orders = lib.roiveclive_orders
+    roiveclive_orders.restype = c_void_p
+    roiveclive_orders.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_state_values = lib.hashmaplive_state_values
-    hashmaplive_state_values.restype = c_void_p
-    hashmaplive_state_values.argtypes = [c_void_p, c_uint64]
+    roiveclive_state_values = lib.roiveclive_state_values
+    roiveclive_state_values.restype = c_void_p
+    roiveclive_state_values.argtypes = [c_void_p, c_uint64]
 
-    hashmaplive_feed_latency = lib.hashmaplive_feed_latency
-    hashmaplive_feed_latency.restype = c_bool
-    hashmaplive_feed_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64)]
+    roiveclive_feed_latency = lib.roiveclive_feed_latency
+    roiveclive_feed_latency.restype = c_bool
+    roiveclive_feed_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64)]
 
-    hashmaplive_order_latency = lib.hashmaplive_order_latency
-    hashmaplive_order_latency.restype = c_bool
-    hashmaplive_order_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64), POINTER(c_int64)]
+    roiveclive_order_latency = lib.roiveclive_order_latency
+    roiveclive_order_latency.restype = c_bool
+    roiveclive_order_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64), POINTER(c_int64)]
 
 
-    class HashMapMarketDepthLiveBot:
+    class ROIVectorMarketDepthLiveBot:
         ptr: voidptr
 
         def __init__(self, ptr: voidptr):
@@ -1617,9 +3091,9 @@ if LIVE_FEATURE:
             """
             In LiveBoting, this timestamp reflects the time at which the LiveBoting is conducted within the provided data.
             """
-            return hashmaplive_current_timestamp(self.ptr)
+            return roiveclive_current_timestamp(self.ptr)
 
-        def depth(self, asset_no: uint64) -> HashMapMarketDepth:
+        def depth(self, asset_no: uint64) -> ROIVectorMarketDepth:
             """
             Args:
                 asset_no: Asset number from which the market depth will be retrieved.
@@ -1627,14 +3101,14 @@ if LIVE_FEATURE:
             Returns:
                 The depth of market of the specific asset.
             """
-            return HashMapMarketDepth_(hashmaplive_depth(self.ptr, asset_no))
+            return ROIVectorMarketDepth_(roiveclive_depth(self.ptr, asset_no))
 
         @property
         def num_assets(self) -> uint64:
             """
             Returns the number of assets.
             """
-            return hashmaplive_num_assets(self.ptr)
+            return roiveclive_num_assets(self.ptr)
 
         def position(self, asset_no: uint64) -> float64:
             """
@@ -1644,7 +3118,7 @@ if LIVE_FEATURE:
             Returns:
                 The quantity of the held position.
             """
-            return hashmaplive_position(self.ptr, asset_no)
+            return roiveclive_position(self.ptr, asset_no)
 
         def state_values(self, asset_no: uint64) -> StateValues:
             """
@@ -1654,7 +3128,7 @@ if LIVE_FEATURE:
             Returns:
                 The state’s values.
             """
-            ptr = hashmaplive_state_values(self.ptr, asset_no)
+            ptr = roiveclive_state_values(self.ptr, asset_no)
             arr = numba.carray(
                 address_as_void_pointer(ptr),
                 1,
@@ -1672,7 +3146,7 @@ if LIVE_FEATURE:
             """
             length = uint64(0)
             len_ptr = ptr_from_val(length)
-            ptr = hashmaplive_last_trades(self.ptr, asset_no, len_ptr)
+            ptr = roiveclive_last_trades(self.ptr, asset_no, len_ptr)
             return numba.carray(
                 address_as_void_pointer(ptr),
                 val_from_ptr(len_ptr),
@@ -1688,7 +3162,7 @@ if LIVE_FEATURE:
                           If :const:`ALL_ASSETS <hftLiveBot.types.ALL_ASSETS>`,
                           all last trades in any assets will be cleared.
             """
-            hashmaplive_clear_last_trades(self.ptr, asset_no)
+            roiveclive_clear_last_trades(self.ptr, asset_no)
 
         def orders(self, asset_no: uint64) -> OrderDict:
             """
@@ -1699,7 +3173,7 @@ if LIVE_FEATURE:
                 An order dictionary where the keys are order IDs and the corresponding values are
                 :class:`Order <hftLiveBot.order.Order>`.
             """
-            return OrderDict_(hashmaplive_orders(self.ptr, asset_no))
+            return OrderDict_(roiveclive_orders(self.ptr, asset_no))
 
         def submit_buy_order(
                 self,
@@ -1739,7 +3213,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_submit_buy_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+            return roiveclive_submit_buy_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
 
         def submit_sell_order(
                 self,
@@ -1779,7 +3253,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_submit_sell_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+            return roiveclive_submit_sell_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
 
         def modify(self, asset_no: uint64, order_id: uint64, price: float, qty: float, wait: bool) -> int64:
             """
@@ -1797,7 +3271,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_modify(self.ptr, asset_no, order_id, price, qty, wait)
+            return roiveclive_modify(self.ptr, asset_no, order_id, price, qty, wait)
 
         def cancel(self, asset_no: uint64, order_id: uint64, wait: bool) -> int64:
             """
@@ -1813,7 +3287,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_cancel(self.ptr, asset_no, order_id, wait)
+            return roiveclive_cancel(self.ptr, asset_no, order_id, wait)
 
         def clear_inactive_orders(self, asset_no: uint64) -> None:
             """
@@ -1825,7 +3299,7 @@ if LIVE_FEATURE:
                           If :const:`ALL_ASSETS <hftLiveBot.types.ALL_ASSETS>`,
                           all inactive orders in any assets will be cleared.
             """
-            hashmaplive_clear_inactive_orders(self.ptr, asset_no)
+            roiveclive_clear_inactive_orders(self.ptr, asset_no)
 
         def wait_order_response(self, asset_no: uint64, order_id: uint64, timeout: int64) -> int64:
             """
@@ -1843,7 +3317,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_hashmaplive_wait_order_response(self.ptr, asset_no, order_id, timeout)
+            return roiveclive_roiveclive_wait_order_response(self.ptr, asset_no, order_id, timeout)
 
         def wait_next_feed(self, include_order_resp: bool, timeout: int64) -> int64:
             """
@@ -1862,7 +3336,7 @@ if LIVE_FEATURE:
                 * `3` when it receives an order response if `include_order_resp` is `True`.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_wait_next_feed(self.ptr, include_order_resp, timeout)
+            return roiveclive_wait_next_feed(self.ptr, include_order_resp, timeout)
 
         def elapse(self, duration: uint64) -> int64:
             """
@@ -1877,7 +3351,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_elapse(self.ptr, duration)
+            return roiveclive_elapse(self.ptr, duration)
 
         def elapse_bt(self, duration: int64) -> int64:
             """
@@ -1895,7 +3369,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_elapse_bt(self.ptr, duration)
+            return roiveclive_elapse_bt(self.ptr, duration)
 
         def close(self) -> int64:
             """
@@ -1905,7 +3379,7 @@ if LIVE_FEATURE:
                 * `0` when it successfully closes the bot.
                 * Otherwise, an error occurred.
             """
-            return hashmaplive_close(self.ptr)
+            return roiveclive_close(self.ptr)
 
         def feed_latency(self, asset_no: uint64) -> Tuple[int64, int64] | None:
             """
@@ -1920,7 +3394,7 @@ if LIVE_FEATURE:
             local_ts = int64(0)
             exch_ts_ptr = ptr_from_val(exch_ts)
             local_ts_ptr = ptr_from_val(local_ts)
-            if hashmaplive_feed_latency(self.ptr, asset_no, exch_ts_ptr, local_ts_ptr):
+            if roiveclive_feed_latency(self.ptr, asset_no, exch_ts_ptr, local_ts_ptr):
                 return val_from_ptr(exch_ts_ptr), val_from_ptr(local_ts_ptr)
             return None
 
@@ -1939,60 +3413,56 @@ if LIVE_FEATURE:
             req_ts_ptr = ptr_from_val(req_ts)
             exch_ts_ptr = ptr_from_val(exch_ts)
             resp_ts_ptr = ptr_from_val(resp_ts)
-            if hashmaplive_order_latency(self.ptr, asset_no, req_ts_ptr, exch_ts_ptr, resp_ts_ptr):
+            if roiveclive_order_latency(self.ptr, asset_no, req_ts_ptr, exch_ts_ptr, resp_ts_ptr):
                 return val_from_ptr(req_ts_ptr), val_from_ptr(exch_ts_ptr), val_from_ptr(resp_ts_ptr)
             return None
 
-        def _goto_end(self) -> int64:
-            return hashmaplive_goto_end(self.ptr)
-
-
-    HashMapMarketDepthLiveBot_ = jitclass(HashMapMarketDepthLiveBot)
 
+    ROIVectorMarketDepthLiveBot_ = jitclass(ROIVectorMarketDepthLiveBot)
 
-    roiveclive_elapse = lib.roiveclive_elapse
-    roiveclive_elapse.restype = c_int64
-    roiveclive_elapse.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_elapse = lib.fusedhashmaplive_elapse
+    fusedhashmaplive_elapse.restype = c_int64
+    fusedhashmaplive_elapse.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_elapse_bt = lib.roiveclive_elapse_bt
-    roiveclive_elapse_bt.restype = c_int64
-    roiveclive_elapse_bt.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_elapse_bt = lib.fusedhashmaplive_elapse_bt
+    fusedhashmaplive_elapse_bt.restype = c_int64
+    fusedhashmaplive_elapse_bt.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_roiveclive_wait_order_response = lib.roiveclive_wait_order_response
-    roiveclive_roiveclive_wait_order_response.restype = c_int64
-    roiveclive_roiveclive_wait_order_response.argtypes = [c_void_p, c_uint64, c_uint64, c_int64]
+    fusedhashmaplive_fusedhashmaplive_wait_order_response = lib.fusedhashmaplive_wait_order_response
+    fusedhashmaplive_fusedhashmaplive_wait_order_response.restype = c_int64
+    fusedhashmaplive_fusedhashmaplive_wait_order_response.argtypes = [c_void_p, c_uint64, c_uint64, c_int64]
 
-    roiveclive_wait_next_feed = lib.roiveclive_wait_next_feed
-    roiveclive_wait_next_feed.restype = c_int64
-    roiveclive_wait_next_feed.argtypes = [c_void_p, c_bool, c_int64]
+    fusedhashmaplive_wait_next_feed = lib.fusedhashmaplive_wait_next_feed
+    fusedhashmaplive_wait_next_feed.restype = c_int64
+    fusedhashmaplive_wait_next_feed.argtypes = [c_void_p, c_bool, c_int64]
 
-    roiveclive_close = lib.roiveclive_close
-    roiveclive_close.restype = c_int64
-    roiveclive_close.argtypes = [c_void_p]
+    fusedhashmaplive_close = lib.fusedhashmaplive_close
+    fusedhashmaplive_close.restype = c_int64
+    fusedhashmaplive_close.argtypes = [c_void_p]
 
-    roiveclive_position = lib.roiveclive_position
-    roiveclive_position.restype = c_double
-    roiveclive_position.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_position = lib.fusedhashmaplive_position
+    fusedhashmaplive_position.restype = c_double
+    fusedhashmaplive_position.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_current_timestamp = lib.roiveclive_current_timestamp
-    roiveclive_current_timestamp.restype = c_int64
-    roiveclive_current_timestamp.argtypes = [c_void_p]
+    fusedhashmaplive_current_timestamp = lib.fusedhashmaplive_current_timestamp
+    fusedhashmaplive_current_timestamp.restype = c_int64
+    fusedhashmaplive_current_timestamp.argtypes = [c_void_p]
 
-    roiveclive_depth = lib.roiveclive_depth
-    roiveclive_depth.restype = c_void_p
-    roiveclive_depth.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_depth = lib.fusedhashmaplive_depth
+    fusedhashmaplive_depth.restype = c_void_p
+    fusedhashmaplive_depth.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_last_trades = lib.roiveclive_last_trades
-    roiveclive_last_trades.restype = c_void_p
-    roiveclive_last_trades.argtypes = [c_void_p, c_uint64, POINTER(c_uint64)]
+    fusedhashmaplive_last_trades = lib.fusedhashmaplive_last_trades
+    fusedhashmaplive_last_trades.restype = c_void_p
+    fusedhashmaplive_last_trades.argtypes = [c_void_p, c_uint64, POINTER(c_uint64)]
 
-    roiveclive_num_assets = lib.roiveclive_num_assets
-    roiveclive_num_assets.restype = c_uint64
-    roiveclive_num_assets.argtypes = [c_void_p]
+    fusedhashmaplive_num_assets = lib.fusedhashmaplive_num_assets
+    fusedhashmaplive_num_assets.restype = c_uint64
+    fusedhashmaplive_num_assets.argtypes = [c_void_p]
 
-    roiveclive_submit_buy_order = lib.roiveclive_submit_buy_order
-    roiveclive_submit_buy_order.restype = c_int64
-    roiveclive_submit_buy_order.argtypes = [
+    fusedhashmaplive_submit_buy_order = lib.fusedhashmaplive_submit_buy_order
+    fusedhashmaplive_submit_buy_order.restype = c_int64
+    fusedhashmaplive_submit_buy_order.argtypes = [
         c_void_p,
         c_uint64,
         c_uint64,
@@ -2003,9 +3473,9 @@ if LIVE_FEATURE:
         c_bool
     ]
 
-    roiveclive_submit_sell_order = lib.roiveclive_submit_sell_order
-    roiveclive_submit_sell_order.restype = c_int64
-    roiveclive_submit_sell_order.argtypes = [
+    fusedhashmaplive_submit_sell_order = lib.fusedhashmaplive_submit_sell_order
+    fusedhashmaplive_submit_sell_order.restype = c_int64
+    fusedhashmaplive_submit_sell_order.argtypes = [
         c_void_p,
         c_uint64,
         c_uint64,
@@ -2016,40 +3486,40 @@ if LIVE_FEATURE:
         c_bool
     ]
 
-    roiveclive_modify = lib.roiveclive_modify
-    roiveclive_modify.restype = c_int64
-    roiveclive_modify.argtypes = [c_void_p, c_uint64, c_uint64, c_double, c_double, c_bool]
+    fusedhashmaplive_modify = lib.fusedhashmaplive_modify
+    fusedhashmaplive_modify.restype = c_int64
+    fusedhashmaplive_modify.argtypes = [c_void_p, c_uint64, c_uint64, c_double, c_double, c_bool]
 
-    roiveclive_cancel = lib.roiveclive_cancel
-    roiveclive_cancel.restype = c_int64
-    roiveclive_cancel.argtypes = [c_void_p, c_uint64, c_uint64, c_bool]
+    fusedhashmaplive_cancel = lib.fusedhashmaplive_cancel
+    fusedhashmaplive_cancel.restype = c_int64
+    fusedhashmaplive_cancel.argtypes = [c_void_p, c_uint64, c_uint64, c_bool]
 
-    roiveclive_clear_last_trades = lib.roiveclive_clear_last_trades
-    roiveclive_clear_last_trades.restype = c_void_p
-    roiveclive_clear_last_trades.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_clear_last_trades = lib.fusedhashmaplive_clear_last_trades
+    fusedhashmaplive_clear_last_trades.restype = c_void_p
+    fusedhashmaplive_clear_last_trades.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_clear_inactive_orders = lib.roiveclive_clear_inactive_orders
-    roiveclive_clear_inactive_orders.restype = c_void_p
-    roiveclive_clear_inactive_orders.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_clear_inactive_orders = lib.fusedhashmaplive_clear_inactive_orders
+    fusedhashmaplive_clear_inactive_orders.restype = c_void_p
+    fusedhashmaplive_clear_inactive_orders.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_orders = lib.roiveclive_orders
-    roiveclive_orders.restype = c_void_p
-    roiveclive_orders.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_orders = lib.fusedhashmaplive_orders
+    fusedhashmaplive_orders.restype = c_void_p
+    fusedhashmaplive_orders.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_state_values = lib.roiveclive_state_values
-    roiveclive_state_values.restype = c_void_p
-    roiveclive_state_values.argtypes = [c_void_p, c_uint64]
+    fusedhashmaplive_state_values = lib.fusedhashmaplive_state_values
+    fusedhashmaplive_state_values.restype = c_void_p
+    fusedhashmaplive_state_values.argtypes = [c_void_p, c_uint64]
 
-    roiveclive_feed_latency = lib.roiveclive_feed_latency
-    roiveclive_feed_latency.restype = c_bool
-    roiveclive_feed_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64)]
+    fusedhashmaplive_feed_latency = lib.fusedhashmaplive_feed_latency
+    fusedhashmaplive_feed_latency.restype = c_bool
+    fusedhashmaplive_feed_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64)]
 
-    roiveclive_order_latency = lib.roiveclive_order_latency
-    roiveclive_order_latency.restype = c_bool
-    roiveclive_order_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64), POINTER(c_int64)]
+    fusedhashmaplive_order_latency = lib.fusedhashmaplive_order_latency
+    fusedhashmaplive_order_latency.restype = c_bool
+    fusedhashmaplive_order_latency.argtypes = [c_void_p, c_uint64, POINTER(c_int64), POINTER(c_int64), POINTER(c_int64)]
 
 
-    class ROIVectorMarketDepthLiveBot:
+    class FusedHashMapMarketDepthLiveBot:
         ptr: voidptr
 
         def __init__(self, ptr: voidptr):
@@ -2060,9 +3530,9 @@ if LIVE_FEATURE:
             """
             In LiveBoting, this timestamp reflects the time at which the LiveBoting is conducted within the provided data.
             """
-            return roiveclive_current_timestamp(self.ptr)
+            return fusedhashmaplive_current_timestamp(self.ptr)
 
-        def depth(self, asset_no: uint64) -> ROIVectorMarketDepth:
+        def depth(self, asset_no: uint64) -> FusedHashMapMarketDepth:
             """
             Args:
                 asset_no: Asset number from which the market depth will be retrieved.
@@ -2070,14 +3540,14 @@ if LIVE_FEATURE:
             Returns:
                 The depth of market of the specific asset.
             """
-            return ROIVectorMarketDepth_(roiveclive_depth(self.ptr, asset_no))
+            return FusedHashMapMarketDepth_(fusedhashmaplive_depth(self.ptr, asset_no))
 
         @property
         def num_assets(self) -> uint64:
             """
             Returns the number of assets.
             """
-            return roiveclive_num_assets(self.ptr)
+            return fusedhashmaplive_num_assets(self.ptr)
 
         def position(self, asset_no: uint64) -> float64:
             """
@@ -2087,7 +3557,7 @@ if LIVE_FEATURE:
             Returns:
                 The quantity of the held position.
             """
-            return roiveclive_position(self.ptr, asset_no)
+            return fusedhashmaplive_position(self.ptr, asset_no)
 
         def state_values(self, asset_no: uint64) -> StateValues:
             """
@@ -2097,7 +3567,7 @@ if LIVE_FEATURE:
             Returns:
                 The state’s values.
             """
-            ptr = roiveclive_state_values(self.ptr, asset_no)
+            ptr = fusedhashmaplive_state_values(self.ptr, asset_no)
             arr = numba.carray(
                 address_as_void_pointer(ptr),
                 1,
@@ -2115,7 +3585,7 @@ if LIVE_FEATURE:
             """
             length = uint64(0)
             len_ptr = ptr_from_val(length)
-            ptr = roiveclive_last_trades(self.ptr, asset_no, len_ptr)
+            ptr = fusedhashmaplive_last_trades(self.ptr, asset_no, len_ptr)
             return numba.carray(
                 address_as_void_pointer(ptr),
                 val_from_ptr(len_ptr),
@@ -2131,7 +3601,7 @@ if LIVE_FEATURE:
                           If :const:`ALL_ASSETS <hftLiveBot.types.ALL_ASSETS>`,
                           all last trades in any assets will be cleared.
             """
-            roiveclive_clear_last_trades(self.ptr, asset_no)
+            fusedhashmaplive_clear_last_trades(self.ptr, asset_no)
 
         def orders(self, asset_no: uint64) -> OrderDict:
             """
@@ -2142,7 +3612,7 @@ if LIVE_FEATURE:
                 An order dictionary where the keys are order IDs and the corresponding values are
                 :class:`Order <hftLiveBot.order.Order>`.
             """
-            return OrderDict_(roiveclive_orders(self.ptr, asset_no))
+            return OrderDict_(fusedhashmaplive_orders(self.ptr, asset_no))
 
         def submit_buy_order(
                 self,
@@ -2182,7 +3652,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_submit_buy_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+            return fusedhashmaplive_submit_buy_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
 
         def submit_sell_order(
                 self,
@@ -2222,7 +3692,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_submit_sell_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
+            return fusedhashmaplive_submit_sell_order(self.ptr, asset_no, order_id, price, qty, time_in_force, order_type, wait)
 
         def modify(self, asset_no: uint64, order_id: uint64, price: float, qty: float, wait: bool) -> int64:
             """
@@ -2240,7 +3710,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_modify(self.ptr, asset_no, order_id, price, qty, wait)
+            return fusedhashmaplive_modify(self.ptr, asset_no, order_id, price, qty, wait)
 
         def cancel(self, asset_no: uint64, order_id: uint64, wait: bool) -> int64:
             """
@@ -2256,7 +3726,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data, if `wait` is `True`.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_cancel(self.ptr, asset_no, order_id, wait)
+            return fusedhashmaplive_cancel(self.ptr, asset_no, order_id, wait)
 
         def clear_inactive_orders(self, asset_no: uint64) -> None:
             """
@@ -2268,7 +3738,7 @@ if LIVE_FEATURE:
                           If :const:`ALL_ASSETS <hftLiveBot.types.ALL_ASSETS>`,
                           all inactive orders in any assets will be cleared.
             """
-            roiveclive_clear_inactive_orders(self.ptr, asset_no)
+            fusedhashmaplive_clear_inactive_orders(self.ptr, asset_no)
 
         def wait_order_response(self, asset_no: uint64, order_id: uint64, timeout: int64) -> int64:
             """
@@ -2286,7 +3756,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_roiveclive_wait_order_response(self.ptr, asset_no, order_id, timeout)
+            return fusedhashmaplive_fusedhashmaplive_wait_order_response(self.ptr, asset_no, order_id, timeout)
 
         def wait_next_feed(self, include_order_resp: bool, timeout: int64) -> int64:
             """
@@ -2305,7 +3775,7 @@ if LIVE_FEATURE:
                 * `3` when it receives an order response if `include_order_resp` is `True`.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_wait_next_feed(self.ptr, include_order_resp, timeout)
+            return fusedhashmaplive_wait_next_feed(self.ptr, include_order_resp, timeout)
 
         def elapse(self, duration: uint64) -> int64:
             """
@@ -2320,7 +3790,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_elapse(self.ptr, duration)
+            return fusedhashmaplive_elapse(self.ptr, duration)
 
         def elapse_bt(self, duration: int64) -> int64:
             """
@@ -2338,7 +3808,7 @@ if LIVE_FEATURE:
                 * `1` when it reaches the end of the data.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_elapse_bt(self.ptr, duration)
+            return fusedhashmaplive_elapse_bt(self.ptr, duration)
 
         def close(self) -> int64:
             """
@@ -2348,7 +3818,7 @@ if LIVE_FEATURE:
                 * `0` when it successfully closes the bot.
                 * Otherwise, an error occurred.
             """
-            return roiveclive_close(self.ptr)
+            return fusedhashmaplive_close(self.ptr)
 
         def feed_latency(self, asset_no: uint64) -> Tuple[int64, int64] | None:
             """
@@ -2363,7 +3833,7 @@ if LIVE_FEATURE:
             local_ts = int64(0)
             exch_ts_ptr = ptr_from_val(exch_ts)
             local_ts_ptr = ptr_from_val(local_ts)
-            if roiveclive_feed_latency(self.ptr, asset_no, exch_ts_ptr, local_ts_ptr):
+            if fusedhashmaplive_feed_latency(self.ptr, asset_no, exch_ts_ptr, local_ts_ptr):
                 return val_from_ptr(exch_ts_ptr), val_from_ptr(local_ts_ptr)
             return None
 
@@ -2382,9 +3852,12 @@ if LIVE_FEATURE:
             req_ts_ptr = ptr_from_val(req_ts)
             exch_ts_ptr = ptr_from_val(exch_ts)
             resp_ts_ptr = ptr_from_val(resp_ts)
-            if roiveclive_order_latency(self.ptr, asset_no, req_ts_ptr, exch_ts_ptr, resp_ts_ptr):
+            if fusedhashmaplive_order_latency(self.ptr, asset_no, req_ts_ptr, exch_ts_ptr, resp_ts_ptr):
                 return val_from_ptr(req_ts_ptr), val_from_ptr(exch_ts_ptr), val_from_ptr(resp_ts_ptr)
             return None
 
+        def _goto_end(self) -> int64:
+            return fusedhashmaplive_goto_end(self.ptr)
+
 
-    ROIVectorMarketDepthLiveBot_ = jitclass(ROIVectorMarketDepthLiveBot)
+    FusedHashMapMarketDepthLiveBot_ = jitclass(FusedHashMapMarketDepthLiveBot)
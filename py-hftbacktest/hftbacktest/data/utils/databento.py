@@ -5,6 +5,7 @@ import numpy as np
 import polars as pl
 from numpy.typing import NDArray
 
+from ..._hftbacktest import databento_convert
 from ..validation import correct_event_order, validate_event_order, correct_local_timestamp
 from ...types import (
     event_dtype,
@@ -125,3 +126,30 @@ def convert(
         np.savez_compressed(output_filename, data=data)
 
     return data
+
+
+def fast_convert(
+        input_file: str,
+        instrument_id: int | None = None,
+        output_filename: str | None = None,
+        base_latency: int = 0,
+) -> NDArray:
+    r"""
+    Converts a DataBento DBN file into a format compatible with HftBacktest, the same as :func:`convert`, but using the
+    streaming Rust implementation, which reads the DBN records directly.
+
+    In addition to the MBO schema, the MBP-1 and MBP-10 schemas are supported. MBP-1 records are converted into BBO
+    events and MBP-10 records are converted into depth events.
+
+    Args:
+        input_file: DataBento's DBN file. e.g. *.mbo.dbn.zst
+        instrument_id: Specify the instrument ID to process in the given file. If the file contains multiple
+                       instruments, it should be provided; otherwise, the output will contain mixed instruments.
+        output_filename: If provided, the converted data will be saved to the specified filename in ``npz`` format.
+        base_latency: The value to be added to the feed latency.
+                      See :func:`.correct_local_timestamp`.
+    Returns:
+        Converted data compatible with HftBacktest.
+    """
+    buf = databento_convert(input_file, instrument_id, output_filename, int(base_latency))
+    return np.frombuffer(buf, event_dtype)
@@ -8,6 +8,7 @@ from numba.experimental import jitclass
 from numpy.typing import NDArray
 
 from .. import FuseMarketDepth
+from ..._hftbacktest import tardis_convert, tardis_convert_fuse
 from ..validation import correct_event_order, validate_event_order, correct_local_timestamp
 from ...types import (
     DEPTH_EVENT,
@@ -643,3 +644,70 @@ def convert_fuse(
         np.savez_compressed(output_filename, data=data)
 
     return data
+
+
+def fast_convert(
+        input_files: List[str],
+        output_filename: Optional[str] = None,
+        base_latency: int = 0,
+        snapshot_mode: Literal['process', 'ignore_sod', 'ignore'] = 'process',
+) -> NDArray:
+    r"""
+    Converts Tardis.dev data files into a format compatible with HftBacktest, the same as :func:`convert`, but
+    using the streaming Rust implementation. It is considerably faster and doesn't need preallocated buffers.
+
+    The file type is determined by the header, and ``book_snapshot`` files are supported in addition to ``trades``
+    and ``incremental_book_L2`` files. The input files can be compressed with gzip or Zstandard.
+
+    Args:
+        input_files: Input filenames for both incremental book and trades files. Trade files should be input before
+                     depth files. e.g. ['trades.csv.gz', 'incremental_book.csv.gz'].
+        output_filename: If provided, the converted data will be saved to the specified filename in ``npz`` format.
+        base_latency: The value to be added to the feed latency.
+                      See :func:`.correct_local_timestamp`.
+        snapshot_mode: See :func:`convert`.
+    Returns:
+        Converted data compatible with HftBacktest.
+    """
+    buf = tardis_convert(input_files, output_filename, int(base_latency), snapshot_mode)
+    return np.frombuffer(buf, event_dtype)
+
+
+def fast_convert_fuse(
+        trades_filename: str,
+        depth_filename: str,
+        book_ticker_filename: str,
+        tick_size: float,
+        lot_size: float,
+        output_filename: Optional[str] = None,
+        base_latency: int = 0,
+        snapshot_mode: Literal['process', 'ignore_sod', 'ignore'] = 'process',
+) -> NDArray:
+    r"""
+    Converts Tardis.dev data files into a format compatible with HftBacktest, the same as :func:`convert_fuse`, but
+    using the streaming Rust implementation.
+
+    Args:
+        trades_filename: Input filenames for a trades file.
+        depth_filename: Input filenames for an incremental book file.
+        book_ticker_filename: Input filenames for a book ticker file.
+        tick_size: tick size.
+        lot_size: lot_size.
+        output_filename: If provided, the converted data will be saved to the specified filename in ``npz`` format.
+        base_latency: The value to be added to the feed latency.
+                      See :func:`.correct_local_timestamp`.
+        snapshot_mode: See :func:`convert_fuse`.
+    Returns:
+        Converted data compatible with HftBacktest.
+    """
+    buf = tardis_convert_fuse(
+        trades_filename,
+        depth_filename,
+        book_ticker_filename,
+        tick_size,
+        lot_size,
+        output_filename,
+        int(base_latency),
+        snapshot_mode
+    )
+    return np.frombuffer(buf, event_dtype)
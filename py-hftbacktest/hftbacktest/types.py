@@ -110,6 +110,19 @@ order_dtype = np.dtype(
     align=True
 )
 
+l3_order_dtype = np.dtype(
+    [
+        ('order_id', 'u8'),
+        ('side', 'i1'),
+        ('price_tick', 'i8'),
+        ('qty', 'f8'),
+        ('timestamp', 'i8')
+    ],
+    align=True
+)
+
+L3_ORDER_ARRAY = np.ndarray[Any, l3_order_dtype]
+
 record_dtype = np.dtype(
     [
         ('timestamp', 'i8'),
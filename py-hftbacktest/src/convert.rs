@@ -0,0 +1,154 @@
+use std::{
+    ffi::{c_int, c_void},
+    mem::size_of_val,
+};
+
+use hftbacktest::{
+    backtest::data::{
+        convert::{SnapshotMode, databento, tardis},
+        write_npz_file,
+    },
+    prelude::Event,
+};
+use pyo3::{
+    exceptions::{PyIOError, PyValueError},
+    ffi,
+    prelude::*,
+};
+
+fn snapshot_mode(snapshot_mode: &str) -> PyResult<SnapshotMode> {
+    match snapshot_mode {
+        "process" => Ok(SnapshotMode::Process),
+        "ignore_sod" => Ok(SnapshotMode::IgnoreSod),
+        "ignore" => Ok(SnapshotMode::Ignore),
+        _ => Err(PyValueError::new_err(format!(
+            "invalid snapshot_mode '{snapshot_mode}'"
+        ))),
+    }
+}
+
+/// Holds the converted events and exposes them through the buffer protocol, so that they can be
+/// viewed as an ``event_dtype`` array by ``np.frombuffer`` without being copied.
+#[pyclass(frozen)]
+pub struct EventBuffer {
+    data: Vec<Event>,
+}
+
+#[pymethods]
+impl EventBuffer {
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut ffi::Py_buffer,
+        flags: c_int,
+    ) -> PyResult<()> {
+        let data = &slf.get().data;
+        let ret = unsafe {
+            ffi::PyBuffer_FillInfo(
+                view,
+                slf.as_ptr(),
+                data.as_ptr() as *mut c_void,
+                size_of_val(data.as_slice()) as ffi::Py_ssize_t,
+                0,
+                flags,
+            )
+        };
+        if ret == -1 {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    unsafe fn __releasebuffer__(&self, _view: *mut ffi::Py_buffer) {}
+}
+
+/// Runs the conversion without holding the GIL and saves the converted events if
+/// `output_filename` is provided.
+fn convert<F>(py: Python<'_>, convert: F, output_filename: Option<String>) -> PyResult<EventBuffer>
+where
+    F: FnOnce() -> std::io::Result<Vec<Event>> + Send,
+{
+    let data = py
+        .allow_threads(|| {
+            let data = convert()?;
+            if let Some(output_filename) = output_filename {
+                write_npz_file(output_filename, "data", &data)?;
+            }
+            Ok::<_, std::io::Error>(data)
+        })
+        .map_err(|error| PyIOError::new_err(error.to_string()))?;
+    Ok(EventBuffer { data })
+}
+
+#[pyfunction]
+#[pyo3(signature = (input_files, output_filename=None, base_latency=0, snapshot_mode="process"))]
+pub fn tardis_convert(
+    py: Python<'_>,
+    input_files: Vec<String>,
+    output_filename: Option<String>,
+    base_latency: i64,
+    snapshot_mode: &str,
+) -> PyResult<EventBuffer> {
+    let snapshot_mode = self::snapshot_mode(snapshot_mode)?;
+    convert(
+        py,
+        || tardis::convert(&input_files, base_latency, snapshot_mode),
+        output_filename,
+    )
+}
+
+#[pyfunction]
+#[pyo3(signature = (
+    trades_filename,
+    depth_filename,
+    book_ticker_filename,
+    tick_size,
+    lot_size,
+    output_filename=None,
+    base_latency=0,
+    snapshot_mode="process"
+))]
+#[allow(clippy::too_many_arguments)]
+pub fn tardis_convert_fuse(
+    py: Python<'_>,
+    trades_filename: String,
+    depth_filename: String,
+    book_ticker_filename: String,
+    tick_size: f64,
+    lot_size: f64,
+    output_filename: Option<String>,
+    base_latency: i64,
+    snapshot_mode: &str,
+) -> PyResult<EventBuffer> {
+    let snapshot_mode = self::snapshot_mode(snapshot_mode)?;
+    convert(
+        py,
+        || {
+            tardis::convert_fuse(
+                trades_filename,
+                depth_filename,
+                book_ticker_filename,
+                tick_size,
+                lot_size,
+                base_latency,
+                snapshot_mode,
+            )
+        },
+        output_filename,
+    )
+}
+
+#[pyfunction]
+#[pyo3(signature = (input_file, instrument_id=None, output_filename=None, base_latency=0))]
+pub fn databento_convert(
+    py: Python<'_>,
+    input_file: String,
+    instrument_id: Option<u32>,
+    output_filename: Option<String>,
+    base_latency: i64,
+) -> PyResult<EventBuffer> {
+    convert(
+        py,
+        || databento::convert(input_file, instrument_id, base_latency),
+        output_filename,
+    )
+}
@@ -1,11 +1,15 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
-use std::mem::forget;
+use std::{mem::forget, ptr::null};
 
 use hftbacktest::prelude::{
     ApplySnapshot,
+    DepthAnalytics,
     Event,
+    FusedHashMapMarketDepth,
     HashMapMarketDepth,
+    L3MarketDepth,
+    L3Order,
     MarketDepth,
     ROIVectorMarketDepth,
 };
@@ -76,6 +80,62 @@ pub extern "C" fn hashmapdepth_ask_qty_at_tick(
     depth.ask_qty_at_tick(price_tick)
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_cum_bid_qty(
+    ptr: *const HashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.cum_bid_qty(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_cum_ask_qty(
+    ptr: *const HashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.cum_ask_qty(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_imbalance(
+    ptr: *const HashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.imbalance(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_weighted_mid(
+    ptr: *const HashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.weighted_mid(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_bid_vwap(
+    ptr: *const HashMapMarketDepth,
+    qty: f64,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.bid_vwap(qty, depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_ask_vwap(
+    ptr: *const HashMapMarketDepth,
+    qty: f64,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.ask_vwap(qty, depth_ticks)
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn hashmapdepth_snapshot(
     ptr: *const HashMapMarketDepth,
@@ -97,6 +157,46 @@ pub extern "C" fn hashmapdepth_snapshot_free(event_ptr: *mut Event, len: usize)
     let _ = unsafe { Vec::from_raw_parts(event_ptr, len, len) };
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_l3_order(
+    ptr: *const HashMapMarketDepth,
+    order_id: u64,
+) -> *const L3Order {
+    let depth = unsafe { &*ptr };
+    depth
+        .orders()
+        .get(&order_id)
+        .map(|order| order as *const L3Order)
+        .unwrap_or(null())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_l3_orders_len(ptr: *const HashMapMarketDepth) -> usize {
+    let depth = unsafe { &*ptr };
+    depth.orders().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_l3_orders(
+    ptr: *const HashMapMarketDepth,
+    len: *mut usize,
+) -> *const L3Order {
+    let depth = unsafe { &*ptr };
+    let mut orders: Vec<L3Order> = depth.orders().values().cloned().collect();
+    orders.shrink_to_fit();
+    let ptr = orders.as_ptr();
+    unsafe {
+        *len = orders.len();
+        forget(orders);
+    }
+    ptr
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn hashmapdepth_l3_orders_free(order_ptr: *mut L3Order, len: usize) {
+    let _ = unsafe { Vec::from_raw_parts(order_ptr, len, len) };
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn roivecdepth_best_bid_tick(ptr: *const ROIVectorMarketDepth) -> i64 {
     let depth = unsafe { &*ptr };
@@ -163,6 +263,62 @@ pub extern "C" fn roivecdepth_ask_qty_at_tick(
     depth.ask_qty_at_tick(price_tick)
 }
 
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_cum_bid_qty(
+    ptr: *const ROIVectorMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.cum_bid_qty(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_cum_ask_qty(
+    ptr: *const ROIVectorMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.cum_ask_qty(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_imbalance(
+    ptr: *const ROIVectorMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.imbalance(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_weighted_mid(
+    ptr: *const ROIVectorMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.weighted_mid(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_bid_vwap(
+    ptr: *const ROIVectorMarketDepth,
+    qty: f64,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.bid_vwap(qty, depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_ask_vwap(
+    ptr: *const ROIVectorMarketDepth,
+    qty: f64,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.ask_vwap(qty, depth_ticks)
+}
+
 #[unsafe(no_mangle)]
 pub extern "C" fn roivecdepth_bid_depth(
     ptr: *const ROIVectorMarketDepth,
@@ -193,3 +349,186 @@ pub extern "C" fn roivecdepth_roi_ub_tick(ptr: *const ROIVectorMarketDepth) -> i
     let depth = unsafe { &*ptr };
     depth.roi_tick().1
 }
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_l3_order(
+    ptr: *const ROIVectorMarketDepth,
+    order_id: u64,
+) -> *const L3Order {
+    let depth = unsafe { &*ptr };
+    depth
+        .orders()
+        .get(&order_id)
+        .map(|order| order as *const L3Order)
+        .unwrap_or(null())
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_l3_orders_len(ptr: *const ROIVectorMarketDepth) -> usize {
+    let depth = unsafe { &*ptr };
+    depth.orders().len()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_l3_orders(
+    ptr: *const ROIVectorMarketDepth,
+    len: *mut usize,
+) -> *const L3Order {
+    let depth = unsafe { &*ptr };
+    let mut orders: Vec<L3Order> = depth.orders().values().cloned().collect();
+    orders.shrink_to_fit();
+    let ptr = orders.as_ptr();
+    unsafe {
+        *len = orders.len();
+        forget(orders);
+    }
+    ptr
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn roivecdepth_l3_orders_free(order_ptr: *mut L3Order, len: usize) {
+    let _ = unsafe { Vec::from_raw_parts(order_ptr, len, len) };
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_best_bid_tick(ptr: *const FusedHashMapMarketDepth) -> i64 {
+    let depth = unsafe { &*ptr };
+    depth.best_bid_tick()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_best_ask_tick(ptr: *const FusedHashMapMarketDepth) -> i64 {
+    let depth = unsafe { &*ptr };
+    depth.best_ask_tick()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_best_bid(ptr: *const FusedHashMapMarketDepth) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.best_bid()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_best_ask(ptr: *const FusedHashMapMarketDepth) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.best_ask()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_best_bid_qty(ptr: *const FusedHashMapMarketDepth) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.best_bid_qty()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_best_ask_qty(ptr: *const FusedHashMapMarketDepth) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.best_ask_qty()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_tick_size(ptr: *const FusedHashMapMarketDepth) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.tick_size()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_lot_size(ptr: *const FusedHashMapMarketDepth) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.lot_size()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_bid_qty_at_tick(
+    ptr: *const FusedHashMapMarketDepth,
+    price_tick: i64,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.bid_qty_at_tick(price_tick)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_ask_qty_at_tick(
+    ptr: *const FusedHashMapMarketDepth,
+    price_tick: i64,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.ask_qty_at_tick(price_tick)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_cum_bid_qty(
+    ptr: *const FusedHashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.cum_bid_qty(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_cum_ask_qty(
+    ptr: *const FusedHashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.cum_ask_qty(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_imbalance(
+    ptr: *const FusedHashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.imbalance(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_weighted_mid(
+    ptr: *const FusedHashMapMarketDepth,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.weighted_mid(depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_bid_vwap(
+    ptr: *const FusedHashMapMarketDepth,
+    qty: f64,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.bid_vwap(qty, depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_ask_vwap(
+    ptr: *const FusedHashMapMarketDepth,
+    qty: f64,
+    depth_ticks: usize,
+) -> f64 {
+    let depth = unsafe { &*ptr };
+    depth.ask_vwap(qty, depth_ticks)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_snapshot(
+    ptr: *const FusedHashMapMarketDepth,
+    len: *mut usize,
+) -> *const Event {
+    let depth = unsafe { &*ptr };
+    let mut snapshot = depth.snapshot();
+    snapshot.shrink_to_fit();
+    let ptr = snapshot.as_ptr();
+    unsafe {
+        *len = snapshot.len();
+        forget(snapshot);
+    }
+    ptr
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmapdepth_snapshot_free(event_ptr: *mut Event, len: usize) {
+    let _ = unsafe { Vec::from_raw_parts(event_ptr, len, len) };
+}
@@ -11,7 +11,7 @@ use hftbacktest::{
         Backtest,
         DataSource,
         assettype::{InverseAsset, LinearAsset},
-        data::{Data, DataPtr, FeedLatencyAdjustment, Reader, read_npz_file},
+        data::{Data, DataPtr, FeedLatencyAdjustment, Reader, read_columnar_file, read_npz_file},
         models::{
             CommonFees,
             ConstantLatency,
@@ -33,6 +33,7 @@ use hftbacktest::{
         proc::{
             L3Local,
             L3NoPartialFillExchange,
+            L3PartialFillExchange,
             Local,
             LocalProcessor,
             NoPartialFillExchange,
@@ -41,7 +42,13 @@ use hftbacktest::{
         },
         state::State,
     },
-    prelude::{ApplySnapshot, Event, HashMapMarketDepth, ROIVectorMarketDepth},
+    prelude::{
+        ApplySnapshot,
+        Event,
+        FusedHashMapMarketDepth,
+        HashMapMarketDepth,
+        ROIVectorMarketDepth,
+    },
 };
 use hftbacktest_derive::build_asset;
 pub use order::*;
@@ -53,9 +60,14 @@ use pyo3::{
 };
 
 #[cfg(feature = "live")]
-use crate::live::{HashMapMarketDepthLiveBot, ROIVectorMarketDepthLiveBot};
+use crate::live::{
+    FusedHashMapMarketDepthLiveBot,
+    HashMapMarketDepthLiveBot,
+    ROIVectorMarketDepthLiveBot,
+};
 
 mod backtest;
+mod convert;
 mod depth;
 mod fuse;
 #[cfg(feature = "live")]
@@ -121,6 +133,10 @@ pub struct BacktestAsset {
     fee_model: FeeModel,
     latency_offset: i64,
     parallel_load: bool,
+    mmap: bool,
+    chunk_size: usize,
+    start: i64,
+    end: i64,
 }
 
 unsafe impl Send for BacktestAsset {}
@@ -152,6 +168,10 @@ impl BacktestAsset {
             },
             latency_offset: 0,
             parallel_load: true,
+            mmap: false,
+            chunk_size: 0,
+            start: i64::MIN,
+            end: i64::MAX,
         }
     }
 
@@ -166,6 +186,51 @@ impl BacktestAsset {
         slf
     }
 
+    /// Sets whether to memory-map ``npy`` data files instead of reading them into memory. The data
+    /// is paged in as it is read, and the pages are shared with other processes backtesting the same
+    /// files, such as parallel parameter sweep workers.
+    ///
+    /// Args:
+    ///     mmap: whether to memory-map ``npy`` data files.
+    ///           The default value is `False`.
+    pub fn mmap(mut slf: PyRefMut<Self>, mmap: bool) -> PyRefMut<Self> {
+        slf.mmap = mmap;
+        slf
+    }
+
+    /// Sets the number of rows into which ``npz`` data files are split, so that each file is
+    /// decompressed in a streaming manner and only a few chunks are held in memory at a time.
+    ///
+    /// Args:
+    ///     chunk_size: the number of rows in each chunk. The default value is `0`, indicating
+    ///                 that each file is loaded entirely.
+    pub fn chunk_size(mut slf: PyRefMut<Self>, chunk_size: usize) -> PyRefMut<Self> {
+        slf.chunk_size = chunk_size;
+        slf
+    }
+
+    /// Sets the start of the time window to backtest, so that a part of the data files can be
+    /// backtested without slicing them. Events before the start are only applied to build the
+    /// market depth.
+    ///
+    /// Args:
+    ///     start: the start timestamp. The default value is the minimum of ``int64``, indicating
+    ///            that the data is backtested from the beginning.
+    pub fn start(mut slf: PyRefMut<Self>, start: i64) -> PyRefMut<Self> {
+        slf.start = start;
+        slf
+    }
+
+    /// Sets the end of the time window to backtest. Events after the end are dropped.
+    ///
+    /// Args:
+    ///     end: the end timestamp. The default value is the maximum of ``int64``, indicating that
+    ///          the data is backtested to the end.
+    pub fn end(mut slf: PyRefMut<Self>, end: i64) -> PyRefMut<Self> {
+        slf.end = end;
+        slf
+    }
+
     /// Sets the latency offset to adjust the feed latency by the specified amount. This is
     /// particularly useful in cross-exchange backtesting, where the feed data is collected from a
     /// different site than the one where the strategy is intended to run.
@@ -391,7 +456,8 @@ impl BacktestAsset {
         slf
     }
 
-    /// Uses the `L3FIFOQueueModel` for the queue position model.
+    /// Uses the `L3FIFOQueueModel` for the queue position model. This switches the asset to the
+    /// Level-3 Market-By-Order backtesting, which requires L3 feed data.
     ///
     /// Please find the details below.
     ///
@@ -445,6 +511,24 @@ impl BacktestAsset {
         slf
     }
 
+    /// Uses the `L3NoPartialFillExchange <https://docs.rs/hftbacktest/latest/hftbacktest/backtest/proc/struct.L3NoPartialFillExchange.html>`_
+    /// for the exchange model with the `L3FIFOQueueModel` for the queue position model, which
+    /// backtests the asset using the Level-3 Market-By-Order feed data.
+    pub fn l3_no_partial_fill_exchange(mut slf: PyRefMut<Self>) -> PyRefMut<Self> {
+        slf.queue_model = QueueModel::L3FIFOQueueModel {};
+        slf.exch_kind = ExchangeKind::NoPartialFillExchange {};
+        slf
+    }
+
+    /// Uses the `L3PartialFillExchange <https://docs.rs/hftbacktest/latest/hftbacktest/backtest/proc/struct.L3PartialFillExchange.html>`_
+    /// for the exchange model with the `L3FIFOQueueModel` for the queue position model, which
+    /// backtests the asset using the Level-3 Market-By-Order feed data with partial fills.
+    pub fn l3_partial_fill_exchange(mut slf: PyRefMut<Self>) -> PyRefMut<Self> {
+        slf.queue_model = QueueModel::L3FIFOQueueModel {};
+        slf.exch_kind = ExchangeKind::PartialFillExchange {};
+        slf
+    }
+
     /// Sets the initial capacity of the vector storing the last market trades.
     /// The default value is `0`, indicating that no last trades are stored.
     pub fn last_trades_capacity(mut slf: PyRefMut<Self>, capacity: usize) -> PyRefMut<Self> {
@@ -496,10 +580,16 @@ impl BacktestAsset {
 fn _hftbacktest(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(build_hashmap_backtest, m)?)?;
     m.add_function(wrap_pyfunction!(build_roivec_backtest, m)?)?;
+    m.add_function(wrap_pyfunction!(build_fused_hashmap_backtest, m)?)?;
+    m.add_function(wrap_pyfunction!(convert::tardis_convert, m)?)?;
+    m.add_function(wrap_pyfunction!(convert::tardis_convert_fuse, m)?)?;
+    m.add_function(wrap_pyfunction!(convert::databento_convert, m)?)?;
     #[cfg(feature = "live")]
     m.add_function(wrap_pyfunction!(build_hashmap_livebot, m)?)?;
     #[cfg(feature = "live")]
     m.add_function(wrap_pyfunction!(build_roivec_livebot, m)?)?;
+    #[cfg(feature = "live")]
+    m.add_function(wrap_pyfunction!(build_fused_hashmap_livebot, m)?)?;
     m.add_class::<BacktestAsset>()?;
     m.add_class::<LiveInstrument>()?;
     Ok(())
@@ -517,14 +607,6 @@ pub fn build_hashmap_backtest(assets: Vec<PyRefMut<BacktestAsset>>) -> PyResult<
     let mut exch = Vec::new();
     let mut readers = Vec::new();
     for asset in assets {
-        if let (QueueModel::L3FIFOQueueModel {}, ExchangeKind::PartialFillExchange {}) =
-            (&asset.queue_model, &asset.exch_kind)
-        {
-            return PyResult::Err(PyErr::new::<PyValueError, _>(
-                "L3PartialFillExchange is unsupported.",
-            ));
-        }
-
         let asst = build_asset!(
             asset,
             HashMapMarketDepth,
@@ -574,17 +656,64 @@ pub fn build_roivec_backtest(assets: Vec<PyRefMut<BacktestAsset>>) -> PyResult<u
     let mut readers = Vec::new();
 
     for asset in assets {
-        if let (QueueModel::L3FIFOQueueModel {}, ExchangeKind::PartialFillExchange {}) =
-            (&asset.queue_model, &asset.exch_kind)
-        {
+        let asst = build_asset!(
+            asset,
+            ROIVectorMarketDepth,
+            [
+                LinearAsset { contract_size },
+                InverseAsset { contract_size }
+            ],
+            [
+                ConstantLatency {
+                    entry_latency,
+                    resp_latency
+                },
+                IntpOrderLatency {
+                    data,
+                    latency_offset
+                }
+            ],
+            [
+                RiskAdverseQueueModel {},
+                LogProbQueueModel {},
+                LogProbQueueModel2 {},
+                PowerProbQueueModel { n },
+                PowerProbQueueModel2 { n },
+                PowerProbQueueModel3 { n },
+                L3FIFOQueueModel {}
+            ],
+            [NoPartialFillExchange {}, PartialFillExchange {}],
+            [
+                TradingValueFeeModel { fees },
+                TradingQtyFeeModel { fees },
+                FlatPerTradeFeeModel { fees },
+            ]
+        );
+        local.push(asst.local);
+        exch.push(asst.exch);
+        readers.push(asst.reader);
+    }
+
+    let hbt = Backtest::new(local, exch, readers);
+    Ok(Box::into_raw(Box::new(hbt)) as *mut c_void as usize)
+}
+
+#[pyfunction]
+pub fn build_fused_hashmap_backtest(assets: Vec<PyRefMut<BacktestAsset>>) -> PyResult<usize> {
+    let mut local = Vec::new();
+    let mut exch = Vec::new();
+    let mut readers = Vec::new();
+
+    for asset in assets {
+        if let QueueModel::L3FIFOQueueModel {} = &asset.queue_model {
             return PyResult::Err(PyErr::new::<PyValueError, _>(
-                "L3PartialFillExchange is unsupported.",
+                "FusedHashMapMarketDepth doesn't support Level-3 Market-By-Order.",
             ));
         }
 
         let asst = build_asset!(
             asset,
-            ROIVectorMarketDepth,
+            FusedHashMapMarketDepth,
             [
                 LinearAsset { contract_size },
                 InverseAsset { contract_size }
@@ -605,8 +734,7 @@ pub fn build_roivec_backtest(assets: Vec<PyRefMut<BacktestAsset>>) -> PyResult<u
                 LogProbQueueModel2 {},
                 PowerProbQueueModel { n },
                 PowerProbQueueModel2 { n },
-                PowerProbQueueModel3 { n },
-                L3FIFOQueueModel {}
+                PowerProbQueueModel3 { n }
             ],
             [NoPartialFillExchange {}, PartialFillExchange {}],
             [
@@ -757,3 +885,26 @@ pub fn build_roivec_livebot(instruments: Vec<PyRefMut<LiveInstrument>>) -> PyRes
 
     Ok(Box::into_raw(Box::new(hbt)) as *mut c_void as usize)
 }
+
+#[cfg(feature = "live")]
+#[pyfunction]
+pub fn build_fused_hashmap_livebot(instruments: Vec<PyRefMut<LiveInstrument>>) -> PyResult<usize> {
+    let mut builder = LiveBotBuilder::new();
+    for instrument in instruments {
+        builder = builder.register(Instrument::new(
+            &instrument.connector_name,
+            &instrument.symbol,
+            instrument.tick_size,
+            instrument.lot_size,
+            FusedHashMapMarketDepth::new(instrument.tick_size, instrument.lot_size),
+            instrument.last_trades_cap,
+        ));
+    }
+    let hbt: FusedHashMapMarketDepthLiveBot = builder
+        .error_handler(|_error| Ok(()))
+        .order_recv_hook(|_prev, _new| Ok(()))
+        .build()
+        .unwrap();
+
+    Ok(Box::into_raw(Box::new(hbt)) as *mut c_void as usize)
+}
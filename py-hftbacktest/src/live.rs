@@ -3,7 +3,7 @@
 use std::{collections::HashMap, mem};
 
 use hftbacktest::{
-    depth::{HashMapMarketDepth, ROIVectorMarketDepth},
+    depth::{FusedHashMapMarketDepth, HashMapMarketDepth, ROIVectorMarketDepth},
     live::{BotError, LiveBot, ipc::iceoryx::IceoryxUnifiedChannel},
     prelude::{Bot, ElapseResult, Event, Order, StateValues},
     types::{OrdType, TimeInForce},
@@ -11,6 +11,7 @@ use hftbacktest::{
 
 pub type HashMapMarketDepthLiveBot = LiveBot<IceoryxUnifiedChannel, HashMapMarketDepth>;
 pub type ROIVectorMarketDepthLiveBot = LiveBot<IceoryxUnifiedChannel, ROIVectorMarketDepth>;
+pub type FusedHashMapMarketDepthLiveBot = LiveBot<IceoryxUnifiedChannel, FusedHashMapMarketDepth>;
 
 fn handle_result(result: Result<ElapseResult, BotError>) -> i64 {
     match result {
@@ -516,3 +517,250 @@ pub extern "C" fn roiveclive_order_latency(
         },
     }
 }
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_current_timestamp(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+) -> i64 {
+    let hbt = unsafe { &*hbt_ptr };
+    hbt.current_timestamp()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_depth(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+) -> *const FusedHashMapMarketDepth {
+    let hbt = unsafe { &*hbt_ptr };
+    let depth = hbt.depth(asset_no);
+    depth as *const _
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_last_trades(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    len_ptr: *mut usize,
+) -> *const Event {
+    let hbt = unsafe { &*hbt_ptr };
+    let trade = hbt.last_trades(asset_no);
+    unsafe {
+        *len_ptr = trade.len();
+    }
+    trade.as_ptr() as *mut _
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_position(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+) -> f64 {
+    let hbt = unsafe { &*hbt_ptr };
+    hbt.position(asset_no)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_close(hbt_ptr: *mut FusedHashMapMarketDepthLiveBot) -> i64 {
+    let mut hbt = unsafe { Box::from_raw(hbt_ptr) };
+    match hbt.close() {
+        Ok(()) => 0,
+        Err(BotError::OrderIdExist) => 10,
+        Err(BotError::OrderNotFound) => 12,
+        Err(BotError::InvalidOrderStatus) => 14,
+        Err(BotError::InstrumentNotFound) => 16,
+        Err(BotError::Timeout) => 17,
+        Err(BotError::Interrupted) => 18,
+        Err(BotError::Custom(_)) => 19,
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_elapse(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    duration: i64,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    handle_result(hbt.elapse(duration))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_elapse_bt(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    duration: i64,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    handle_result(hbt.elapse_bt(duration))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_num_assets(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+) -> usize {
+    let hbt = unsafe { &*hbt_ptr };
+    hbt.num_assets()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_wait_order_response(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    order_id: u64,
+    timeout: i64,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    handle_result(hbt.wait_order_response(asset_no, order_id, timeout))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_wait_next_feed(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    include_resp: bool,
+    timeout: i64,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    handle_result(hbt.wait_next_feed(include_resp, timeout))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_submit_buy_order(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    order_id: u64,
+    price: f64,
+    qty: f64,
+    time_in_force: u8,
+    order_type: u8,
+    wait: bool,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    let tif = unsafe { mem::transmute::<u8, TimeInForce>(time_in_force) };
+    handle_result(hbt.submit_buy_order(
+        asset_no,
+        order_id,
+        price,
+        qty,
+        tif,
+        unsafe { mem::transmute::<u8, OrdType>(order_type) },
+        wait,
+    ))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_submit_sell_order(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    order_id: u64,
+    price: f64,
+    qty: f64,
+    time_in_force: u8,
+    order_type: u8,
+    wait: bool,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    handle_result(hbt.submit_sell_order(
+        asset_no,
+        order_id,
+        price,
+        qty,
+        unsafe { mem::transmute::<u8, TimeInForce>(time_in_force) },
+        unsafe { mem::transmute::<u8, OrdType>(order_type) },
+        wait,
+    ))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_cancel(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    order_id: u64,
+    wait: bool,
+) -> i64 {
+    let hbt = unsafe { &mut *hbt_ptr };
+    handle_result(hbt.cancel(asset_no, order_id, wait))
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_clear_last_trades(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+) {
+    let hbt = unsafe { &mut *hbt_ptr };
+    if asset_no == usize::MAX {
+        hbt.clear_last_trades(None);
+    } else {
+        hbt.clear_last_trades(Some(asset_no));
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_clear_inactive_orders(
+    hbt_ptr: *mut FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+) {
+    let hbt = unsafe { &mut *hbt_ptr };
+    if asset_no == usize::MAX {
+        hbt.clear_inactive_orders(None);
+    } else {
+        hbt.clear_inactive_orders(Some(asset_no));
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_orders(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+) -> *const HashMap<u64, Order> {
+    let hbt = unsafe { &*hbt_ptr };
+    hbt.orders(asset_no) as *const _
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_state_values(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+) -> *const StateValues {
+    let hbt = unsafe { &*hbt_ptr };
+    hbt.state_values(asset_no) as *const _
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_feed_latency(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    exch_ts: *mut i64,
+    local_ts: *mut i64,
+) -> bool {
+    let hbt = unsafe { &*hbt_ptr };
+    match hbt.feed_latency(asset_no) {
+        None => false,
+        Some((exch_ts_, local_ts_)) => {
+            unsafe {
+                *exch_ts = exch_ts_;
+                *local_ts = local_ts_;
+            }
+            true
+        },
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn fusedhashmaplive_order_latency(
+    hbt_ptr: *const FusedHashMapMarketDepthLiveBot,
+    asset_no: usize,
+    req_ts: *mut i64,
+    exch_ts: *mut i64,
+    resp_ts: *mut i64,
+) -> bool {
+    let hbt = unsafe { &*hbt_ptr };
+    match hbt.order_latency(asset_no) {
+        None => false,
+        Some((req_ts_, exch_ts_, resp_ts_)) => {
+            unsafe {
+                *req_ts = req_ts_;
+                *exch_ts = exch_ts_;
+                *resp_ts = resp_ts_;
+            }
+            true
+        },
+    }
+}